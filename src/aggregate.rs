@@ -1,6 +1,6 @@
 //! Aggregate function module for sqawk
 //!
-//! This module implements SQL aggregate functions (COUNT, SUM, AVG, MIN, MAX) for the sqawk utility.
+//! This module implements SQL aggregate functions for the sqawk utility.
 //! It provides the core aggregation functionality for SQL queries with features including:
 //!
 //! - Type-aware aggregation that preserves the appropriate result type (integer vs. float)
@@ -8,12 +8,66 @@
 //! - Support for mixed-type aggregation with type coercion
 //! - Comprehensive test coverage for all aggregate operations
 //!
-//! All aggregate functions operate on columns (vectors of values) and return a single
-//! Value representing the result of the operation.
+//! Built-in aggregates:
+//! - COUNT(), SUM(), AVG(), MIN(), MAX()
+//! - GROUP_CONCAT() / STRING_AGG(): Join values into one string
+//! - STDDEV_SAMP(), STDDEV_POP(), VAR_SAMP(), VAR_POP() and the STDDEV/VARIANCE aliases
+//! - MEDIAN(), PERCENTILE_CONT(), PERCENTILE_DISC(), MODE()
+//! - BOOL_AND() / EVERY(), BOOL_OR(), ANY_VALUE()
+//!
+//! Aggregation is streaming: an `Aggregator` creates one `Accumulator` per group,
+//! which is fed the argument value of each row in turn and produces the result
+//! when the group is complete, so a group's values never need to be collected
+//! (order statistics such as MEDIAN necessarily keep them). The built-in
+//! functions implement the same traits that library users implement for their
+//! own aggregates (see `FunctionRegistry::register_aggregate`).
+
+use std::collections::{HashMap, HashSet};
 
-use crate::error::SqawkResult;
+use crate::arithmetic::ArithmeticOperator;
+use crate::error::{SqawkError, SqawkResult};
+use crate::functions::function_names;
 use crate::table::Value;
 
+/// Running state of an aggregate function for one group
+///
+/// The lifecycle is: created by `Aggregator::create_accumulator` (init), fed each
+/// row with `update`, optionally combined with partial results from other
+/// accumulators of the same aggregate with `merge`, and read with `finalize`.
+pub trait Accumulator {
+    /// Add one input value (a NULL for rows where the argument is NULL)
+    fn update(&mut self, value: &Value) -> SqawkResult<()>;
+
+    /// The intermediate state, in a form that `merge` on another accumulator accepts
+    fn state(&self) -> Vec<Value>;
+
+    /// Combine the state of another accumulator of the same aggregate into this one
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()>;
+
+    /// Compute the aggregate result for the values seen so far
+    fn finalize(&self) -> SqawkResult<Value>;
+}
+
+/// An aggregate function that can be called from SQL
+///
+/// Like scalar functions, aggregates must be `Send + Sync` so that the registry
+/// holding them doesn't tie a `Database` to one thread.
+pub trait Aggregator: Send + Sync {
+    /// The function's name as used in SQL (matched case-insensitively)
+    fn name(&self) -> &str;
+
+    /// Create an accumulator in its initial state, for one group of rows
+    ///
+    /// # Arguments
+    /// * `params` - Constant arguments that follow the aggregated value, such as
+    ///   the separator in `GROUP_CONCAT(name, ';')`; empty for a one-argument call
+    ///
+    /// # Returns
+    /// * `Ok(Box<dyn Accumulator>)` - A fresh accumulator
+    /// * `Err(SqawkError::InvalidFunctionArguments)` - If the parameters are not accepted
+    fn create_accumulator(&self, params: &[Value]) -> SqawkResult<Box<dyn Accumulator>>;
+}
+
 /// Supported aggregate functions
 #[derive(Debug, Clone, Copy)]
 pub enum AggregateFunction {
@@ -27,27 +81,53 @@ pub enum AggregateFunction {
     Min,
     /// MAX function - finds the maximum value in a column
     Max,
+    /// GROUP_CONCAT function - joins values with a separator - GROUP_CONCAT(x[, sep])
+    GroupConcat,
+    /// STDDEV_SAMP function - sample standard deviation
+    StddevSamp,
+    /// STDDEV_POP function - population standard deviation
+    StddevPop,
+    /// VAR_SAMP function - sample variance
+    VarSamp,
+    /// VAR_POP function - population variance
+    VarPop,
+    /// MEDIAN function - the middle value, interpolated for an even count
+    Median,
+    /// PERCENTILE_CONT function - interpolated percentile - PERCENTILE_CONT(x, fraction)
+    PercentileCont,
+    /// PERCENTILE_DISC function - first value at or above a percentile - PERCENTILE_DISC(x, fraction)
+    PercentileDisc,
+    /// MODE function - the most frequent value
+    Mode,
+    /// BOOL_AND function - true if every value is true
+    BoolAnd,
+    /// BOOL_OR function - true if any value is true
+    BoolOr,
+    /// ANY_VALUE function - an arbitrary (the first) non-NULL value
+    AnyValue,
 }
 
-impl AggregateFunction {
-    /// Convert a function name string to an AggregateFunction enum
-    ///
-    /// # Arguments
-    /// * `name` - The function name (case-insensitive)
-    ///
-    /// # Returns
-    /// * Some(AggregateFunction) if the name is a valid aggregate function, None otherwise
-    pub fn from_name(name: &str) -> Option<Self> {
-        match name.to_uppercase().as_str() {
-            "COUNT" => Some(AggregateFunction::Count),
-            "SUM" => Some(AggregateFunction::Sum),
-            "AVG" => Some(AggregateFunction::Avg),
-            "MIN" => Some(AggregateFunction::Min),
-            "MAX" => Some(AggregateFunction::Max),
-            _ => None,
-        }
-    }
+function_names!(AggregateFunction {
+    Count => "COUNT",
+    Sum => "SUM",
+    Avg => "AVG",
+    Min => "MIN",
+    Max => "MAX",
+    GroupConcat => "GROUP_CONCAT" | "STRING_AGG",
+    StddevSamp => "STDDEV_SAMP" | "STDDEV",
+    StddevPop => "STDDEV_POP",
+    VarSamp => "VAR_SAMP" | "VARIANCE",
+    VarPop => "VAR_POP",
+    Median => "MEDIAN",
+    PercentileCont => "PERCENTILE_CONT",
+    PercentileDisc => "PERCENTILE_DISC",
+    Mode => "MODE",
+    BoolAnd => "BOOL_AND" | "EVERY",
+    BoolOr => "BOOL_OR",
+    AnyValue => "ANY_VALUE",
+});
 
+impl AggregateFunction {
     /// Execute the aggregate function on a column of values
     ///
     /// # Arguments
@@ -56,150 +136,648 @@ impl AggregateFunction {
     /// # Returns
     /// * The result of the aggregate function
     pub fn execute(&self, values: &[Value]) -> SqawkResult<Value> {
-        match self {
-            AggregateFunction::Count => self.count(values),
-            AggregateFunction::Sum => self.sum(values),
-            AggregateFunction::Avg => self.avg(values),
-            AggregateFunction::Min => self.min(values),
-            AggregateFunction::Max => self.max(values),
+        let mut accumulator = self.create_accumulator(&[])?;
+        for value in values {
+            accumulator.update(value)?;
         }
+        accumulator.finalize()
     }
 
-    /// COUNT function implementation
+    /// Check the constant arguments that follow the aggregated value
     ///
-    /// Counts non-NULL values in the column
-    fn count(&self, values: &[Value]) -> SqawkResult<Value> {
-        let count = values.iter().filter(|v| !matches!(v, Value::Null)).count();
+    /// GROUP_CONCAT takes an optional separator and the percentiles a fraction;
+    /// the other aggregates take only the value.
+    fn check_params(&self, params: &[Value]) -> SqawkResult<()> {
+        let max = match self {
+            AggregateFunction::GroupConcat
+            | AggregateFunction::PercentileCont
+            | AggregateFunction::PercentileDisc => 1,
+            _ => 0,
+        };
+        if params.len() > max {
+            let expected = if max == 0 {
+                "1 argument"
+            } else {
+                "1 or 2 arguments"
+            };
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "{} requires {}, got {}",
+                self.name(),
+                expected,
+                params.len() + 1
+            )));
+        }
+        Ok(())
+    }
 
-        Ok(Value::Integer(count as i64))
+    /// Get the separator parameter of GROUP_CONCAT (a comma by default)
+    fn separator_param(&self, params: &[Value]) -> String {
+        match params.first() {
+            None => ",".to_string(),
+            Some(Value::Null) => String::new(),
+            Some(value) => value.to_string(),
+        }
     }
 
-    /// SUM function implementation
-    ///
-    /// Sums numeric values in the column
-    fn sum(&self, values: &[Value]) -> SqawkResult<Value> {
-        // Keep track of whether we need to return an integer or float
-        let mut is_float = false;
-        let mut int_sum: i64 = 0;
-        let mut float_sum: f64 = 0.0;
+    /// Get the fraction parameter of a percentile function, between 0 and 1
+    fn fraction_param(&self, params: &[Value]) -> SqawkResult<f64> {
+        let fraction = match params.first() {
+            Some(Value::Integer(i)) => *i as f64,
+            Some(Value::Float(f)) => *f,
+            _ => {
+                return Err(SqawkError::InvalidFunctionArguments(format!(
+                    "{} requires a fraction between 0 and 1, e.g. {}(x, 0.9)",
+                    self.name(),
+                    self.name()
+                )))
+            }
+        };
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "{} fraction must be between 0 and 1, got {}",
+                self.name(),
+                fraction
+            )));
+        }
+        Ok(fraction)
+    }
+}
+
+impl Aggregator for AggregateFunction {
+    fn name(&self) -> &str {
+        AggregateFunction::name(self)
+    }
 
-        // Count non-null values
-        let mut count = 0;
+    fn create_accumulator(&self, params: &[Value]) -> SqawkResult<Box<dyn Accumulator>> {
+        self.check_params(params)?;
 
-        for value in values {
-            match value {
-                Value::Integer(i) => {
-                    if is_float {
-                        float_sum += *i as f64;
-                    } else {
-                        int_sum += *i;
-                    }
-                    count += 1;
-                }
-                Value::Float(f) => {
-                    if !is_float {
-                        // Convert accumulated integer sum to float
-                        float_sum = int_sum as f64;
-                        is_float = true;
-                    }
-                    float_sum += *f;
-                    count += 1;
-                }
-                // Ignore non-numeric values
-                _ => {}
+        Ok(match self {
+            AggregateFunction::Count => Box::<CountAccumulator>::default(),
+            AggregateFunction::Sum => Box::<SumAccumulator>::default(),
+            AggregateFunction::Avg => Box::<AvgAccumulator>::default(),
+            AggregateFunction::Min => Box::new(ExtremeAccumulator::new(false)),
+            AggregateFunction::Max => Box::new(ExtremeAccumulator::new(true)),
+            AggregateFunction::GroupConcat => {
+                Box::new(GroupConcatAccumulator::new(self.separator_param(params)))
             }
+            AggregateFunction::StddevSamp => Box::new(VarianceAccumulator::new(false, true)),
+            AggregateFunction::StddevPop => Box::new(VarianceAccumulator::new(true, true)),
+            AggregateFunction::VarSamp => Box::new(VarianceAccumulator::new(false, false)),
+            AggregateFunction::VarPop => Box::new(VarianceAccumulator::new(true, false)),
+            AggregateFunction::Median => Box::new(PercentileAccumulator::new(0.5, true)),
+            AggregateFunction::PercentileCont => Box::new(PercentileAccumulator::new(
+                self.fraction_param(params)?,
+                true,
+            )),
+            AggregateFunction::PercentileDisc => Box::new(PercentileAccumulator::new(
+                self.fraction_param(params)?,
+                false,
+            )),
+            AggregateFunction::Mode => Box::<ModeAccumulator>::default(),
+            AggregateFunction::BoolAnd => Box::new(BoolAccumulator::new(true)),
+            AggregateFunction::BoolOr => Box::new(BoolAccumulator::new(false)),
+            AggregateFunction::AnyValue => Box::<AnyValueAccumulator>::default(),
+        })
+    }
+}
+
+/// Accumulator wrapper that passes each distinct value to the inner accumulator once
+///
+/// Used for `COUNT(DISTINCT x)`, `SUM(DISTINCT x)` and the like.
+pub struct DistinctAccumulator {
+    /// Values already passed on
+    seen: HashSet<Value>,
+    /// The accumulator that receives the distinct values
+    inner: Box<dyn Accumulator>,
+}
+
+impl DistinctAccumulator {
+    /// Wrap an accumulator so that it only sees distinct values
+    pub fn new(inner: Box<dyn Accumulator>) -> Self {
+        DistinctAccumulator {
+            seen: HashSet::new(),
+            inner,
         }
+    }
+}
 
-        // If no numeric values were found, return NULL
-        if count == 0 {
-            return Ok(Value::Null);
+impl Accumulator for DistinctAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if self.seen.insert(value.clone()) {
+            self.inner.update(value)?;
         }
+        Ok(())
+    }
 
-        // Return the sum in the appropriate type
-        if is_float {
-            Ok(Value::Float(float_sum))
-        } else {
-            Ok(Value::Integer(int_sum))
+    fn state(&self) -> Vec<Value> {
+        self.seen.iter().cloned().collect()
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        self.inner.finalize()
+    }
+}
+
+/// Get the single integer held in an accumulator state
+fn integer_state(state: &[Value]) -> SqawkResult<i64> {
+    match state.first() {
+        Some(Value::Integer(n)) => Ok(*n),
+        other => Err(SqawkError::TypeError(format!(
+            "Invalid aggregate state: {:?}",
+            other
+        ))),
+    }
+}
+
+/// COUNT accumulator - counts non-NULL values
+#[derive(Debug, Default)]
+struct CountAccumulator {
+    count: i64,
+}
+
+impl Accumulator for CountAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if !matches!(value, Value::Null) {
+            self.count += 1;
         }
+        Ok(())
     }
 
-    /// AVG function implementation
-    ///
-    /// Calculates the average of numeric values in the column
-    fn avg(&self, values: &[Value]) -> SqawkResult<Value> {
-        // Get the sum first
-        let sum = self.sum(values)?;
+    fn state(&self) -> Vec<Value> {
+        vec![Value::Integer(self.count)]
+    }
 
-        // Count numeric values
-        let count = values
-            .iter()
-            .filter(|v| matches!(v, Value::Integer(_) | Value::Float(_)))
-            .count();
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        self.count += integer_state(state)?;
+        Ok(())
+    }
 
-        if count == 0 {
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(Value::Integer(self.count))
+    }
+}
+
+/// SUM accumulator - sums numeric values
+///
+/// Integer sums that overflow continue in floating point (see the arithmetic
+/// module); non-numeric values are ignored and an empty sum is NULL.
+#[derive(Debug, Default)]
+struct SumAccumulator {
+    sum: Option<Value>,
+}
+
+impl SumAccumulator {
+    /// The running total, or NULL if nothing has been added
+    fn finalize_value(&self) -> Value {
+        self.sum.clone().unwrap_or(Value::Null)
+    }
+}
+
+impl Accumulator for SumAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if !matches!(value, Value::Integer(_) | Value::Float(_)) {
+            return Ok(());
+        }
+        self.sum = Some(match &self.sum {
+            None => value.clone(),
+            Some(sum) => ArithmeticOperator::Add.apply(sum, value)?,
+        });
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        vec![self.finalize_value()]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(self.finalize_value())
+    }
+}
+
+/// AVG accumulator - average of numeric values, always a float
+#[derive(Debug, Default)]
+struct AvgAccumulator {
+    sum: SumAccumulator,
+    count: i64,
+}
+
+impl Accumulator for AvgAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if matches!(value, Value::Integer(_) | Value::Float(_)) {
+            self.sum.update(value)?;
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        vec![Value::Integer(self.count), self.sum.finalize_value()]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        self.count += integer_state(state)?;
+        self.sum.merge(&state[1..])
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        if self.count == 0 {
             return Ok(Value::Null);
         }
 
         // Convert to float and divide by count
-        match sum {
-            Value::Integer(i) => Ok(Value::Float(i as f64 / count as f64)),
-            Value::Float(f) => Ok(Value::Float(f / count as f64)),
+        match self.sum.finalize_value() {
+            Value::Integer(i) => Ok(Value::Float(i as f64 / self.count as f64)),
+            Value::Float(f) => Ok(Value::Float(f / self.count as f64)),
             _ => Ok(Value::Null), // This shouldn't happen, but handle it just in case
         }
     }
+}
 
-    /// MIN function implementation
-    ///
-    /// Finds the minimum value in the column
-    fn min(&self, values: &[Value]) -> SqawkResult<Value> {
-        // Filter out NULL values
-        let non_null_values: Vec<&Value> = values
-            .iter()
-            .filter(|v| !matches!(v, Value::Null))
-            .collect();
+/// MIN and MAX accumulator - keeps the smallest or largest non-NULL value
+#[derive(Debug)]
+struct ExtremeAccumulator {
+    /// Keep the largest value (MAX) rather than the smallest (MIN)
+    keep_greater: bool,
+    best: Option<Value>,
+}
 
-        if non_null_values.is_empty() {
+impl ExtremeAccumulator {
+    fn new(keep_greater: bool) -> Self {
+        ExtremeAccumulator {
+            keep_greater,
+            best: None,
+        }
+    }
+}
+
+impl Accumulator for ExtremeAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if matches!(value, Value::Null) {
+            return Ok(());
+        }
+        let replace = match &self.best {
+            None => true,
+            Some(best) if self.keep_greater => value > best,
+            Some(best) => value < best,
+        };
+        if replace {
+            self.best = Some(value.clone());
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        vec![self.best.clone().unwrap_or(Value::Null)]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(self.best.clone().unwrap_or(Value::Null))
+    }
+}
+
+/// Get a numeric value as f64, or None for non-numeric values
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// GROUP_CONCAT accumulator - joins the text of non-NULL values
+#[derive(Debug)]
+struct GroupConcatAccumulator {
+    separator: String,
+    parts: Vec<String>,
+}
+
+impl GroupConcatAccumulator {
+    fn new(separator: String) -> Self {
+        GroupConcatAccumulator {
+            separator,
+            parts: Vec::new(),
+        }
+    }
+}
+
+impl Accumulator for GroupConcatAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if !matches!(value, Value::Null) {
+            self.parts.push(value.to_string());
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        self.parts.iter().cloned().map(Value::String).collect()
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        if self.parts.is_empty() {
             return Ok(Value::Null);
         }
+        Ok(Value::String(self.parts.join(&self.separator)))
+    }
+}
 
-        // Start with the first value as our minimum
-        let mut min_value = non_null_values[0].clone();
+/// Variance and standard deviation accumulator
+///
+/// Uses Welford's online algorithm, so the values are never stored. Non-numeric
+/// values are ignored. A sample statistic needs at least two values and a
+/// population statistic at least one; otherwise the result is NULL.
+#[derive(Debug)]
+struct VarianceAccumulator {
+    /// Divide by n (population) rather than n - 1 (sample)
+    population: bool,
+    /// Return the standard deviation rather than the variance
+    stddev: bool,
+    count: i64,
+    mean: f64,
+    /// Sum of squared differences from the mean
+    m2: f64,
+}
 
-        // Compare with the rest of the values
-        for value in &non_null_values[1..] {
-            if value < &&min_value {
-                min_value = (*value).clone();
+impl VarianceAccumulator {
+    fn new(population: bool, stddev: bool) -> Self {
+        VarianceAccumulator {
+            population,
+            stddev,
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl Accumulator for VarianceAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if let Some(x) = numeric_value(value) {
+            self.count += 1;
+            let delta = x - self.mean;
+            self.mean += delta / self.count as f64;
+            self.m2 += delta * (x - self.mean);
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        vec![
+            Value::Integer(self.count),
+            Value::Float(self.mean),
+            Value::Float(self.m2),
+        ]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        // Combine the partial statistics (Chan et al.)
+        let other_count = integer_state(state)?;
+        let (other_mean, other_m2) = match state.get(1..3) {
+            Some([Value::Float(mean), Value::Float(m2)]) => (*mean, *m2),
+            _ => {
+                return Err(SqawkError::TypeError(format!(
+                    "Invalid aggregate state: {:?}",
+                    state
+                )))
             }
+        };
+        if other_count == 0 {
+            return Ok(());
         }
 
-        Ok(min_value)
+        let total = self.count + other_count;
+        let delta = other_mean - self.mean;
+        self.mean += delta * other_count as f64 / total as f64;
+        self.m2 += other_m2 + delta * delta * self.count as f64 * other_count as f64 / total as f64;
+        self.count = total;
+        Ok(())
     }
 
-    /// MAX function implementation
-    ///
-    /// Finds the maximum value in the column
-    fn max(&self, values: &[Value]) -> SqawkResult<Value> {
-        // Filter out NULL values
-        let non_null_values: Vec<&Value> = values
-            .iter()
-            .filter(|v| !matches!(v, Value::Null))
-            .collect();
+    fn finalize(&self) -> SqawkResult<Value> {
+        let divisor = if self.population {
+            self.count
+        } else {
+            self.count - 1
+        };
+        if divisor <= 0 {
+            return Ok(Value::Null);
+        }
+
+        let variance = self.m2 / divisor as f64;
+        Ok(Value::Float(if self.stddev {
+            variance.sqrt()
+        } else {
+            variance
+        }))
+    }
+}
+
+/// MEDIAN and PERCENTILE_CONT / PERCENTILE_DISC accumulator
+///
+/// Order statistics need every value, so they are kept until the group is complete.
+#[derive(Debug)]
+struct PercentileAccumulator {
+    fraction: f64,
+    /// Interpolate between neighbouring values (PERCENTILE_CONT) rather than
+    /// picking one (PERCENTILE_DISC)
+    continuous: bool,
+    values: Vec<Value>,
+}
+
+impl PercentileAccumulator {
+    fn new(fraction: f64, continuous: bool) -> Self {
+        PercentileAccumulator {
+            fraction,
+            continuous,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl Accumulator for PercentileAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        // Interpolation needs numbers; a discrete percentile works on any ordered values
+        let accepted = if self.continuous {
+            numeric_value(value).is_some()
+        } else {
+            !matches!(value, Value::Null)
+        };
+        if accepted {
+            self.values.push(value.clone());
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        self.values.clone()
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
 
-        if non_null_values.is_empty() {
+    fn finalize(&self) -> SqawkResult<Value> {
+        if self.values.is_empty() {
             return Ok(Value::Null);
         }
 
-        // Start with the first value as our maximum
-        let mut max_value = non_null_values[0].clone();
+        let mut sorted = self.values.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
-        // Compare with the rest of the values
-        for value in &non_null_values[1..] {
-            if value > &&max_value {
-                max_value = (*value).clone();
+        if !self.continuous {
+            // The first value whose cumulative distribution reaches the fraction
+            let position = (self.fraction * sorted.len() as f64).ceil() as usize;
+            return Ok(sorted[position.max(1) - 1].clone());
+        }
+
+        let rank = self.fraction * (sorted.len() - 1) as f64;
+        let lower = numeric_value(&sorted[rank.floor() as usize]).unwrap_or(0.0);
+        let upper = numeric_value(&sorted[rank.ceil() as usize]).unwrap_or(0.0);
+        Ok(Value::Float(lower + (upper - lower) * rank.fract()))
+    }
+}
+
+/// MODE accumulator - the most frequent non-NULL value, the smallest on a tie
+#[derive(Debug, Default)]
+struct ModeAccumulator {
+    counts: HashMap<Value, i64>,
+}
+
+impl Accumulator for ModeAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if !matches!(value, Value::Null) {
+            *self.counts.entry(value.clone()).or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        // Value and count pairs
+        self.counts
+            .iter()
+            .flat_map(|(value, count)| [value.clone(), Value::Integer(*count)])
+            .collect()
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        for pair in state.chunks(2) {
+            let count = integer_state(&pair[1..])?;
+            *self.counts.entry(pair[0].clone()).or_insert(0) += count;
+        }
+        Ok(())
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        let mut best: Option<(&Value, i64)> = None;
+        for (value, &count) in &self.counts {
+            let better = match best {
+                None => true,
+                Some((best_value, best_count)) => {
+                    count > best_count || (count == best_count && value < best_value)
+                }
+            };
+            if better {
+                best = Some((value, count));
             }
         }
+        Ok(best.map(|(value, _)| value.clone()).unwrap_or(Value::Null))
+    }
+}
+
+/// BOOL_AND and BOOL_OR accumulator
+#[derive(Debug)]
+struct BoolAccumulator {
+    /// Combine with AND (BOOL_AND) rather than OR (BOOL_OR)
+    all: bool,
+    result: Option<bool>,
+}
+
+impl BoolAccumulator {
+    fn new(all: bool) -> Self {
+        BoolAccumulator { all, result: None }
+    }
+}
+
+impl Accumulator for BoolAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        let b = match value {
+            Value::Null => return Ok(()),
+            Value::Boolean(b) => *b,
+            other => {
+                return Err(SqawkError::TypeError(format!(
+                    "{} requires boolean values, got {}",
+                    if self.all { "BOOL_AND" } else { "BOOL_OR" },
+                    other.type_name()
+                )))
+            }
+        };
+        self.result = Some(match self.result {
+            None => b,
+            Some(acc) if self.all => acc && b,
+            Some(acc) => acc || b,
+        });
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        vec![self.result.map(Value::Boolean).unwrap_or(Value::Null)]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(self.result.map(Value::Boolean).unwrap_or(Value::Null))
+    }
+}
+
+/// ANY_VALUE accumulator - keeps the first non-NULL value
+#[derive(Debug, Default)]
+struct AnyValueAccumulator {
+    value: Option<Value>,
+}
+
+impl Accumulator for AnyValueAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if self.value.is_none() && !matches!(value, Value::Null) {
+            self.value = Some(value.clone());
+        }
+        Ok(())
+    }
 
-        Ok(max_value)
+    fn state(&self) -> Vec<Value> {
+        vec![self.finalize_value()]
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(self.finalize_value())
+    }
+}
+
+impl AnyValueAccumulator {
+    /// The kept value, or NULL if there was none
+    fn finalize_value(&self) -> Value {
+        self.value.clone().unwrap_or(Value::Null)
     }
 }
 
@@ -241,6 +819,14 @@ mod tests {
         assert_eq!(sum, Value::Integer(60));
     }
 
+    #[test]
+    fn test_sum_overflow_promotes_to_float() {
+        let values = vec![Value::Integer(i64::MAX), Value::Integer(i64::MAX)];
+
+        let sum = AggregateFunction::Sum.execute(&values).unwrap();
+        assert_eq!(sum, Value::Float(i64::MAX as f64 * 2.0));
+    }
+
     #[test]
     fn test_avg_function() {
         let values = vec![
@@ -309,4 +895,185 @@ mod tests {
         let max = AggregateFunction::Max.execute(&values).unwrap();
         assert_eq!(max, Value::String("xyz".to_string()));
     }
+
+    #[test]
+    fn test_accumulator_merge() {
+        // Partial aggregates over two halves combine into the whole-column result
+        for func in [
+            AggregateFunction::Count,
+            AggregateFunction::Sum,
+            AggregateFunction::Avg,
+            AggregateFunction::Min,
+            AggregateFunction::Max,
+        ] {
+            let values = [
+                Value::Integer(4),
+                Value::Null,
+                Value::Float(1.5),
+                Value::Integer(10),
+            ];
+            let mut left = func.create_accumulator(&[]).unwrap();
+            let mut right = func.create_accumulator(&[]).unwrap();
+            for value in &values[..2] {
+                left.update(value).unwrap();
+            }
+            for value in &values[2..] {
+                right.update(value).unwrap();
+            }
+            left.merge(&right.state()).unwrap();
+
+            assert_eq!(
+                left.finalize().unwrap(),
+                func.execute(&values).unwrap(),
+                "{:?}",
+                func
+            );
+        }
+    }
+
+    #[test]
+    fn test_variance_and_stddev() {
+        let values = vec![
+            Value::Integer(2),
+            Value::Integer(4),
+            Value::Null,
+            Value::Integer(4),
+            Value::Integer(4),
+            Value::Integer(5),
+            Value::Integer(5),
+            Value::Integer(7),
+            Value::Integer(9),
+        ];
+
+        assert_eq!(
+            AggregateFunction::VarPop.execute(&values).unwrap(),
+            Value::Float(4.0)
+        );
+        assert_eq!(
+            AggregateFunction::StddevPop.execute(&values).unwrap(),
+            Value::Float(2.0)
+        );
+        assert_eq!(
+            AggregateFunction::VarSamp.execute(&values).unwrap(),
+            Value::Float(32.0 / 7.0)
+        );
+
+        // A sample statistic needs two values
+        assert_eq!(
+            AggregateFunction::StddevSamp
+                .execute(&[Value::Integer(3)])
+                .unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let values = vec![
+            Value::Integer(40),
+            Value::Integer(10),
+            Value::Null,
+            Value::Integer(30),
+            Value::Integer(20),
+        ];
+
+        assert_eq!(
+            AggregateFunction::Median.execute(&values).unwrap(),
+            Value::Float(25.0)
+        );
+
+        let fraction = [Value::Float(0.25)];
+        let mut cont = AggregateFunction::PercentileCont
+            .create_accumulator(&fraction)
+            .unwrap();
+        let mut disc = AggregateFunction::PercentileDisc
+            .create_accumulator(&fraction)
+            .unwrap();
+        for value in &values {
+            cont.update(value).unwrap();
+            disc.update(value).unwrap();
+        }
+        assert_eq!(cont.finalize().unwrap(), Value::Float(17.5));
+        assert_eq!(disc.finalize().unwrap(), Value::Integer(10));
+
+        // The fraction is required and must lie in [0, 1]
+        assert!(AggregateFunction::PercentileCont
+            .create_accumulator(&[])
+            .is_err());
+        assert!(AggregateFunction::PercentileDisc
+            .create_accumulator(&[Value::Float(1.5)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_group_concat_mode_and_bool() {
+        let mut concat = AggregateFunction::GroupConcat
+            .create_accumulator(&[Value::String("; ".to_string())])
+            .unwrap();
+        for value in [
+            Value::String("a".to_string()),
+            Value::Null,
+            Value::Integer(2),
+        ] {
+            concat.update(&value).unwrap();
+        }
+        assert_eq!(
+            concat.finalize().unwrap(),
+            Value::String("a; 2".to_string())
+        );
+
+        let values = vec![
+            Value::String("y".to_string()),
+            Value::String("x".to_string()),
+            Value::String("y".to_string()),
+            Value::String("x".to_string()),
+        ];
+        // Ties go to the smallest value
+        assert_eq!(
+            AggregateFunction::Mode.execute(&values).unwrap(),
+            Value::String("x".to_string())
+        );
+        assert_eq!(
+            AggregateFunction::AnyValue
+                .execute(&[Value::Null, Value::Integer(3)])
+                .unwrap(),
+            Value::Integer(3)
+        );
+
+        let flags = vec![Value::Boolean(true), Value::Null, Value::Boolean(false)];
+        assert_eq!(
+            AggregateFunction::BoolAnd.execute(&flags).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            AggregateFunction::BoolOr.execute(&flags).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(AggregateFunction::BoolOr
+            .execute(&[Value::Integer(1)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_distinct_accumulator() {
+        let mut sum =
+            DistinctAccumulator::new(AggregateFunction::Sum.create_accumulator(&[]).unwrap());
+        for value in [1, 2, 2, 3, 1] {
+            sum.update(&Value::Integer(value)).unwrap();
+        }
+        assert_eq!(sum.finalize().unwrap(), Value::Integer(6));
+
+        // Values that compare equal are the same distinct value
+        let mut count =
+            DistinctAccumulator::new(AggregateFunction::Count.create_accumulator(&[]).unwrap());
+        for value in [
+            Value::Integer(1),
+            Value::Float(1.0),
+            Value::Float(-0.0),
+            Value::Integer(0),
+        ] {
+            count.update(&value).unwrap();
+        }
+        assert_eq!(count.finalize().unwrap(), Value::Integer(2));
+    }
 }
@@ -0,0 +1,372 @@
+//! Arithmetic operator module for sqawk
+//!
+//! This module implements the SQL arithmetic operators (+, -, *, /, DIV, %) used by both
+//! the direct SQL executor and the bytecode VM, so that the two engines agree on
+//! the result of every calculation.
+//!
+//! The semantics are:
+//!
+//! - Any NULL operand produces NULL
+//! - Two integers produce an integer, except `/` which always produces a float
+//! - If either operand is a float, the calculation is done in floating point
+//! - Integer results that would overflow 64 bits are promoted to float instead of wrapping,
+//!   except for `DIV`, whose result must stay an integer: its one overflowing case
+//!   (`i64::MIN DIV -1`) raises `SqawkError::ArithmeticOverflow`
+//! - A floating-point result that overflows to infinity raises `SqawkError::ArithmeticOverflow`
+//! - `/`, `DIV` and `%` raise `SqawkError::DivideByZero` for a zero divisor
+//! - `DIV` truncates the quotient toward zero
+//! - `%` returns the remainder with the sign of the dividend
+//! - Strings and booleans are not implicitly converted and raise a type error
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::table::Value;
+use sqlparser::ast::BinaryOperator;
+
+/// Supported arithmetic operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    /// Addition (+)
+    Add,
+    /// Subtraction (-)
+    Subtract,
+    /// Multiplication (*)
+    Multiply,
+    /// Division (/) - always produces a float
+    Divide,
+    /// Integer division (DIV) - truncates toward zero
+    IntegerDivide,
+    /// Remainder (%) - takes the sign of the dividend
+    Modulo,
+}
+
+impl ArithmeticOperator {
+    /// Convert a SQL binary operator to an ArithmeticOperator
+    ///
+    /// # Arguments
+    /// * `op` - The binary operator from the parsed SQL expression
+    ///
+    /// # Returns
+    /// * Some(ArithmeticOperator) if the operator is arithmetic, None otherwise
+    pub fn from_binary_operator(op: &BinaryOperator) -> Option<Self> {
+        match op {
+            BinaryOperator::Plus => Some(ArithmeticOperator::Add),
+            BinaryOperator::Minus => Some(ArithmeticOperator::Subtract),
+            BinaryOperator::Multiply => Some(ArithmeticOperator::Multiply),
+            BinaryOperator::Divide => Some(ArithmeticOperator::Divide),
+            BinaryOperator::MyIntegerDivide | BinaryOperator::DuckIntegerDivide => {
+                Some(ArithmeticOperator::IntegerDivide)
+            }
+            BinaryOperator::Modulo => Some(ArithmeticOperator::Modulo),
+            _ => None,
+        }
+    }
+
+    /// The SQL symbol for this operator, used in error messages
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ArithmeticOperator::Add => "+",
+            ArithmeticOperator::Subtract => "-",
+            ArithmeticOperator::Multiply => "*",
+            ArithmeticOperator::Divide => "/",
+            ArithmeticOperator::IntegerDivide => "DIV",
+            ArithmeticOperator::Modulo => "%",
+        }
+    }
+
+    /// Apply the operator to two values
+    ///
+    /// # Arguments
+    /// * `left` - The left operand
+    /// * `right` - The right operand
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The result of the calculation (NULL if either operand is NULL)
+    /// * `Err(SqawkError::DivideByZero)` - If the divisor of /, DIV or % is zero
+    /// * `Err(SqawkError::ArithmeticOverflow)` - If a float or DIV result overflows
+    /// * `Err(SqawkError::TypeError)` - If either operand is not numeric
+    pub fn apply(&self, left: &Value, right: &Value) -> SqawkResult<Value> {
+        match (left, right) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Integer(a), Value::Integer(b)) => self.apply_integers(*a, *b),
+            (Value::Integer(a), Value::Float(b)) => self.apply_floats(*a as f64, *b),
+            (Value::Float(a), Value::Integer(b)) => self.apply_floats(*a, *b as f64),
+            (Value::Float(a), Value::Float(b)) => self.apply_floats(*a, *b),
+            _ => Err(SqawkError::TypeError(format!(
+                "Cannot apply '{}' to {} '{}' and {} '{}'",
+                self.symbol(),
+                left.type_name(),
+                left,
+                right.type_name(),
+                right
+            ))),
+        }
+    }
+
+    /// Apply the operator to two integers, promoting to float on overflow (except DIV)
+    fn apply_integers(&self, a: i64, b: i64) -> SqawkResult<Value> {
+        let checked = match self {
+            ArithmeticOperator::Add => a.checked_add(b),
+            ArithmeticOperator::Subtract => a.checked_sub(b),
+            ArithmeticOperator::Multiply => a.checked_mul(b),
+            ArithmeticOperator::Divide => return self.apply_floats(a as f64, b as f64),
+            ArithmeticOperator::IntegerDivide => {
+                if b == 0 {
+                    return Err(SqawkError::DivideByZero);
+                }
+                // Integer division never promotes: only i64::MIN DIV -1 overflows
+                return a.checked_div(b).map(Value::Integer).ok_or_else(|| {
+                    SqawkError::ArithmeticOverflow(format!("{} DIV {} is out of range", a, b))
+                });
+            }
+            ArithmeticOperator::Modulo => {
+                if b == 0 {
+                    return Err(SqawkError::DivideByZero);
+                }
+                // Only i64::MIN % -1 overflows, and its remainder is 0
+                Some(a.wrapping_rem(b))
+            }
+        };
+
+        match checked {
+            Some(result) => Ok(Value::Integer(result)),
+            None => self.apply_floats(a as f64, b as f64),
+        }
+    }
+
+    /// Apply the operator to two floats, rejecting results that overflow
+    fn apply_floats(&self, a: f64, b: f64) -> SqawkResult<Value> {
+        let result = match self {
+            ArithmeticOperator::Add => a + b,
+            ArithmeticOperator::Subtract => a - b,
+            ArithmeticOperator::Multiply => a * b,
+            ArithmeticOperator::Divide | ArithmeticOperator::IntegerDivide => {
+                if b == 0.0 {
+                    return Err(SqawkError::DivideByZero);
+                }
+                if *self == ArithmeticOperator::IntegerDivide {
+                    (a / b).trunc()
+                } else {
+                    a / b
+                }
+            }
+            ArithmeticOperator::Modulo => {
+                if b == 0.0 {
+                    return Err(SqawkError::DivideByZero);
+                }
+                a % b
+            }
+        };
+
+        check_float(result, a.is_finite() && b.is_finite(), || {
+            format!("{:?} {} {:?} is out of range", a, self.symbol(), b)
+        })
+    }
+}
+
+/// Negate a value (the unary minus operator)
+///
+/// Follows the same rules as the binary operators: NULL stays NULL and
+/// negating the smallest 64-bit integer promotes to float.
+///
+/// # Arguments
+/// * `val` - The value to negate
+///
+/// # Returns
+/// * `Ok(Value)` - The negated value
+/// * `Err(SqawkError::TypeError)` - If the value is not numeric
+pub fn negate(val: &Value) -> SqawkResult<Value> {
+    match val {
+        Value::Null => Ok(Value::Null),
+        Value::Integer(i) => Ok(i
+            .checked_neg()
+            .map(Value::Integer)
+            .unwrap_or(Value::Float(-(*i as f64)))),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        _ => Err(SqawkError::TypeError(format!(
+            "Cannot apply negation to non-numeric value: {:?}",
+            val
+        ))),
+    }
+}
+
+/// Sum a sequence of numbers with the same promotion rules as `+`
+///
+/// Integers are summed exactly until the total no longer fits in 64 bits,
+/// after which the sum continues in floating point. Non-numeric values are ignored.
+///
+/// # Arguments
+/// * `values` - The values to sum
+///
+/// # Returns
+/// * `Ok(Some(Value))` - The total, as an integer if every addend was an integer and it fits
+/// * `Ok(None)` - If there were no numeric values
+/// * `Err(SqawkError::ArithmeticOverflow)` - If the floating-point total overflows
+pub fn checked_sum<'a, I>(values: I) -> SqawkResult<Option<Value>>
+where
+    I: IntoIterator<Item = &'a Value>,
+{
+    let mut total: Option<Value> = None;
+
+    for value in values {
+        if !matches!(value, Value::Integer(_) | Value::Float(_)) {
+            continue;
+        }
+        total = Some(match total {
+            None => value.clone(),
+            Some(sum) => ArithmeticOperator::Add.apply(&sum, value)?,
+        });
+    }
+
+    Ok(total)
+}
+
+/// Check that a floating-point result did not overflow
+///
+/// A non-finite result is only an error if the inputs were finite; an infinite
+/// or NaN input is allowed to propagate.
+fn check_float(
+    result: f64,
+    inputs_finite: bool,
+    describe: impl FnOnce() -> String,
+) -> SqawkResult<Value> {
+    if !result.is_finite() && inputs_finite {
+        return Err(SqawkError::ArithmeticOverflow(describe()));
+    }
+    Ok(Value::Float(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_arithmetic() {
+        let a = Value::Integer(7);
+        let b = Value::Integer(2);
+        assert_eq!(
+            ArithmeticOperator::Add.apply(&a, &b).unwrap(),
+            Value::Integer(9)
+        );
+        assert_eq!(
+            ArithmeticOperator::Subtract.apply(&a, &b).unwrap(),
+            Value::Integer(5)
+        );
+        assert_eq!(
+            ArithmeticOperator::Multiply.apply(&a, &b).unwrap(),
+            Value::Integer(14)
+        );
+        assert_eq!(
+            ArithmeticOperator::Divide.apply(&a, &b).unwrap(),
+            Value::Float(3.5)
+        );
+        assert_eq!(
+            ArithmeticOperator::IntegerDivide.apply(&a, &b).unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            ArithmeticOperator::Modulo.apply(&a, &b).unwrap(),
+            Value::Integer(1)
+        );
+    }
+
+    #[test]
+    fn test_truncation_and_sign() {
+        let a = Value::Integer(-7);
+        let b = Value::Integer(2);
+        assert_eq!(
+            ArithmeticOperator::IntegerDivide.apply(&a, &b).unwrap(),
+            Value::Integer(-3)
+        );
+        assert_eq!(
+            ArithmeticOperator::Modulo.apply(&a, &b).unwrap(),
+            Value::Integer(-1)
+        );
+        assert_eq!(
+            ArithmeticOperator::IntegerDivide
+                .apply(&Value::Float(7.5), &Value::Integer(2))
+                .unwrap(),
+            Value::Float(3.0)
+        );
+        assert_eq!(
+            ArithmeticOperator::Modulo
+                .apply(&Value::Float(7.5), &Value::Integer(2))
+                .unwrap(),
+            Value::Float(1.5)
+        );
+    }
+
+    #[test]
+    fn test_overflow_promotes_to_float() {
+        let max = Value::Integer(i64::MAX);
+        let min = Value::Integer(i64::MIN);
+        let one = Value::Integer(1);
+        assert_eq!(
+            ArithmeticOperator::Add.apply(&max, &one).unwrap(),
+            Value::Float(i64::MAX as f64 + 1.0)
+        );
+        assert_eq!(
+            ArithmeticOperator::Subtract.apply(&min, &one).unwrap(),
+            Value::Float(i64::MIN as f64 - 1.0)
+        );
+        assert!(matches!(
+            ArithmeticOperator::Multiply.apply(&max, &Value::Integer(2)),
+            Ok(Value::Float(_))
+        ));
+        assert!(matches!(
+            ArithmeticOperator::IntegerDivide.apply(&min, &Value::Integer(-1)),
+            Err(SqawkError::ArithmeticOverflow(_))
+        ));
+        assert_eq!(
+            ArithmeticOperator::Modulo
+                .apply(&min, &Value::Integer(-1))
+                .unwrap(),
+            Value::Integer(0)
+        );
+        assert_eq!(negate(&min).unwrap(), Value::Float(-(i64::MIN as f64)));
+    }
+
+    #[test]
+    fn test_float_overflow_is_error() {
+        let result = ArithmeticOperator::Multiply.apply(&Value::Float(1e308), &Value::Integer(10));
+        assert!(matches!(result, Err(SqawkError::ArithmeticOverflow(_))));
+    }
+
+    #[test]
+    fn test_null_zero_and_type_errors() {
+        let one = Value::Integer(1);
+        assert_eq!(
+            ArithmeticOperator::Add.apply(&Value::Null, &one).unwrap(),
+            Value::Null
+        );
+        for op in [
+            ArithmeticOperator::Divide,
+            ArithmeticOperator::IntegerDivide,
+            ArithmeticOperator::Modulo,
+        ] {
+            assert!(matches!(
+                op.apply(&one, &Value::Integer(0)),
+                Err(SqawkError::DivideByZero)
+            ));
+            assert!(matches!(
+                op.apply(&one, &Value::Float(0.0)),
+                Err(SqawkError::DivideByZero)
+            ));
+        }
+        assert!(matches!(
+            ArithmeticOperator::Add.apply(&one, &Value::String("x".to_string())),
+            Err(SqawkError::TypeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        let values = vec![Value::Integer(i64::MAX), Value::Integer(1), Value::Null];
+        assert_eq!(
+            checked_sum(&values).unwrap(),
+            Some(Value::Float(i64::MAX as f64 + 1.0))
+        );
+        let values = vec![Value::Integer(2), Value::String("x".to_string())];
+        assert_eq!(checked_sum(&values).unwrap(), Some(Value::Integer(2)));
+        assert_eq!(checked_sum(&[Value::Null]).unwrap(), None);
+    }
+}
@@ -10,9 +10,20 @@
 //! - Opt-in file modification with the --write flag
 //! - Diagnostic output control via the --verbose flag
 
+use std::collections::HashMap;
+
 use anyhow::Result;
 use clap::Parser;
 
+use crate::csv_dialect::{DialectChar, DialectSettings, LineTerminator};
+use crate::encoding::{EncodingName, EncodingSettings};
+use crate::error::SqawkResult;
+use crate::file_handler::FileFormat;
+use crate::fixed_width_handler::FixedWidthLayout;
+use crate::header_options::{HeaderMode, HeaderSettings, TableOption};
+use crate::output::OutputFormat;
+use crate::table::ValueFormat;
+
 /// Command-line arguments for sqawk
 ///
 /// This struct represents all configurable aspects of the application through
@@ -53,11 +64,14 @@ pub struct SqawkArgs {
     ///
     /// Users can optionally specify a table name by prefixing the file path.
     /// If no table name is specified, the base filename (without extension)
-    /// is used as the table name in SQL queries.
+    /// is used as the table name in SQL queries. A file path of `-` reads
+    /// standard input, as a table named "stdin" unless a name is given.
+    /// Files may be omitted when no statement reads a table, as with
+    /// `SELECT 1 + 1` or a VALUES list.
     /// Example: users=data/people.csv or just data/products.csv
     #[clap(
-        required = true,
-        help = "Input files to process as [table_name=]file_path"
+        num_args = 0..,
+        help = "Input files to process as [table_name=]file_path ('-' reads stdin)"
     )]
     pub files: Vec<String>,
 
@@ -67,9 +81,13 @@ pub struct SqawkArgs {
     /// specifying explicit column names to use instead of the default a,b,c,... naming.
     /// Multiple table definitions can be provided for different tables.
     /// Example: --tabledef=passwd:username,password,uid,gid,gecos,home,shell
+    ///
+    /// Giving columns as name@start-end (1-based, inclusive) makes the table's file
+    /// fixed-width, and table_name:@path reads such a layout from a file.
+    /// Example: --tabledef=accounts:id@1-6,name@7-30,amount@31-40
     #[clap(
         long,
-        help = "Define column names for tables as table_name:col1,col2,col3,..."
+        help = "Define column names for tables as table_name:col1,col2,col3,... or fixed-width columns as table_name:col1@1-6,col2@7-30,..."
     )]
     pub tabledef: Vec<String>,
 
@@ -78,9 +96,155 @@ pub struct SqawkArgs {
     /// Similar to awk's -F option, this sets the field separator for all input files.
     /// Default behavior is to use commas for .csv files and tabs for other file types.
     /// Examples: -F: for colon-separated files, -F\\t for tab-separated files.
-    #[clap(short = 'F', help = "Field separator character")]
+    /// As in awk, `-F ' '` (or `-F whitespace`) splits on runs of whitespace; longer
+    /// strings are literal separators and `/pattern/` is a regular expression.
+    #[clap(
+        short = 'F',
+        help = "Field separator: a character, ' ' or 'whitespace' for whitespace, a string, or /regex/"
+    )]
     pub field_separator: Option<String>,
 
+    /// Format of the input files
+    ///
+    /// By default the format is detected from each file's extension, and files
+    /// without one (including stdin) are read as CSV. This option applies one
+    /// format to every input file instead, which is needed for stdin and for
+    /// paths such as process substitutions that have no meaningful extension.
+    #[clap(
+        long = "input-format",
+        help = "Input format for all files: csv, tsv, json, ndjson or fixed-width (default: by extension)"
+    )]
+    pub input_format: Option<FileFormat>,
+
+    /// Whether input files have a header row
+    ///
+    /// present (the default) uses the header row as column names; absent reads
+    /// every line as data, with columns named a, b, c, ... or by --tabledef; auto
+    /// uses the row as a header only if every field is distinct, non-empty text
+    /// that doesn't load as a number, boolean or NULL. Prefix the mode with a
+    /// table name to set it for one file.
+    /// Example: --header absent --header people=present
+    #[clap(
+        long,
+        value_name = "[TABLE=]MODE",
+        help = "Header row mode: present, absent or auto, for all files or TABLE (repeatable)"
+    )]
+    pub header: Vec<TableOption<HeaderMode>>,
+
+    /// Read input files as having no header row (same as --header absent)
+    #[clap(
+        long,
+        help = "Input files have no header row (same as --header absent)"
+    )]
+    pub no_header: bool,
+
+    /// Line number of the header row
+    ///
+    /// Lines above the header, such as report titles, are skipped when loading
+    /// and written back unchanged with --write.
+    #[clap(
+        long = "header-row",
+        value_name = "[TABLE=]N",
+        help = "Line number of the header row; earlier lines are skipped (default: 1)"
+    )]
+    pub header_row: Vec<TableOption<usize>>,
+
+    /// Number of lines to skip before the data
+    ///
+    /// Skips lines after the header row, such as units or separator lines, or
+    /// at the start of a file without a header. Skipped lines are written back
+    /// unchanged with --write.
+    #[clap(
+        long = "skip-lines",
+        value_name = "[TABLE=]N",
+        help = "Number of lines to skip between the header and the data (default: 0)"
+    )]
+    pub skip_lines: Vec<TableOption<usize>>,
+
+    /// Quote character of input files
+    ///
+    /// Fields enclosed in this character may contain delimiters, quotes and line
+    /// breaks. With none, quotes are read as ordinary text and never written.
+    /// Example: --quote "'" --quote legacy=none
+    #[clap(
+        long,
+        value_name = "[TABLE=]CHAR",
+        help = "Quote character, or none to read quotes as text (default: \")"
+    )]
+    pub quote: Vec<TableOption<DialectChar>>,
+
+    /// Escape character for quotes inside quoted fields
+    ///
+    /// For files that write an embedded quote as \" rather than "". Giving an
+    /// escape character turns off quote doubling unless --double-quote true is
+    /// also given.
+    #[clap(
+        long,
+        value_name = "[TABLE=]CHAR",
+        help = "Escape character inside quoted fields, e.g. '\\' (default: none)"
+    )]
+    pub escape: Vec<TableOption<DialectChar>>,
+
+    /// Whether a doubled quote inside a quoted field is a literal quote
+    #[clap(
+        long = "double-quote",
+        value_name = "[TABLE=]BOOL",
+        help = "Read and write \"\" as an escaped quote: true or false (default: true without --escape)"
+    )]
+    pub double_quote: Vec<TableOption<bool>>,
+
+    /// Comment prefix of input files
+    ///
+    /// Lines starting with this character are skipped when loading. Use none for
+    /// files whose first field can legitimately start with #.
+    #[clap(
+        long,
+        value_name = "[TABLE=]CHAR",
+        help = "Lines starting with CHAR are comments, or none (default: #)"
+    )]
+    pub comment: Vec<TableOption<DialectChar>>,
+
+    /// Remove whitespace around fields and column names when loading
+    #[clap(
+        long,
+        value_name = "[TABLE=]BOOL",
+        help = "Trim whitespace around fields: true or false (default: false)"
+    )]
+    pub trim: Vec<TableOption<bool>>,
+
+    /// Line terminator used when writing files back
+    ///
+    /// By default each file is written with the line endings it was read with.
+    #[clap(
+        long = "line-terminator",
+        value_name = "[TABLE=]EOL",
+        help = "Line terminator for written files: lf or crlf (default: as read)"
+    )]
+    pub line_terminator: Vec<TableOption<LineTerminator>>,
+
+    /// Character encoding of input files
+    ///
+    /// Files are converted to UTF-8 when loaded and back to their encoding when
+    /// written with --write. A byte order mark at the start of a file overrides
+    /// this option. Any WHATWG encoding label is accepted.
+    /// Example: --encoding windows-1252 --encoding export=utf-16le
+    #[clap(
+        long,
+        value_name = "[TABLE=]NAME",
+        help = "Character encoding, e.g. windows-1252, latin1 or utf-16le (default: utf-8)"
+    )]
+    pub encoding: Vec<TableOption<EncodingName>>,
+
+    /// Whether written files start with a byte order mark
+    ///
+    /// By default a file is written with a BOM only if it was read with one.
+    #[clap(
+        long,
+        value_name = "[TABLE=]BOOL",
+        help = "Write a byte order mark: true or false (default: as read)"
+    )]
+    pub bom: Vec<TableOption<bool>>,
+
     /// Enable verbose diagnostic output
     ///
     /// When enabled, shows detailed information about SQL execution,
@@ -103,6 +267,122 @@ pub struct SqawkArgs {
     /// instead of the default direct execution engine.
     #[clap(long, help = "Use VM-based SQL execution engine")]
     pub vm: bool,
+
+    /// Field values to treat as NULL when loading files
+    ///
+    /// By default only empty fields are loaded as NULL. Each use of this option
+    /// adds a token to recognize instead; include an empty string to keep
+    /// treating empty fields as NULL.
+    /// Example: --null-value NA --null-value '\N' --null-value ''
+    #[clap(long = "null-value", help = "Field value to load as NULL (repeatable)")]
+    pub null_values: Vec<String>,
+
+    /// Disable boolean inference when loading files
+    ///
+    /// By default, fields such as "true", "false", "yes" and "no" are loaded as
+    /// booleans. With this flag they are kept as text.
+    #[clap(long, help = "Load true/false/yes/no as text instead of booleans")]
+    pub no_bool_inference: bool,
+
+    /// Text used for NULL values in output
+    ///
+    /// Applies both to printed query results and to tables written back with
+    /// --write. Without this option results show NULL, and written files use
+    /// the first --null-value token (an empty field by default).
+    #[clap(long, help = "Text used for NULL values in output")]
+    pub null_output: Option<String>,
+
+    /// Format used to print query results
+    ///
+    /// csv (the default) quotes fields as RFC 4180 requires; tsv escapes tabs and
+    /// newlines; table draws an aligned box; markdown, json, ndjson and html
+    /// produce documents in those formats. JSON output keeps numbers, booleans
+    /// and NULL as JSON types.
+    #[clap(
+        long = "output-format",
+        default_value = "csv",
+        help = "Output format: csv, tsv, table, markdown, json, ndjson or html"
+    )]
+    pub output_format: OutputFormat,
+}
+
+impl SqawkArgs {
+    /// Build the value format described by the NULL and boolean options
+    ///
+    /// # Returns
+    /// * `ValueFormat` - Rules for converting between field text and values
+    pub fn value_format(&self) -> ValueFormat {
+        let null_values = if self.null_values.is_empty() {
+            vec![String::new()]
+        } else {
+            self.null_values.clone()
+        };
+
+        ValueFormat::new(
+            null_values,
+            !self.no_bool_inference,
+            self.null_output.clone(),
+        )
+    }
+
+    /// Build the header settings described by the header options
+    ///
+    /// # Returns
+    /// * `Ok(HeaderSettings)` - Header modes, rows and skipped lines per table
+    /// * `Err` if a header row is 0
+    pub fn header_settings(&self) -> SqawkResult<HeaderSettings> {
+        let mut modes = Vec::new();
+        if self.no_header {
+            modes.push(TableOption {
+                table: None,
+                value: HeaderMode::Absent,
+            });
+        }
+        modes.extend(self.header.iter().cloned());
+
+        let settings = HeaderSettings {
+            modes,
+            header_rows: self.header_row.clone(),
+            skip_lines: self.skip_lines.clone(),
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Build the CSV dialect settings described by the dialect options
+    ///
+    /// # Returns
+    /// * `DialectSettings` - Quoting, comment, trimming and line terminators per table
+    pub fn dialect_settings(&self) -> DialectSettings {
+        DialectSettings {
+            quotes: self.quote.clone(),
+            escapes: self.escape.clone(),
+            double_quotes: self.double_quote.clone(),
+            comments: self.comment.clone(),
+            trims: self.trim.clone(),
+            terminators: self.line_terminator.clone(),
+        }
+    }
+
+    /// Parse the fixed-width layouts among the table definitions
+    ///
+    /// # Returns
+    /// * `Ok(HashMap)` - The fixed-width layout of each table that has one
+    /// * `Err` if a layout file can't be read or a layout is invalid
+    pub fn fixed_width_layouts(&self) -> SqawkResult<HashMap<String, FixedWidthLayout>> {
+        FixedWidthLayout::from_table_definitions(&self.tabledef)
+    }
+
+    /// Build the encoding settings described by the encoding options
+    ///
+    /// # Returns
+    /// * `EncodingSettings` - Character encodings and byte order marks per table
+    pub fn encoding_settings(&self) -> EncodingSettings {
+        EncodingSettings {
+            encodings: self.encoding.clone(),
+            boms: self.bom.clone(),
+        }
+    }
 }
 
 /// Parse command-line arguments into the SqawkArgs structure
@@ -0,0 +1,277 @@
+//! Compressed file support for sqawk
+//!
+//! This module lets sqawk read and write compressed files without a separate
+//! decompression step. It provides:
+//!
+//! - Codec detection from a compression extension (`.gz`, `.zst`, `.bz2`),
+//!   falling back to the codec's magic bytes, e.g. for compressed stdin
+//! - Looking past the compression extension to the file's own extension, so
+//!   `sales.csv.gz` is the CSV table `sales`
+//! - Streaming decompression on load, and recompression with the same codec
+//!   when a table is written back
+//!
+//! gzip is always supported. zstd and bzip2 come from the `zstd` and `bzip2`
+//! cargo features, which are on by default; without them such files are still
+//! recognized, but loading or saving them is an error.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{SqawkError, SqawkResult};
+
+/// The compression codec of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Not compressed (the default)
+    #[default]
+    None,
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+    /// bzip2 (`.bz2`)
+    Bzip2,
+}
+
+impl Compression {
+    /// Get the codec named by a file's extension
+    ///
+    /// # Arguments
+    /// * `path` - The file path
+    ///
+    /// # Returns
+    /// * The codec of a `.gz`, `.zst` or `.bz2` file, otherwise `None`
+    pub fn from_extension(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            Some("bz2" | "bzip2") => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    /// Get the codec whose magic bytes start some data
+    ///
+    /// # Arguments
+    /// * `start` - The first bytes of the data
+    ///
+    /// # Returns
+    /// * The codec the data is compressed with, or `None` for anything else
+    pub fn from_magic(start: &[u8]) -> Self {
+        const BZIP2_BLOCK: &[u8] = b"\x31\x41\x59\x26\x53\x59";
+        if start.starts_with(b"\x1F\x8B") {
+            Compression::Gzip
+        } else if start.starts_with(b"\x28\xB5\x2F\xFD") {
+            Compression::Zstd
+        } else if start.len() >= 10
+            && start.starts_with(b"BZh")
+            && (b'1'..=b'9').contains(&start[3])
+            && &start[4..10] == BZIP2_BLOCK
+        {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Check whether this build can read and write the codec
+    ///
+    /// # Returns
+    /// * `false` for a codec whose cargo feature was left out of the build
+    pub fn is_available(&self) -> bool {
+        match self {
+            Compression::None | Compression::Gzip => true,
+            Compression::Zstd => cfg!(feature = "zstd"),
+            Compression::Bzip2 => cfg!(feature = "bzip2"),
+        }
+    }
+
+    /// The error for a codec whose cargo feature was left out of the build
+    fn unavailable(&self) -> SqawkError {
+        SqawkError::InvalidArgument(format!(
+            "{0} compressed files aren't supported by this build of sqawk \
+             (rebuild it with the '{0}' feature)",
+            self
+        ))
+    }
+
+    /// Remove a compression extension from a path
+    ///
+    /// Format detection and table naming use the result, so `sales.csv.gz` is
+    /// treated like `sales.csv`.
+    ///
+    /// # Arguments
+    /// * `path` - The file path
+    ///
+    /// # Returns
+    /// * The path without its compression extension, or the path unchanged
+    pub fn strip_extension(path: &Path) -> PathBuf {
+        match Self::from_extension(path) {
+            Compression::None => path.to_path_buf(),
+            _ => path.with_extension(""),
+        }
+    }
+
+    /// Wrap a reader so that it yields decompressed data
+    ///
+    /// The codec comes from the file's extension if it has one, otherwise from
+    /// the data's magic bytes. Data is decompressed as it is read, so the
+    /// compressed file is never held in memory as a whole.
+    ///
+    /// # Arguments
+    /// * `input` - The raw input
+    /// * `path` - The file the input comes from, if any
+    ///
+    /// # Returns
+    /// * `Ok((Box<dyn Read>, Compression))` - The decompressed input and its codec
+    /// * `Err` if the start of the input can't be read, or the codec isn't available
+    pub fn decompress<'a, R: Read + 'a>(
+        input: R,
+        path: Option<&Path>,
+    ) -> SqawkResult<(Box<dyn Read + 'a>, Compression)> {
+        let mut input = BufReader::new(input);
+        let compression = match path.map(Self::from_extension) {
+            Some(Compression::None) | None => Self::from_magic(input.fill_buf()?),
+            Some(compression) => compression,
+        };
+
+        let reader: Box<dyn Read + 'a> = match compression {
+            Compression::None => return Ok((Box::new(input), compression)),
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(input)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(input)?),
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(input)),
+            #[allow(unreachable_patterns)]
+            _ => return Err(compression.unavailable()),
+        };
+        let reader = Decompressor {
+            inner: reader,
+            compression,
+        };
+        Ok((Box::new(reader), compression))
+    }
+
+    /// Compress data with this codec
+    ///
+    /// # Arguments
+    /// * `data` - The bytes to compress
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The compressed bytes, or `data` itself when not compressing
+    /// * `Err` if compression fails or the codec isn't available
+    pub fn compress(&self, data: Vec<u8>) -> SqawkResult<Vec<u8>> {
+        let compressed = match self {
+            Compression::None => return Ok(data),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&data)?;
+                encoder.finish()?
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::encode_all(&data[..], 0)?,
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(&data)?;
+                encoder.finish()?
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(self.unavailable()),
+        };
+        Ok(compressed)
+    }
+}
+
+/// A decompressing reader whose errors say which codec failed
+///
+/// Decompression happens as the table is parsed, so without this a corrupt
+/// file would be reported as a parse error of the text inside it.
+struct Decompressor<R> {
+    /// The decompressing reader
+    inner: R,
+    /// The codec it decompresses
+    compression: Compression,
+}
+
+impl<R: Read> Read for Decompressor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("can't decompress {} data: {}", self.compression, e),
+            )
+        })
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(
+            Compression::from_extension(Path::new("sales.csv.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_extension(Path::new("sales.ZST")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_extension(Path::new("sales.csv")),
+            Compression::None
+        );
+        assert_eq!(
+            Compression::strip_extension(Path::new("dir/sales.csv.bz2")),
+            Path::new("dir/sales.csv")
+        );
+        assert_eq!(
+            Compression::from_magic(b"BZh9 is not bzip2"),
+            Compression::None
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = b"id,name\n1,Alice\n".repeat(100);
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+        ] {
+            if !compression.is_available() {
+                assert!(compression.compress(text.clone()).is_err());
+                continue;
+            }
+            let compressed = compression.compress(text.clone()).unwrap();
+            assert_eq!(Compression::from_magic(&compressed), compression);
+
+            let (mut reader, detected) = Compression::decompress(&compressed[..], None).unwrap();
+            let mut decompressed = Vec::new();
+            reader.read_to_end(&mut decompressed).unwrap();
+            assert_eq!(detected, compression);
+            assert_eq!(decompressed, text);
+        }
+    }
+}
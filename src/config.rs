@@ -4,12 +4,35 @@
 //! It handles global settings that are passed down through the application rather
 //! than using global state or passing individual settings.
 
+use std::collections::HashMap;
+
+use crate::csv_dialect::{CsvDialect, DialectSettings};
+use crate::encoding::{EncodingSettings, FileEncoding};
+use crate::file_handler::FileFormat;
+use crate::fixed_width_handler::FixedWidthLayout;
+use crate::header_options::{HeaderOptions, HeaderSettings};
+use crate::output::OutputFormat;
+use crate::table::ValueFormat;
+
 /// Application configuration
 ///
 /// This struct encapsulates all global configuration settings for the application.
 /// It is created at startup and passed to components that need access to configuration.
 /// This approach avoids global mutable state and makes dependencies explicit.
-#[derive(Debug, Clone)]
+///
+/// The basic flags are given to [`AppConfig::new`]; value, output and input file
+/// options are added with the `with_*` methods, and are left at their defaults
+/// otherwise:
+///
+/// ```
+/// use sqawk::config::AppConfig;
+/// use sqawk::output::OutputFormat;
+///
+/// let config = AppConfig::new(false, None, Vec::new(), false, false)
+///     .with_output_format(OutputFormat::Json);
+/// assert_eq!(config.output_format(), OutputFormat::Json);
+/// ```
+#[derive(Debug, Clone, Default)]
 pub struct AppConfig {
     /// Whether to show verbose output
     verbose: bool,
@@ -18,14 +41,35 @@ pub struct AppConfig {
     field_separator: Option<String>,
 
     /// Custom column definitions for tables
-    /// Format: "table_name:col1,col2,..."
+    /// Format: "table_name:col1,col2,..." or a fixed-width layout "table_name:col@1-6,..."
     table_definitions: Vec<String>,
 
+    /// Fixed-width layouts parsed from the table definitions, by table name
+    fixed_width_layouts: HashMap<String, FixedWidthLayout>,
+
     /// Whether to write changes back to files
     write_changes: bool,
 
     /// Whether to use the VM execution engine
     use_vm: bool,
+
+    /// Rules for recognizing NULLs and booleans on load, and rendering NULL on output
+    value_format: ValueFormat,
+
+    /// Format used to print query results
+    output_format: OutputFormat,
+
+    /// Format of the input files, overriding detection from their extensions
+    input_format: Option<FileFormat>,
+
+    /// Header modes, header rows and skipped lines for each input file
+    header_settings: HeaderSettings,
+
+    /// Quoting, comment, trimming and line terminator options for each input file
+    dialect_settings: DialectSettings,
+
+    /// Character encoding and byte order mark options for each input file
+    encoding_settings: EncodingSettings,
 }
 
 impl AppConfig {
@@ -36,6 +80,7 @@ impl AppConfig {
     /// * `field_separator` - Optional field separator character/string from command line
     /// * `table_definitions` - Optional vector of table column definitions
     /// * `write_changes` - Whether to write changes back to files
+    /// * `use_vm` - Whether to use the VM execution engine
     pub fn new(
         verbose: bool,
         field_separator: Option<String>,
@@ -49,9 +94,55 @@ impl AppConfig {
             table_definitions,
             write_changes,
             use_vm,
+            ..Self::default()
         }
     }
 
+    /// Set the fixed-width layouts of tables, parsed from the table definitions
+    ///
+    /// See [`FixedWidthLayout::from_table_definitions`]; tables whose definitions
+    /// are layouts are only read as fixed-width files once their layouts are set.
+    pub fn with_fixed_width_layouts(mut self, layouts: HashMap<String, FixedWidthLayout>) -> Self {
+        self.fixed_width_layouts = layouts;
+        self
+    }
+
+    /// Set the rules for NULL/boolean recognition on load and NULL output
+    pub fn with_value_format(mut self, value_format: ValueFormat) -> Self {
+        self.value_format = value_format;
+        self
+    }
+
+    /// Set the format used to print query results
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Set the format of the input files, overriding detection from their extensions
+    pub fn with_input_format(mut self, input_format: Option<FileFormat>) -> Self {
+        self.input_format = input_format;
+        self
+    }
+
+    /// Set the header modes, header rows and skipped lines for input files
+    pub fn with_header_settings(mut self, settings: HeaderSettings) -> Self {
+        self.header_settings = settings;
+        self
+    }
+
+    /// Set the quoting, comment, trimming and line terminator options for input files
+    pub fn with_dialect_settings(mut self, settings: DialectSettings) -> Self {
+        self.dialect_settings = settings;
+        self
+    }
+
+    /// Set the character encoding and byte order mark options for input files
+    pub fn with_encoding_settings(mut self, settings: EncodingSettings) -> Self {
+        self.encoding_settings = settings;
+        self
+    }
+
     /// Get the verbose flag
     pub fn verbose(&self) -> bool {
         self.verbose
@@ -67,6 +158,38 @@ impl AppConfig {
         &self.table_definitions
     }
 
+    /// Get the column names defined for a table with `--tabledef`, if any
+    ///
+    /// Fixed-width layouts are not included; see [`AppConfig::fixed_width_layout`].
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table
+    ///
+    /// # Returns
+    /// * `Some(columns)` if the table's last definition is a plain column list
+    /// * `None` otherwise
+    pub fn table_columns(&self, table_name: &str) -> Option<Vec<String>> {
+        self.table_definitions
+            .iter()
+            .rev()
+            .filter_map(|tabledef| tabledef.split_once(':'))
+            .find(|(name, _)| *name == table_name)
+            .filter(|(_, spec)| !FixedWidthLayout::is_layout(spec))
+            .map(|(_, spec)| spec.split(',').map(|s| s.trim().to_string()).collect())
+    }
+
+    /// Get the fixed-width layout defined for a table, if any
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table
+    ///
+    /// # Returns
+    /// * `Some(layout)` if the table's last definition is a fixed-width layout
+    /// * `None` if the table has no definition or a plain column list
+    pub fn fixed_width_layout(&self, table_name: &str) -> Option<&FixedWidthLayout> {
+        self.fixed_width_layouts.get(table_name)
+    }
+
     /// Get whether to write changes
     pub fn write_changes(&self) -> bool {
         self.write_changes
@@ -81,4 +204,34 @@ impl AppConfig {
     pub fn use_vm(&self) -> bool {
         self.use_vm
     }
+
+    /// Get the rules for converting between field text and values
+    pub fn value_format(&self) -> &ValueFormat {
+        &self.value_format
+    }
+
+    /// Get the format used to print query results
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// Get the format of the input files, if it overrides detection from their extensions
+    pub fn input_format(&self) -> Option<FileFormat> {
+        self.input_format
+    }
+
+    /// Get the header options for a table's file
+    pub fn header_options(&self, table_name: &str) -> HeaderOptions {
+        self.header_settings.for_table(table_name)
+    }
+
+    /// Get the CSV dialect for a table's file
+    pub fn csv_dialect(&self, table_name: &str) -> CsvDialect {
+        self.dialect_settings.for_table(table_name)
+    }
+
+    /// Get the character encoding for a table's file
+    pub fn file_encoding(&self, table_name: &str) -> FileEncoding {
+        self.encoding_settings.for_table(table_name)
+    }
 }
@@ -0,0 +1,335 @@
+//! CSV dialect options for sqawk
+//!
+//! This module describes the variations of CSV that sqawk can read and write back:
+//!
+//! - The quote character, or no quoting at all
+//! - An escape character inside quoted fields, and whether doubled quotes are escapes
+//! - The comment prefix, or no comments
+//! - Trimming whitespace around fields
+//! - LF or CRLF line terminators, detected from the file unless given
+//!
+//! Each option can be given for every file or for one table as `[table=]value`.
+//! The dialect a file was read with is remembered on its table, so write-back
+//! produces the same dialect.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::header_options::{option_for, TableOption};
+
+/// A single-byte dialect character, or none (`--quote`, `--escape`, `--comment`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialectChar(pub Option<u8>);
+
+impl FromStr for DialectChar {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" | "" => Ok(DialectChar(None)),
+            "\\t" => Ok(DialectChar(Some(b'\t'))),
+            s if s.len() == 1 && s.is_ascii() => Ok(DialectChar(Some(s.as_bytes()[0]))),
+            _ => Err(SqawkError::InvalidArgument(format!(
+                "Invalid dialect character '{}' (expected a single ASCII character or none)",
+                s
+            ))),
+        }
+    }
+}
+
+/// The line terminator written after each record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineTerminator {
+    /// `\n`, as on Unix (the default)
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows and in RFC 4180
+    CrLf,
+}
+
+impl LineTerminator {
+    /// Names accepted by `--line-terminator`
+    pub const NAMES: &'static [&'static str] = &["lf", "crlf"];
+
+    /// Detect the line terminator from the start of a file
+    ///
+    /// # Arguments
+    /// * `start` - The first bytes of the file
+    ///
+    /// # Returns
+    /// * `CrLf` if the first line ends with `\r\n`, otherwise `Lf`
+    pub fn detect(start: &[u8]) -> Self {
+        match start.iter().position(|&b| b == b'\n') {
+            Some(end) if end > 0 && start[end - 1] == b'\r' => LineTerminator::CrLf,
+            _ => LineTerminator::Lf,
+        }
+    }
+
+    /// The terminator text
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineTerminator::Lf => "\n",
+            LineTerminator::CrLf => "\r\n",
+        }
+    }
+}
+
+impl FromStr for LineTerminator {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lf" | "unix" => Ok(LineTerminator::Lf),
+            "crlf" | "dos" | "windows" => Ok(LineTerminator::CrLf),
+            _ => Err(SqawkError::InvalidArgument(format!(
+                "Unknown line terminator '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LineTerminator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LineTerminator::Lf => "lf",
+            LineTerminator::CrLf => "crlf",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a delimited file is quoted, escaped, commented and terminated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// Quote character; `None` reads quotes as ordinary text and never writes them
+    pub quote: Option<u8>,
+    /// Escape character for quotes inside quoted fields, e.g. `\`
+    pub escape: Option<u8>,
+    /// Whether a doubled quote inside a quoted field is a literal quote
+    pub double_quote: bool,
+    /// Lines starting with this character are comments
+    pub comment: Option<u8>,
+    /// Whether whitespace around fields (and column names) is removed on load
+    pub trim: bool,
+    /// Line terminator for write-back; `None` until detected from the file
+    pub terminator: Option<LineTerminator>,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            quote: Some(b'"'),
+            escape: None,
+            double_quote: true,
+            comment: Some(b'#'),
+            trim: false,
+            terminator: None,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// The comment prefix as a character, for code that reads lines itself
+    pub fn comment_char(&self) -> Option<char> {
+        self.comment.map(char::from)
+    }
+
+    /// The line terminator to write, LF unless one was given or detected
+    pub fn line_terminator(&self) -> LineTerminator {
+        self.terminator.unwrap_or_default()
+    }
+
+    /// Create a CSV reader configured for this dialect
+    ///
+    /// The reader treats every record as data and allows records of any length;
+    /// callers handle the header and mismatched rows themselves.
+    ///
+    /// # Arguments
+    /// * `delimiter` - The field delimiter
+    ///
+    /// # Returns
+    /// A reader builder for the dialect
+    pub fn reader_builder(&self, delimiter: u8) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .delimiter(delimiter)
+            .flexible(true)
+            .quoting(self.quote.is_some())
+            .quote(self.quote.unwrap_or(b'"'))
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .trim(if self.trim {
+                csv::Trim::All
+            } else {
+                csv::Trim::None
+            });
+        builder
+    }
+
+    /// Create a CSV writer configured for this dialect
+    ///
+    /// # Arguments
+    /// * `delimiter` - The field delimiter
+    ///
+    /// # Returns
+    /// A writer builder for the dialect
+    pub fn writer_builder(&self, delimiter: u8) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(delimiter)
+            .flexible(true)
+            .quote_style(match self.quote {
+                Some(_) => csv::QuoteStyle::Necessary,
+                None => csv::QuoteStyle::Never,
+            })
+            .quote(self.quote.unwrap_or(b'"'))
+            .double_quote(self.double_quote)
+            .escape(self.escape.unwrap_or(b'\\'))
+            .terminator(match self.line_terminator() {
+                LineTerminator::Lf => csv::Terminator::Any(b'\n'),
+                LineTerminator::CrLf => csv::Terminator::CRLF,
+            });
+        builder
+    }
+
+    /// Parse a single line of delimited text into its fields
+    ///
+    /// Used for header lines, which are read before the rest of the file is handed
+    /// to the CSV reader.
+    ///
+    /// # Arguments
+    /// * `line` - The line, without its terminator
+    /// * `delimiter` - The field delimiter
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - The fields of the line
+    /// * `Err` if the line is malformed
+    pub fn parse_line(&self, line: &str, delimiter: u8) -> SqawkResult<Vec<String>> {
+        // The line was already chosen as a header, so it is never a comment
+        let mut reader = self
+            .reader_builder(delimiter)
+            .comment(None)
+            .from_reader(line.as_bytes());
+        match reader.records().next() {
+            Some(record) => Ok(record?.iter().map(str::to_string).collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// CSV dialect options from the command line, resolved per table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DialectSettings {
+    /// Values of `--quote`
+    pub quotes: Vec<TableOption<DialectChar>>,
+    /// Values of `--escape`
+    pub escapes: Vec<TableOption<DialectChar>>,
+    /// Values of `--double-quote`
+    pub double_quotes: Vec<TableOption<bool>>,
+    /// Values of `--comment`
+    pub comments: Vec<TableOption<DialectChar>>,
+    /// Values of `--trim`
+    pub trims: Vec<TableOption<bool>>,
+    /// Values of `--line-terminator`
+    pub terminators: Vec<TableOption<LineTerminator>>,
+}
+
+impl DialectSettings {
+    /// Get the CSV dialect for a table
+    ///
+    /// A value given for the table by name wins over one given for all tables.
+    /// Quote doubling stays on unless it is turned off or an escape character is given.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table being loaded
+    ///
+    /// # Returns
+    /// * `CsvDialect` - The dialect of the table's file
+    pub fn for_table(&self, table_name: &str) -> CsvDialect {
+        let defaults = CsvDialect::default();
+        let escape = option_for(&self.escapes, table_name).map_or(defaults.escape, |c| c.0);
+        CsvDialect {
+            quote: option_for(&self.quotes, table_name).map_or(defaults.quote, |c| c.0),
+            escape,
+            double_quote: option_for(&self.double_quotes, table_name)
+                .copied()
+                .unwrap_or(escape.is_none()),
+            comment: option_for(&self.comments, table_name).map_or(defaults.comment, |c| c.0),
+            trim: option_for(&self.trims, table_name)
+                .copied()
+                .unwrap_or(defaults.trim),
+            terminator: option_for(&self.terminators, table_name).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dialect_for_table() {
+        let settings = DialectSettings {
+            quotes: vec!["'".parse().unwrap()],
+            escapes: vec!["logs=\\".parse().unwrap()],
+            comments: vec!["none".parse().unwrap()],
+            terminators: vec!["logs=crlf".parse().unwrap()],
+            ..DialectSettings::default()
+        };
+
+        let data = settings.for_table("data");
+        assert_eq!(data.quote, Some(b'\''));
+        assert_eq!(data.escape, None);
+        assert!(data.double_quote);
+        assert_eq!(data.comment, None);
+        assert_eq!(data.terminator, None);
+
+        // An escape character turns off quote doubling unless it is asked for
+        let logs = settings.for_table("logs");
+        assert_eq!(logs.escape, Some(b'\\'));
+        assert!(!logs.double_quote);
+        assert_eq!(logs.line_terminator(), LineTerminator::CrLf);
+
+        assert!("ab".parse::<DialectChar>().is_err());
+        assert!("cr".parse::<LineTerminator>().is_err());
+    }
+
+    #[test]
+    fn test_detect_terminator() {
+        assert_eq!(
+            LineTerminator::detect(b"a,b\r\n1,2\r\n"),
+            LineTerminator::CrLf
+        );
+        assert_eq!(LineTerminator::detect(b"a,b\n1,2\r\n"), LineTerminator::Lf);
+        assert_eq!(LineTerminator::detect(b"a,b"), LineTerminator::Lf);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let dialect = CsvDialect {
+            quote: Some(b'\''),
+            escape: Some(b'\\'),
+            double_quote: false,
+            trim: true,
+            ..CsvDialect::default()
+        };
+        assert_eq!(
+            dialect.parse_line("id ,'it\\'s, here'", b',').unwrap(),
+            ["id", "it's, here"]
+        );
+
+        let unquoted = CsvDialect {
+            quote: None,
+            ..CsvDialect::default()
+        };
+        assert_eq!(
+            unquoted.parse_line("\"a\",b", b',').unwrap(),
+            ["\"a\"", "b"]
+        );
+    }
+}
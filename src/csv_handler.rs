@@ -3,7 +3,8 @@
 //! This module handles loading CSV files into in-memory tables and saving tables back to CSV files.
 //! It provides functionality for:
 //!
-//! - Loading CSV files with automatic header detection
+//! - Loading CSV files with or without a header row, optionally below title lines
+//! - Reading and writing back CSV dialects with other quoting, comments or line endings
 //! - Parsing file specifications in the format [table_name=]file_path.csv
 //! - Managing a collection of in-memory tables
 //! - Converting between CSV records and the internal Value type
@@ -13,18 +14,25 @@
 //! a mapping between table names and their source files for writeback operations.
 
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 
+use crate::csv_dialect::LineTerminator;
 use crate::error::{SqawkError, SqawkResult};
-use crate::table::{Table, Value};
+use crate::header_options::{
+    generated_column_names, looks_like_header, read_header_lines, write_lines, LoadOptions,
+};
+use crate::table::{Table, Value, ValueFormat};
 
 /// Handles loading and saving CSV files
 ///
 /// This struct provides methods for loading tables from CSV files
 /// and writing them back when modified. It's specialized for handling
 /// data in CSV format with commas as separators.
-pub struct CsvHandler {}
+pub struct CsvHandler {
+    /// Rules for recognizing NULLs and booleans on load and writing NULLs on save
+    value_format: ValueFormat,
+}
 
 impl Default for CsvHandler {
     fn default() -> Self {
@@ -38,11 +46,25 @@ impl CsvHandler {
     /// # Returns
     /// A new CsvHandler instance ready to load and manage CSV files
     pub fn new() -> Self {
-        CsvHandler {}
+        Self::with_value_format(ValueFormat::default())
+    }
+
+    /// Create a new CsvHandler with custom NULL and boolean handling
+    ///
+    /// # Arguments
+    /// * `value_format` - Rules for converting between field text and values
+    ///
+    /// # Returns
+    /// A new CsvHandler that loads and saves values using the given rules
+    pub fn with_value_format(value_format: ValueFormat) -> Self {
+        CsvHandler { value_format }
     }
 
     /// Save a table to a CSV file
     ///
+    /// The file is written in the CSV dialect, encoding and compression the
+    /// table was loaded with.
+    ///
     /// # Arguments
     /// * `table` - The table to save
     /// * `file_path` - The path to the file
@@ -50,18 +72,45 @@ impl CsvHandler {
     /// # Returns
     /// * `SqawkResult<()>` - Result of the operation
     pub fn save_csv(&self, table: &Table, file_path: &Path) -> SqawkResult<()> {
-        // Create a CSV writer
-        let file = File::create(file_path).map_err(SqawkError::IoError)?;
-        let mut writer = csv::Writer::from_writer(file);
+        let mut text = Vec::new();
+        self.write_csv(table, &mut text)?;
+        table.write_file(file_path, text)
+    }
+
+    /// Write a table as CSV to any writer
+    ///
+    /// This is the formatting half of [`CsvHandler::save_csv`].
+    ///
+    /// # Arguments
+    /// * `table` - The table to write
+    /// * `out` - Destination of the CSV text
+    ///
+    /// # Returns
+    /// * `SqawkResult<()>` - Result of the operation
+    pub fn write_csv<W: Write>(&self, table: &Table, mut out: W) -> SqawkResult<()> {
+        // Write the header row, and the lines kept around it, as the file had them
+        let layout = table.header_layout();
+        let dialect = table.csv_dialect();
+        let terminator = dialect.line_terminator().as_str();
+        write_lines(&mut out, &layout.before_header, terminator)?;
+        if layout.has_header {
+            let mut writer = dialect.writer_builder(b',').from_writer(&mut out);
+            writer
+                .write_record(table.columns())
+                .map_err(SqawkError::CsvError)?;
+            writer.flush().map_err(SqawkError::IoError)?;
+        }
+        write_lines(&mut out, &layout.after_header, terminator)?;
 
-        // Write header row
-        writer
-            .write_record(table.columns())
-            .map_err(SqawkError::CsvError)?;
+        // Create a CSV writer for the data
+        let mut writer = dialect.writer_builder(b',').from_writer(&mut out);
 
         // Write data rows
         for row in table.rows() {
-            let string_values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+            let string_values: Vec<String> = row
+                .iter()
+                .map(|value| self.value_format.write(value))
+                .collect();
 
             writer
                 .write_record(&string_values)
@@ -69,6 +118,8 @@ impl CsvHandler {
         }
 
         writer.flush().map_err(SqawkError::IoError)?;
+        drop(writer);
+        out.flush().map_err(SqawkError::IoError)?;
 
         Ok(())
     }
@@ -86,6 +137,7 @@ impl CsvHandler {
     ///
     /// # Features
     /// * Supports comments in CSV files (lines starting with #)
+    /// * Remembers the file's line terminator for write-back
     /// * Can recover from malformed rows by skipping them
     /// * Provides detailed error information including line numbers
     ///
@@ -109,44 +161,96 @@ impl CsvHandler {
 
         // Open the CSV file
         let file = File::open(&file_path)?;
-        let reader = BufReader::new(file);
 
-        // Create a CSV reader with enhanced options
-        let mut csv_reader = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .comment(Some(b'#')) // Support comment lines starting with #
-            // Enable flexible mode only if error recovery is requested
-            .flexible(true) // Always use flexible mode to allow for skipping errors
-            .from_reader(reader);
+        let options = LoadOptions {
+            columns: custom_columns,
+            ..LoadOptions::default()
+        };
+        self.read_csv(&table_name, file, Some(file_path), options, recover_errors)
+    }
+
+    /// Read CSV data from any reader into an in-memory table
+    ///
+    /// This is the parsing half of [`CsvHandler::load_csv`], used directly for
+    /// input that doesn't come from a named file, such as stdin.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to create
+    /// * `input` - Source of the CSV data
+    /// * `file_path` - The file the data came from, if any, used for write-back
+    /// * `options` - Custom column names, header position and CSV dialect of the data
+    /// * `recover_errors` - When true, malformed rows will be skipped instead of causing the operation to fail
+    ///
+    /// # Returns
+    /// * `Ok(Table)` - The successfully loaded table
+    /// * `Err` if the CSV data could not be parsed
+    pub fn read_csv<R: Read>(
+        &self,
+        table_name: &str,
+        input: R,
+        file_path: Option<PathBuf>,
+        options: LoadOptions,
+        recover_errors: Option<bool>,
+    ) -> SqawkResult<Table> {
+        let LoadOptions {
+            columns: custom_columns,
+            header,
+            mut dialect,
+        } = options;
+        let mut reader = BufReader::new(input);
+        let source = file_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<stdin>".to_string());
+
+        // Write-back keeps the file's line endings unless others were asked for
+        if dialect.terminator.is_none() {
+            dialect.terminator = Some(LineTerminator::detect(reader.fill_buf()?));
+        }
+
+        // Read the header and any lines around it, leaving the data in the reader
+        let start = read_header_lines(&mut reader, &header, dialect.comment_char(), |line| {
+            Ok(looks_like_header(
+                &dialect.parse_line(line, b',')?,
+                &self.value_format,
+            ))
+        })?;
+        let first_data_line = start
+            .first_data_line
+            .map(|line| line + "\n")
+            .unwrap_or_default();
+
+        // Create a CSV reader for the dialect; it is always flexible, so rows with
+        // the wrong number of fields can be skipped or padded below
+        let mut csv_reader = dialect
+            .reader_builder(b',')
+            .from_reader(Cursor::new(first_data_line).chain(reader));
+        let mut records = csv_reader.records().peekable();
 
         // Get headers or use custom column names if provided
-        let headers = if let Some(columns) = custom_columns {
+        let headers = match (custom_columns, &start.header) {
             // Use the provided custom column names
-            columns
-        } else {
+            (Some(columns), _) => columns,
             // Use column names from the CSV header row
-            csv_reader
-                .headers()
-                .map_err(SqawkError::CsvError)?
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>()
+            (None, Some(line)) => dialect.parse_line(line, b',')?,
+            // Without a header, name as many columns as the first row has
+            (None, None) => match records.peek() {
+                Some(Ok(record)) => generated_column_names(record.len()),
+                _ => generated_column_names(1),
+            },
         };
 
         // Create a new table with comma delimiter (since this is the CSV handler)
-        let mut table = Table::new_with_delimiter(
-            &table_name,
-            headers,
-            Some(file_path.clone()),
-            ",".to_string(),
-        );
+        let mut table = Table::new_with_delimiter(table_name, headers, file_path, ",".to_string());
+        table.set_header_layout(start.layout);
+        table.set_csv_dialect(dialect);
 
         // Read rows with enhanced error handling
         let should_recover = recover_errors.unwrap_or(false);
         let mut skipped_rows = 0;
         let mut row_number = 0;
 
-        for result in csv_reader.records() {
+        for result in records {
             row_number += 1;
 
             match result {
@@ -159,7 +263,7 @@ impl CsvHandler {
                         for i in 0..table.column_count() {
                             if i < record.len() {
                                 // Use the value if it exists
-                                row.push(Value::from(record.get(i).unwrap_or("")));
+                                row.push(self.value_format.parse(record.get(i).unwrap_or("")));
                             } else {
                                 // Pad with null values if we need more
                                 row.push(Value::Null);
@@ -170,7 +274,10 @@ impl CsvHandler {
                         table.add_row_recovery(row)?;
                     } else {
                         // Normal path - convert record to a row of values and validate
-                        let row = record.iter().map(Value::from).collect();
+                        let row = record
+                            .iter()
+                            .map(|field| self.value_format.parse(field))
+                            .collect();
                         // This call can fail if the columns don't match and we're not in recovery mode
                         if let Err(e) = table.add_row(row) {
                             if should_recover {
@@ -178,7 +285,7 @@ impl CsvHandler {
                                 skipped_rows += 1;
                                 eprintln!(
                                     "Warning: Skipping row at line {} with inconsistent field count: {}",
-                                    row_number + 1, // +1 for header row
+                                    start.lines_read + row_number,
                                     e
                                 );
                             } else {
@@ -193,15 +300,15 @@ impl CsvHandler {
                     skipped_rows += 1;
                     eprintln!(
                         "Warning: Skipping malformed row at line {}: {}",
-                        row_number + 1, // +1 for header row
+                        start.lines_read + row_number,
                         csv_err
                     );
                 }
                 Err(csv_err) => {
                     // Provide detailed error context when failing
                     return Err(SqawkError::CsvParseError {
-                        file: file_path.to_string_lossy().to_string(),
-                        line: row_number + 1, // +1 for header row
+                        file: source,
+                        line: start.lines_read + row_number,
                         error: format!("{}", csv_err),
                     });
                 }
@@ -212,8 +319,7 @@ impl CsvHandler {
         if should_recover && skipped_rows > 0 {
             eprintln!(
                 "Note: Skipped {} malformed rows while loading {}",
-                skipped_rows,
-                file_path.to_string_lossy()
+                skipped_rows, source
             );
         }
 
@@ -7,9 +7,13 @@
 //! The Database struct is responsible for:
 //! - Storing all tables with their names
 //! - Providing a unified interface for table operations
+//! - Holding the registry of scalar functions available to SQL statements
 
+use crate::aggregate::Aggregator;
 use crate::config::AppConfig;
 use crate::error::{SqawkError, SqawkResult};
+use crate::fixed_width_handler::FixedWidthLayout;
+use crate::functions::{FunctionRegistry, ScalarFunction};
 use crate::table::Table;
 use std::collections::HashMap;
 
@@ -17,6 +21,9 @@ use std::collections::HashMap;
 pub struct Database {
     /// In-memory tables indexed by their names
     tables: HashMap<String, Table>,
+
+    /// Scalar functions callable from SQL, including user-defined ones
+    functions: FunctionRegistry,
 }
 
 /// Methods for direct table manipulation - used only in special cases
@@ -43,13 +50,43 @@ impl Default for Database {
 }
 
 impl Database {
-    /// Create a new, empty database
+    /// Create a new, empty database with the built-in scalar functions
     pub fn new() -> Self {
         Database {
             tables: HashMap::new(),
+            functions: FunctionRegistry::with_builtins(),
         }
     }
 
+    /// Register a user-defined scalar function
+    ///
+    /// The function becomes callable from SQL under its name. Registering a
+    /// function with the same name as an existing one, including a built-in,
+    /// replaces it. An aggregate function of the same name takes precedence.
+    ///
+    /// # Arguments
+    /// * `function` - The function to register
+    pub fn register_function<F: ScalarFunction + 'static>(&mut self, function: F) {
+        self.functions.register(function);
+    }
+
+    /// Register a user-defined aggregate function
+    ///
+    /// The aggregate becomes callable from SQL under its name, with or without
+    /// GROUP BY. Registering an aggregate with the name of an existing one,
+    /// including a built-in, replaces it.
+    ///
+    /// # Arguments
+    /// * `aggregate` - The aggregate function to register
+    pub fn register_aggregate<A: Aggregator + 'static>(&mut self, aggregate: A) {
+        self.functions.register_aggregate(aggregate);
+    }
+
+    /// Get the registry of scalar and aggregate functions
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
     /// Add a table to the database
     ///
     /// # Arguments
@@ -138,13 +175,23 @@ impl Database {
     /// * `Ok(())` if all table definitions were compiled successfully
     /// * `Err` if there was an issue with any table definition
     pub fn compile_table_definitions(&mut self, config: &AppConfig) -> SqawkResult<()> {
-        // Process each table definition string (format: "table_name:col1,col2,...")
+        // Process each table definition string (format: "table_name:col1,col2,...",
+        // or a fixed-width layout "table_name:col1@1-6,col2@7-30,...")
         for tabledef in config.table_definitions() {
             if let Some((table_name, columns_str)) = tabledef.split_once(':') {
-                let columns = columns_str
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .collect::<Vec<String>>();
+                let columns = if FixedWidthLayout::is_layout(columns_str) {
+                    // Layouts are parsed once, with the configuration; a table whose
+                    // later definition is a plain column list has none
+                    match config.fixed_width_layout(table_name) {
+                        Some(layout) => layout.column_names(),
+                        None => continue,
+                    }
+                } else {
+                    columns_str
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .collect::<Vec<String>>()
+                };
 
                 if !columns.is_empty() {
                     // Create a new table with the specified columns
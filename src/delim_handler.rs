@@ -6,20 +6,29 @@
 //!
 //! The module provides functionality for:
 //!
-//! - Loading files with custom field separators specified by the user
+//! - Loading files with custom field separators specified by the user, including
+//!   awk-style whitespace splitting, multi-character strings and regular expressions
 //! - Parsing file specifications in the format [table_name=]file_path
 //! - Converting between delimited records and the internal Value type
+//! - Applying the CSV dialect's quoting, comment and line terminator rules
 //! - Writing modified tables back to delimiter-separated files
 //!
-//! This implementation reuses the CSV crate's functionality but configures it
-//! to use the specified delimiter instead of commas.
+//! Single-character separators reuse the CSV crate's functionality, configured to
+//! use the specified delimiter instead of commas. Other separators split each line
+//! directly with [`FieldSeparator`].
 
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 
+use regex::Regex;
+
+use crate::csv_dialect::{CsvDialect, LineTerminator};
 use crate::error::{SqawkError, SqawkResult};
-use crate::table::{Table, Value};
+use crate::header_options::{
+    generated_column_names, looks_like_header, read_header_lines, write_lines, LoadOptions,
+};
+use crate::table::{Table, Value, ValueFormat};
 
 /// Handles loading and saving delimiter-separated value files
 ///
@@ -32,7 +41,10 @@ use crate::table::{Table, Value};
 /// - Inferring column names when headers are missing
 /// - Automatic data type detection for values
 /// - Writing tables back to delimited files
-pub struct DelimHandler {}
+pub struct DelimHandler {
+    /// Rules for recognizing NULLs and booleans on load and writing NULLs on save
+    value_format: ValueFormat,
+}
 
 impl Default for DelimHandler {
     fn default() -> Self {
@@ -46,11 +58,26 @@ impl DelimHandler {
     /// # Returns
     /// A new DelimHandler instance ready to load and manage delimiter-separated files
     pub fn new() -> Self {
-        DelimHandler {}
+        Self::with_value_format(ValueFormat::default())
+    }
+
+    /// Create a new DelimHandler with custom NULL and boolean handling
+    ///
+    /// # Arguments
+    /// * `value_format` - Rules for converting between field text and values
+    ///
+    /// # Returns
+    /// A new DelimHandler that loads and saves values using the given rules
+    pub fn with_value_format(value_format: ValueFormat) -> Self {
+        DelimHandler { value_format }
     }
 
     /// Save a table to a delimiter-separated file
     ///
+    /// Single-character delimiters quote fields as the table's CSV dialect does;
+    /// other separators write fields as they are. The file is written in the
+    /// encoding and compression the table was loaded with.
+    ///
     /// # Arguments
     /// * `table` - The table to save
     /// * `file_path` - The path to the file
@@ -64,23 +91,83 @@ impl DelimHandler {
         file_path: &Path,
         delimiter: &str,
     ) -> SqawkResult<()> {
-        use std::fs::File;
-        use std::io::{BufWriter, Write};
+        let mut text = Vec::new();
+        self.write_delimited(table, &mut text, delimiter)?;
+        table.write_file(file_path, text)
+    }
 
-        // Open the file for writing
-        let file = File::create(file_path).map_err(SqawkError::IoError)?;
-        let mut writer = BufWriter::new(file);
+    /// Write a table as delimiter-separated text to any writer
+    ///
+    /// This is the formatting half of [`DelimHandler::save_delimited`].
+    ///
+    /// # Arguments
+    /// * `table` - The table to write
+    /// * `writer` - Destination of the delimited text
+    /// * `delimiter` - The delimiter to use (e.g., ",", "\t")
+    ///
+    /// # Returns
+    /// * `SqawkResult<()>` - Result of the operation
+    pub fn write_delimited<W: Write>(
+        &self,
+        table: &Table,
+        mut writer: W,
+        delimiter: &str,
+    ) -> SqawkResult<()> {
+        // Whitespace separators write a single space; a regex can't be written back
+        let separator = FieldSeparator::parse(delimiter)?;
+        let delimiter = separator.output_separator().ok_or_else(|| {
+            SqawkError::InvalidFileSpec(format!(
+                "Can't write table '{}': regex field separator {} has no fixed output form",
+                table.name(),
+                delimiter
+            ))
+        })?;
+        let delimiter = delimiter.as_str();
 
-        // Write the header row
-        let header = table.columns().join(delimiter);
-        writeln!(writer, "{}", header).map_err(SqawkError::IoError)?;
+        // Write the header row, and the lines kept around it, as the file had them
+        let layout = table.header_layout();
+        let dialect = table.csv_dialect();
+        let terminator = dialect.line_terminator().as_str();
+        let rows = table.rows().iter().map(|row| {
+            row.iter()
+                .map(|value| self.value_format.write(value))
+                .collect::<Vec<String>>()
+        });
+        write_lines(&mut writer, &layout.before_header, terminator)?;
+        match separator {
+            FieldSeparator::Char(byte) => {
+                let mut csv_writer = dialect.writer_builder(byte).from_writer(&mut writer);
+                if layout.has_header {
+                    csv_writer
+                        .write_record(table.columns())
+                        .map_err(SqawkError::CsvError)?;
+                }
+                csv_writer.flush().map_err(SqawkError::IoError)?;
+                drop(csv_writer);
+                write_lines(&mut writer, &layout.after_header, terminator)?;
 
-        // Write data rows
-        for row in table.rows() {
-            let row_values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                // Write data rows
+                let mut csv_writer = dialect.writer_builder(byte).from_writer(&mut writer);
+                for row in rows {
+                    csv_writer
+                        .write_record(&row)
+                        .map_err(SqawkError::CsvError)?;
+                }
+                csv_writer.flush().map_err(SqawkError::IoError)?;
+            }
+            _ => {
+                if layout.has_header {
+                    let header = table.columns().join(delimiter);
+                    write!(writer, "{}{}", header, terminator).map_err(SqawkError::IoError)?;
+                }
+                write_lines(&mut writer, &layout.after_header, terminator)?;
 
-            let row_str = row_values.join(delimiter);
-            writeln!(writer, "{}", row_str).map_err(SqawkError::IoError)?;
+                // Write data rows
+                for row in rows {
+                    write!(writer, "{}{}", row.join(delimiter), terminator)
+                        .map_err(SqawkError::IoError)?;
+                }
+            }
         }
 
         // Flush and close the writer
@@ -98,14 +185,19 @@ impl DelimHandler {
     /// # Headers and Column Detection
     /// The method implements several strategies for determining column names:
     /// - Uses custom column names if provided
-    /// - Detects header row in the file when present
-    /// - Intelligently determines if first row is data rather than headers (for files like /etc/passwd)
+    /// - Uses the header row in the file when present
     /// - Generates alphabetical column names (a, b, c...) when headers are missing
     ///
+    /// This method always expects a header row; use [`DelimHandler::read_delimited`]
+    /// with [`LoadOptions`] for headerless files like /etc/passwd.
+    ///
     /// # Special Delimiter Handling
     /// - Supports single-character delimiters (e.g., comma, pipe, colon)
     /// - Special handling for tab delimiter with `\t` notation
-    /// - Supports comment lines starting with '#' for system files
+    /// - A single space splits on runs of whitespace, like awk's default field separator
+    /// - Longer strings are literal separators, and `/pattern/` is a regular expression
+    /// - Supports comment lines starting with '#' for system files, unless the
+    ///   CSV dialect says otherwise
     ///
     /// # Arguments
     /// * `file_spec` - File specification in the format `[table_name=]file_path`
@@ -126,118 +218,101 @@ impl DelimHandler {
 
         // Open the file
         let file = File::open(&file_path)?;
-        let reader = BufReader::new(file);
 
-        // Get the delimiter as a byte
-        let delimiter_byte = if delimiter.len() == 1 {
-            delimiter.as_bytes()[0]
-        } else if delimiter == "\\t" {
-            b'\t' // Handle special case for tab
-        } else {
-            return Err(SqawkError::InvalidFileSpec(format!(
-                "Invalid delimiter: {}. Must be a single character.",
-                delimiter
-            )));
+        let options = LoadOptions {
+            columns: custom_columns,
+            ..LoadOptions::default()
         };
+        self.read_delimited(&table_name, file, Some(file_path), delimiter, options)
+    }
 
-        // Create a CSV reader with custom delimiter
-        // Also add support for comment lines (starting with #) for system files like /etc/passwd
-        let mut csv_reader = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .delimiter(delimiter_byte)
-            .comment(Some(b'#')) // Support for comment lines starting with #
-            .flexible(true) // Allow for variable number of fields
-            .from_reader(reader);
-
-        // If custom column names are provided, use them
-        // Otherwise detect/generate headers based on file content
-        let headers = if let Some(columns) = custom_columns {
-            // Use the provided custom column names
-            columns
-        } else {
-            // No custom column names, generate or detect from file
-            match csv_reader.headers().map_err(SqawkError::CsvError) {
-                Ok(header_row) => {
-                    // Check if the first row looks like data rather than headers
-                    // This helps with system files like /etc/passwd that don't have headers
-                    let is_likely_data = header_row.iter().any(|field| {
-                        // Common indicators that a field is data, not a header
-                        field.starts_with('/') || // Path
-                        field == "*" ||           // Password placeholder
-                        field == "root" ||        // Common username
-                        field == "nobody" ||      // Common username
-                        field.parse::<i32>().is_ok() // Numeric ID
-                    });
-
-                    if is_likely_data {
-                        // Generate alphabetical column names (a, b, c, etc.)
-                        (0..header_row.len())
-                            .map(|i| {
-                                // Convert number to alphabetical column name (a, b, ..., z, aa, ab, ...)
-                                let mut name = String::new();
-                                let mut n = i;
-                                loop {
-                                    name.insert(0, (b'a' + (n % 26) as u8) as char);
-                                    n /= 26;
-                                    if n == 0 {
-                                        break;
-                                    }
-                                    n -= 1; // Adjust for the shift from 0-based to 1-based
-                                }
-                                name
-                            })
-                            .collect::<Vec<_>>()
-                    } else {
-                        // Use the headers as they are
-                        header_row.iter().map(|s| s.to_string()).collect::<Vec<_>>()
-                    }
-                }
-                Err(_) => {
-                    // If we couldn't read headers, try to determine column count from first record
-                    let record_iter = csv_reader.records();
-                    let first_record = record_iter.into_iter().next();
-
-                    if let Some(Ok(record)) = first_record {
-                        // Generate alphabetical column names (a, b, c, etc.)
-                        (0..record.len())
-                            .map(|i| {
-                                // Convert number to alphabetical column name (a, b, ..., z, aa, ab, ...)
-                                let mut name = String::new();
-                                let mut n = i;
-                                loop {
-                                    name.insert(0, (b'a' + (n % 26) as u8) as char);
-                                    n /= 26;
-                                    if n == 0 {
-                                        break;
-                                    }
-                                    n -= 1; // Adjust for the shift from 0-based to 1-based
-                                }
-                                name
-                            })
-                            .collect::<Vec<_>>()
-                    } else {
-                        // Fallback to a minimal set if we can't determine field count
-                        vec!["a".to_string()]
-                    }
-                }
-            }
+    /// Read delimiter-separated data from any reader into an in-memory table
+    ///
+    /// This is the parsing half of [`DelimHandler::load_delimited`], used directly
+    /// for input that doesn't come from a named file, such as stdin.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to create
+    /// * `input` - Source of the delimited data
+    /// * `file_path` - The file the data came from, if any, used for write-back
+    /// * `delimiter` - Delimiter character to use for parsing
+    /// * `options` - Custom column names, header position and CSV dialect of the data
+    ///
+    /// # Returns
+    /// * `Ok(Table)` - The successfully loaded table
+    /// * `Err` if the delimiter is invalid or the data could not be parsed
+    pub fn read_delimited<R: Read>(
+        &self,
+        table_name: &str,
+        input: R,
+        file_path: Option<PathBuf>,
+        delimiter: &str,
+        options: LoadOptions,
+    ) -> SqawkResult<Table> {
+        let LoadOptions {
+            columns: custom_columns,
+            header,
+            mut dialect,
+        } = options;
+        let separator = FieldSeparator::parse(delimiter)?;
+        let mut reader = BufReader::new(input);
+
+        // Write-back keeps the file's line endings unless others were asked for
+        if dialect.terminator.is_none() {
+            dialect.terminator = Some(LineTerminator::detect(reader.fill_buf()?));
+        }
+
+        // Read the header and any lines around it, leaving the data in the reader
+        // Lines starting with # are comments by default, as in system files like /etc/passwd
+        let start = read_header_lines(&mut reader, &header, dialect.comment_char(), |line| {
+            Ok(looks_like_header(
+                &separator.fields(line, &dialect)?,
+                &self.value_format,
+            ))
+        })?;
+        let header_fields = start
+            .header
+            .as_deref()
+            .map(|line| separator.fields(line, &dialect))
+            .transpose()?;
+        let first_data_line = start
+            .first_data_line
+            .map(|line| line + "\n")
+            .unwrap_or_default();
+        let data = Cursor::new(first_data_line).chain(reader);
+
+        // Column names fix the number of fields; without them, the longest row does
+        let width = custom_columns
+            .as_ref()
+            .or(header_fields.as_ref())
+            .map(Vec::len);
+        let records = match separator {
+            // Only single-byte separators go through the CSV reader, which keeps quoting support
+            FieldSeparator::Char(byte) => read_records(data, byte, &dialect)?,
+            _ => split_lines(data, &separator, width, &dialect)?,
+        };
+        let headers = match (custom_columns, header_fields) {
+            (Some(columns), _) => columns,
+            (None, Some(fields)) => fields,
+            (None, None) => generated_column_names(records.iter().map(Vec::len).max().unwrap_or(1)),
         };
 
         // Create a new table with the determined headers and custom delimiter
-        let mut table = Table::new_with_delimiter(
-            &table_name,
-            headers,
-            Some(file_path.clone()),
-            delimiter.to_string(),
-        );
-
-        // Read rows
-        for result in csv_reader.records() {
-            let record = result.map_err(SqawkError::CsvError)?;
-
-            // Convert record to a row of values
-            let row = record.iter().map(Value::from).collect();
+        let mut table =
+            Table::new_with_delimiter(table_name, headers, file_path, delimiter.to_string());
+        table.set_header_layout(start.layout);
+        table.set_csv_dialect(dialect);
 
+        // Rows with too few fields are padded with NULLs
+        let width = table.column_count();
+        for fields in records {
+            let mut row: Vec<Value> = fields
+                .iter()
+                .map(|field| self.value_format.parse(field))
+                .collect();
+            if row.len() < width {
+                row.resize(width, Value::Null);
+            }
             table.add_row(row)?;
         }
 
@@ -276,3 +351,239 @@ impl DelimHandler {
         }
     }
 }
+
+/// How the fields on each line of a delimited file are separated
+///
+/// Parsed from the `-F` argument the way awk interprets its field separator:
+/// a single space (or the word `whitespace`) means runs of whitespace, and a
+/// pattern between slashes is a regular expression. Any other string longer than
+/// one character is matched literally.
+#[derive(Debug, Clone)]
+pub enum FieldSeparator {
+    /// A single byte, read with the CSV reader so quoted fields still work
+    Char(u8),
+    /// Runs of whitespace, ignoring leading and trailing whitespace (`-F ' '` or `-F whitespace`)
+    Whitespace,
+    /// A fixed multi-character string such as `::` or ` | `
+    Literal(String),
+    /// A regular expression written as `/pattern/`
+    Regex(Regex),
+}
+
+impl FieldSeparator {
+    /// Parse a field separator as given to `-F`
+    ///
+    /// # Arguments
+    /// * `separator` - The separator text, e.g. `:`, `\t`, ` `, `whitespace`, `::` or `/[,;]\s*/`
+    ///
+    /// # Returns
+    /// * `Ok(FieldSeparator)` - The parsed separator
+    /// * `Err` if the separator is empty or the regular expression is invalid
+    pub fn parse(separator: &str) -> SqawkResult<Self> {
+        match separator {
+            "" => Err(SqawkError::InvalidFileSpec(
+                "Invalid delimiter: the field separator can't be empty".to_string(),
+            )),
+            " " | "whitespace" => Ok(FieldSeparator::Whitespace),
+            "\\t" => Ok(FieldSeparator::Char(b'\t')),
+            s if s.len() == 1 => Ok(FieldSeparator::Char(s.as_bytes()[0])),
+            s if s.len() > 2 && s.starts_with('/') && s.ends_with('/') => {
+                Regex::new(&s[1..s.len() - 1])
+                    .map(FieldSeparator::Regex)
+                    .map_err(|e| {
+                        SqawkError::InvalidFileSpec(format!("Invalid delimiter regex {}: {}", s, e))
+                    })
+            }
+            s => Ok(FieldSeparator::Literal(s.to_string())),
+        }
+    }
+
+    /// Split a line into at most `limit` fields
+    ///
+    /// The last field holds the unsplit remainder of the line once the limit is reached.
+    ///
+    /// # Arguments
+    /// * `line` - The line to split, without its line terminator
+    /// * `limit` - The maximum number of fields to return
+    ///
+    /// # Returns
+    /// The fields of the line
+    pub fn split(&self, line: &str, limit: usize) -> Vec<String> {
+        match self {
+            FieldSeparator::Char(byte) => line
+                .splitn(limit, *byte as char)
+                .map(str::to_string)
+                .collect(),
+            FieldSeparator::Whitespace => {
+                let mut fields = Vec::new();
+                let mut rest = line.trim();
+                while !rest.is_empty() {
+                    if fields.len() + 1 >= limit {
+                        fields.push(rest.to_string());
+                        break;
+                    }
+                    match rest.find(char::is_whitespace) {
+                        Some(end) => {
+                            fields.push(rest[..end].to_string());
+                            rest = rest[end..].trim_start();
+                        }
+                        None => {
+                            fields.push(rest.to_string());
+                            break;
+                        }
+                    }
+                }
+                fields
+            }
+            FieldSeparator::Literal(literal) => line
+                .splitn(limit, literal.as_str())
+                .map(str::to_string)
+                .collect(),
+            FieldSeparator::Regex(regex) => regex.splitn(line, limit).map(str::to_string).collect(),
+        }
+    }
+
+    /// Split a whole line into fields
+    ///
+    /// Single-byte separators parse the line as CSV in the given dialect, so quoted
+    /// fields may contain the separator. Other separators only use its trimming.
+    ///
+    /// # Arguments
+    /// * `line` - The line to split, without its line terminator
+    /// * `dialect` - Quoting and trimming rules for the line
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - The fields of the line
+    /// * `Err` if the line is malformed
+    pub fn fields(&self, line: &str, dialect: &CsvDialect) -> SqawkResult<Vec<String>> {
+        match self {
+            FieldSeparator::Char(byte) => dialect.parse_line(line, *byte),
+            _ => Ok(trim_fields(self.split(line, usize::MAX), dialect)),
+        }
+    }
+
+    /// The text written between fields when saving a table
+    ///
+    /// # Returns
+    /// * `Some(String)` - The output separator; whitespace separators write a single space
+    /// * `None` for regular expressions, which have no fixed output form
+    pub fn output_separator(&self) -> Option<String> {
+        match self {
+            FieldSeparator::Char(byte) => Some((*byte as char).to_string()),
+            FieldSeparator::Whitespace => Some(" ".to_string()),
+            FieldSeparator::Literal(literal) => Some(literal.clone()),
+            FieldSeparator::Regex(_) => None,
+        }
+    }
+}
+
+/// Read every record with the CSV reader, skipping comment lines
+fn read_records<R: Read>(
+    input: R,
+    delimiter: u8,
+    dialect: &CsvDialect,
+) -> SqawkResult<Vec<Vec<String>>> {
+    let mut csv_reader = dialect.reader_builder(delimiter).from_reader(input);
+
+    csv_reader
+        .records()
+        .map(|result| {
+            let record = result.map_err(SqawkError::CsvError)?;
+            Ok(record.iter().map(str::to_string).collect())
+        })
+        .collect()
+}
+
+/// Split every line with a whitespace, multi-character or regex separator
+///
+/// Quotes have no special meaning, and blank lines and comment lines are skipped.
+/// With a known number of columns, extra fields stay together in the last column
+/// (so `ps aux` keeps whole command lines).
+fn split_lines<R: BufRead>(
+    input: R,
+    separator: &FieldSeparator,
+    width: Option<usize>,
+    dialect: &CsvDialect,
+) -> SqawkResult<Vec<Vec<String>>> {
+    let comment = dialect.comment_char();
+    let mut records = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        let is_comment = comment.map_or(false, |c| line.starts_with(c));
+        if !line.trim().is_empty() && !is_comment {
+            let fields = separator.split(line, width.unwrap_or(usize::MAX).max(1));
+            records.push(trim_fields(fields, dialect));
+        }
+    }
+    Ok(records)
+}
+
+/// Remove whitespace around each field if the dialect asks for it
+fn trim_fields(fields: Vec<String>, dialect: &CsvDialect) -> Vec<String> {
+    if !dialect.trim {
+        return fields;
+    }
+    fields
+        .iter()
+        .map(|field| field.trim().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(separator: &str, line: &str, limit: usize) -> Vec<String> {
+        FieldSeparator::parse(separator).unwrap().split(line, limit)
+    }
+
+    #[test]
+    fn test_parse_separators() {
+        assert!(matches!(
+            FieldSeparator::parse("\\t"),
+            Ok(FieldSeparator::Char(b'\t'))
+        ));
+        assert!(matches!(
+            FieldSeparator::parse(" "),
+            Ok(FieldSeparator::Whitespace)
+        ));
+        assert!(matches!(
+            FieldSeparator::parse("whitespace"),
+            Ok(FieldSeparator::Whitespace)
+        ));
+        assert!(matches!(
+            FieldSeparator::parse("::"),
+            Ok(FieldSeparator::Literal(_))
+        ));
+        assert!(matches!(
+            FieldSeparator::parse("/[,;]/"),
+            Ok(FieldSeparator::Regex(_))
+        ));
+        // A lone slash is an ordinary single-character separator
+        assert!(matches!(
+            FieldSeparator::parse("/"),
+            Ok(FieldSeparator::Char(b'/'))
+        ));
+        assert!(FieldSeparator::parse("").is_err());
+        assert!(FieldSeparator::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn test_split_with_limit() {
+        assert_eq!(split(" ", "  a \t b   c  ", usize::MAX), ["a", "b", "c"]);
+        assert_eq!(split(" ", "  a  b   c d ", 2), ["a", "b   c d"]);
+        assert!(split(" ", "   ", usize::MAX).is_empty());
+        assert_eq!(split("::", "a::b::c", 2), ["a", "b::c"]);
+        assert_eq!(split("/ *[,;] */", "a , b;c", usize::MAX), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_output_separator() {
+        let output = |s: &str| FieldSeparator::parse(s).unwrap().output_separator();
+        assert_eq!(output("\\t").as_deref(), Some("\t"));
+        assert_eq!(output(" ").as_deref(), Some(" "));
+        assert_eq!(output(" | ").as_deref(), Some(" | "));
+        assert_eq!(output("/,+/"), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! SQL dialect module for sqawk
+//!
+//! This module defines the SQL dialect used to parse sqawk statements. It follows
+//! the Hive dialect's identifier rules, which are what sqawk has always accepted,
+//! and extends the expression grammar with operators the Hive dialect lacks:
+//!
+//! - `a DIV b` - integer division (see the arithmetic module for its semantics)
+//! - `PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY x)` - ordered-set aggregates, which
+//!   are rewritten to the equivalent `PERCENTILE_CONT(x, 0.5)`
+//! - `json -> 'key'` and `json ->> '$.path'` - JSON access, binding tighter than
+//!   comparisons so `payload ->> 'status' = 'ok'` compares the extracted value
+//!
+//! Statements should be parsed with [`parse_sql`], which also accepts
+//! `table UNPIVOT (value FOR name IN (a, b))` in the FROM clause. The parser has no
+//! UNPIVOT table factor, so the clause alone is rewritten to the join
+//! `CROSS APPLY UNPIVOT(value, name, a, b)` before parsing. The parser then reads
+//! whatever precedes it (a table, an aliased table, a derived table, ...) as usual,
+//! and the executor applies each such join, found with [`unpivot_clause`], to the
+//! table factor before it.
+
+use sqlparser::ast::{
+    BinaryOperator, Expr, FunctionArg, FunctionArgExpr, Ident, Join, JoinOperator, JsonOperator,
+    ObjectName, Statement, TableAlias, TableFactor, Value,
+};
+use sqlparser::dialect::{Dialect, HiveDialect};
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+
+/// Parse SQL text into statements using the sqawk dialect
+///
+/// # Arguments
+/// * `sql` - The SQL text, possibly holding several statements
+///
+/// # Returns
+/// * The parsed statements
+/// * `Err` if the text cannot be tokenized or parsed
+pub fn parse_sql(sql: &str) -> Result<Vec<Statement>, ParserError> {
+    let dialect = SqawkDialect {};
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    Parser::new(&dialect)
+        .with_tokens(rewrite_unpivot(tokens)?)
+        .parse_statements()
+}
+
+/// Get the arguments of an UNPIVOT clause from the join it was parsed as
+///
+/// # Arguments
+/// * `join` - A join following a table factor in FROM
+///
+/// # Returns
+/// * `Some((args, alias))` - The value column, name column and unpivoted columns,
+///   and the alias given after the clause, if the join is an UNPIVOT clause
+/// * `None` for any other join
+pub fn unpivot_clause(join: &Join) -> Option<(&[FunctionArg], Option<&TableAlias>)> {
+    match (&join.join_operator, &join.relation) {
+        (
+            JoinOperator::CrossApply,
+            TableFactor::Table {
+                name,
+                args: Some(args),
+                alias,
+                ..
+            },
+        ) if name.to_string().eq_ignore_ascii_case("UNPIVOT") => Some((args, alias.as_ref())),
+        _ => None,
+    }
+}
+
+/// Rewrite `UNPIVOT (value FOR col IN (a, b))` to `CROSS APPLY UNPIVOT(value, col, a, b)`
+///
+/// Only the clause itself is rewritten; the table factor before it is left to the
+/// parser. `UNPIVOT` not followed by a parenthesis (e.g. a column of that name) is
+/// left unchanged.
+///
+/// # Returns
+/// * The rewritten tokens
+/// * `Err` if an UNPIVOT clause is malformed or doesn't follow a table
+fn rewrite_unpivot(tokens: Vec<Token>) -> Result<Vec<Token>, ParserError> {
+    let is_word = |token: &Token, word: &str| matches!(token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(word));
+    let syntax_error = || {
+        ParserError::ParserError(
+            "Expected UNPIVOT (value FOR name IN (column, ...)) after a table in FROM".to_string(),
+        )
+    };
+
+    let mut rewritten: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        if !is_word(&tokens[i], "UNPIVOT") {
+            rewritten.push(tokens[i].clone());
+            i += 1;
+            continue;
+        }
+
+        // The significant (non-whitespace) tokens from UNPIVOT on
+        let significant: Vec<usize> = (i..tokens.len())
+            .filter(|&j| !matches!(tokens[j], Token::Whitespace(_)))
+            .collect();
+        let token_at = |k: usize| significant.get(k).map(|&j| &tokens[j]);
+        if token_at(1) != Some(&Token::LParen) {
+            rewritten.push(tokens[i].clone());
+            i += 1;
+            continue;
+        }
+
+        // The clause must follow a table factor, not start the FROM list
+        let previous = rewritten
+            .iter()
+            .rev()
+            .find(|token| !matches!(token, Token::Whitespace(_)));
+        let follows_table = match previous {
+            Some(Token::Word(w)) => !matches!(w.keyword, Keyword::FROM | Keyword::JOIN),
+            Some(Token::RParen) => true,
+            _ => false,
+        };
+
+        // ( value FOR name IN ( column, ... ) )
+        let is_name = |k: usize| matches!(token_at(k), Some(Token::Word(_)));
+        if !follows_table
+            || !is_name(2)
+            || !token_at(3).map_or(false, |t| is_word(t, "FOR"))
+            || !is_name(4)
+            || !token_at(5).map_or(false, |t| is_word(t, "IN"))
+            || token_at(6) != Some(&Token::LParen)
+        {
+            return Err(syntax_error());
+        }
+        let mut columns = Vec::new();
+        let mut k = 7;
+        loop {
+            if !is_name(k) {
+                return Err(syntax_error());
+            }
+            columns.push(token_at(k).cloned().unwrap_or(Token::EOF));
+            match token_at(k + 1) {
+                Some(Token::Comma) => k += 2,
+                Some(Token::RParen) => break,
+                _ => return Err(syntax_error()),
+            }
+        }
+        if token_at(k + 2) != Some(&Token::RParen) {
+            return Err(syntax_error());
+        }
+
+        let space = Token::Whitespace(Whitespace::Space);
+        rewritten.extend([
+            space.clone(),
+            Token::make_keyword("CROSS"),
+            space.clone(),
+            Token::make_keyword("APPLY"),
+            space,
+            Token::make_word("UNPIVOT", None),
+            Token::LParen,
+            tokens[significant[2]].clone(),
+            Token::Comma,
+            tokens[significant[4]].clone(),
+        ]);
+        for column in columns {
+            rewritten.push(Token::Comma);
+            rewritten.push(column);
+        }
+        rewritten.push(Token::RParen);
+        i = significant[k + 2] + 1;
+    }
+
+    Ok(rewritten)
+}
+
+/// The SQL dialect accepted by sqawk
+///
+/// Identifier rules are delegated to `HiveDialect`, so table and column names parse
+/// exactly as they would with Hive; only the infix operator handling is extended.
+#[derive(Debug, Default)]
+pub struct SqawkDialect {}
+
+impl Dialect for SqawkDialect {
+    fn is_delimited_identifier_start(&self, ch: char) -> bool {
+        HiveDialect {}.is_delimited_identifier_start(ch)
+    }
+
+    fn is_identifier_start(&self, ch: char) -> bool {
+        HiveDialect {}.is_identifier_start(ch)
+    }
+
+    fn is_identifier_part(&self, ch: char) -> bool {
+        HiveDialect {}.is_identifier_part(ch)
+    }
+
+    fn supports_filter_during_aggregation(&self) -> bool {
+        HiveDialect {}.supports_filter_during_aggregation()
+    }
+
+    /// Accept `ROLLUP`, `CUBE` and `GROUPING SETS` in GROUP BY
+    fn supports_group_by_expr(&self) -> bool {
+        true
+    }
+
+    /// Parse ordered-set aggregate calls with a `WITHIN GROUP (ORDER BY ...)` clause
+    ///
+    /// The sort expression becomes the first argument of the call. A descending
+    /// percentile is the ascending percentile of `1 - fraction`.
+    fn parse_prefix(&self, parser: &mut Parser) -> Option<Result<Expr, ParserError>> {
+        let name = match parser.peek_token().token {
+            Token::Word(w)
+                if matches!(
+                    w.value.to_uppercase().as_str(),
+                    "PERCENTILE_CONT" | "PERCENTILE_DISC" | "MODE"
+                ) && parser.peek_nth_token(1).token == Token::LParen =>
+            {
+                w.value
+            }
+            _ => return None,
+        };
+        parser.next_token();
+
+        Some(
+            parser
+                .parse_function(ObjectName(vec![Ident::new(name)]))
+                .and_then(|expr| {
+                    let mut func = match expr {
+                        Expr::Function(func) => func,
+                        other => return Ok(other),
+                    };
+                    if !parser.parse_keywords(&[Keyword::WITHIN, Keyword::GROUP]) {
+                        return Ok(Expr::Function(func));
+                    }
+
+                    parser.expect_token(&Token::LParen)?;
+                    parser.expect_keywords(&[Keyword::ORDER, Keyword::BY])?;
+                    let order = parser.parse_order_by_expr()?;
+                    parser.expect_token(&Token::RParen)?;
+
+                    if order.asc == Some(false) {
+                        for arg in func.args.iter_mut() {
+                            if let FunctionArg::Unnamed(FunctionArgExpr::Expr(fraction)) = arg {
+                                *fraction = Expr::BinaryOp {
+                                    left: Box::new(Expr::Value(Value::Number(
+                                        "1".to_string(),
+                                        false,
+                                    ))),
+                                    op: BinaryOperator::Minus,
+                                    right: Box::new(Expr::Nested(Box::new(fraction.clone()))),
+                                };
+                            }
+                        }
+                    }
+                    func.args
+                        .insert(0, FunctionArg::Unnamed(FunctionArgExpr::Expr(order.expr)));
+                    Ok(Expr::Function(func))
+                }),
+        )
+    }
+
+    /// Parse the `DIV` integer division operator and the `->`/`->>` JSON operators
+    ///
+    /// The parser already assigns `DIV` multiplicative precedence but only builds
+    /// the expression for some dialects, so the operator is handled here. The JSON
+    /// operators are built by the parser, but with everything to their right as the
+    /// selector, so they are handled here to parse only an operand of their own
+    /// precedence.
+    fn parse_infix(
+        &self,
+        parser: &mut Parser,
+        expr: &Expr,
+        precedence: u8,
+    ) -> Option<Result<Expr, ParserError>> {
+        match parser.peek_token().token {
+            Token::Word(w) if w.keyword == Keyword::DIV => {
+                parser.next_token();
+                Some(
+                    parser
+                        .parse_subexpr(precedence)
+                        .map(|right| Expr::BinaryOp {
+                            left: Box::new(expr.clone()),
+                            op: BinaryOperator::MyIntegerDivide,
+                            right: Box::new(right),
+                        }),
+                )
+            }
+            Token::Arrow | Token::LongArrow => {
+                let operator = if parser.next_token().token == Token::Arrow {
+                    JsonOperator::Arrow
+                } else {
+                    JsonOperator::LongArrow
+                };
+                Some(
+                    parser
+                        .parse_subexpr(precedence)
+                        .map(|right| Expr::JsonAccess {
+                            left: Box::new(expr.clone()),
+                            operator,
+                            right: Box::new(right),
+                        }),
+                )
+            }
+            _ => None,
+        }
+    }
+}
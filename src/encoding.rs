@@ -0,0 +1,362 @@
+//! Character encoding module for sqawk
+//!
+//! This module converts input files to UTF-8 before they are parsed, and converts
+//! tables back to their file's encoding when they are written. It provides:
+//!
+//! - Byte order mark (BOM) detection and removal, so a UTF-8 BOM doesn't end up
+//!   in the first column name
+//! - Transcoding from any encoding known to the WHATWG Encoding Standard, such as
+//!   `windows-1252`, `latin1` or `utf-16le`, chosen with `--encoding`
+//! - Per-table settings given on the command line as `[table=]value`
+//!
+//! The encoding and BOM of each file are remembered on its table, so write-back
+//! produces the same bytes a Windows tool would expect.
+
+use std::fmt;
+use std::io::{self, Cursor, ErrorKind, Read};
+use std::str::FromStr;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::header_options::{option_for, TableOption};
+
+/// An encoding named on the command line (`--encoding`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodingName(pub &'static Encoding);
+
+impl FromStr for EncodingName {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Encoding::for_label(s.trim().as_bytes())
+            .map(EncodingName)
+            .ok_or_else(|| {
+                SqawkError::InvalidArgument(format!(
+                    "Unknown encoding '{}' (e.g. utf-8, windows-1252, latin1, utf-16le)",
+                    s
+                ))
+            })
+    }
+}
+
+impl fmt::Display for EncodingName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
+
+/// How a file's text is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    /// The character encoding of the file
+    pub encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark; `None` until read from the file
+    pub bom: Option<bool>,
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        FileEncoding {
+            encoding: UTF_8,
+            bom: None,
+        }
+    }
+}
+
+impl FileEncoding {
+    /// Wrap an input so that it yields UTF-8 text
+    ///
+    /// A byte order mark decides the encoding, whatever encoding was given, and is
+    /// removed from the text. Invalid input is an error rather than being replaced,
+    /// so that writing the table back can't corrupt the file.
+    ///
+    /// Plain UTF-8 is streamed and checked as it is read; other encodings are
+    /// read whole and transcoded here.
+    ///
+    /// # Arguments
+    /// * `input` - The raw file contents
+    ///
+    /// # Returns
+    /// * `Ok((Box<dyn Read>, FileEncoding))` - The UTF-8 text and the encoding it was read with
+    /// * `Err` if the input can't be read or isn't valid in the encoding
+    pub fn decode<'a, R: Read + 'a>(
+        &self,
+        mut input: R,
+    ) -> SqawkResult<(Box<dyn Read + 'a>, FileEncoding)> {
+        // Read just enough to see a byte order mark, however the input is chunked
+        let mut start = [0; 3];
+        let mut start_length = 0;
+        while start_length < start.len() {
+            match input.read(&mut start[start_length..]) {
+                Ok(0) => break,
+                Ok(n) => start_length += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let start = &start[..start_length];
+
+        let (encoding, bom_length) = match Encoding::for_bom(start) {
+            Some((encoding, length)) => (encoding, length),
+            None => (self.encoding, 0),
+        };
+        let read_as = FileEncoding {
+            encoding,
+            bom: Some(self.bom.unwrap_or(bom_length > 0)),
+        };
+        let start = start[bom_length..].to_vec();
+
+        // Plain UTF-8 needs no copy, only validation
+        if encoding == UTF_8 {
+            return Ok((Box::new(Utf8Reader::new(input, start)), read_as));
+        }
+
+        let mut bytes = start;
+        input.read_to_end(&mut bytes)?;
+        let text = encoding
+            .decode_without_bom_handling_and_without_replacement(&bytes)
+            .ok_or_else(|| invalid_text(encoding))?;
+        Ok((Box::new(Cursor::new(text.into_owned())), read_as))
+    }
+
+    /// Convert UTF-8 text to this encoding, adding a byte order mark if the file had one
+    ///
+    /// # Arguments
+    /// * `text` - The UTF-8 text to write
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded bytes
+    /// * `Err` if the text isn't UTF-8 or has characters the encoding can't represent
+    pub fn encode(&self, text: Vec<u8>) -> SqawkResult<Vec<u8>> {
+        let text = String::from_utf8(text).map_err(|_| invalid_text(UTF_8))?;
+        let bom = self.bom.unwrap_or(false);
+
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.encoding == UTF_8 {
+            if bom {
+                bytes.extend_from_slice(b"\xEF\xBB\xBF");
+            }
+            bytes.extend_from_slice(text.as_bytes());
+        } else if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            // encoding_rs only encodes to ASCII-compatible encodings, so UTF-16 is done here
+            let little_endian = self.encoding == UTF_16LE;
+            for unit in std::iter::once(0xFEFF)
+                .filter(|_| bom)
+                .chain(text.encode_utf16())
+            {
+                if little_endian {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+        } else {
+            let (encoded, _, unmappable) = self.encoding.encode(&text);
+            if unmappable {
+                let character = text
+                    .chars()
+                    .find(|c| {
+                        let mut buffer = [0; 4];
+                        self.encoding.encode(c.encode_utf8(&mut buffer)).2
+                    })
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Err(SqawkError::InvalidArgument(format!(
+                    "Character '{}' can't be written in {}",
+                    character,
+                    self.encoding.name()
+                )));
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+        Ok(bytes)
+    }
+
+    /// Check whether text in this encoding is written exactly as UTF-8 is
+    pub fn is_plain_utf8(&self) -> bool {
+        self.encoding == UTF_8 && !self.bom.unwrap_or(false)
+    }
+}
+
+/// The error for input that isn't valid in its encoding
+fn invalid_text(encoding: &'static Encoding) -> SqawkError {
+    SqawkError::InvalidArgument(invalid_text_message(encoding))
+}
+
+/// Describe input that isn't valid in its encoding
+fn invalid_text_message(encoding: &'static Encoding) -> String {
+    format!(
+        "Input is not valid {} text (use --encoding to read it as another encoding, e.g. windows-1252)",
+        encoding.name()
+    )
+}
+
+/// A reader that passes UTF-8 through, failing at the first invalid byte
+///
+/// The start of a character split across two reads of the input is held back
+/// until the rest of it arrives, so a character cut off at the end is caught too.
+struct Utf8Reader<R> {
+    /// The raw input
+    inner: R,
+    /// Bytes read from the input but not yet returned
+    buffer: Vec<u8>,
+    /// Start of the bytes in `buffer` that haven't been returned
+    position: usize,
+    /// End of the checked, whole characters in `buffer`
+    checked: usize,
+}
+
+impl<R: Read> Utf8Reader<R> {
+    /// Size of each read from the input
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    /// Wrap an input whose first bytes have already been read
+    fn new(inner: R, start: Vec<u8>) -> Self {
+        Utf8Reader {
+            inner,
+            buffer: start,
+            position: 0,
+            checked: 0,
+        }
+    }
+
+    fn invalid() -> io::Error {
+        io::Error::new(ErrorKind::InvalidData, invalid_text_message(UTF_8))
+    }
+}
+
+impl<R: Read> Read for Utf8Reader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.checked {
+            // Keep the start of a character that was cut off by the last read
+            self.buffer.drain(..self.checked);
+            self.position = 0;
+            self.checked = 0;
+
+            let kept = self.buffer.len();
+            self.buffer.resize(kept + Self::CHUNK_SIZE, 0);
+            let read = self.inner.read(&mut self.buffer[kept..]);
+            self.buffer.truncate(kept + *read.as_ref().unwrap_or(&0));
+            let end_of_input = read? == 0;
+
+            self.checked = match std::str::from_utf8(&self.buffer) {
+                Ok(_) => self.buffer.len(),
+                Err(e) if e.error_len().is_none() && !end_of_input => e.valid_up_to(),
+                Err(_) => return Err(Self::invalid()),
+            };
+            if end_of_input && self.buffer.is_empty() {
+                return Ok(0);
+            }
+        }
+
+        let length = out.len().min(self.checked - self.position);
+        out[..length].copy_from_slice(&self.buffer[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Encoding options from the command line, resolved per table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodingSettings {
+    /// Values of `--encoding`
+    pub encodings: Vec<TableOption<EncodingName>>,
+    /// Values of `--bom`
+    pub boms: Vec<TableOption<bool>>,
+}
+
+impl EncodingSettings {
+    /// Get the file encoding for a table
+    ///
+    /// A value given for the table by name wins over one given for all tables.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table being loaded
+    ///
+    /// # Returns
+    /// * `FileEncoding` - The encoding of the table's file
+    pub fn for_table(&self, table_name: &str) -> FileEncoding {
+        FileEncoding {
+            encoding: option_for(&self.encodings, table_name).map_or(UTF_8, |name| name.0),
+            bom: option_for(&self.boms, table_name).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::WINDOWS_1252;
+
+    /// Decode some bytes and read all of the resulting text
+    fn decode_all(encoding: &FileEncoding, bytes: &[u8]) -> SqawkResult<(Vec<u8>, FileEncoding)> {
+        let (mut reader, read_as) = encoding.decode(bytes)?;
+        let mut text = Vec::new();
+        reader.read_to_end(&mut text)?;
+        Ok((text, read_as))
+    }
+
+    #[test]
+    fn test_decode_bom() {
+        let (text, read_as) =
+            decode_all(&FileEncoding::default(), b"\xEF\xBB\xBFid,name\n").unwrap();
+        assert_eq!(text, b"id,name\n");
+        assert_eq!(read_as.encoding, UTF_8);
+        assert_eq!(read_as.bom, Some(true));
+
+        // A BOM wins over the encoding that was asked for
+        let latin1 = FileEncoding {
+            encoding: WINDOWS_1252,
+            bom: None,
+        };
+        let (text, read_as) = decode_all(&latin1, b"\xFF\xFEi\0d\0").unwrap();
+        assert_eq!(text, b"id");
+        assert_eq!(read_as.encoding, UTF_16LE);
+
+        assert!(decode_all(&FileEncoding::default(), b"caf\xE9").is_err());
+        assert!(decode_all(&FileEncoding::default(), b"\xEF").is_err());
+    }
+
+    #[test]
+    fn test_utf8_reader() {
+        // Characters split across reads of the input and of the output survive
+        let text = "caf\u{e9} \u{4e2d}\n".repeat(2000);
+        let mut reader = Utf8Reader::new(&text.as_bytes()[3..], text.as_bytes()[..3].to_vec());
+        let mut out = Vec::new();
+        let mut chunk = [0; 7];
+        loop {
+            let length = reader.read(&mut chunk).unwrap();
+            if length == 0 {
+                break;
+            }
+            out.extend_from_slice(&chunk[..length]);
+        }
+        assert_eq!(out, text.as_bytes());
+
+        let mut reader = Utf8Reader::new(&b"Ren\xE9\n"[..], b"id\n".to_vec());
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("not valid UTF-8"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let latin1 = FileEncoding {
+            encoding: "latin1".parse::<EncodingName>().unwrap().0,
+            bom: None,
+        };
+        let (text, read_as) = decode_all(&latin1, b"caf\xE9\n").unwrap();
+        assert_eq!(String::from_utf8(text.clone()).unwrap(), "caf\u{e9}\n");
+        assert_eq!(read_as.bom, Some(false));
+        assert_eq!(read_as.encode(text).unwrap(), b"caf\xE9\n");
+
+        let utf16 = FileEncoding {
+            encoding: UTF_16BE,
+            bom: Some(true),
+        };
+        assert_eq!(utf16.encode(b"a".to_vec()).unwrap(), b"\xFE\xFF\0a");
+
+        assert!(latin1.encode("\u{4e2d}".as_bytes().to_vec()).is_err());
+        assert!("klingon".parse::<EncodingName>().is_err());
+    }
+}
@@ -39,6 +39,14 @@ pub enum SqawkError {
         error: String,
     },
 
+    /// JSON parsing error with file location information
+    #[error("JSON parse error in {file} at line {line}: {error}")]
+    JsonParseError {
+        file: String,
+        line: usize,
+        error: String,
+    },
+
     /// Error during SQL query parsing with sqlparser
     #[error("SQL parsing error: {0}")]
     SqlParseError(#[from] sqlparser::parser::ParserError),
@@ -83,6 +91,14 @@ pub enum SqawkError {
     #[error("Division by zero")]
     DivideByZero,
 
+    /// Error for arithmetic results too large to represent
+    #[error("Numeric overflow: {0}")]
+    ArithmeticOverflow(String),
+
+    /// Error for invalid command-line or REPL option values
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     /// Error for invalid function arguments
     #[error("Invalid function arguments: {0}")]
     InvalidFunctionArguments(String),
@@ -103,6 +119,7 @@ impl PartialEq for SqawkError {
             (SqawkError::IoError(_), SqawkError::IoError(_)) => true,
             (SqawkError::CsvError(_), SqawkError::CsvError(_)) => true,
             (SqawkError::CsvParseError { .. }, SqawkError::CsvParseError { .. }) => true,
+            (SqawkError::JsonParseError { .. }, SqawkError::JsonParseError { .. }) => true,
             (SqawkError::SqlParseError(_), SqawkError::SqlParseError(_)) => true,
             (SqawkError::TableNotFound(_), SqawkError::TableNotFound(_)) => true,
             (SqawkError::TableAlreadyExists(_), SqawkError::TableAlreadyExists(_)) => true,
@@ -114,6 +131,8 @@ impl PartialEq for SqawkError {
             (SqawkError::TypeError(_), SqawkError::TypeError(_)) => true,
             (SqawkError::InvalidSqlQuery(_), SqawkError::InvalidSqlQuery(_)) => true,
             (SqawkError::DivideByZero, SqawkError::DivideByZero) => true,
+            (SqawkError::ArithmeticOverflow(_), SqawkError::ArithmeticOverflow(_)) => true,
+            (SqawkError::InvalidArgument(_), SqawkError::InvalidArgument(_)) => true,
             (SqawkError::InvalidFunctionArguments(_), SqawkError::InvalidFunctionArguments(_)) => {
                 true
             }
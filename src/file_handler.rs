@@ -3,28 +3,91 @@
 //! This module provides a unified interface for loading and saving different file formats:
 //! - CSV files (comma-separated values)
 //! - Delimiter-separated files (tab, colon, etc.)
+//! - JSON arrays of objects and newline-delimited JSON
+//! - Fixed-width files, laid out by a `--tabledef` with column positions
 //!
 //! It abstracts away the specific file format details and provides a consistent API
 //! for the rest of the application to work with in-memory tables.
+//!
+//! A file path of `-` reads the table from standard input, so sqawk can be used in
+//! the middle of a shell pipeline. Such a table has no file to write back to.
 
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
 
+use crate::compression::Compression;
 use crate::config::AppConfig;
 use crate::csv_handler::CsvHandler;
 use crate::database::Database;
 use crate::delim_handler::DelimHandler;
 use crate::error::{SqawkError, SqawkResult};
+use crate::fixed_width_handler::{FixedWidthHandler, FixedWidthLayout};
+use crate::header_options::LoadOptions;
+use crate::json_handler::JsonHandler;
 use crate::table::Table;
 
 /// Enum representing different file formats supported by sqawk
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FileFormat {
     /// CSV (comma-separated values)
     Csv,
     /// Delimiter-separated values
     Delimited,
+    /// JSON array of objects (also reads newline-delimited JSON)
+    Json,
+    /// Newline-delimited JSON, one object per line
+    Ndjson,
+    /// Fixed-width columns at the positions given by the table's `--tabledef`
+    FixedWidth,
+}
+
+impl FileFormat {
+    /// Names accepted by `--input-format`
+    pub const NAMES: &'static [&'static str] = &["csv", "tsv", "json", "ndjson", "fixed-width"];
+}
+
+impl FromStr for FileFormat {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(FileFormat::Csv),
+            "tsv" | "delimited" => Ok(FileFormat::Delimited),
+            "json" => Ok(FileFormat::Json),
+            "ndjson" | "jsonl" => Ok(FileFormat::Ndjson),
+            "fixed-width" | "fixed" => Ok(FileFormat::FixedWidth),
+            _ => Err(SqawkError::InvalidArgument(format!(
+                "Unknown input format '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Delimited => "tsv",
+            FileFormat::Json => "json",
+            FileFormat::Ndjson => "ndjson",
+            FileFormat::FixedWidth => "fixed-width",
+        };
+        write!(f, "{}", name)
+    }
 }
 
+/// File path that stands for standard input
+pub const STDIN_PATH: &str = "-";
+
+/// Table name used for standard input when the file spec doesn't give one
+pub const STDIN_TABLE_NAME: &str = "stdin";
+
 /// Unified file handler that delegates to specific format handlers
 ///
 /// This struct provides a consistent interface for working with different file formats
@@ -43,12 +106,21 @@ pub struct FileHandler {
     /// Handler for delimiter-separated files (tab, pipe, etc.)
     delim_handler: DelimHandler,
 
+    /// Handler for JSON and newline-delimited JSON files
+    json_handler: JsonHandler,
+
+    /// Handler for fixed-width files
+    fixed_width_handler: FixedWidthHandler,
+
     /// Reference to a database object which is the source of truth for tables
     /// Stored as a raw pointer to avoid borrowing limitations
     database: *mut Database,
 
     /// Application configuration for global settings like verbosity and field separators
     config: AppConfig,
+
+    /// Name of the table read from standard input, once it has been read
+    stdin_table: Mutex<Option<String>>,
 }
 
 // Add safety implementation for the raw pointer to Database
@@ -66,11 +138,16 @@ impl FileHandler {
     /// A new FileHandler instance ready to load and manage tables
     pub fn new(config: &AppConfig, database: &mut Database) -> Self {
         FileHandler {
-            csv_handler: CsvHandler::new(),
-            delim_handler: DelimHandler::new(),
+            csv_handler: CsvHandler::with_value_format(config.value_format().clone()),
+            delim_handler: DelimHandler::with_value_format(config.value_format().clone()),
+            json_handler: JsonHandler::new(),
+            fixed_width_handler: FixedWidthHandler::with_value_format(
+                config.value_format().clone(),
+            ),
             // SAFETY: The caller must ensure that the database outlives this FileHandler
             database: database as *mut Database,
             config: config.clone(),
+            stdin_table: Mutex::new(None),
         }
     }
 
@@ -134,28 +211,9 @@ impl FileHandler {
             );
         }
 
-        // Determine the file format based on extension
-        let format = self.detect_format(&file_path);
-
         // Column definitions now come exclusively from Database
         // No need for custom columns logic here anymore
-
-        // Create the table based on the format
-        let table = match format {
-            FileFormat::Csv => {
-                // Load the table from the CSV file, no custom columns since we use Database schemas
-                self.csv_handler.load_csv(file_spec, None, None)?
-            }
-            FileFormat::Delimited => {
-                let delimiter = self
-                    .config
-                    .field_separator()
-                    .unwrap_or_else(|| "\t".to_string());
-                // No custom columns since we use Database schemas
-                self.delim_handler
-                    .load_delimited(file_spec, &delimiter, None)?
-            }
-        };
+        let table = self.read_file(file_spec, None)?;
 
         // Now that we have the table, we can update the database without borrowing conflicts
         {
@@ -179,6 +237,87 @@ impl FileHandler {
         Ok(Some((table_name, file_path_str)))
     }
 
+    /// Read a file into a table without adding it to the database
+    ///
+    /// The format is detected from the file extension as in [`FileHandler::load_file`]
+    /// (or taken from `--input-format` or the table's fixed-width layout), unless an
+    /// explicit delimiter is given, in which case the file is read as
+    /// delimiter-separated regardless of its extension.
+    /// A path of `-` reads standard input, which can only be read once.
+    ///
+    /// # Arguments
+    /// * `file_spec` - File specification in format [table_name=]file_path
+    /// * `delimiter` - Optional field delimiter overriding format detection
+    ///
+    /// # Returns
+    /// * `SqawkResult<Table>` - The loaded table
+    pub fn read_file(&self, file_spec: &str, delimiter: Option<&str>) -> SqawkResult<Table> {
+        let (table_name, path) = self.parse_file_spec(file_spec)?;
+        let format = match delimiter {
+            Some(_) => FileFormat::Delimited,
+            None => self.file_format(&table_name, &path)?,
+        };
+
+        let (raw_input, file_path): (Box<dyn Read>, Option<PathBuf>) = if is_stdin(&path) {
+            let mut stdin_table = self
+                .stdin_table
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if stdin_table.is_some() {
+                return Err(SqawkError::InvalidFileSpec(
+                    "Standard input can only be read once".to_string(),
+                ));
+            }
+            *stdin_table = Some(table_name.clone());
+            (Box::new(std::io::stdin()), None)
+        } else {
+            (Box::new(File::open(&path)?), Some(path))
+        };
+
+        // Decompress the input and convert it to UTF-8, removing any byte order mark
+        let (raw_input, compression) = Compression::decompress(raw_input, file_path.as_deref())?;
+        let (input, encoding) = self.config.file_encoding(&table_name).decode(raw_input)?;
+
+        // Column names from --tabledef replace the header, or name a headerless file's columns
+        let options = LoadOptions {
+            columns: self.config.table_columns(&table_name),
+            header: self.config.header_options(&table_name),
+            dialect: self.config.csv_dialect(&table_name),
+        };
+
+        let mut table = match format {
+            FileFormat::Csv => {
+                self.csv_handler
+                    .read_csv(&table_name, input, file_path, options, None)
+            }
+            FileFormat::Delimited => {
+                let delimiter = delimiter.map(str::to_string).unwrap_or_else(|| {
+                    self.config
+                        .field_separator()
+                        .unwrap_or_else(|| "\t".to_string())
+                });
+                self.delim_handler.read_delimited(
+                    &table_name,
+                    input,
+                    file_path,
+                    &delimiter,
+                    options,
+                )
+            }
+            FileFormat::Json | FileFormat::Ndjson => {
+                self.json_handler.read_json(&table_name, input, file_path)
+            }
+            FileFormat::FixedWidth => {
+                let layout = self.fixed_width_layout(&table_name)?;
+                self.fixed_width_handler
+                    .read_fixed_width(&table_name, input, file_path, layout)
+            }
+        }?;
+        table.set_file_encoding(encoding);
+        table.set_compression(compression);
+        Ok(table)
+    }
+
     /// Parse a file specification into a table name and path
     ///
     /// # Arguments
@@ -187,6 +326,11 @@ impl FileHandler {
     /// # Returns
     /// * `SqawkResult<(String, PathBuf)>` - Tuple of (table_name, file_path)
     pub fn parse_file_spec(&self, file_spec: &str) -> SqawkResult<(String, PathBuf)> {
+        // Standard input is named "stdin" unless the spec names it
+        if file_spec == STDIN_PATH {
+            return Ok((STDIN_TABLE_NAME.to_string(), PathBuf::from(STDIN_PATH)));
+        }
+
         // Check for explicit table name in format "table_name=file_path"
         if let Some(pos) = file_spec.find('=') {
             let (table_name, file_path) = file_spec.split_at(pos);
@@ -196,7 +340,7 @@ impl FileHandler {
 
             // Validate that the file exists
             let path = PathBuf::from(file_path);
-            if !path.exists() {
+            if !is_stdin(&path) && !path.exists() {
                 return Err(SqawkError::FileNotFound(file_path.to_string()));
             }
 
@@ -210,8 +354,10 @@ impl FileHandler {
                 return Err(SqawkError::FileNotFound(file_spec.to_string()));
             }
 
-            // Get file name without extension as table name
-            let file_name = path
+            // Get file name without extension as table name, looking past
+            // a compression extension such as .gz
+            let file_name = Compression::strip_extension(&path);
+            let file_name = file_name
                 .file_name()
                 .ok_or_else(|| SqawkError::InvalidFileSpec(file_spec.to_string()))?
                 .to_string_lossy();
@@ -309,6 +455,23 @@ impl FileHandler {
         db.table_count()
     }
 
+    /// Check if a table was read from standard input
+    ///
+    /// Such a table has no file, so it can't be written back.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to check
+    ///
+    /// # Returns
+    /// * `bool` - True if the table was read from standard input
+    pub fn is_stdin_table(&self, table_name: &str) -> bool {
+        self.stdin_table
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_deref()
+            == Some(table_name)
+    }
+
     /// Check if a table exists
     ///
     /// # Arguments
@@ -385,38 +548,98 @@ impl FileHandler {
             }
         }
 
-        // Determine the format based on the file extension
-        let format = self.detect_format(file_path);
+        // Determine the format based on the file extension or --input-format
+        let format = self.file_format(table_name, file_path)?;
 
         // Get the delimiter from the table
         let delimiter = table.delimiter();
 
-        // Save the table based on the format
+        // Format the table based on the format; the text is UTF-8 until encoded below
+        let mut text = Vec::new();
         match format {
             FileFormat::Csv => {
                 // Delegation to CSV handler (comma is the standard CSV delimiter)
                 if delimiter == "," {
-                    self.csv_handler.save_csv(table, file_path)?;
+                    self.csv_handler.write_csv(table, &mut text)?;
                 } else {
                     // If delimiter is not a comma, use the delimited handler
                     self.delim_handler
-                        .save_delimited(table, file_path, delimiter)?;
+                        .write_delimited(table, &mut text, delimiter)?;
                 }
             }
             FileFormat::Delimited => {
                 // Delegation to delimited handler
                 self.delim_handler
-                    .save_delimited(table, file_path, delimiter)?;
+                    .write_delimited(table, &mut text, delimiter)?;
             }
+            FileFormat::Json => self.json_handler.write_json(table, &mut text, false)?,
+            FileFormat::Ndjson => self.json_handler.write_json(table, &mut text, true)?,
+            FileFormat::FixedWidth => {
+                let layout = self.fixed_width_layout(table_name)?;
+                self.fixed_width_handler
+                    .write_fixed_width(table, &mut text, layout)?
+            }
+        }
+
+        // Write the file in the encoding and compression it was read with
+        table.write_file(file_path, text)
+    }
+
+    /// Get the format of a table's file
+    ///
+    /// A fixed-width layout in the table's `--tabledef` takes precedence, then
+    /// `--input-format`, then `-F` (which reads every file as delimiter-separated),
+    /// then detection from the file extension.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table read from or written to the file
+    /// * `path` - File path to analyze
+    ///
+    /// # Returns
+    /// * `Ok(FileFormat)` - The format used to read and write the file
+    /// * `Err` if the table's fixed-width layout is invalid
+    fn file_format(&self, table_name: &str, path: &Path) -> SqawkResult<FileFormat> {
+        if self.config.fixed_width_layout(table_name).is_some() {
+            return Ok(FileFormat::FixedWidth);
         }
 
-        Ok(())
+        Ok(
+            match (self.config.input_format(), self.detect_format(path)) {
+                (Some(format), _) => format,
+                // -F changes the separator of text tables, not the format of JSON files
+                (None, FileFormat::Csv | FileFormat::Delimited)
+                    if self.config.field_separator().is_some() =>
+                {
+                    FileFormat::Delimited
+                }
+                (None, format) => format,
+            },
+        )
+    }
+
+    /// Get the fixed-width layout of a table
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table
+    ///
+    /// # Returns
+    /// * `Ok(&FixedWidthLayout)` - The layout from the table's `--tabledef`
+    /// * `Err` if the table has no fixed-width layout
+    fn fixed_width_layout(&self, table_name: &str) -> SqawkResult<&FixedWidthLayout> {
+        self.config.fixed_width_layout(table_name).ok_or_else(|| {
+            SqawkError::InvalidArgument(format!(
+                "No fixed-width layout defined for table '{}'",
+                table_name
+            ))
+        })
     }
 
     /// Detect file format based on file extension
     ///
+    /// A compression extension such as `.gz` is skipped, so `data.csv.gz` is CSV.
     /// This method examines the file extension to determine the appropriate handler:
     /// - `.csv` files are treated as CSV (comma-separated values)
+    /// - `.json` files are treated as JSON, and `.ndjson`/`.jsonl` files as NDJSON
     /// - All other extensions are treated as delimiter-separated files
     /// - Files without extensions default to CSV format
     ///
@@ -424,11 +647,13 @@ impl FileHandler {
     /// * `path` - File path to analyze
     ///
     /// # Returns
-    /// * `FileFormat` - Detected format (Csv or Delimited) based on file extension
+    /// * `FileFormat` - Detected format based on file extension
     fn detect_format(&self, path: &Path) -> FileFormat {
-        if let Some(ext) = path.extension() {
+        if let Some(ext) = Compression::strip_extension(path).extension() {
             match ext.to_string_lossy().to_lowercase().as_str() {
                 "csv" => FileFormat::Csv,
+                "json" => FileFormat::Json,
+                "ndjson" | "jsonl" => FileFormat::Ndjson,
                 _ => FileFormat::Delimited,
             }
         } else {
@@ -437,3 +662,21 @@ impl FileHandler {
         }
     }
 }
+
+/// Check whether a file path stands for standard input
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_format_names_round_trip() {
+        for name in FileFormat::NAMES {
+            assert_eq!(name.parse::<FileFormat>().unwrap().to_string(), *name);
+        }
+        assert!("yaml".parse::<FileFormat>().is_err());
+    }
+}
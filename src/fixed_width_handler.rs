@@ -0,0 +1,426 @@
+//! Fixed-width file handling module for sqawk
+//!
+//! This module handles loading and saving fixed-width files, where each column
+//! occupies the same character positions on every line, as in many mainframe and
+//! bank exports. It provides functionality for:
+//!
+//! - Parsing column layouts such as `id@1-6,name@7-30,amount@31-40`
+//! - Reading layouts from a sidecar file given as `@path`
+//! - Trimming the padding around each field on load
+//! - Writing unchanged values back as they were, and re-padding changed ones
+//!   to their column widths
+//!
+//! Positions are 1-based and inclusive, matching the way record layouts are
+//! usually documented. Fixed-width files have no header line; column names come
+//! from the layout.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::table::{Table, Value, ValueFormat};
+
+/// One column of a fixed-width layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidthColumn {
+    /// Column name
+    pub name: String,
+    /// Zero-based character offset of the first character of the column
+    pub start: usize,
+    /// Number of characters in the column
+    pub width: usize,
+}
+
+impl FixedWidthColumn {
+    /// Zero-based character offset just past the end of the column
+    pub fn end(&self) -> usize {
+        self.start + self.width
+    }
+}
+
+/// The column positions of a fixed-width file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidthLayout {
+    /// Columns in table order
+    columns: Vec<FixedWidthColumn>,
+}
+
+impl FixedWidthLayout {
+    /// Check whether a `--tabledef` column list describes a fixed-width layout
+    ///
+    /// # Arguments
+    /// * `spec` - The column list after `table_name:`
+    ///
+    /// # Returns
+    /// * `true` if the list uses `name@start-end` positions or names a layout file
+    pub fn is_layout(spec: &str) -> bool {
+        spec.contains('@')
+    }
+
+    /// Parse a fixed-width layout
+    ///
+    /// The layout is a comma-separated list of `name@start-end` columns, where `start`
+    /// and `end` are 1-based inclusive character positions (`name@start` is a
+    /// single character). A layout starting with `@` names a sidecar file holding the
+    /// columns, one per line or comma-separated, with `#` starting a comment.
+    ///
+    /// # Arguments
+    /// * `spec` - The layout, e.g. `id@1-6,name@7-30` or `@layouts/accounts.txt`
+    ///
+    /// # Returns
+    /// * `Ok(FixedWidthLayout)` - The parsed layout
+    /// * `Err` if the layout file can't be read, a column is malformed, or columns overlap
+    pub fn parse(spec: &str) -> SqawkResult<Self> {
+        let text = match spec.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path).map_err(|e| {
+                SqawkError::InvalidArgument(format!(
+                    "Can't read fixed-width layout file {}: {}",
+                    path, e
+                ))
+            })?,
+            None => spec.to_string(),
+        };
+
+        let columns = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(parse_column)
+            .collect::<SqawkResult<Vec<_>>>()?;
+
+        if columns.is_empty() {
+            return Err(SqawkError::InvalidArgument(format!(
+                "Fixed-width layout '{}' has no columns",
+                spec
+            )));
+        }
+
+        // Columns may be listed in any order, but may not share positions
+        let mut by_position: Vec<&FixedWidthColumn> = columns.iter().collect();
+        by_position.sort_by_key(|column| column.start);
+        for pair in by_position.windows(2) {
+            if pair[1].start < pair[0].end() {
+                return Err(SqawkError::InvalidArgument(format!(
+                    "Fixed-width columns '{}' and '{}' overlap",
+                    pair[0].name, pair[1].name
+                )));
+            }
+        }
+
+        Ok(FixedWidthLayout { columns })
+    }
+
+    /// Parse the fixed-width layouts among `--tabledef` table definitions
+    ///
+    /// Each table uses its last definition, so a later plain column list replaces
+    /// an earlier layout. Layout files named with `@path` are read now, relative to
+    /// the current directory, so that later directory changes don't affect them.
+    ///
+    /// # Arguments
+    /// * `table_definitions` - Definitions in the form `table_name:columns`
+    ///
+    /// # Returns
+    /// * `Ok(HashMap)` - The layout of each table whose last definition is one
+    /// * `Err` if a layout file can't be read or a layout is invalid
+    pub fn from_table_definitions(
+        table_definitions: &[String],
+    ) -> SqawkResult<HashMap<String, FixedWidthLayout>> {
+        let mut layouts = HashMap::new();
+        for (table_name, spec) in table_definitions
+            .iter()
+            .filter_map(|tabledef| tabledef.split_once(':'))
+        {
+            if Self::is_layout(spec) {
+                layouts.insert(table_name.to_string(), Self::parse(spec)?);
+            } else {
+                layouts.remove(table_name);
+            }
+        }
+        Ok(layouts)
+    }
+
+    /// Get the columns of the layout in table order
+    pub fn columns(&self) -> &[FixedWidthColumn] {
+        &self.columns
+    }
+
+    /// Get the column names of the layout in table order
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// Get the length of a full record: the end of the rightmost column
+    pub fn record_width(&self) -> usize {
+        self.columns
+            .iter()
+            .map(FixedWidthColumn::end)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Parse one `name@start-end` or `name@start` layout entry
+fn parse_column(entry: &str) -> SqawkResult<FixedWidthColumn> {
+    let invalid = || {
+        SqawkError::InvalidArgument(format!(
+            "Invalid fixed-width column '{}': expected name@start-end with positions from 1",
+            entry
+        ))
+    };
+
+    let (name, range) = entry.split_once('@').ok_or_else(invalid)?;
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    let name = name.trim();
+    if name.is_empty() || start == 0 || end < start {
+        return Err(invalid());
+    }
+
+    Ok(FixedWidthColumn {
+        name: name.to_string(),
+        start: start - 1,
+        width: end - start + 1,
+    })
+}
+
+/// Handles loading and saving fixed-width files
+///
+/// The handler itself only holds value conversion rules; the layout of each
+/// file is passed in, since it comes from that table's `--tabledef`.
+pub struct FixedWidthHandler {
+    /// Rules for recognizing NULLs and booleans on load and writing NULLs on save
+    value_format: ValueFormat,
+}
+
+impl Default for FixedWidthHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixedWidthHandler {
+    /// Create a new FixedWidthHandler
+    ///
+    /// # Returns
+    /// A new FixedWidthHandler instance using the default value rules
+    pub fn new() -> Self {
+        Self::with_value_format(ValueFormat::default())
+    }
+
+    /// Create a new FixedWidthHandler with custom NULL and boolean handling
+    ///
+    /// # Arguments
+    /// * `value_format` - Rules for converting between field text and values
+    ///
+    /// # Returns
+    /// A new FixedWidthHandler that loads and saves values using the given rules
+    pub fn with_value_format(value_format: ValueFormat) -> Self {
+        FixedWidthHandler { value_format }
+    }
+
+    /// Read fixed-width records from any reader into an in-memory table
+    ///
+    /// Each non-blank line is one row. Fields are cut at the layout's positions and
+    /// trimmed of surrounding spaces; a line too short to reach a column leaves it empty.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to create
+    /// * `input` - Source of the fixed-width data
+    /// * `file_path` - The file the data came from, if any, used for write-back
+    /// * `layout` - Column positions of the file
+    ///
+    /// # Returns
+    /// * `Ok(Table)` - The successfully loaded table
+    /// * `Err` if the data could not be read
+    pub fn read_fixed_width<R: Read>(
+        &self,
+        table_name: &str,
+        input: R,
+        file_path: Option<PathBuf>,
+        layout: &FixedWidthLayout,
+    ) -> SqawkResult<Table> {
+        let mut table = Table::new(table_name, layout.column_names(), file_path);
+
+        for line in BufReader::new(input).lines() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Positions count characters, not bytes
+            let chars: Vec<char> = line.chars().collect();
+            let fields: Vec<String> = layout
+                .columns()
+                .iter()
+                .map(|column| chars.iter().skip(column.start).take(column.width).collect())
+                .collect();
+            let row = fields
+                .iter()
+                .map(|field| self.value_format.parse(field.trim()))
+                .collect();
+
+            table.add_row(row)?;
+            if let Some(row_id) = table.get_row_id_at_index(table.row_count() - 1) {
+                table.set_source_fields(row_id, fields);
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Write a table as fixed-width records to any writer
+    ///
+    /// Values that haven't changed since the file was loaded are written as they
+    /// were, keeping padding such as leading zeros. Other numbers are right-aligned
+    /// in their columns and everything else is left-aligned.
+    /// Characters not covered by any column are written as spaces. Nothing is written
+    /// if a value doesn't fit its column.
+    ///
+    /// # Arguments
+    /// * `table` - The table to write
+    /// * `writer` - Destination of the records
+    /// * `layout` - Column positions of the file
+    ///
+    /// # Returns
+    /// * `SqawkResult<()>` - Result of the operation
+    pub fn write_fixed_width<W: Write>(
+        &self,
+        table: &Table,
+        mut writer: W,
+        layout: &FixedWidthLayout,
+    ) -> SqawkResult<()> {
+        if table.column_count() != layout.columns().len() {
+            return Err(SqawkError::InvalidArgument(format!(
+                "Table '{}' has {} columns, but its fixed-width layout has {}",
+                table.name(),
+                table.column_count(),
+                layout.columns().len()
+            )));
+        }
+
+        // Format every record before writing any, so a value that doesn't fit
+        // leaves the output untouched
+        let mut records = Vec::with_capacity(table.row_count());
+        for (index, row) in table.rows().iter().enumerate() {
+            let source = table
+                .get_row_id_at_index(index)
+                .and_then(|row_id| table.source_fields(row_id));
+            let mut record = vec![' '; layout.record_width()];
+            for (col_idx, (value, column)) in row.iter().zip(layout.columns()).enumerate() {
+                // A value read from this field and not changed since keeps its text
+                if let Some(field) = source.and_then(|fields| fields.get(col_idx)) {
+                    if self.value_format.parse(field.trim()) == *value {
+                        for (i, c) in field.chars().enumerate() {
+                            record[column.start + i] = c;
+                        }
+                        continue;
+                    }
+                }
+
+                let text = self.value_format.write(value);
+                let len = text.chars().count();
+                if len > column.width {
+                    return Err(SqawkError::InvalidArgument(format!(
+                        "Value '{}' doesn't fit in the {} characters of column '{}' in table '{}'",
+                        text,
+                        column.width,
+                        column.name,
+                        table.name()
+                    )));
+                }
+
+                let offset = match value {
+                    Value::Integer(_) | Value::Float(_) => column.width - len,
+                    _ => 0,
+                };
+                for (i, c) in text.chars().enumerate() {
+                    record[column.start + offset + i] = c;
+                }
+            }
+            records.push(record.into_iter().collect::<String>());
+        }
+
+        for record in records {
+            writeln!(writer, "{}", record).map_err(SqawkError::IoError)?;
+        }
+        writer.flush().map_err(SqawkError::IoError)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_layout() {
+        let layout = FixedWidthLayout::parse("id@1-6, name@7-30,flag@31").unwrap();
+        assert_eq!(layout.column_names(), ["id", "name", "flag"]);
+        assert_eq!(layout.columns()[1].start, 6);
+        assert_eq!(layout.columns()[1].width, 24);
+        assert_eq!(layout.columns()[2].width, 1);
+        assert_eq!(layout.record_width(), 31);
+
+        assert!(FixedWidthLayout::is_layout("id@1-6"));
+        assert!(FixedWidthLayout::is_layout("@layout.txt"));
+        assert!(!FixedWidthLayout::is_layout("id,name"));
+    }
+
+    #[test]
+    fn test_parse_invalid_layouts() {
+        for spec in ["id@0-5", "id@5-1", "@1-5", "id@a-b", "id@1-6,name@6-10", ""] {
+            assert!(FixedWidthLayout::parse(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_read_and_pad() {
+        let layout = FixedWidthLayout::parse("id@1-4,name@5-10,amount@11-17").unwrap();
+        let handler = FixedWidthHandler::new();
+        let input = "0001Alice    12.50\n\n   2Bob         7\n";
+        let table = handler
+            .read_fixed_width("t", input.as_bytes(), None, &layout)
+            .unwrap();
+
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.rows()[0][1], Value::String("Alice".to_string()));
+        assert_eq!(table.rows()[0][2], Value::Float(12.5));
+        assert_eq!(table.rows()[1][0], Value::Integer(2));
+        assert_eq!(table.rows()[1][2], Value::Integer(7));
+
+        // Unchanged fields are written as read; changed ones are re-padded
+        let mut table = table;
+        table.update_value(1, 2, Value::Integer(8)).unwrap();
+        let mut output = Vec::new();
+        handler
+            .write_fixed_width(&table, &mut output, &layout)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "0001Alice    12.5\n   2Bob         8\n"
+        );
+    }
+
+    #[test]
+    fn test_layouts_from_table_definitions() {
+        let definitions = [
+            "a:id@1-4".to_string(),
+            "b:x,y".to_string(),
+            "c:id@1-2".to_string(),
+            "c:id,name".to_string(),
+        ];
+        let layouts = FixedWidthLayout::from_table_definitions(&definitions).unwrap();
+        assert_eq!(layouts.len(), 1);
+        assert_eq!(layouts["a"].column_names(), ["id"]);
+
+        assert!(
+            FixedWidthLayout::from_table_definitions(&["a:@missing.layout".to_string()]).is_err()
+        );
+    }
+}
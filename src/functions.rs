@@ -0,0 +1,574 @@
+//! Scalar function registry module for sqawk
+//!
+//! This module defines the extension point for SQL functions. Every scalar
+//! function, built-in or user-defined, implements the `ScalarFunction` trait and is
+//! looked up by name in a `FunctionRegistry` owned by the `Database`. Aggregate
+//! functions implement `aggregate::Aggregator` and live in the same registry.
+//!
+//! The registry is responsible for:
+//! - Resolving function names case-insensitively, including aliases
+//! - Checking argument counts and types against each function's `Signature`
+//! - Owning per-statement state shared by built-ins, such as compiled regexes
+//!
+//! Applications embedding sqawk can add domain-specific functions:
+//!
+//! ```
+//! use sqawk::database::Database;
+//! use sqawk::error::SqawkResult;
+//! use sqawk::functions::{ArgType, ScalarFunction, Signature};
+//! use sqawk::table::Value;
+//!
+//! struct NormalizeSku;
+//!
+//! impl ScalarFunction for NormalizeSku {
+//!     fn name(&self) -> &str {
+//!         "NORMALIZE_SKU"
+//!     }
+//!
+//!     fn signature(&self) -> Signature {
+//!         Signature::exact(vec![ArgType::String])
+//!     }
+//!
+//!     fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+//!         Ok(match &args[0] {
+//!             Value::String(s) => Value::String(s.trim().to_uppercase().replace(' ', "-")),
+//!             _ => Value::Null,
+//!         })
+//!     }
+//! }
+//!
+//! let mut database = Database::new();
+//! database.register_function(NormalizeSku);
+//! assert!(database.functions().contains("normalize_sku"));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::aggregate::{AggregateFunction, Aggregator};
+use crate::error::{SqawkError, SqawkResult};
+use crate::json_functions::JsonFunction;
+use crate::regex_functions::{RegexCache, RegexFunction, RegexScalarFunction};
+use crate::string_functions::{check_arg_count, StringFunction};
+use crate::table::Value;
+
+/// Generate the name lookups of a built-in function enum from one table
+///
+/// Each entry maps a variant to its canonical name, optionally followed by
+/// aliases. The macro implements `NAMES` (every name and alias, for
+/// registration), `from_name` (case-insensitive) and `name` (the canonical name)
+/// so that a function's names are written down exactly once.
+macro_rules! function_names {
+    ($function:ident { $($variant:ident => $name:literal $(| $alias:literal)*,)* }) => {
+        impl $function {
+            /// All names, including aliases, under which the functions are registered
+            pub const NAMES: &'static [&'static str] = &[$($name, $($alias,)*)*];
+
+            /// Look up a function by name or alias (case-insensitive)
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name.to_uppercase().as_str() {
+                    $($name $(| $alias)* => Some($function::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// The canonical name of the function
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($function::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+pub(crate) use function_names;
+
+/// The type of value a function parameter accepts
+///
+/// NULL is accepted for every parameter type; functions decide for themselves
+/// how a NULL argument affects the result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType {
+    /// Any value
+    Any,
+    /// A string value
+    String,
+    /// An integer value
+    Integer,
+    /// An integer or floating-point value
+    Number,
+    /// A boolean value
+    Boolean,
+}
+
+impl ArgType {
+    /// Check whether a value is acceptable for this parameter type
+    pub fn accepts(&self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (_, Value::Null)
+                | (ArgType::Any, _)
+                | (ArgType::String, Value::String(_))
+                | (ArgType::Integer, Value::Integer(_))
+                | (ArgType::Number, Value::Integer(_) | Value::Float(_))
+                | (ArgType::Boolean, Value::Boolean(_))
+        )
+    }
+
+    /// Name of the parameter type as used in error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgType::Any => "any value",
+            ArgType::String => "a string",
+            ArgType::Integer => "an integer",
+            ArgType::Number => "a number",
+            ArgType::Boolean => "a boolean",
+        }
+    }
+}
+
+/// The arguments a scalar function accepts
+///
+/// A signature lists the fixed parameters, how many of them are required, and
+/// optionally the type of any further (variadic) arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    /// Types of the fixed parameters, in order
+    params: Vec<ArgType>,
+
+    /// Number of leading parameters that must be supplied
+    required: usize,
+
+    /// Type of the arguments after the fixed parameters, if any are allowed
+    variadic: Option<ArgType>,
+}
+
+impl Signature {
+    /// Create a signature with a fixed list of required parameters
+    pub fn exact(params: Vec<ArgType>) -> Self {
+        let required = params.len();
+        Signature {
+            params,
+            required,
+            variadic: None,
+        }
+    }
+
+    /// Create a signature with required parameters followed by optional ones
+    ///
+    /// # Arguments
+    /// * `required` - Types of the parameters that must be supplied
+    /// * `optional` - Types of the parameters that may follow them
+    pub fn optional(required: Vec<ArgType>, optional: Vec<ArgType>) -> Self {
+        let required_count = required.len();
+        let mut params = required;
+        params.extend(optional);
+        Signature {
+            params,
+            required: required_count,
+            variadic: None,
+        }
+    }
+
+    /// Create a signature with required parameters followed by any number of
+    /// arguments of one type
+    ///
+    /// # Arguments
+    /// * `required` - Types of the parameters that must be supplied
+    /// * `rest` - Type of each additional argument
+    pub fn variadic(required: Vec<ArgType>, rest: ArgType) -> Self {
+        let required_count = required.len();
+        Signature {
+            params: required,
+            required: required_count,
+            variadic: Some(rest),
+        }
+    }
+
+    /// Minimum number of arguments
+    pub fn min_args(&self) -> usize {
+        self.required
+    }
+
+    /// Maximum number of arguments, or None if the function is variadic
+    pub fn max_args(&self) -> Option<usize> {
+        match self.variadic {
+            Some(_) => None,
+            None => Some(self.params.len()),
+        }
+    }
+
+    /// Check a list of argument values against this signature
+    ///
+    /// # Arguments
+    /// * `name` - The function name, used in error messages
+    /// * `args` - The evaluated arguments
+    ///
+    /// # Returns
+    /// * `Ok(())` if the arguments match
+    /// * `Err(SqawkError::InvalidFunctionArguments)` for a wrong argument count or type
+    pub fn check(&self, name: &str, args: &[Value]) -> SqawkResult<()> {
+        check_arg_count(
+            name,
+            args,
+            self.min_args(),
+            self.max_args().unwrap_or(usize::MAX),
+        )?;
+
+        for (idx, arg) in args.iter().enumerate() {
+            let expected = self.params.get(idx).or(self.variadic.as_ref());
+            if let Some(expected) = expected {
+                if !expected.accepts(arg) {
+                    return Err(SqawkError::InvalidFunctionArguments(format!(
+                        "{} argument {} must be {}, got {}",
+                        name,
+                        idx + 1,
+                        expected.name(),
+                        arg.type_name()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A SQL scalar function
+///
+/// Scalar functions compute one value from the argument values of a single row.
+/// The registry checks arguments against `signature()` before calling `call()`,
+/// so implementations only need to handle argument lists that match it.
+/// Functions must be `Send + Sync` so that a `Database` holding them can be moved
+/// to, or shared with, other threads.
+pub trait ScalarFunction: Send + Sync {
+    /// The function's name as used in SQL (matched case-insensitively)
+    fn name(&self) -> &str;
+
+    /// The arguments the function accepts
+    fn signature(&self) -> Signature;
+
+    /// Compute the function result
+    ///
+    /// # Arguments
+    /// * `args` - The evaluated arguments, already checked against `signature()`
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The result of the function
+    /// * `Err` - If the arguments cannot be processed
+    fn call(&self, args: &[Value]) -> SqawkResult<Value>;
+
+    /// Compute the function result, knowing which arguments hold JSON
+    ///
+    /// `json_args[i]` is true when argument `i` is JSON text produced by a JSON
+    /// function or the `->` operator. Functions that build JSON override this to
+    /// embed those arguments as JSON rather than as strings; by default the flags
+    /// are ignored.
+    ///
+    /// # Arguments
+    /// * `args` - The evaluated arguments, already checked against `signature()`
+    /// * `json_args` - Which arguments hold JSON; missing entries mean false
+    fn call_with_json_args(&self, args: &[Value], _json_args: &[bool]) -> SqawkResult<Value> {
+        self.call(args)
+    }
+}
+
+/// Registry of the scalar and aggregate functions available to SQL statements
+pub struct FunctionRegistry {
+    /// Functions indexed by their upper-cased name or alias
+    functions: HashMap<String, Arc<dyn ScalarFunction>>,
+
+    /// Aggregate functions indexed by their upper-cased name
+    aggregates: HashMap<String, Arc<dyn Aggregator>>,
+
+    /// Compiled regular expressions shared by the REGEXP_* built-ins
+    regex_cache: Arc<RegexCache>,
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl FunctionRegistry {
+    /// Create a registry with no functions
+    pub fn new() -> Self {
+        FunctionRegistry {
+            functions: HashMap::new(),
+            aggregates: HashMap::new(),
+            regex_cache: Arc::new(RegexCache::new()),
+        }
+    }
+
+    /// Create a registry containing all of sqawk's built-in functions
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+
+        for name in StringFunction::NAMES {
+            if let Some(func) = StringFunction::from_name(name) {
+                registry.register_as(name, Arc::new(func));
+            }
+        }
+
+        for name in RegexFunction::NAMES {
+            if let Some(func) = RegexFunction::from_name(name) {
+                let cached = RegexScalarFunction::new(func, Arc::clone(&registry.regex_cache));
+                registry.register_as(name, Arc::new(cached));
+            }
+        }
+
+        for name in JsonFunction::NAMES {
+            if let Some(func) = JsonFunction::from_name(name) {
+                registry.register_as(name, Arc::new(func));
+            }
+        }
+
+        for name in AggregateFunction::NAMES {
+            if let Some(func) = AggregateFunction::from_name(name) {
+                registry.aggregates.insert(name.to_string(), Arc::new(func));
+            }
+        }
+
+        registry
+    }
+
+    /// Register a scalar function under its name
+    ///
+    /// A function registered under the name of an existing function, including
+    /// a built-in, replaces it.
+    ///
+    /// # Arguments
+    /// * `function` - The function to register
+    pub fn register<F: ScalarFunction + 'static>(&mut self, function: F) {
+        let name = function.name().to_string();
+        self.register_as(&name, Arc::new(function));
+    }
+
+    /// Register a function under a specific name or alias
+    fn register_as(&mut self, name: &str, function: Arc<dyn ScalarFunction>) {
+        self.functions.insert(name.to_uppercase(), function);
+    }
+
+    /// Look up a function by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&dyn ScalarFunction> {
+        self.functions
+            .get(&name.to_uppercase())
+            .map(|function| function.as_ref())
+    }
+
+    /// Check if a function with the given name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(&name.to_uppercase())
+    }
+
+    /// Get the names of all registered functions, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.functions.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Call a function by name after checking its arguments
+    ///
+    /// # Arguments
+    /// * `name` - The function name (case-insensitive)
+    /// * `args` - The evaluated arguments
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The function result
+    /// * `Err(SqawkError::UnsupportedSqlFeature)` - If no function has this name
+    /// * `Err(SqawkError::InvalidFunctionArguments)` - If the arguments don't match
+    pub fn call(&self, name: &str, args: &[Value]) -> SqawkResult<Value> {
+        self.call_with_json_args(name, args, &[])
+    }
+
+    /// Call a function by name, marking which arguments hold JSON
+    ///
+    /// # Arguments
+    /// * `name` - The function name (case-insensitive)
+    /// * `args` - The evaluated arguments
+    /// * `json_args` - Which arguments are JSON text produced by a JSON function
+    ///   or the `->` operator
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The function result
+    /// * `Err(SqawkError::UnsupportedSqlFeature)` - If no function has this name
+    /// * `Err(SqawkError::InvalidFunctionArguments)` - If the arguments don't match
+    pub fn call_with_json_args(
+        &self,
+        name: &str,
+        args: &[Value],
+        json_args: &[bool],
+    ) -> SqawkResult<Value> {
+        let function = self.get(name).ok_or_else(|| {
+            SqawkError::UnsupportedSqlFeature(format!("Unknown function: {}", name))
+        })?;
+
+        function.signature().check(function.name(), args)?;
+        function.call_with_json_args(args, json_args)
+    }
+
+    /// Register an aggregate function under its name
+    ///
+    /// Aggregate names take precedence over scalar functions of the same name.
+    /// An aggregate registered under the name of an existing aggregate, including
+    /// a built-in, replaces it.
+    ///
+    /// # Arguments
+    /// * `aggregate` - The aggregate function to register
+    pub fn register_aggregate<A: Aggregator + 'static>(&mut self, aggregate: A) {
+        self.aggregates
+            .insert(aggregate.name().to_uppercase(), Arc::new(aggregate));
+    }
+
+    /// Look up an aggregate function by name (case-insensitive)
+    pub fn get_aggregate(&self, name: &str) -> Option<&dyn Aggregator> {
+        self.aggregates
+            .get(&name.to_uppercase())
+            .map(|aggregate| aggregate.as_ref())
+    }
+
+    /// Check if an aggregate function with the given name is registered
+    pub fn is_aggregate(&self, name: &str) -> bool {
+        self.aggregates.contains_key(&name.to_uppercase())
+    }
+
+    /// Discard state kept by built-in functions between calls
+    ///
+    /// Called before each statement so that caches don't outlive a query.
+    pub fn clear_caches(&self) {
+        self.regex_cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Double;
+
+    impl ScalarFunction for Double {
+        fn name(&self) -> &str {
+            "double"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::exact(vec![ArgType::Number])
+        }
+
+        fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+            crate::arithmetic::ArithmeticOperator::Multiply.apply(&args[0], &Value::Integer(2))
+        }
+    }
+
+    #[test]
+    fn test_signature_check() {
+        let sig = Signature::optional(vec![ArgType::String], vec![ArgType::Integer]);
+        assert_eq!(sig.min_args(), 1);
+        assert_eq!(sig.max_args(), Some(2));
+        assert!(sig.check("F", &[Value::String("a".into())]).is_ok());
+        assert!(sig.check("F", &[Value::Null, Value::Integer(3)]).is_ok());
+        assert!(sig.check("F", &[]).is_err());
+        assert!(sig.check("F", &[Value::Integer(1)]).is_err());
+
+        let sig = Signature::variadic(vec![ArgType::String], ArgType::Number);
+        assert_eq!(sig.max_args(), None);
+        assert!(sig
+            .check(
+                "F",
+                &[
+                    Value::String("a".into()),
+                    Value::Integer(1),
+                    Value::Float(2.5)
+                ]
+            )
+            .is_ok());
+        assert!(sig
+            .check("F", &[Value::String("a".into()), Value::Boolean(true)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_registry_user_function() {
+        let mut registry = FunctionRegistry::with_builtins();
+        registry.register(Double);
+
+        assert!(registry.contains("DOUBLE"));
+        assert_eq!(
+            registry.call("Double", &[Value::Integer(21)]).unwrap(),
+            Value::Integer(42)
+        );
+
+        // Argument type and count mismatches are reported before the call
+        match registry.call("double", &[Value::String("x".into())]) {
+            Err(SqawkError::InvalidFunctionArguments(msg)) => {
+                assert!(msg.contains("argument 1 must be a number"), "{}", msg)
+            }
+            other => panic!("expected InvalidFunctionArguments, got {:?}", other),
+        }
+        assert!(matches!(
+            registry.call("double", &[]),
+            Err(SqawkError::InvalidFunctionArguments(_))
+        ));
+        assert!(registry.call("no_such_function", &[]).is_err());
+    }
+
+    #[test]
+    fn test_registry_builtins() {
+        let registry = FunctionRegistry::with_builtins();
+
+        // Aliases resolve to the same implementation
+        assert_eq!(
+            registry
+                .call("char_length", &[Value::String("abc".into())])
+                .unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            registry
+                .call(
+                    "regexp_count",
+                    &[Value::String("a1b22".into()), Value::String("\\d+".into())]
+                )
+                .unwrap(),
+            Value::Integer(2)
+        );
+        assert!(FunctionRegistry::new().names().is_empty());
+
+        // Built-in signatures declare real argument types
+        match registry.call("substr", &[Value::Integer(12), Value::String("x".into())]) {
+            Err(SqawkError::InvalidFunctionArguments(msg)) => {
+                assert!(msg.contains("argument 2 must be an integer"), "{}", msg)
+            }
+            other => panic!("expected InvalidFunctionArguments, got {:?}", other),
+        }
+        // Text arguments accept any value
+        assert_eq!(
+            registry.call("upper", &[Value::Integer(1)]).unwrap(),
+            Value::String("1".into())
+        );
+        assert!(registry
+            .call("power", &[Value::Integer(2), Value::String("3".into())])
+            .is_err());
+    }
+
+    #[test]
+    fn test_function_names() {
+        assert_eq!(
+            StringFunction::from_name("ceiling"),
+            Some(StringFunction::Ceil)
+        );
+        assert_eq!(StringFunction::Ceil.name(), "CEIL");
+        assert!(StringFunction::NAMES.contains(&"CEILING"));
+        assert_eq!(RegexFunction::from_name("nope"), None);
+    }
+
+    #[test]
+    fn test_registry_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FunctionRegistry>();
+        assert_send_sync::<crate::database::Database>();
+        assert_send_sync::<crate::sql_executor::SqlExecutor<'static>>();
+    }
+}
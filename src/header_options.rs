@@ -0,0 +1,448 @@
+//! Header handling options for delimited text files
+//!
+//! This module decides which lines of a CSV or delimiter-separated file hold column
+//! names and which hold data. It provides:
+//!
+//! - Header modes: the first line is a header (`present`), is data (`absent`),
+//!   or is classified by a simple detector (`auto`)
+//! - A header row offset, for files with title lines above the column names
+//! - A count of lines to skip before the data, such as a units or `----` row
+//! - Per-table settings given on the command line as `[table=]value`
+//! - [`LoadOptions`], which bundles the column names, header and dialect for a reader
+//!
+//! Lines skipped above or below the header are kept so that write-back can
+//! reproduce them.
+
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use crate::csv_dialect::CsvDialect;
+use crate::error::{SqawkError, SqawkResult};
+use crate::table::{Value, ValueFormat};
+
+/// Whether a file's first line holds column names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderMode {
+    /// The header line holds column names (the default)
+    #[default]
+    Present,
+    /// There is no header; every line is data and columns are named a, b, c, ...
+    Absent,
+    /// The header line is used as column names only if it looks like one
+    Auto,
+}
+
+impl HeaderMode {
+    /// Names accepted by `--header`
+    pub const NAMES: &'static [&'static str] = &["present", "absent", "auto"];
+}
+
+impl FromStr for HeaderMode {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "present" | "yes" | "true" => Ok(HeaderMode::Present),
+            "absent" | "no" | "false" | "none" => Ok(HeaderMode::Absent),
+            "auto" => Ok(HeaderMode::Auto),
+            _ => Err(SqawkError::InvalidArgument(format!(
+                "Unknown header mode '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HeaderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HeaderMode::Present => "present",
+            HeaderMode::Absent => "absent",
+            HeaderMode::Auto => "auto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How to find the header and the data in one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderOptions {
+    /// Whether the header line holds column names
+    pub mode: HeaderMode,
+    /// 1-based line number of the header; the lines above it are skipped
+    pub header_row: usize,
+    /// Number of lines to skip after the header, or at the start of the data without one
+    pub skip_lines: usize,
+}
+
+impl Default for HeaderOptions {
+    fn default() -> Self {
+        HeaderOptions {
+            mode: HeaderMode::Present,
+            header_row: 1,
+            skip_lines: 0,
+        }
+    }
+}
+
+/// Everything a CSV or delimited reader needs to know besides the data itself
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Column names to use instead of the header row, or to name a headerless file's columns
+    pub columns: Option<Vec<String>>,
+    /// Where the header row is, and whether there is one
+    pub header: HeaderOptions,
+    /// Quoting, comment and line terminator rules of the data
+    pub dialect: CsvDialect,
+}
+
+/// A command-line value that applies to one table, or to all tables without a prefix
+///
+/// Parsed from `[table=]value`, matching the `[table=]path` syntax of file arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOption<T> {
+    /// The table the value applies to, or `None` for every table
+    pub table: Option<String>,
+    /// The option value
+    pub value: T,
+}
+
+impl<T: FromStr> FromStr for TableOption<T>
+where
+    T::Err: fmt::Display,
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (table, value) = match s.split_once('=') {
+            Some((table, value)) => (Some(table.to_string()), value),
+            None => (None, s),
+        };
+        let value = value.parse::<T>().map_err(|e| e.to_string())?;
+        Ok(TableOption { table, value })
+    }
+}
+
+/// Find the value that applies to a table: its own, else the last one without a table
+pub(crate) fn option_for<'a, T>(options: &'a [TableOption<T>], table_name: &str) -> Option<&'a T> {
+    options
+        .iter()
+        .rev()
+        .find(|option| option.table.as_deref() == Some(table_name))
+        .or_else(|| options.iter().rev().find(|option| option.table.is_none()))
+        .map(|option| &option.value)
+}
+
+/// Header options from the command line, resolved per table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderSettings {
+    /// Values of `--header`
+    pub modes: Vec<TableOption<HeaderMode>>,
+    /// Values of `--header-row`
+    pub header_rows: Vec<TableOption<usize>>,
+    /// Values of `--skip-lines`
+    pub skip_lines: Vec<TableOption<usize>>,
+}
+
+impl HeaderSettings {
+    /// Get the header options for a table
+    ///
+    /// A value given for the table by name wins over one given for all tables.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table being loaded
+    ///
+    /// # Returns
+    /// * `HeaderOptions` - The options for the table's file
+    pub fn for_table(&self, table_name: &str) -> HeaderOptions {
+        let defaults = HeaderOptions::default();
+        HeaderOptions {
+            mode: option_for(&self.modes, table_name)
+                .copied()
+                .unwrap_or(defaults.mode),
+            header_row: option_for(&self.header_rows, table_name)
+                .copied()
+                .unwrap_or(defaults.header_row),
+            skip_lines: option_for(&self.skip_lines, table_name)
+                .copied()
+                .unwrap_or(defaults.skip_lines),
+        }
+    }
+
+    /// Check that every header row is a valid line number
+    ///
+    /// # Returns
+    /// * `Ok(())` if all settings are usable
+    /// * `Err` if a header row is 0
+    pub fn validate(&self) -> SqawkResult<()> {
+        if self.header_rows.iter().any(|option| option.value == 0) {
+            return Err(SqawkError::InvalidArgument(
+                "--header-row counts lines from 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How a loaded file's header was laid out, remembered on the table for write-back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderLayout {
+    /// Whether the file has a header line with the column names
+    pub has_header: bool,
+    /// Lines written above the header
+    pub before_header: Vec<String>,
+    /// Lines written between the header and the data
+    pub after_header: Vec<String>,
+}
+
+impl Default for HeaderLayout {
+    fn default() -> Self {
+        HeaderLayout {
+            has_header: true,
+            before_header: Vec::new(),
+            after_header: Vec::new(),
+        }
+    }
+}
+
+/// The start of a file, as read by [`read_header_lines`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderLines {
+    /// The header and skipped lines, to be remembered on the table
+    pub layout: HeaderLayout,
+    /// The header line, if the file has one
+    pub header: Option<String>,
+    /// A line read while looking for the header that turned out to be data
+    pub first_data_line: Option<String>,
+    /// Number of lines consumed from the reader, not counting `first_data_line`
+    pub lines_read: usize,
+}
+
+/// Read the lines of a file up to its data
+///
+/// Skips `header_row - 1` lines, then takes the next line that isn't blank or a
+/// comment as the header, then skips `skip_lines` more lines. In `auto` mode,
+/// `is_header` decides whether that line is a header; if not, it is treated as
+/// the start of the data, and counts towards `skip_lines`. With no header, only
+/// `skip_lines` lines are skipped. Everything after is left in the reader.
+///
+/// # Arguments
+/// * `reader` - The file contents
+/// * `options` - Where the header and data are
+/// * `comment` - Lines starting with this character are passed over when looking for the header
+/// * `is_header` - Decides whether a line is a header in `auto` mode
+///
+/// # Returns
+/// * `Ok(HeaderLines)` - The lines read
+/// * `Err` if the input could not be read or `is_header` fails
+pub fn read_header_lines<R: BufRead>(
+    reader: &mut R,
+    options: &HeaderOptions,
+    comment: Option<char>,
+    is_header: impl FnOnce(&str) -> SqawkResult<bool>,
+) -> SqawkResult<HeaderLines> {
+    let mut lines_read = 0;
+    let mut before_header = Vec::new();
+    let mut candidate = None;
+    if options.mode != HeaderMode::Absent {
+        before_header = read_lines(reader, options.header_row - 1, &mut lines_read)?;
+        while let Some(line) = read_lines(reader, 1, &mut lines_read)?.pop() {
+            let is_comment = comment.map_or(false, |c| line.starts_with(c));
+            if !line.trim().is_empty() && !is_comment {
+                candidate = Some(line);
+                break;
+            }
+        }
+    }
+
+    let has_header = match (&candidate, options.mode) {
+        (None, _) => false,
+        (Some(_), HeaderMode::Present) => true,
+        (Some(line), _) => is_header(line)?,
+    };
+
+    let mut header = None;
+    let mut first_data_line = None;
+    let mut after_header = Vec::new();
+    let mut skip_lines = options.skip_lines;
+    match candidate {
+        Some(line) if has_header => header = Some(line),
+        // A line that isn't a header is the first data line, which may itself be skipped
+        Some(line) if skip_lines > 0 => {
+            after_header.push(line);
+            skip_lines -= 1;
+        }
+        Some(line) => {
+            lines_read -= 1;
+            first_data_line = Some(line);
+        }
+        None => {}
+    }
+    after_header.extend(read_lines(reader, skip_lines, &mut lines_read)?);
+
+    Ok(HeaderLines {
+        layout: HeaderLayout {
+            has_header,
+            before_header,
+            after_header,
+        },
+        header,
+        first_data_line,
+        lines_read,
+    })
+}
+
+/// Read up to `count` lines, without their line terminators, adding them to `lines_read`
+fn read_lines<R: BufRead>(
+    reader: &mut R,
+    count: usize,
+    lines_read: &mut usize,
+) -> SqawkResult<Vec<String>> {
+    let mut lines = Vec::new();
+    for _ in 0..count {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        *lines_read += 1;
+        let len = line.trim_end_matches(['\r', '\n']).len();
+        line.truncate(len);
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+/// Write lines kept from a loaded file, each followed by a line terminator
+///
+/// # Arguments
+/// * `writer` - Destination of the lines
+/// * `lines` - The lines to write
+/// * `terminator` - The line terminator of the file
+///
+/// # Returns
+/// * `SqawkResult<()>` - Result of the operation
+pub fn write_lines<W: Write>(
+    writer: &mut W,
+    lines: &[String],
+    terminator: &str,
+) -> SqawkResult<()> {
+    for line in lines {
+        write!(writer, "{}{}", line, terminator)?;
+    }
+    Ok(())
+}
+
+/// Check whether the fields of a line look like column names
+///
+/// This is the detector behind `--header auto`: a line is a header when every
+/// field is non-empty, no two fields are the same, and no field loads as a
+/// number, boolean or NULL.
+///
+/// # Arguments
+/// * `fields` - The fields of the first line
+/// * `value_format` - The rules used to load field values
+///
+/// # Returns
+/// * `true` if the line should be used as column names
+pub fn looks_like_header(fields: &[String], value_format: &ValueFormat) -> bool {
+    let mut seen = std::collections::HashSet::new();
+    fields.iter().all(|field| {
+        !field.trim().is_empty()
+            && seen.insert(field.as_str())
+            && matches!(value_format.parse(field), Value::String(_))
+    })
+}
+
+/// Generate alphabetical column names (a, b, ..., z, aa, ab, ...)
+///
+/// # Arguments
+/// * `count` - Number of columns
+///
+/// # Returns
+/// * The column names
+pub fn generated_column_names(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            let mut name = String::new();
+            let mut n = i;
+            loop {
+                name.insert(0, (b'a' + (n % 26) as u8) as char);
+                n /= 26;
+                if n == 0 {
+                    break;
+                }
+                n -= 1; // Adjust for the shift from 0-based to 1-based
+            }
+            name
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_table_options() {
+        let settings = HeaderSettings {
+            modes: vec!["absent".parse().unwrap(), "people=present".parse().unwrap()],
+            header_rows: vec!["people=3".parse().unwrap()],
+            skip_lines: Vec::new(),
+        };
+
+        assert_eq!(settings.for_table("people").mode, HeaderMode::Present);
+        assert_eq!(settings.for_table("people").header_row, 3);
+        assert_eq!(settings.for_table("other").mode, HeaderMode::Absent);
+        assert_eq!(settings.for_table("other").header_row, 1);
+        assert!("t=sometimes".parse::<TableOption<HeaderMode>>().is_err());
+        assert!("t=x".parse::<TableOption<usize>>().is_err());
+    }
+
+    #[test]
+    fn test_read_header_lines() {
+        let options = HeaderOptions {
+            mode: HeaderMode::Present,
+            header_row: 2,
+            skip_lines: 1,
+        };
+        let mut input = "Report\r\n# note\nid,name\n--,----\n1,a\n".as_bytes();
+        let lines = read_header_lines(&mut input, &options, Some('#'), |_| Ok(true)).unwrap();
+
+        assert!(lines.layout.has_header);
+        assert_eq!(lines.layout.before_header, ["Report"]);
+        assert_eq!(lines.header.as_deref(), Some("id,name"));
+        assert_eq!(lines.layout.after_header, ["--,----"]);
+        assert_eq!(input, b"1,a\n");
+    }
+
+    #[test]
+    fn test_auto_header_without_header() {
+        let options = HeaderOptions {
+            mode: HeaderMode::Auto,
+            ..HeaderOptions::default()
+        };
+        let mut input = "1,a\n2,b\n".as_bytes();
+        let lines = read_header_lines(&mut input, &options, None, |_| Ok(false)).unwrap();
+
+        assert!(!lines.layout.has_header);
+        assert_eq!(lines.header, None);
+        assert_eq!(lines.first_data_line.as_deref(), Some("1,a"));
+        assert_eq!(input, b"2,b\n");
+    }
+
+    #[test]
+    fn test_looks_like_header() {
+        let format = ValueFormat::default();
+        assert!(looks_like_header(&strings(&["id", "name"]), &format));
+        assert!(!looks_like_header(&strings(&["root", "x", "0"]), &format));
+        assert!(!looks_like_header(&strings(&["a", ""]), &format));
+        assert!(!looks_like_header(&strings(&["a", "a"]), &format));
+        assert!(!looks_like_header(&strings(&["yes", "no"]), &format));
+    }
+}
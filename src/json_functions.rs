@@ -0,0 +1,536 @@
+//! JSON function module for sqawk
+//!
+//! This module implements SQL functions for working with JSON text stored in a
+//! column, so fields inside a payload can be filtered and projected directly:
+//! - JSON_EXTRACT(): Extract one or more values by path
+//! - JSON_TYPE(): The type of a JSON value
+//! - JSON_ARRAY_LENGTH(): The number of elements in a JSON array
+//! - JSON_OBJECT() and JSON_ARRAY(): Build JSON text from SQL values
+//! - JSON(): Check and minify JSON text
+//!
+//! It also implements the `->` and `->>` operators: `json -> sel` returns the
+//! selected value as JSON text and `json ->> sel` returns it as a SQL value. The
+//! selector is a path (`'$.a.b[0]'`), an object key (`'a'`) or an array index.
+//!
+//! Paths start with `$` and are followed by `.key`, `."quoted key"`, `[n]` or
+//! `[#-n]` (the nth element from the end) steps. A path that selects nothing
+//! yields NULL. When building values, only arguments that are themselves JSON
+//! (the result of JSON(), JSON_OBJECT(), JSON_ARRAY() or `->`) are embedded as
+//! JSON; all other text becomes a JSON string, however it looks.
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::functions::{function_names, ArgType, ScalarFunction, Signature};
+use crate::json_handler::{from_json, to_json};
+use crate::string_functions::{check_arg_count, text_arg};
+use crate::table::Value;
+
+/// Enum of supported JSON functions
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonFunction {
+    /// Extract values by path - JSON_EXTRACT(json, path, ...)
+    Extract,
+    /// Type of a value - JSON_TYPE(json[, path])
+    Type,
+    /// Length of an array - JSON_ARRAY_LENGTH(json[, path])
+    ArrayLength,
+    /// Build an object from key/value pairs - JSON_OBJECT(key, value, ...)
+    Object,
+    /// Build an array from values - JSON_ARRAY(value, ...)
+    Array,
+    /// Check and minify JSON text - JSON(json)
+    Json,
+}
+
+function_names!(JsonFunction {
+    Extract => "JSON_EXTRACT",
+    Type => "JSON_TYPE",
+    ArrayLength => "JSON_ARRAY_LENGTH",
+    Object => "JSON_OBJECT",
+    Array => "JSON_ARRAY",
+    Json => "JSON",
+});
+
+impl JsonFunction {
+    /// Whether the function's result is JSON text, to be embedded as JSON when
+    /// passed to JSON_OBJECT or JSON_ARRAY
+    pub fn returns_json(&self) -> bool {
+        matches!(
+            self,
+            JsonFunction::Object | JsonFunction::Array | JsonFunction::Json
+        )
+    }
+
+    /// Apply the JSON function to its arguments
+    ///
+    /// # Arguments
+    /// * `args` - The evaluated function arguments
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The function result (NULL if the JSON argument is NULL)
+    /// * `Err` - If the JSON text or a path is malformed
+    pub fn apply(&self, args: &[Value]) -> SqawkResult<Value> {
+        self.apply_with_json_args(args, &[])
+    }
+
+    /// Apply the JSON function, embedding the arguments marked as JSON
+    ///
+    /// # Arguments
+    /// * `args` - The evaluated function arguments
+    /// * `json_args` - Which arguments are JSON text produced by a JSON function
+    ///   or the `->` operator
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The function result (NULL if the JSON argument is NULL)
+    /// * `Err` - If the JSON text or a path is malformed
+    pub fn apply_with_json_args(&self, args: &[Value], json_args: &[bool]) -> SqawkResult<Value> {
+        match self {
+            JsonFunction::Extract => self.apply_extract(args),
+            JsonFunction::Type => self.apply_type(args),
+            JsonFunction::ArrayLength => self.apply_array_length(args),
+            JsonFunction::Object => self.apply_object(args, json_args),
+            JsonFunction::Array => {
+                let elements = (0..args.len())
+                    .map(|idx| member(args, json_args, idx))
+                    .collect();
+                Ok(Value::String(
+                    serde_json::Value::Array(elements).to_string(),
+                ))
+            }
+            JsonFunction::Json => Ok(parse_json_arg(self.name(), &args[0])?
+                .map_or(Value::Null, |json| Value::String(json.to_string()))),
+        }
+    }
+
+    /// Apply JSON_EXTRACT function - return the value at one or more paths
+    ///
+    /// With one path, scalars are returned as SQL values and objects or arrays as
+    /// JSON text. With several paths, the result is a JSON array of the selected
+    /// values, with null for paths that select nothing.
+    fn apply_extract(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count(self.name(), args, 2, usize::MAX)?;
+
+        let Some(json) = parse_json_arg(self.name(), &args[0])? else {
+            return Ok(Value::Null);
+        };
+
+        let mut selected = Vec::with_capacity(args.len() - 1);
+        for idx in 1..args.len() {
+            let Some(path) = text_arg(args, idx) else {
+                return Ok(Value::Null);
+            };
+            selected.push(lookup(&json, &parse_path(&path)?));
+        }
+
+        if let [value] = selected.as_slice() {
+            return Ok(value.cloned().map(from_json).unwrap_or(Value::Null));
+        }
+        let values = selected
+            .into_iter()
+            .map(|value| value.cloned().unwrap_or(serde_json::Value::Null))
+            .collect();
+        Ok(Value::String(serde_json::Value::Array(values).to_string()))
+    }
+
+    /// Apply JSON_TYPE function - name the type of a value
+    ///
+    /// The names are `object`, `array`, `text`, `integer`, `real`, `true`,
+    /// `false` and `null`.
+    fn apply_type(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count(self.name(), args, 1, 2)?;
+
+        let Some(value) = select_arg(self.name(), args)? else {
+            return Ok(Value::Null);
+        };
+        let name = match value {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(true) => "true",
+            serde_json::Value::Bool(false) => "false",
+            serde_json::Value::Number(n) if n.is_i64() => "integer",
+            serde_json::Value::Number(_) => "real",
+            serde_json::Value::String(_) => "text",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        };
+        Ok(Value::String(name.to_string()))
+    }
+
+    /// Apply JSON_ARRAY_LENGTH function - count the elements of an array
+    ///
+    /// Returns 0 for values that are not arrays.
+    fn apply_array_length(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count(self.name(), args, 1, 2)?;
+
+        let Some(value) = select_arg(self.name(), args)? else {
+            return Ok(Value::Null);
+        };
+        let length = match value {
+            serde_json::Value::Array(elements) => elements.len(),
+            _ => 0,
+        };
+        Ok(Value::Integer(length as i64))
+    }
+
+    /// Apply JSON_OBJECT function - build an object from alternating keys and values
+    fn apply_object(&self, args: &[Value], json_args: &[bool]) -> SqawkResult<Value> {
+        if args.len() % 2 != 0 {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "{} requires an even number of arguments, got {}",
+                self.name(),
+                args.len()
+            )));
+        }
+
+        let mut object = serde_json::Map::new();
+        for (idx, pair) in args.chunks(2).enumerate() {
+            let Some(key) = text_arg(pair, 0) else {
+                return Err(SqawkError::InvalidFunctionArguments(format!(
+                    "{} key {} must not be NULL",
+                    self.name(),
+                    idx + 1
+                )));
+            };
+            object.insert(key, member(args, json_args, idx * 2 + 1));
+        }
+        Ok(Value::String(serde_json::Value::Object(object).to_string()))
+    }
+}
+
+impl ScalarFunction for JsonFunction {
+    fn name(&self) -> &str {
+        JsonFunction::name(self)
+    }
+
+    fn signature(&self) -> Signature {
+        use ArgType::{Any, String};
+        match self {
+            JsonFunction::Extract => Signature::variadic(vec![Any, String], String),
+            JsonFunction::Type | JsonFunction::ArrayLength => {
+                Signature::optional(vec![Any], vec![String])
+            }
+            JsonFunction::Object | JsonFunction::Array => Signature::variadic(vec![], Any),
+            JsonFunction::Json => Signature::exact(vec![Any]),
+        }
+    }
+
+    fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+        self.apply(args)
+    }
+
+    fn call_with_json_args(&self, args: &[Value], json_args: &[bool]) -> SqawkResult<Value> {
+        self.apply_with_json_args(args, json_args)
+    }
+}
+
+/// Convert an argument of JSON_OBJECT or JSON_ARRAY to a JSON value
+///
+/// Arguments marked as JSON are embedded as the value their text holds; all
+/// others, including text that only looks like JSON, are converted by type.
+fn member(args: &[Value], json_args: &[bool], idx: usize) -> serde_json::Value {
+    match &args[idx] {
+        Value::String(text) if json_args.get(idx).copied().unwrap_or(false) => {
+            serde_json::from_str(text).unwrap_or_else(|_| to_json(&args[idx]))
+        }
+        value => to_json(value),
+    }
+}
+
+/// Apply the `->` or `->>` operator
+///
+/// # Arguments
+/// * `json` - The JSON text (or a scalar, treated as its JSON form)
+/// * `selector` - A path starting with `$`, an object key, or an array index
+///   (negative indexes count from the end)
+/// * `as_text` - True for `->>`, which returns a SQL value rather than JSON text
+///
+/// # Returns
+/// * `Ok(Value)` - The selected value, or NULL if nothing is selected
+/// * `Err` - If the JSON text, path or selector is invalid
+pub fn json_access(json: &Value, selector: &Value, as_text: bool) -> SqawkResult<Value> {
+    let operator = if as_text { "->>" } else { "->" };
+    let Some(json) = parse_json_arg(operator, json)? else {
+        return Ok(Value::Null);
+    };
+
+    let path = match selector {
+        Value::Null => return Ok(Value::Null),
+        Value::String(s) if s.starts_with('$') => parse_path(s)?,
+        Value::String(s) => vec![PathStep::Key(s.clone())],
+        Value::Integer(i) if *i < 0 => vec![PathStep::FromEnd(i.unsigned_abs() as usize)],
+        Value::Integer(i) => vec![PathStep::Index(*i as usize)],
+        other => {
+            return Err(SqawkError::TypeError(format!(
+                "The {} operator requires a path, key or array index, got {}",
+                operator,
+                other.type_name()
+            )))
+        }
+    };
+
+    Ok(match lookup(&json, &path) {
+        None => Value::Null,
+        Some(value) if as_text => from_json(value.clone()),
+        Some(value) => Value::String(value.to_string()),
+    })
+}
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq)]
+enum PathStep {
+    /// An object member
+    Key(String),
+    /// An array element counted from the start
+    Index(usize),
+    /// An array element counted from the end (1 is the last element)
+    FromEnd(usize),
+}
+
+/// Parse a path such as `$.a."b c"[0][#-1]` into steps
+fn parse_path(path: &str) -> SqawkResult<Vec<PathStep>> {
+    let invalid = || SqawkError::InvalidFunctionArguments(format!("Invalid JSON path: {}", path));
+
+    let rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut chars = rest.chars().peekable();
+    let mut steps = Vec::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => key.push(c),
+                            None => return Err(invalid()),
+                        }
+                    }
+                } else {
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
+                        }
+                        key.push(c);
+                        chars.next();
+                    }
+                    if key.is_empty() {
+                        return Err(invalid());
+                    }
+                }
+                steps.push(PathStep::Key(key));
+            }
+            '[' => {
+                let mut index = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => index.push(c),
+                        None => return Err(invalid()),
+                    }
+                }
+                let step = match index.strip_prefix("#-") {
+                    Some(n) => PathStep::FromEnd(n.parse().map_err(|_| invalid())?),
+                    None => PathStep::Index(index.parse().map_err(|_| invalid())?),
+                };
+                steps.push(step);
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Follow path steps into a JSON value
+fn lookup<'a>(json: &'a serde_json::Value, path: &[PathStep]) -> Option<&'a serde_json::Value> {
+    path.iter()
+        .try_fold(json, |value, step| match (step, value) {
+            (PathStep::Key(key), serde_json::Value::Object(object)) => object.get(key),
+            (PathStep::Index(i), serde_json::Value::Array(elements)) => elements.get(*i),
+            (PathStep::FromEnd(n), serde_json::Value::Array(elements)) => {
+                elements.len().checked_sub(*n).and_then(|i| elements.get(i))
+            }
+            _ => None,
+        })
+}
+
+/// Parse the JSON argument of a function or operator
+///
+/// Text is parsed as JSON; numbers and booleans stand for themselves.
+///
+/// # Returns
+/// * `Ok(None)` for a NULL argument
+/// * `Err` if the text is not valid JSON
+fn parse_json_arg(name: &str, value: &Value) -> SqawkResult<Option<serde_json::Value>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) => serde_json::from_str(s).map(Some).map_err(|e| {
+            SqawkError::InvalidFunctionArguments(format!("{}: malformed JSON: {}", name, e))
+        }),
+        other => Ok(Some(to_json(other))),
+    }
+}
+
+/// Parse the JSON argument of JSON_TYPE or JSON_ARRAY_LENGTH and apply its optional path
+///
+/// # Returns
+/// * `Ok(None)` if an argument is NULL or the path selects nothing
+fn select_arg(name: &str, args: &[Value]) -> SqawkResult<Option<serde_json::Value>> {
+    let Some(json) = parse_json_arg(name, &args[0])? else {
+        return Ok(None);
+    };
+    match args.get(1) {
+        None => Ok(Some(json)),
+        Some(Value::Null) => Ok(None),
+        Some(_) => {
+            let path = text_arg(args, 1).unwrap_or_default();
+            Ok(lookup(&json, &parse_path(&path)?).cloned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper to build a string value
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    const PAYLOAD: &str = r#"{"a":{"b":[10,20,{"c":"x"}]},"ok":true,"n":1.5,"k y":null}"#;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path(r#"$.a."k y"[2][#-1]"#).unwrap(),
+            vec![
+                PathStep::Key("a".to_string()),
+                PathStep::Key("k y".to_string()),
+                PathStep::Index(2),
+                PathStep::FromEnd(1),
+            ]
+        );
+        assert_eq!(parse_path("$").unwrap(), vec![]);
+        for bad in ["a.b", "$.", "$[x]", "$[1", "$a"] {
+            assert!(parse_path(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_json_extract() {
+        let extract = |path: &str| {
+            JsonFunction::Extract
+                .apply(&[text(PAYLOAD), text(path)])
+                .unwrap()
+        };
+        assert_eq!(extract("$.a.b[1]"), Value::Integer(20));
+        assert_eq!(extract("$.a.b[#-1].c"), text("x"));
+        assert_eq!(extract("$.ok"), Value::Boolean(true));
+        assert_eq!(extract("$.n"), Value::Float(1.5));
+        assert_eq!(extract("$.a.b"), text(r#"[10,20,{"c":"x"}]"#));
+        assert_eq!(extract("$.missing"), Value::Null);
+
+        let result = JsonFunction::Extract
+            .apply(&[text(PAYLOAD), text("$.ok"), text("$.missing")])
+            .unwrap();
+        assert_eq!(result, text("[true,null]"));
+
+        assert!(JsonFunction::Extract
+            .apply(&[text("{oops"), text("$")])
+            .is_err());
+        assert_eq!(
+            JsonFunction::Extract
+                .apply(&[Value::Null, text("$")])
+                .unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_json_type_and_array_length() {
+        let json_type = |path: &str| {
+            JsonFunction::Type
+                .apply(&[text(PAYLOAD), text(path)])
+                .unwrap()
+        };
+        assert_eq!(json_type("$"), text("object"));
+        assert_eq!(json_type("$.a.b"), text("array"));
+        assert_eq!(json_type("$.a.b[0]"), text("integer"));
+        assert_eq!(json_type("$.n"), text("real"));
+        assert_eq!(json_type("$.ok"), text("true"));
+        assert_eq!(json_type(r#"$."k y""#), text("null"));
+        assert_eq!(json_type("$.missing"), Value::Null);
+
+        let length = JsonFunction::ArrayLength
+            .apply(&[text(PAYLOAD), text("$.a.b")])
+            .unwrap();
+        assert_eq!(length, Value::Integer(3));
+        let length = JsonFunction::ArrayLength.apply(&[text("{}")]).unwrap();
+        assert_eq!(length, Value::Integer(0));
+    }
+
+    #[test]
+    fn test_json_constructors() {
+        let array = JsonFunction::Array
+            .apply(&[Value::Integer(1), text("a"), Value::Null, text("[2]")])
+            .unwrap();
+        assert_eq!(array, text(r#"[1,"a",null,"[2]"]"#));
+
+        let object = JsonFunction::Object
+            .apply_with_json_args(
+                &[text("id"), Value::Integer(7), text("tags"), array],
+                &[false, false, false, true],
+            )
+            .unwrap();
+        assert_eq!(object, text(r#"{"id":7,"tags":[1,"a",null,"[2]"]}"#));
+
+        assert!(JsonFunction::Object.apply(&[text("id")]).is_err());
+        assert!(JsonFunction::Object
+            .apply(&[Value::Null, Value::Integer(1)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_json_function() {
+        assert_eq!(
+            JsonFunction::Json.apply(&[text(" [1, 2] ")]).unwrap(),
+            text("[1,2]")
+        );
+        assert_eq!(
+            JsonFunction::Json.apply(&[Value::Null]).unwrap(),
+            Value::Null
+        );
+        assert!(JsonFunction::Json.apply(&[text("[1,")]).is_err());
+        assert!(JsonFunction::Json.returns_json());
+        assert!(!JsonFunction::Extract.returns_json());
+    }
+
+    #[test]
+    fn test_json_access_operators() {
+        let payload = text(PAYLOAD);
+        assert_eq!(
+            json_access(&payload, &text("a"), false).unwrap(),
+            text(r#"{"b":[10,20,{"c":"x"}]}"#)
+        );
+        assert_eq!(
+            json_access(&payload, &text("$.a.b[2].c"), false).unwrap(),
+            text(r#""x""#)
+        );
+        assert_eq!(
+            json_access(&payload, &text("$.a.b[2].c"), true).unwrap(),
+            text("x")
+        );
+
+        let array = text("[1,2,3]");
+        assert_eq!(
+            json_access(&array, &Value::Integer(-1), true).unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            json_access(&array, &Value::Integer(5), true).unwrap(),
+            Value::Null
+        );
+        assert!(json_access(&array, &Value::Float(1.0), true).is_err());
+    }
+}
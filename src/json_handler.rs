@@ -0,0 +1,528 @@
+//! JSON file handling module for sqawk
+//!
+//! This module handles loading JSON and newline-delimited JSON (NDJSON) files into
+//! in-memory tables and saving tables back in the same format. It provides functionality for:
+//!
+//! - Loading a JSON array of objects, or one object per line, as table rows
+//! - Using top-level keys as columns, in order of first appearance
+//! - Flattening nested objects into dotted column names (`user.name`)
+//! - Keeping arrays (and empty objects) as JSON text
+//! - Writing modified tables back in the form they were read, as a JSON array or as NDJSON
+//!
+//! JSON values keep their types: numbers become integers or floats, `true`/`false`
+//! become booleans, `null` and missing keys become NULL, and strings stay strings.
+//! A [`JsonLayout`] stored with the table records the key path of each column and
+//! the value each object held, so write-back rebuilds the original objects without
+//! guessing from column names or value text, and writes values that weren't
+//! changed exactly as they were read.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use serde_json::{Map, Number};
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::table::{RowId, Table, Value};
+
+/// What a loaded JSON object held for one column
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonField {
+    /// The object didn't have the key
+    Missing,
+    /// A string, number, boolean or null, as it was read
+    Scalar(serde_json::Value),
+    /// An array or empty object, held in the table as JSON text
+    Json(serde_json::Value),
+}
+
+/// How the objects of a JSON file were laid out, for write-back
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonLayout {
+    /// Key path of each column, e.g. `["user", "name"]` for `user.name`
+    pub paths: Vec<Vec<String>>,
+    /// Whether the file held one object per line rather than a JSON array
+    pub lines: bool,
+    /// What each loaded row held for each column, by row ID
+    pub fields: HashMap<RowId, Vec<JsonField>>,
+}
+
+/// Handles loading and saving JSON and NDJSON files
+///
+/// Each JSON object is one row. Nested objects are flattened on load and
+/// rebuilt from the table's [`JsonLayout`] on save, so a file that is loaded,
+/// modified and written back keeps its shape.
+pub struct JsonHandler;
+
+impl Default for JsonHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonHandler {
+    /// Create a new JsonHandler
+    ///
+    /// # Returns
+    /// A new JsonHandler instance ready to load and save JSON files
+    pub fn new() -> Self {
+        JsonHandler
+    }
+
+    /// Load a JSON or NDJSON file into an in-memory table
+    ///
+    /// A file whose first non-whitespace character is `[` is read as a JSON
+    /// array of objects; anything else is read as one object per line, with
+    /// blank lines ignored.
+    ///
+    /// # Arguments
+    /// * `file_spec` - File specification in the format `[table_name=]file_path`
+    ///
+    /// # Returns
+    /// * `Ok(Table)` - The successfully loaded table
+    /// * `Err` if the file can't be read or doesn't hold JSON objects
+    pub fn load_json(&self, file_spec: &str) -> SqawkResult<Table> {
+        let (table_name, file_path) = self.parse_file_spec(file_spec)?;
+        let file = File::open(&file_path)?;
+
+        self.read_json(&table_name, file, Some(file_path))
+    }
+
+    /// Read JSON or NDJSON data from any reader into an in-memory table
+    ///
+    /// This is the parsing half of [`JsonHandler::load_json`], used directly for
+    /// input that doesn't come from a named file, such as stdin.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to create
+    /// * `input` - Source of the JSON data
+    /// * `file_path` - The file the data came from, if any, used for write-back
+    ///
+    /// # Returns
+    /// * `Ok(Table)` - The successfully loaded table
+    /// * `Err` if the data can't be read or doesn't hold JSON objects
+    pub fn read_json<R: Read>(
+        &self,
+        table_name: &str,
+        mut input: R,
+        file_path: Option<PathBuf>,
+    ) -> SqawkResult<Table> {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        let file = file_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<stdin>".to_string());
+
+        let parse_error = |line: usize, error: String| SqawkError::JsonParseError {
+            file: file.clone(),
+            line,
+            error,
+        };
+
+        // Collect each record as flattened (key path, value, field) entries
+        let mut records = Vec::new();
+        let lines = !text.trim_start().starts_with('[');
+        if !lines {
+            let values: Vec<serde_json::Value> = serde_json::from_str(&text)
+                .map_err(|err| parse_error(err.line(), err.to_string()))?;
+            for (index, value) in values.into_iter().enumerate() {
+                match value {
+                    serde_json::Value::Object(object) => records.push(flatten_object(object)),
+                    other => {
+                        return Err(parse_error(
+                            0,
+                            format!("array element {} is not an object: {}", index + 1, other),
+                        ))
+                    }
+                }
+            }
+        } else {
+            for (index, line) in text.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(line) {
+                    Ok(serde_json::Value::Object(object)) => records.push(flatten_object(object)),
+                    Ok(other) => {
+                        return Err(parse_error(
+                            index + 1,
+                            format!("expected a JSON object, found {}", other),
+                        ))
+                    }
+                    Err(err) => return Err(parse_error(index + 1, err.to_string())),
+                }
+            }
+        }
+
+        // Columns are the union of all key paths, in order of first appearance
+        let mut paths: Vec<Vec<String>> = Vec::new();
+        let mut path_columns: HashMap<Vec<String>, usize> = HashMap::new();
+        let mut column_paths: HashMap<String, usize> = HashMap::new();
+        for record in &records {
+            for (path, _, _) in record {
+                if path_columns.contains_key(path) {
+                    continue;
+                }
+                // `{"a": {"b": 1}}` and `{"a.b": 2}` would both be column a.b
+                let column = path.join(".");
+                if let Some(&other) = column_paths.get(&column) {
+                    return Err(parse_error(
+                        0,
+                        format!(
+                            "keys {:?} and {:?} both flatten to the column name '{}'",
+                            paths[other], path, column
+                        ),
+                    ));
+                }
+                column_paths.insert(column, paths.len());
+                path_columns.insert(path.clone(), paths.len());
+                paths.push(path.clone());
+            }
+        }
+        let columns: Vec<String> = paths.iter().map(|path| path.join(".")).collect();
+
+        let mut table = Table::new(table_name, columns, file_path);
+        let mut fields = HashMap::new();
+        for record in records {
+            let mut row = vec![Value::Null; paths.len()];
+            let mut row_fields = vec![JsonField::Missing; paths.len()];
+            for (path, value, field) in record {
+                let index = path_columns[&path];
+                row[index] = value;
+                row_fields[index] = field;
+            }
+            table.add_row(row)?;
+            if let Some(row_id) = table.get_row_id_at_index(table.row_count() - 1) {
+                fields.insert(row_id, row_fields);
+            }
+        }
+        table.set_json_layout(JsonLayout {
+            paths,
+            lines,
+            fields,
+        });
+
+        Ok(table)
+    }
+
+    /// Write a table as a JSON array of objects or as NDJSON to any writer
+    ///
+    /// Tables loaded from JSON are written back from their [`JsonLayout`]: in
+    /// the form the file had, with each column at its original key path, arrays
+    /// and objects written as JSON, keys a row never had left out, and values
+    /// that weren't changed written exactly as they were read.
+    ///
+    /// # Arguments
+    /// * `table` - The table to write
+    /// * `writer` - Destination of the JSON text
+    /// * `lines` - Whether to write one object per line instead of an array,
+    ///   for tables that weren't loaded from JSON
+    ///
+    /// # Returns
+    /// * `SqawkResult<()>` - Result of the operation
+    pub fn write_json<W: Write>(
+        &self,
+        table: &Table,
+        mut writer: W,
+        lines: bool,
+    ) -> SqawkResult<()> {
+        let columns = table.columns();
+        let layout = table.json_layout();
+        let lines = layout.map_or(lines, |layout| layout.lines);
+
+        let objects = table.rows().iter().enumerate().map(|(index, row)| {
+            let fields = layout.and_then(|layout| {
+                let row_id = table.get_row_id_at_index(index)?;
+                layout.fields.get(&row_id)
+            });
+            let mut object = Map::new();
+            for (column, value) in row.iter().enumerate() {
+                let field = fields.and_then(|fields| fields.get(column));
+                if field == Some(&JsonField::Missing) && *value == Value::Null {
+                    continue;
+                }
+                let path = layout
+                    .and_then(|layout| layout.paths.get(column))
+                    .map_or(std::slice::from_ref(&columns[column]), Vec::as_slice);
+                let json = match field {
+                    Some(field) => field_json(value, field),
+                    None => to_json(value),
+                };
+                insert_path(&mut object, path, json);
+            }
+            serde_json::Value::Object(object).to_string()
+        });
+
+        if lines {
+            for object in objects {
+                writeln!(writer, "{}", object)?;
+            }
+        } else {
+            let objects: Vec<String> = objects.collect();
+            if objects.is_empty() {
+                writeln!(writer, "[]")?;
+            } else {
+                writeln!(writer, "[")?;
+                writeln!(writer, "  {}", objects.join(",\n  "))?;
+                writeln!(writer, "]")?;
+            }
+        }
+
+        writer.flush().map_err(SqawkError::IoError)?;
+
+        Ok(())
+    }
+
+    /// Parse a file specification into table name and file path
+    ///
+    /// Handles two formats:
+    /// 1. `table_name=file_path.json` - Explicit table name and file path
+    /// 2. `file_path.json` - Table name derived from file name
+    ///
+    /// # Arguments
+    /// * `file_spec` - File specification in one of the supported formats
+    ///
+    /// # Returns
+    /// * `Ok((String, PathBuf))` - Tuple of (table_name, file_path)
+    /// * `Err` - If the file specification is invalid
+    fn parse_file_spec(&self, file_spec: &str) -> SqawkResult<(String, PathBuf)> {
+        if let Some((table_name, file_path)) = file_spec.split_once('=') {
+            // Table name specified explicitly
+            Ok((table_name.to_string(), PathBuf::from(file_path)))
+        } else {
+            // Table name derived from file name
+            let path = PathBuf::from(file_spec);
+            let stem = path.file_stem().ok_or_else(|| {
+                SqawkError::InvalidFileSpec(format!("Invalid file specification: {}", file_spec))
+            })?;
+
+            Ok((stem.to_string_lossy().to_string(), path))
+        }
+    }
+}
+
+/// A flattened JSON entry: key path, table value and what the key held
+type FlatEntry = (Vec<String>, Value, JsonField);
+
+/// Flatten a JSON object into (key path, value, field) entries
+///
+/// Nested non-empty objects contribute one entry per leaf; arrays and empty
+/// objects are kept as JSON text.
+///
+/// # Arguments
+/// * `object` - The JSON object to flatten
+///
+/// # Returns
+/// * `Vec<FlatEntry>` - Key paths, values and fields in key order
+fn flatten_object(object: Map<String, serde_json::Value>) -> Vec<FlatEntry> {
+    let mut fields = Vec::new();
+    flatten_into(object, &[], &mut fields);
+    fields
+}
+
+/// Recursive helper for [`flatten_object`] that prefixes paths with `prefix`
+fn flatten_into(
+    object: Map<String, serde_json::Value>,
+    prefix: &[String],
+    fields: &mut Vec<FlatEntry>,
+) {
+    for (key, value) in object {
+        let mut path = prefix.to_vec();
+        path.push(key);
+        match value {
+            serde_json::Value::Object(nested) if !nested.is_empty() => {
+                flatten_into(nested, &path, fields);
+            }
+            json @ (serde_json::Value::Array(_) | serde_json::Value::Object(_)) => {
+                fields.push((path, from_json(json.clone()), JsonField::Json(json)))
+            }
+            other => fields.push((path, from_json(other.clone()), JsonField::Scalar(other))),
+        }
+    }
+}
+
+/// Convert a JSON value to a table value
+///
+/// Integers that don't fit in an i64 become floats; arrays and objects become JSON text.
+pub fn from_json(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => Value::String(s),
+        other => Value::String(other.to_string()),
+    }
+}
+
+/// Convert a table value to a JSON value by its type
+///
+/// Non-finite floats become null, and text is always a JSON string.
+pub fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Float(f) => Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::String(s) => serde_json::Value::String(s.clone()),
+    }
+}
+
+/// Convert a table value to JSON for a column of a loaded row
+///
+/// A value that hasn't changed since it was loaded is written as it was read, so
+/// numbers keep their exact text. Text is written as an array or object only when
+/// the key held one; if the text no longer parses, it is written as a string.
+fn field_json(value: &Value, field: &JsonField) -> serde_json::Value {
+    match field {
+        JsonField::Scalar(original) | JsonField::Json(original)
+            if from_json(original.clone()) == *value =>
+        {
+            original.clone()
+        }
+        JsonField::Json(_) => match value {
+            Value::String(text) => serde_json::from_str(text).unwrap_or_else(|_| to_json(value)),
+            _ => to_json(value),
+        },
+        _ => to_json(value),
+    }
+}
+
+/// Insert a value under a key path, creating nested objects as needed
+///
+/// If an intermediate key already holds a non-object value, it is replaced
+/// by an object.
+fn insert_path(
+    object: &mut Map<String, serde_json::Value>,
+    path: &[String],
+    value: serde_json::Value,
+) {
+    match path {
+        [] => {}
+        [key] => {
+            object.insert(key.clone(), value);
+        }
+        [head, rest @ ..] => {
+            let nested = object
+                .entry(head.clone())
+                .or_insert_with(|| serde_json::Value::Object(Map::new()));
+            if !nested.is_object() {
+                *nested = serde_json::Value::Object(Map::new());
+            }
+            if let serde_json::Value::Object(nested) = nested {
+                insert_path(nested, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_nested_objects() {
+        let object = json!({
+            "id": 1,
+            "user": {"name": "ann", "geo": {"lat": 1.5}},
+            "tags": ["a", "b"],
+            "meta": {},
+            "active": true,
+            "note": null
+        });
+        let serde_json::Value::Object(object) = object else {
+            unreachable!()
+        };
+
+        let fields = flatten_object(object);
+        let paths: Vec<String> = fields.iter().map(|(path, _, _)| path.join(".")).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "id",
+                "user.name",
+                "user.geo.lat",
+                "tags",
+                "meta",
+                "active",
+                "note"
+            ]
+        );
+        assert_eq!(fields[0].1, Value::Integer(1));
+        assert_eq!(fields[2].1, Value::Float(1.5));
+        assert_eq!(fields[3].1, Value::String("[\"a\",\"b\"]".to_string()));
+        assert_eq!(fields[3].2, JsonField::Json(json!(["a", "b"])));
+        assert_eq!(fields[4].1, Value::String("{}".to_string()));
+        assert_eq!(fields[4].2, JsonField::Json(json!({})));
+        assert_eq!(fields[5].1, Value::Boolean(true));
+        assert_eq!(fields[6].1, Value::Null);
+        assert_eq!(fields[6].2, JsonField::Scalar(json!(null)));
+    }
+
+    #[test]
+    fn test_insert_path_rebuilds_objects() {
+        let path = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let mut object = Map::new();
+        insert_path(&mut object, &path(&["id"]), json!(1));
+        insert_path(&mut object, &path(&["user", "name"]), json!("ann"));
+        insert_path(&mut object, &path(&["user", "geo", "lat"]), json!(1.5));
+        insert_path(&mut object, &path(&["a.b"]), json!(2));
+
+        assert_eq!(
+            serde_json::Value::Object(object),
+            json!({"id": 1, "user": {"name": "ann", "geo": {"lat": 1.5}}, "a.b": 2})
+        );
+    }
+
+    #[test]
+    fn test_field_json_embeds_only_json_fields() {
+        let array = JsonField::Json(json!([1]));
+        let text = Value::String("[1,2]".to_string());
+        assert_eq!(field_json(&text, &array), json!([1, 2]));
+        assert_eq!(
+            field_json(&text, &JsonField::Scalar(json!("x"))),
+            json!("[1,2]")
+        );
+        assert_eq!(
+            field_json(&Value::String("[oops".to_string()), &array),
+            json!("[oops")
+        );
+    }
+
+    #[test]
+    fn test_field_json_keeps_unchanged_values() {
+        let big: serde_json::Value = serde_json::from_str("12345678901234567890").unwrap();
+        let field = JsonField::Scalar(big.clone());
+        let loaded = from_json(big);
+        assert_eq!(
+            field_json(&loaded, &field).to_string(),
+            "12345678901234567890"
+        );
+        assert_eq!(field_json(&Value::Integer(7), &field), json!(7));
+
+        let price: serde_json::Value = serde_json::from_str("1.50").unwrap();
+        let field = JsonField::Scalar(price.clone());
+        assert_eq!(field_json(&from_json(price), &field).to_string(), "1.50");
+    }
+
+    #[test]
+    fn test_colliding_column_names_rejected() {
+        let error = JsonHandler::new()
+            .read_json("t", &br#"[{"a": {"b": 1}, "a.b": 2}]"#[..], None)
+            .unwrap_err();
+        assert!(error.to_string().contains("column name 'a.b'"));
+    }
+
+    #[test]
+    fn test_to_json_keeps_text_as_strings() {
+        assert_eq!(to_json(&Value::String("[1,2]".to_string())), json!("[1,2]"));
+        assert_eq!(to_json(&Value::Float(f64::NAN)), serde_json::Value::Null);
+    }
+}
@@ -4,13 +4,25 @@
 //! It facilitates SQL querying against in-memory CSV tables.
 
 pub mod aggregate;
+pub mod arithmetic;
 pub mod cli;
+pub mod compression;
 pub mod config;
+pub mod csv_dialect;
 pub mod csv_handler;
 pub mod database;
 pub mod delim_handler;
+pub mod dialect;
+pub mod encoding;
 pub mod error;
 pub mod file_handler;
+pub mod fixed_width_handler;
+pub mod functions;
+pub mod header_options;
+pub mod json_functions;
+pub mod json_handler;
+pub mod output;
+pub mod regex_functions;
 pub mod repl;
 pub mod sql_executor;
 pub mod string_functions;
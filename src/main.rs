@@ -26,27 +26,15 @@
 //! 4. Print query results to stdout
 //! 5. Save modified tables back to disk if requested
 
-mod aggregate;
-mod cli;
-mod config;
-mod csv_handler;
-mod database;
-mod delim_handler;
-mod error;
-mod file_handler;
-mod repl;
-mod sql_executor;
-mod string_functions;
-mod table;
-mod vm;
-
 use anyhow::{Context, Result};
 // Not explicitly importing SqawkArgs as it's not directly used
-use config::AppConfig;
-use database::Database;
-use file_handler::FileHandler;
-use repl::Repl;
-use sql_executor::SqlExecutor;
+use sqawk::cli;
+use sqawk::config::AppConfig;
+use sqawk::database::Database;
+use sqawk::file_handler::{FileHandler, STDIN_PATH};
+use sqawk::output;
+use sqawk::repl::Repl;
+use sqawk::sql_executor::SqlExecutor;
 
 /// Main entry point for the sqawk utility
 ///
@@ -77,7 +65,19 @@ fn main() -> Result<()> {
         args.tabledef.clone(),        // Table column definitions
         args.write,                   // Whether to write changes to files
         args.vm,                      // Whether to use VM execution engine
-    );
+    )
+    .with_fixed_width_layouts(args.fixed_width_layouts()?)
+    .with_value_format(args.value_format())
+    .with_output_format(args.output_format)
+    .with_input_format(args.input_format)
+    .with_header_settings(args.header_settings()?)
+    .with_dialect_settings(args.dialect_settings())
+    .with_encoding_settings(args.encoding_settings());
+
+    // The REPL reads its commands from stdin, so stdin can't also be a table
+    if args.interactive && args.files.iter().any(|spec| is_stdin_spec(spec)) {
+        anyhow::bail!("Standard input ('-') can't be used as a table in interactive mode");
+    }
 
     // Configure diagnostics output if verbose mode is enabled (-v flag)
     // This is important for debugging and understanding the execution flow
@@ -163,8 +163,8 @@ fn main() -> Result<()> {
                     let row_count = table.row_count();
                     println!("Query returned {row_count} rows");
                 }
-                // Print the result table in delimiter-separated format
-                table.print_to_stdout()?;
+                // Print the result table in the selected output format
+                output::print_to_stdout(&table, config.output_format(), config.value_format())?;
             }
             // For statements that don't return data (UPDATE, DELETE, INSERT)
             None => {
@@ -190,3 +190,11 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Check whether a file specification reads standard input (`-` or `table=-`)
+fn is_stdin_spec(file_spec: &str) -> bool {
+    let path = file_spec
+        .split_once('=')
+        .map_or(file_spec, |(_, path)| path);
+    path == STDIN_PATH
+}
@@ -0,0 +1,488 @@
+//! Output formatting module for sqawk
+//!
+//! This module renders query results in the formats selected with `--output-format`
+//! on the command line or `.mode` in the REPL:
+//! - CSV (RFC 4180 quoting through the csv crate)
+//! - TSV (tabs and newlines inside values escaped)
+//! - Aligned box tables for reading in a terminal
+//! - Markdown tables
+//! - JSON arrays and newline-delimited JSON (one object per row)
+//! - HTML tables
+//!
+//! Every format is implemented as an [`OutputWriter`], so that callers only
+//! choose a format and hand over the result table.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::table::{Table, Value, ValueFormat};
+
+/// Output formats for query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// Aligned table with box borders
+    Table,
+    /// Markdown (GitHub-flavored) table
+    Markdown,
+    /// A JSON array with one object per row
+    Json,
+    /// Newline-delimited JSON, one object per line
+    Ndjson,
+    /// HTML table
+    Html,
+}
+
+impl OutputFormat {
+    /// Names accepted for each format, used in help and error messages
+    pub const NAMES: &'static [&'static str] =
+        &["csv", "tsv", "table", "markdown", "json", "ndjson", "html"];
+
+    /// Get the writer that renders tables in this format
+    ///
+    /// # Returns
+    /// * The output writer for this format
+    pub fn writer(&self) -> Box<dyn OutputWriter> {
+        match self {
+            OutputFormat::Csv => Box::new(CsvWriter),
+            OutputFormat::Tsv => Box::new(TsvWriter),
+            OutputFormat::Table => Box::new(BoxTableWriter),
+            OutputFormat::Markdown => Box::new(MarkdownWriter),
+            OutputFormat::Json => Box::new(JsonWriter { lines: false }),
+            OutputFormat::Ndjson => Box::new(JsonWriter { lines: true }),
+            OutputFormat::Html => Box::new(HtmlWriter),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = SqawkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" | "tabs" => Ok(OutputFormat::Tsv),
+            "table" | "box" => Ok(OutputFormat::Table),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            "html" => Ok(OutputFormat::Html),
+            _ => Err(SqawkError::InvalidArgument(format!(
+                "Unknown output format '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Table => "table",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Html => "html",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Renders a result table to an output stream
+///
+/// Text formats render values with the configured [`ValueFormat`] (so NULL uses
+/// `--null-output`), while JSON formats keep their types: numbers, booleans and
+/// `null`.
+pub trait OutputWriter {
+    /// Write the table, including its column names, to `out`
+    ///
+    /// # Arguments
+    /// * `table` - The table to write
+    /// * `format` - Rules for rendering values as text
+    /// * `out` - The stream to write to
+    ///
+    /// # Returns
+    /// * `Ok(())` if the table was written
+    /// * `Err` if writing failed
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()>;
+}
+
+/// RFC 4180 CSV: fields with commas, quotes or newlines are quoted
+struct CsvWriter;
+
+impl OutputWriter for CsvWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        let mut writer = csv::Writer::from_writer(out);
+        writer.write_record(table.columns())?;
+        for row in table.rows() {
+            writer.write_record(row.iter().map(|value| format.display(value)))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Tab-separated values with backslash escapes for tabs, newlines and backslashes
+struct TsvWriter;
+
+impl TsvWriter {
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    }
+}
+
+impl OutputWriter for TsvWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        let header: Vec<String> = table.columns().iter().map(|c| Self::escape(c)).collect();
+        writeln!(out, "{}", header.join("\t"))?;
+        for row in table.rows() {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|value| Self::escape(&format.display(value)))
+                .collect();
+            writeln!(out, "{}", fields.join("\t"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Aligned table with `+---+` borders; numbers are right-aligned
+struct BoxTableWriter;
+
+impl BoxTableWriter {
+    /// Render a value on one line, showing embedded newlines as `\n`
+    fn cell(value: &Value, format: &ValueFormat) -> String {
+        format
+            .display(value)
+            .replace('\r', "\\r")
+            .replace('\n', "\\n")
+    }
+}
+
+impl OutputWriter for BoxTableWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        let columns = table.columns();
+        let cells: Vec<Vec<String>> = table
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|value| Self::cell(value, format)).collect())
+            .collect();
+
+        let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let border: String = widths
+            .iter()
+            .map(|width| format!("+{}", "-".repeat(width + 2)))
+            .collect::<String>()
+            + "+";
+
+        writeln!(out, "{}", border)?;
+        let header: String = columns
+            .iter()
+            .zip(&widths)
+            .map(|(name, width)| format!("| {:<width$} ", name, width = width))
+            .collect();
+        writeln!(out, "{}|", header)?;
+        writeln!(out, "{}", border)?;
+        for (row, row_cells) in table.rows().iter().zip(&cells) {
+            let line: String = row
+                .iter()
+                .zip(row_cells)
+                .zip(&widths)
+                .map(|((value, cell), width)| match value {
+                    Value::Integer(_) | Value::Float(_) => {
+                        format!("| {:>width$} ", cell, width = width)
+                    }
+                    _ => format!("| {:<width$} ", cell, width = width),
+                })
+                .collect();
+            writeln!(out, "{}|", line)?;
+        }
+        if !cells.is_empty() {
+            writeln!(out, "{}", border)?;
+        }
+        Ok(())
+    }
+}
+
+/// GitHub-flavored markdown table
+struct MarkdownWriter;
+
+impl MarkdownWriter {
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    }
+}
+
+impl OutputWriter for MarkdownWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        let columns = table.columns();
+        let header: Vec<String> = columns.iter().map(|c| Self::escape(c)).collect();
+        writeln!(out, "| {} |", header.join(" | "))?;
+        writeln!(out, "|{}", " --- |".repeat(columns.len()))?;
+        for row in table.rows() {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|value| Self::escape(&format.display(value)))
+                .collect();
+            writeln!(out, "| {} |", fields.join(" | "))?;
+        }
+        Ok(())
+    }
+}
+
+/// JSON objects keyed by column name, as one array or one object per line
+struct JsonWriter {
+    /// Whether to write newline-delimited JSON instead of an array
+    lines: bool,
+}
+
+impl JsonWriter {
+    /// Render a row as a JSON object
+    fn object(columns: &[String], row: &[Value]) -> String {
+        let object: serde_json::Map<String, serde_json::Value> = columns
+            .iter()
+            .zip(row)
+            .map(|(name, value)| (name.clone(), json_value(value)))
+            .collect();
+        serde_json::Value::Object(object).to_string()
+    }
+}
+
+impl OutputWriter for JsonWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        _format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        let columns = table.columns();
+        if self.lines {
+            for row in table.rows() {
+                writeln!(out, "{}", Self::object(&columns, row))?;
+            }
+            return Ok(());
+        }
+
+        write!(out, "[")?;
+        for (i, row) in table.rows().iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(out, "{}\n  {}", separator, Self::object(&columns, row))?;
+        }
+        if table.row_count() > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "]")?;
+        Ok(())
+    }
+}
+
+/// Convert a value to JSON; non-finite floats become null
+fn json_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        Value::String(s) => serde_json::Value::String(s.clone()),
+    }
+}
+
+/// HTML table with a header row
+struct HtmlWriter;
+
+impl HtmlWriter {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+impl OutputWriter for HtmlWriter {
+    fn write_table(
+        &self,
+        table: &Table,
+        format: &ValueFormat,
+        out: &mut dyn Write,
+    ) -> SqawkResult<()> {
+        writeln!(out, "<table>")?;
+        let header: String = table
+            .columns()
+            .iter()
+            .map(|name| format!("<th>{}</th>", Self::escape(name)))
+            .collect();
+        writeln!(out, "<tr>{}</tr>", header)?;
+        for row in table.rows() {
+            let cells: String = row
+                .iter()
+                .map(|value| format!("<td>{}</td>", Self::escape(&format.display(value))))
+                .collect();
+            writeln!(out, "<tr>{}</tr>", cells)?;
+        }
+        writeln!(out, "</table>")?;
+        Ok(())
+    }
+}
+
+/// Print a table to stdout in the given output format
+///
+/// # Arguments
+/// * `table` - The table to print
+/// * `output_format` - The format to render the table in
+/// * `format` - Rules for rendering values (e.g. the text used for NULL)
+///
+/// # Returns
+/// * `Ok(())` if the table was successfully printed
+/// * `Err` if there was an error writing to stdout
+pub fn print_to_stdout(
+    table: &Table,
+    output_format: OutputFormat,
+    format: &ValueFormat,
+) -> SqawkResult<()> {
+    let stdout = std::io::stdout();
+    output_format
+        .writer()
+        .write_table(table, format, &mut stdout.lock())
+}
+
+/// Convert all rows of a table to their text representation
+///
+/// # Arguments
+/// * `table` - The table whose rows to convert
+/// * `format` - Rules for rendering values (e.g. the text used for NULL)
+///
+/// # Returns
+/// * Vec<Vec<String>> - All rows converted to strings
+pub fn rows_as_strings(table: &Table, format: &ValueFormat) -> Vec<Vec<String>> {
+    table
+        .rows()
+        .iter()
+        .map(|row| row.iter().map(|value| format.display(value)).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_table() -> Table {
+        let mut table = Table::new("t", vec!["id".to_string(), "note".to_string()], None);
+        table
+            .add_row(vec![
+                Value::Integer(1),
+                Value::String("a, \"b\"".to_string()),
+            ])
+            .unwrap();
+        table.add_row(vec![Value::Float(2.5), Value::Null]).unwrap();
+        table
+    }
+
+    fn render(format: OutputFormat) -> String {
+        let mut out = Vec::new();
+        format
+            .writer()
+            .write_table(&sample_table(), &ValueFormat::default(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv_quotes_special_characters() {
+        assert_eq!(
+            render(OutputFormat::Csv),
+            "id,note\n1,\"a, \"\"b\"\"\"\n2.5,NULL\n"
+        );
+    }
+
+    #[test]
+    fn test_json_keeps_types() {
+        assert_eq!(
+            render(OutputFormat::Json),
+            "[\n  {\"id\":1,\"note\":\"a, \\\"b\\\"\"},\n  {\"id\":2.5,\"note\":null}\n]\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Ndjson),
+            "{\"id\":1,\"note\":\"a, \\\"b\\\"\"}\n{\"id\":2.5,\"note\":null}\n"
+        );
+        assert_eq!(json_value(&Value::Float(3.0)).to_string(), "3.0");
+        assert_eq!(json_value(&Value::Float(f64::NAN)).to_string(), "null");
+        assert_eq!(
+            json_value(&Value::String("tab\there\u{1}".to_string())).to_string(),
+            "\"tab\\there\\u0001\""
+        );
+    }
+
+    #[test]
+    fn test_box_table_alignment() {
+        assert_eq!(
+            render(OutputFormat::Table),
+            "+-----+--------+\n\
+             | id  | note   |\n\
+             +-----+--------+\n\
+             |   1 | a, \"b\" |\n\
+             | 2.5 | NULL   |\n\
+             +-----+--------+\n"
+        );
+    }
+
+    #[test]
+    fn test_format_names_round_trip() {
+        for name in OutputFormat::NAMES {
+            assert_eq!(name.parse::<OutputFormat>().unwrap().to_string(), *name);
+        }
+        assert_eq!(
+            "JSONL".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Ndjson
+        );
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
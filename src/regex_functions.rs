@@ -0,0 +1,422 @@
+//! Regular expression function module for sqawk
+//!
+//! This module implements SQL functions for pulling structured fields out of
+//! free-form text, the job awk is most often used for:
+//! - REGEXP_EXTRACT(): Extract a match or capture group
+//! - REGEXP_REPLACE(): Replace matches, with capture group references
+//! - REGEXP_COUNT(): Count matches
+//! - REGEXP_SPLIT_PART(): Split on a pattern and return one field
+//!
+//! Patterns use the syntax of the Rust `regex` crate. Compiled patterns are kept
+//! in a `RegexCache` owned by the function registry, which is cleared before each
+//! statement, so a pattern used on every row of a query is only compiled once.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use regex::Regex;
+
+use crate::error::{SqawkError, SqawkResult};
+use crate::functions::{function_names, ArgType, ScalarFunction, Signature};
+use crate::string_functions::{check_arg_count, integer_arg, text_arg};
+use crate::table::Value;
+
+/// Enum of supported regular expression functions
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegexFunction {
+    /// Extract a match or capture group - REGEXP_EXTRACT(str, pattern[, group])
+    Extract,
+    /// Replace all matches - REGEXP_REPLACE(str, pattern, replacement)
+    Replace,
+    /// Count non-overlapping matches - REGEXP_COUNT(str, pattern)
+    Count,
+    /// Field n of a string split on a pattern - REGEXP_SPLIT_PART(str, pattern, n)
+    SplitPart,
+}
+
+function_names!(RegexFunction {
+    Extract => "REGEXP_EXTRACT",
+    Replace => "REGEXP_REPLACE",
+    Count => "REGEXP_COUNT",
+    SplitPart => "REGEXP_SPLIT_PART" | "REGEXP_SPLIT",
+});
+
+impl RegexFunction {
+    /// Apply the regex function to its arguments
+    ///
+    /// # Arguments
+    /// * `args` - The evaluated function arguments
+    /// * `cache` - Cache of compiled patterns for the current query
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The function result (NULL if the string or pattern is NULL)
+    /// * `Err` - If the arguments are invalid or the pattern does not compile
+    pub fn apply(&self, args: &[Value], cache: &RegexCache) -> SqawkResult<Value> {
+        match self {
+            RegexFunction::Extract => self.apply_extract(args, cache),
+            RegexFunction::Replace => self.apply_replace(args, cache),
+            RegexFunction::Count => self.apply_count(args, cache),
+            RegexFunction::SplitPart => self.apply_split_part(args, cache),
+        }
+    }
+
+    /// Apply REGEXP_EXTRACT function - return the first match or one of its groups
+    ///
+    /// The group may be a number (0, the default, is the whole match) or the name
+    /// of a named group. Returns NULL if the pattern does not match or the group
+    /// did not take part in the match.
+    fn apply_extract(&self, args: &[Value], cache: &RegexCache) -> SqawkResult<Value> {
+        check_arg_count("REGEXP_EXTRACT", args, 2, 3)?;
+
+        let (Some(string), Some(pattern)) = (text_arg(args, 0), text_arg(args, 1)) else {
+            return Ok(Value::Null);
+        };
+        let regex = cache.get(&pattern)?;
+
+        let Some(captures) = regex.captures(&string) else {
+            return Ok(Value::Null);
+        };
+
+        let group = match args.get(2) {
+            None => captures.get(0),
+            Some(Value::Null) => return Ok(Value::Null),
+            Some(Value::String(name)) => {
+                if !regex.capture_names().any(|n| n == Some(name.as_str())) {
+                    return Err(SqawkError::InvalidFunctionArguments(format!(
+                        "REGEXP_EXTRACT pattern '{}' has no group named '{}'",
+                        pattern, name
+                    )));
+                }
+                captures.name(name)
+            }
+            Some(_) => {
+                let index = integer_arg("REGEXP_EXTRACT", args, 2)?.unwrap_or(0);
+                match usize::try_from(index) {
+                    Ok(index) if index < regex.captures_len() => captures.get(index),
+                    _ => {
+                        return Err(SqawkError::InvalidFunctionArguments(format!(
+                            "REGEXP_EXTRACT pattern '{}' has no group {}",
+                            pattern, index
+                        )))
+                    }
+                }
+            }
+        };
+
+        Ok(group
+            .map(|m| Value::String(m.as_str().to_string()))
+            .unwrap_or(Value::Null))
+    }
+
+    /// Apply REGEXP_REPLACE function - replace every match
+    ///
+    /// The replacement may refer to capture groups as `$1`, `${name}` or `\1`.
+    fn apply_replace(&self, args: &[Value], cache: &RegexCache) -> SqawkResult<Value> {
+        check_arg_count("REGEXP_REPLACE", args, 3, 3)?;
+
+        let (Some(string), Some(pattern), Some(replacement)) =
+            (text_arg(args, 0), text_arg(args, 1), text_arg(args, 2))
+        else {
+            return Ok(Value::Null);
+        };
+        let regex = cache.get(&pattern)?;
+
+        let replacement = convert_backreferences(&replacement);
+        let result = regex.replace_all(&string, replacement.as_str());
+        Ok(Value::String(result.into_owned()))
+    }
+
+    /// Apply REGEXP_COUNT function - count non-overlapping matches
+    fn apply_count(&self, args: &[Value], cache: &RegexCache) -> SqawkResult<Value> {
+        check_arg_count("REGEXP_COUNT", args, 2, 2)?;
+
+        let (Some(string), Some(pattern)) = (text_arg(args, 0), text_arg(args, 1)) else {
+            return Ok(Value::Null);
+        };
+        let regex = cache.get(&pattern)?;
+
+        Ok(Value::Integer(regex.find_iter(&string).count() as i64))
+    }
+
+    /// Apply REGEXP_SPLIT_PART function - split on a pattern and return field n
+    ///
+    /// Fields are numbered from 1; a negative n counts from the end. A field
+    /// number past the end yields an empty string, as with SPLIT_PART.
+    fn apply_split_part(&self, args: &[Value], cache: &RegexCache) -> SqawkResult<Value> {
+        check_arg_count("REGEXP_SPLIT_PART", args, 3, 3)?;
+
+        let (Some(string), Some(pattern)) = (text_arg(args, 0), text_arg(args, 1)) else {
+            return Ok(Value::Null);
+        };
+        let Some(n) = integer_arg("REGEXP_SPLIT_PART", args, 2)? else {
+            return Ok(Value::Null);
+        };
+        if n == 0 {
+            return Err(SqawkError::InvalidFunctionArguments(
+                "Field position in REGEXP_SPLIT_PART must not be 0".to_string(),
+            ));
+        }
+        let regex = cache.get(&pattern)?;
+
+        let fields: Vec<&str> = regex.split(&string).collect();
+        let index = if n > 0 {
+            usize::try_from(n - 1).ok()
+        } else {
+            fields.len().checked_sub(n.unsigned_abs() as usize)
+        };
+
+        let field = index.and_then(|i| fields.get(i)).copied().unwrap_or("");
+        Ok(Value::String(field.to_string()))
+    }
+}
+
+/// A regex function bound to the pattern cache it compiles patterns into
+///
+/// This is the form in which the regex functions are registered as scalar
+/// functions; the cache is shared with the registry that clears it.
+pub struct RegexScalarFunction {
+    /// The function to apply
+    function: RegexFunction,
+
+    /// Cache of compiled patterns for the current statement
+    cache: Arc<RegexCache>,
+}
+
+impl RegexScalarFunction {
+    /// Bind a regex function to a pattern cache
+    pub fn new(function: RegexFunction, cache: Arc<RegexCache>) -> Self {
+        RegexScalarFunction { function, cache }
+    }
+}
+
+impl ScalarFunction for RegexScalarFunction {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+
+    /// The string is converted to text and the REGEXP_EXTRACT group may be a
+    /// number or a name; patterns and replacements must be strings
+    fn signature(&self) -> Signature {
+        use ArgType::{Any, Integer, String};
+        match self.function {
+            RegexFunction::Extract => Signature::optional(vec![Any, String], vec![Any]),
+            RegexFunction::Replace => Signature::exact(vec![Any, String, String]),
+            RegexFunction::Count => Signature::exact(vec![Any, String]),
+            RegexFunction::SplitPart => Signature::exact(vec![Any, String, Integer]),
+        }
+    }
+
+    fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+        self.function.apply(args, &self.cache)
+    }
+}
+
+/// Cache of compiled regular expressions
+///
+/// Uses interior mutability so that expression evaluation, which only borrows
+/// the executor immutably, can still add patterns to the cache. A mutex rather
+/// than a `RefCell` keeps the registry that owns the cache `Send + Sync`.
+#[derive(Debug, Default)]
+pub struct RegexCache {
+    /// Compiled patterns keyed by their source text
+    patterns: Mutex<HashMap<String, Regex>>,
+}
+
+impl RegexCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the pattern map; a panic while it was held can't leave it inconsistent
+    fn patterns(&self) -> MutexGuard<'_, HashMap<String, Regex>> {
+        self.patterns.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the compiled form of a pattern, compiling it on first use
+    ///
+    /// # Arguments
+    /// * `pattern` - The regular expression source
+    ///
+    /// # Returns
+    /// * `Ok(Regex)` - The compiled pattern (cloning a Regex is cheap)
+    /// * `Err(SqawkError::InvalidFunctionArguments)` - If the pattern is not valid
+    pub fn get(&self, pattern: &str) -> SqawkResult<Regex> {
+        if let Some(regex) = self.patterns().get(pattern) {
+            return Ok(regex.clone());
+        }
+
+        let regex = Regex::new(pattern).map_err(|e| {
+            SqawkError::InvalidFunctionArguments(format!(
+                "Invalid regular expression '{}': {}",
+                pattern, e
+            ))
+        })?;
+        self.patterns().insert(pattern.to_string(), regex.clone());
+
+        Ok(regex)
+    }
+
+    /// Number of compiled patterns in the cache
+    pub fn len(&self) -> usize {
+        self.patterns().len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.patterns().is_empty()
+    }
+
+    /// Remove all compiled patterns
+    pub fn clear(&self) {
+        self.patterns().clear();
+    }
+}
+
+/// Convert `\1`-style backreferences into the `${1}` form the regex crate expects
+///
+/// A doubled backslash stands for a literal backslash.
+fn convert_backreferences(replacement: &str) -> String {
+    let mut result = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(d)) if d.is_ascii_digit() => {
+                result.push_str(&format!("${{{}}}", d));
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper to build a string value
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_regexp_extract() {
+        let cache = RegexCache::new();
+        let line = text("GET /index.html 200 512ms");
+
+        let result = RegexFunction::Extract
+            .apply(&[line.clone(), text(r"\d+ms")], &cache)
+            .unwrap();
+        assert_eq!(result, text("512ms"));
+
+        let result = RegexFunction::Extract
+            .apply(
+                &[line.clone(), text(r"(\w+) (\S+)"), Value::Integer(2)],
+                &cache,
+            )
+            .unwrap();
+        assert_eq!(result, text("/index.html"));
+
+        let result = RegexFunction::Extract
+            .apply(
+                &[line.clone(), text(r"(?P<status>\d{3}) "), text("status")],
+                &cache,
+            )
+            .unwrap();
+        assert_eq!(result, text("200"));
+
+        // No match yields NULL
+        let result = RegexFunction::Extract
+            .apply(&[line.clone(), text("POST")], &cache)
+            .unwrap();
+        assert_eq!(result, Value::Null);
+
+        // Missing groups are an error
+        assert!(RegexFunction::Extract
+            .apply(&[line.clone(), text("(GET)"), Value::Integer(2)], &cache)
+            .is_err());
+        assert!(RegexFunction::Extract
+            .apply(&[line, text("(GET)"), text("verb")], &cache)
+            .is_err());
+    }
+
+    #[test]
+    fn test_regexp_replace_with_backreferences() {
+        let cache = RegexCache::new();
+        let date = text("2024-03-15");
+
+        for replacement in [r"\3/\2/\1", "$3/$2/$1", "${3}/${2}/${1}"] {
+            let result = RegexFunction::Replace
+                .apply(
+                    &[date.clone(), text(r"(\d+)-(\d+)-(\d+)"), text(replacement)],
+                    &cache,
+                )
+                .unwrap();
+            assert_eq!(result, text("15/03/2024"));
+        }
+
+        let result = RegexFunction::Replace
+            .apply(&[text("a1b22c333"), text(r"\d+"), text("#")], &cache)
+            .unwrap();
+        assert_eq!(result, text("a#b#c#"));
+
+        assert_eq!(convert_backreferences(r"a\\1"), r"a\1");
+    }
+
+    #[test]
+    fn test_regexp_count_and_split_part() {
+        let cache = RegexCache::new();
+
+        let result = RegexFunction::Count
+            .apply(&[text("a1b22c333"), text(r"\d+")], &cache)
+            .unwrap();
+        assert_eq!(result, Value::Integer(3));
+
+        let args = |n: i64| vec![text("one  two\tthree"), text(r"\s+"), Value::Integer(n)];
+        for (n, expected) in [(1, "one"), (2, "two"), (-1, "three"), (4, "")] {
+            assert_eq!(
+                RegexFunction::SplitPart.apply(&args(n), &cache).unwrap(),
+                text(expected)
+            );
+        }
+        assert!(RegexFunction::SplitPart.apply(&args(0), &cache).is_err());
+    }
+
+    #[test]
+    fn test_null_and_invalid_patterns() {
+        let cache = RegexCache::new();
+
+        let result = RegexFunction::Count
+            .apply(&[Value::Null, text("a")], &cache)
+            .unwrap();
+        assert_eq!(result, Value::Null);
+
+        let result = RegexFunction::Count.apply(&[text("abc"), text("(")], &cache);
+        assert!(matches!(
+            result,
+            Err(SqawkError::InvalidFunctionArguments(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_reuses_compiled_patterns() {
+        let cache = RegexCache::new();
+
+        for _ in 0..3 {
+            RegexFunction::Count
+                .apply(&[text("aaa"), text("a")], &cache)
+                .unwrap();
+        }
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
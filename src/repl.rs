@@ -11,6 +11,7 @@ use std::fmt;
 
 use crate::config::AppConfig;
 use crate::error::SqawkError;
+use crate::output::{self, OutputFormat};
 use crate::sql_executor::SqlExecutor;
 
 // Define a custom error type for the REPL
@@ -74,8 +75,8 @@ impl CommandCompleter {
     /// Create a new command completer with the list of available commands
     fn new() -> Self {
         let commands = vec![
-            ".cd", ".changes", ".exit", ".help", ".load", ".print", ".quit", ".save", ".schema",
-            ".show", ".stats", ".tables", ".version", ".write",
+            ".cd", ".changes", ".exit", ".help", ".load", ".mode", ".print", ".quit", ".save",
+            ".schema", ".show", ".stats", ".tables", ".version", ".write",
         ]
         .into_iter()
         .map(|s| s.to_string())
@@ -172,6 +173,8 @@ enum ReplCommand {
     ChangeDirectory(String),
     /// Toggle showing number of changes
     Changes(Option<String>),
+    /// Show or set the output format
+    Mode(Option<String>),
     /// Print a string literal
     Print(String),
     /// Show version information
@@ -200,6 +203,8 @@ pub struct Repl<'a> {
     show_changes: bool,
     /// Whether to show query statistics
     show_stats: bool,
+    /// Format used to print query results
+    output_format: OutputFormat,
 }
 
 impl<'a> Repl<'a> {
@@ -227,6 +232,7 @@ impl<'a> Repl<'a> {
             running: true,
             show_changes: false, // Default to not showing changes
             show_stats: false,   // Default to not showing stats
+            output_format: app_config.output_format(),
         }
     }
 
@@ -344,6 +350,13 @@ impl<'a> Repl<'a> {
                         ReplCommand::Print("".to_string()) // Print an empty line
                     }
                 }
+                "mode" => {
+                    if parts.len() > 1 {
+                        ReplCommand::Mode(Some(parts[1].trim().to_string()))
+                    } else {
+                        ReplCommand::Mode(None)
+                    }
+                }
                 "version" => ReplCommand::Version,
                 "help" => ReplCommand::Help,
                 "save" => {
@@ -391,6 +404,7 @@ impl<'a> Repl<'a> {
             ReplCommand::Save(table_name) => self.save_tables(table_name.as_deref()),
             ReplCommand::Show(option) => self.show_settings(option.as_deref()),
             ReplCommand::Stats(option) => self.show_stats(option.as_deref()),
+            ReplCommand::Mode(format) => self.set_mode(format.as_deref()),
             ReplCommand::Print(text) => {
                 println!("{}", text);
                 Ok(())
@@ -412,24 +426,18 @@ impl<'a> Repl<'a> {
             None
         };
 
-        let result = match self.executor.execute_sql(sql) {
-            Ok(result) => result,
-            Err(err) => return Err(ReplError::SqlExecutor(err)),
-        };
+        let result = self
+            .executor
+            .execute(sql)
+            .map_err(|err| ReplError::SqlExecutor(err.into()))?;
 
-        // Print results
-        if let Some(result_set) = result {
-            if result_set.rows.is_empty() {
+        // Print results in the current output mode
+        if let Some(table) = result {
+            if table.row_count() == 0 {
                 println!("Query returned no rows");
             } else {
-                println!("Query returned {} rows", result_set.rows.len());
-                // Print column headers
-                println!("{}", result_set.columns.join(","));
-
-                // Print rows
-                for row in result_set.rows {
-                    println!("{}", row.join(","));
-                }
+                println!("Query returned {} rows", table.row_count());
+                output::print_to_stdout(&table, self.output_format, self.config.value_format())?;
             }
         } else if self.show_changes {
             // For non-SELECT statements that don't return rows (INSERT, UPDATE, DELETE)
@@ -547,6 +555,10 @@ impl<'a> Repl<'a> {
         println!("  .exit ?CODE?          Exit the REPL with optional code");
         println!("  .help                 Show this help message");
         println!("  .load [TABLE=]FILE    Load FILE into TABLE");
+        println!(
+            "  .mode ?FORMAT?        Show or set the output format (currently: {})",
+            self.output_format
+        );
         println!("  .print STRING...      Print literal STRING");
         println!("  .quit                 Exit the REPL");
         println!("  .save ?TABLE?         Save changes to all tables or a specific TABLE");
@@ -771,6 +783,25 @@ impl Repl<'_> {
         Ok(())
     }
 
+    /// Show or set the output format used for query results
+    fn set_mode(&mut self, format: Option<&str>) -> Result<()> {
+        match format {
+            Some(name) => match name.parse::<OutputFormat>() {
+                Ok(output_format) => {
+                    self.output_format = output_format;
+                    println!("Output mode set to {}", output_format);
+                }
+                Err(err) => eprintln!("{}", err),
+            },
+            None => println!(
+                "Output mode: {} (available: {})",
+                self.output_format,
+                OutputFormat::NAMES.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
     /// Show current settings and configuration
     fn show_settings(&self, option: Option<&str>) -> Result<()> {
         match option {
@@ -797,6 +828,7 @@ impl Repl<'_> {
                     "  Statistics:  {}",
                     if self.show_stats { "ON" } else { "OFF" }
                 );
+                println!("  Output Mode: {}", self.output_format);
                 println!(
                     "  Verbose:     {}",
                     if self.config.verbose() { "ON" } else { "OFF" }
@@ -14,24 +14,393 @@
 //! The module implements a non-destructive approach, modifying only in-memory tables
 //! until explicitly requested to save changes back to the original files.
 
+use std::borrow::Cow;
 use std::collections::HashSet;
 
 use anyhow::Result;
 use sqlparser::ast::{
-    Assignment, ColumnDef as SqlColumnDef, Expr, FileFormat as SqlFileFormat, Join as SqlJoin,
-    JoinConstraint, JoinOperator, ObjectName, Query, Select, SelectItem, SetExpr, SqlOption,
-    Statement, TableFactor, TableWithJoins, Value as SqlValue,
+    Assignment, ColumnDef as SqlColumnDef, DataType as SqlDataType, Expr,
+    FileFormat as SqlFileFormat, Join as SqlJoin, JoinConstraint, JoinOperator, JsonOperator,
+    ObjectName, Query, Select, SelectItem, SetExpr, SqlOption, Statement, TableAlias, TableFactor,
+    TableWithJoins, Value as SqlValue,
 };
-use sqlparser::dialect::HiveDialect;
-use sqlparser::parser::Parser;
 
-use crate::aggregate::AggregateFunction;
+use crate::aggregate::{Accumulator, Aggregator, DistinctAccumulator};
+use crate::arithmetic::{self, ArithmeticOperator};
 use crate::config::AppConfig;
 use crate::database::Database;
+use crate::dialect;
 use crate::error::{SqawkError, SqawkResult};
 use crate::file_handler::FileHandler;
-use crate::string_functions::StringFunction;
-use crate::table::{ColumnDefinition, DataType, SortDirection, Table, Value};
+use crate::functions::ScalarFunction;
+use crate::json_functions::{json_access, JsonFunction};
+use crate::output;
+use crate::table::{
+    ColumnDefinition, DataType, NullOrdering, SortDirection, SortKey, Table, Value,
+};
+
+/// The value an aggregate function receives for each row
+enum AggregateInput {
+    /// A non-NULL placeholder for every row, as for COUNT(*)
+    EveryRow,
+    /// The value of a column, by index
+    Column(usize),
+    /// An expression evaluated against each row, as in SUM(price * quantity)
+    Expr(Expr),
+}
+
+/// An aggregate function call from the SELECT list, resolved against the input table
+struct AggregateCall<'f> {
+    /// The aggregate function
+    aggregator: &'f dyn Aggregator,
+    /// Constant arguments after the aggregated value (e.g. a separator)
+    params: Vec<Value>,
+    /// The aggregated value
+    input: AggregateInput,
+    /// Whether duplicate values are ignored, as in COUNT(DISTINCT x)
+    distinct: bool,
+    /// Sort keys from an ORDER BY inside the call, each with its direction (true = ascending)
+    order_by: Vec<(AggregateInput, bool)>,
+    /// Condition from a FILTER (WHERE ...) clause; rows that fail it are skipped
+    filter: Option<Expr>,
+}
+
+impl AggregateCall<'_> {
+    /// Create the accumulator for one group
+    fn create_accumulator(&self) -> SqawkResult<GroupAccumulator> {
+        let mut accumulator = self.aggregator.create_accumulator(&self.params)?;
+        if self.distinct {
+            accumulator = Box::new(DistinctAccumulator::new(accumulator));
+        }
+        Ok(GroupAccumulator {
+            accumulator,
+            ordered_rows: Vec::new(),
+        })
+    }
+}
+
+/// The state of one aggregate call for one group
+///
+/// Values are passed straight to the accumulator unless the call has an ORDER BY,
+/// in which case they are buffered with their sort keys until the group is complete.
+struct GroupAccumulator {
+    accumulator: Box<dyn Accumulator>,
+    ordered_rows: Vec<(Vec<Value>, Value)>,
+}
+
+impl GroupAccumulator {
+    /// Feed one input value, with its ORDER BY sort keys, to the aggregate
+    fn update(&mut self, call: &AggregateCall, value: Value, keys: Vec<Value>) -> SqawkResult<()> {
+        if call.order_by.is_empty() {
+            return self.accumulator.update(&value);
+        }
+
+        self.ordered_rows.push((keys, value));
+        Ok(())
+    }
+
+    /// Compute the aggregate result once every row of the group has been seen
+    fn finalize(mut self, call: &AggregateCall) -> SqawkResult<Value> {
+        if !call.order_by.is_empty() {
+            // Stable sort, so rows with equal keys keep their input order
+            self.ordered_rows.sort_by(|(a, _), (b, _)| {
+                for ((x, y), (_, ascending)) in a.iter().zip(b).zip(&call.order_by) {
+                    let ordering = x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal);
+                    if ordering != std::cmp::Ordering::Equal {
+                        return if *ascending {
+                            ordering
+                        } else {
+                            ordering.reverse()
+                        };
+                    }
+                }
+                std::cmp::Ordering::Equal
+            });
+            for (_, value) in &self.ordered_rows {
+                self.accumulator.update(value)?;
+            }
+        }
+
+        self.accumulator.finalize()
+    }
+}
+
+/// One output column of a grouped query
+enum GroupedColumn {
+    /// The value of a grouping key, by key index
+    Key(usize),
+    /// GROUPING(...) of the given keys: one bit per key, set when the key is rolled up
+    Grouping(Vec<usize>),
+    /// The result of an aggregate call, by call index
+    Aggregate(usize),
+}
+
+/// A group's key values and the accumulators of its aggregate functions
+type GroupState = (Vec<Value>, Vec<GroupAccumulator>);
+
+/// Create a table with no columns and a single empty row
+///
+/// Expressions that don't reference columns, such as those in `SELECT 1 + 1` or a
+/// VALUES list, are evaluated against its row.
+fn single_row_table() -> SqawkResult<Table> {
+    let mut table = Table::new("", Vec::new(), None);
+    table.add_row(Vec::new())?;
+    Ok(table)
+}
+
+/// Find the column an unqualified reference such as `id` refers to
+///
+/// The reference matches a column with exactly that name or, failing that, the one
+/// qualified column ending in it (`users.id`). A name that ends more than one
+/// qualified column, as in a self-join, must be qualified.
+///
+/// # Arguments
+/// * `name` - The column name
+/// * `table` - The table to search
+///
+/// # Returns
+/// * The index of the referenced column, if found
+/// * `Err` if the name matches columns of more than one table
+fn unqualified_column_index(name: &str, table: &Table) -> SqawkResult<Option<usize>> {
+    if let Some(idx) = table.column_index(name) {
+        return Ok(Some(idx));
+    }
+
+    let suffix = format!(".{}", name);
+    let columns = table.columns();
+    let matches: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| column.ends_with(&suffix))
+        .map(|(idx, _)| idx)
+        .collect();
+    match matches.as_slice() {
+        [] => Ok(None),
+        [idx] => Ok(Some(*idx)),
+        _ => Err(SqawkError::InvalidSqlQuery(format!(
+            "Ambiguous column reference '{}' could be any of {}; qualify it with a table name or alias",
+            name,
+            matches
+                .iter()
+                .map(|idx| columns[*idx].as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Find the column a qualified reference such as `users.id` refers to
+///
+/// The reference matches, in order: a column with exactly that name, a joined
+/// column ending in it (`orders.users.id`), or, for `table.column`, a column of a
+/// table whose name (or alias) is `table`. The column part may itself be dotted, as
+/// with flattened JSON fields (`events.user.name`).
+///
+/// # Arguments
+/// * `parts` - The parts of the qualified reference
+/// * `table` - The table to search
+///
+/// # Returns
+/// * The index of the referenced column, if found
+fn qualified_column_index(parts: &[sqlparser::ast::Ident], table: &Table) -> Option<usize> {
+    let qualified_name = parts
+        .iter()
+        .map(|ident| ident.value.clone())
+        .collect::<Vec<_>>()
+        .join(".");
+    if let Some(idx) = table.column_index(&qualified_name) {
+        return Some(idx);
+    }
+
+    if let [qualifier, column @ ..] = parts {
+        if column.is_empty() {
+            return None;
+        }
+        let suffix = format!(".{}", qualified_name);
+        if let Some(idx) = table.columns().iter().position(|c| c.ends_with(&suffix)) {
+            return Some(idx);
+        }
+        if qualifier.value == table.name() {
+            let column = column
+                .iter()
+                .map(|ident| ident.value.as_str())
+                .collect::<Vec<_>>()
+                .join(".");
+            return table.column_index(&column);
+        }
+    }
+    None
+}
+
+/// Get the column part of a qualified column name such as `users.id`
+///
+/// # Arguments
+/// * `name` - A column name
+///
+/// # Returns
+/// * The part after the qualifier if `name` is a qualified identifier, else `None`
+fn unqualified_column_name(name: &str) -> Option<&str> {
+    let (qualifier, column) = name.rsplit_once('.')?;
+    let is_identifier = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with(|c: char| c.is_ascii_digit())
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    (qualifier.split('.').all(is_identifier) && is_identifier(column)).then_some(column)
+}
+
+/// The most rows `generate_series` produces; larger series are an error rather
+/// than an out-of-memory abort
+const MAX_SERIES_ROWS: usize = 10_000_000;
+
+/// Check that a series of `count` values fits within `MAX_SERIES_ROWS`
+///
+/// # Arguments
+/// * `count` - The number of values the series would have
+///
+/// # Returns
+/// * `Ok(())` if the series may be generated
+/// * `Err` if it has too many values
+fn check_series_length(count: f64) -> SqawkResult<()> {
+    if count > MAX_SERIES_ROWS as f64 {
+        return Err(SqawkError::InvalidSqlQuery(format!(
+            "generate_series would produce more than {} rows",
+            MAX_SERIES_ROWS
+        )));
+    }
+    Ok(())
+}
+
+/// Rename a table, and optionally its columns, by a table alias from FROM
+///
+/// # Arguments
+/// * `table` - The resolved table
+/// * `alias` - The alias, if the query gave one
+///
+/// # Returns
+/// * The table under its alias, for the rest of the query
+/// * `Err` if the alias lists the wrong number of column names
+fn apply_table_alias<'t>(
+    table: Cow<'t, Table>,
+    alias: Option<&TableAlias>,
+) -> SqawkResult<Cow<'t, Table>> {
+    let Some(alias) = alias else {
+        return Ok(table);
+    };
+    let mut table = table.into_owned();
+    table.set_name(&alias.name.value);
+    if !alias.columns.is_empty() {
+        table.rename_columns(
+            alias
+                .columns
+                .iter()
+                .map(|ident| ident.value.clone())
+                .collect(),
+        )?;
+    }
+    Ok(Cow::Owned(table))
+}
+
+/// Check whether an expression yields JSON text rather than a plain string
+///
+/// The results of JSON(), JSON_OBJECT(), JSON_ARRAY() and the `->` operator are
+/// JSON, and are embedded as JSON when passed to JSON_OBJECT or JSON_ARRAY.
+fn yields_json(expr: &Expr) -> bool {
+    match expr {
+        Expr::Nested(inner) => yields_json(inner),
+        Expr::JsonAccess {
+            operator: JsonOperator::Arrow,
+            ..
+        } => true,
+        Expr::Function(func) => func
+            .name
+            .0
+            .last()
+            .and_then(|ident| JsonFunction::from_name(&ident.value))
+            .map_or(false, |function| function.returns_json()),
+        _ => false,
+    }
+}
+
+/// Join the parts of a possibly qualified name with dots
+fn object_name_to_string(name: &ObjectName) -> String {
+    name.0
+        .iter()
+        .map(|ident| ident.value.clone())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Positional and named arguments of a table function call
+type TableFunctionArgs<'e> = (Vec<&'e Expr>, Vec<(&'e sqlparser::ast::Ident, &'e Expr)>);
+
+/// Separate the positional and named (`name => value`) arguments of a table function
+///
+/// # Arguments
+/// * `function_name` - The function name, for error messages
+/// * `args` - The function arguments
+///
+/// # Returns
+/// * The positional argument expressions and the named ones, in order
+/// * `Err` if an argument is a wildcard
+fn split_table_function_args<'e>(
+    function_name: &str,
+    args: &'e [sqlparser::ast::FunctionArg],
+) -> SqawkResult<TableFunctionArgs<'e>> {
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    for arg in args {
+        match arg {
+            sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(expr)) => {
+                positional.push(expr)
+            }
+            sqlparser::ast::FunctionArg::Named {
+                name,
+                arg: sqlparser::ast::FunctionArgExpr::Expr(expr),
+            } => named.push((name, expr)),
+            _ => {
+                return Err(SqawkError::InvalidSqlQuery(format!(
+                    "Invalid argument to {}: {}",
+                    function_name, arg
+                )))
+            }
+        }
+    }
+    Ok((positional, named))
+}
+
+/// Read a table function argument that names a table or column
+///
+/// The name may be given as an identifier (`tags`) or a string literal (`'tags'`).
+///
+/// # Arguments
+/// * `function_name` - The function name, for error messages
+/// * `what` - What the argument names (`table` or `column`), for error messages
+/// * `expr` - The argument expression
+///
+/// # Returns
+/// * The name as an identifier expression
+/// * `Err` if the argument is neither an identifier nor a string literal
+fn table_function_name_arg(function_name: &str, what: &str, expr: &Expr) -> SqawkResult<Expr> {
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => Ok(expr.clone()),
+        Expr::Value(SqlValue::SingleQuotedString(name)) => {
+            Ok(Expr::Identifier(sqlparser::ast::Ident::new(name.clone())))
+        }
+        _ => Err(SqawkError::InvalidSqlQuery(format!(
+            "{} expects an identifier or string literal for the {} name, got {}",
+            function_name, what, expr
+        ))),
+    }
+}
+
+/// Split a SELECT expression into the aggregate call and its FILTER (WHERE ...) condition
+fn split_aggregate_filter(expr: &Expr) -> (&Expr, Option<&Expr>) {
+    match expr {
+        Expr::AggregateExpressionWithFilter { expr, filter } => (expr, Some(filter)),
+        _ => (expr, None),
+    }
+}
 
 /// SQL statement executor
 pub struct SqlExecutor<'a> {
@@ -103,6 +472,22 @@ impl<'a> SqlExecutor<'a> {
         crate::vm::execute_vm(&sql, self.database, self.config.verbose())
     }
 
+    /// Register a user-defined scalar function with the executor's database
+    ///
+    /// # Arguments
+    /// * `function` - The function to make callable from SQL
+    pub fn register_function<F: ScalarFunction + 'static>(&mut self, function: F) {
+        self.database.register_function(function);
+    }
+
+    /// Register a user-defined aggregate function with the executor's database
+    ///
+    /// # Arguments
+    /// * `aggregate` - The aggregate function to make callable from SQL
+    pub fn register_aggregate<A: Aggregator + 'static>(&mut self, aggregate: A) {
+        self.database.register_aggregate(aggregate);
+    }
+
     /// Get the number of rows affected by the last executed statement
     pub fn get_affected_row_count(&self) -> SqawkResult<usize> {
         Ok(self.affected_row_count)
@@ -112,15 +497,13 @@ impl<'a> SqlExecutor<'a> {
     ///
     /// Returns Some(Table) for SELECT queries, None for other statements.
     pub fn execute(&mut self, sql: &str) -> SqawkResult<Option<Table>> {
-        // For CREATE TABLE statements with LOCATION, we need to use a dialect that
-        // properly supports the LOCATION clause - HiveDialect is made for this
-        let dialect = HiveDialect {}; // Hive dialect is specifically designed for LOCATION clauses
-
         if self.config.verbose() {
             println!("Executing SQL: {}", sql);
         }
 
-        let statements = Parser::parse_sql(&dialect, sql).map_err(SqawkError::SqlParseError)?;
+        // The sqawk dialect follows Hive's rules (including the LOCATION clause for
+        // CREATE TABLE) and adds operators such as DIV and the UNPIVOT clause
+        let statements = dialect::parse_sql(sql).map_err(SqawkError::SqlParseError)?;
 
         if statements.is_empty() {
             return Err(SqawkError::InvalidSqlQuery(
@@ -133,6 +516,8 @@ impl<'a> SqlExecutor<'a> {
         for statement in statements {
             // We've handled CREATE TABLE with LOCATION properly now, no need for extra debug logging here
 
+            // Function caches, such as compiled regex patterns, last for one statement
+            self.database.functions().clear_caches();
             result = self.execute_statement(statement)?;
         }
 
@@ -307,18 +692,27 @@ impl<'a> SqlExecutor<'a> {
     fn execute_query(&self, query: Query) -> SqawkResult<Option<Table>> {
         match *query.body {
             SetExpr::Select(ref select) => {
-                // Handle special case of SELECT without FROM (e.g., SELECT 1)
-                if select.from.is_empty() {
-                    return Err(SqawkError::InvalidSqlQuery(
-                        "SELECT query must have at least one table".to_string(),
-                    ));
-                }
-
-                // Process the FROM clause to get a table or join result
-                let source_table = self.process_from_clause(&select.from)?;
+                // SELECT without FROM (e.g., SELECT 1 + 1) evaluates against one empty row;
+                // otherwise process the FROM clause to get a table or join result
+                let source_table = if select.from.is_empty() {
+                    if select.projection.iter().any(|item| {
+                        matches!(
+                            item,
+                            SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(_, _)
+                        )
+                    }) {
+                        return Err(SqawkError::InvalidSqlQuery(
+                            "SELECT * requires a FROM clause".to_string(),
+                        ));
+                    }
+                    single_row_table()?
+                } else {
+                    self.process_from_clause(&select.from)?
+                };
 
-                // Check if the query contains any aggregate functions
-                let has_aggregates = self.contains_aggregate_functions(&select.projection);
+                // Queries with aggregate functions or a GROUP BY clause are aggregated
+                let has_aggregates = self.contains_aggregate_functions(&select.projection)
+                    || !select.group_by.is_empty();
 
                 // Process based on whether we have aggregates or not
                 if has_aggregates {
@@ -330,12 +724,65 @@ impl<'a> SqlExecutor<'a> {
                     self.execute_simple_query(source_table, select, &query)
                 }
             }
+            // A bare VALUES list is a table of its own
+            SetExpr::Values(ref values) => {
+                let mut table = self.values_table(values)?;
+                if !query.order_by.is_empty() {
+                    table = self.apply_order_by(table, &query.order_by, &[], None)?;
+                }
+                if query.limit.is_some() || query.offset.is_some() {
+                    table = self.apply_limit_offset(table, &query)?;
+                }
+                Ok(Some(table))
+            }
             _ => Err(SqawkError::UnsupportedSqlFeature(
                 "Only simple SELECT statements are supported".to_string(),
             )),
         }
     }
 
+    /// Build a table from a VALUES list
+    ///
+    /// Each row of the list becomes a table row. Columns are named `column1`,
+    /// `column2`, ... and typed by their values; an alias in FROM can rename them.
+    ///
+    /// # Arguments
+    /// * `values` - The VALUES list; expressions are evaluated without a source row
+    ///
+    /// # Returns
+    /// * The table holding the values, named `values`
+    /// * `Err` if the rows differ in length
+    fn values_table(&self, values: &sqlparser::ast::Values) -> SqawkResult<Table> {
+        let empty_source = single_row_table()?;
+        let width = values.rows.first().map_or(0, |row| row.len());
+        let mut rows = Vec::with_capacity(values.rows.len());
+        for exprs in &values.rows {
+            if exprs.len() != width {
+                return Err(SqawkError::InvalidSqlQuery(
+                    "All VALUES rows must have the same number of values".to_string(),
+                ));
+            }
+            let row = exprs
+                .iter()
+                .map(|expr| self.evaluate_expr_with_row(expr, &[], &empty_source))
+                .collect::<SqawkResult<Vec<_>>>()?;
+            rows.push(row);
+        }
+
+        let schema = (0..width)
+            .map(|idx| ColumnDefinition {
+                name: format!("column{}", idx + 1),
+                data_type: DataType::from_values(rows.iter().map(|row| &row[idx])),
+            })
+            .collect();
+
+        let mut table = Table::new_with_schema("values", schema, None, None);
+        for row in rows {
+            table.add_row(row)?;
+        }
+        Ok(table)
+    }
+
     /// Executes a SQL query containing aggregate functions
     ///
     /// This function implements specialized processing for SQL queries that use aggregate
@@ -380,7 +827,7 @@ impl<'a> SqlExecutor<'a> {
             if self.config.verbose() {
                 eprintln!("Applying GROUP BY");
             }
-            SqlExecutor::apply_grouped_aggregate_functions(
+            self.apply_grouped_aggregate_functions(
                 &select.projection,
                 &filtered_table,
                 &select.group_by,
@@ -400,7 +847,8 @@ impl<'a> SqlExecutor<'a> {
         };
 
         // Apply post-processing steps (DISTINCT, ORDER BY, LIMIT, OFFSET)
-        let final_result = self.apply_post_processing_steps(result_after_having, select, query)?;
+        let final_result =
+            self.apply_post_processing_steps(result_after_having, select, query, None)?;
         Ok(Some(final_result))
     }
 
@@ -435,17 +883,26 @@ impl<'a> SqlExecutor<'a> {
         select: &Select,
         query: &Query,
     ) -> SqawkResult<Option<Table>> {
-        // For non-aggregate queries, use the normal column resolution
-        let column_specs = self.resolve_select_items(&select.projection, &source_table)?;
+        // For non-aggregate queries that only reference columns, use the normal
+        // column resolution; anything else is evaluated per row after filtering
+        let column_specs = if self.select_items_are_column_refs(&select.projection) {
+            Some(self.resolve_select_items(&select.projection, &source_table)?)
+        } else {
+            None
+        };
 
         // Apply WHERE clause before projection
         let filtered_table = self.apply_where_clause_if_present(source_table, &select.selection)?;
 
         // Apply projection to get only the requested columns with aliases
-        let result_table = filtered_table.project_with_aliases(&column_specs)?;
+        let result_table = match column_specs {
+            Some(specs) => filtered_table.project_with_aliases(&specs)?,
+            None => self.project_select_expressions(&select.projection, &filtered_table)?,
+        };
 
         // Apply post-processing steps (DISTINCT, ORDER BY, LIMIT, OFFSET)
-        let final_result = self.apply_post_processing_steps(result_table, select, query)?;
+        let final_result =
+            self.apply_post_processing_steps(result_table, select, query, Some(&filtered_table))?;
         Ok(Some(final_result))
     }
 
@@ -503,6 +960,8 @@ impl<'a> SqlExecutor<'a> {
     /// * `table` - The working table after core query processing, ready for post-processing
     /// * `select` - The SELECT statement containing DISTINCT clause information
     /// * `query` - The full Query object with ORDER BY, LIMIT, and OFFSET clauses
+    /// * `source` - The table the result was projected from, row for row, so ORDER BY
+    ///   can use columns that were not selected (None for aggregate queries)
     ///
     /// # Returns
     /// * `SqawkResult<Table>` - The final query result table after all post-processing
@@ -511,6 +970,7 @@ impl<'a> SqlExecutor<'a> {
         mut table: Table,
         select: &Select,
         query: &Query,
+        source: Option<&Table>,
     ) -> SqawkResult<Table> {
         // Apply DISTINCT if present
         if select.distinct.is_some() {
@@ -519,13 +979,15 @@ impl<'a> SqlExecutor<'a> {
             }
             table = table.distinct()?;
         }
+        // After DISTINCT, result rows no longer correspond to source rows
+        let source = source.filter(|_| select.distinct.is_none());
 
         // Apply ORDER BY if present
         if !query.order_by.is_empty() {
             if self.config.verbose() {
                 eprintln!("Applying ORDER BY");
             }
-            table = self.apply_order_by(table, &query.order_by)?;
+            table = self.apply_order_by(table, &query.order_by, &select.projection, source)?;
         }
 
         // Apply LIMIT and OFFSET if present
@@ -604,70 +1066,46 @@ impl<'a> SqlExecutor<'a> {
 
     /// Apply an ORDER BY clause to sort the result table
     ///
+    /// Each ORDER BY key is resolved, in order of preference, as:
+    /// 1. An integer literal: the 1-based position of a result column (`ORDER BY 2 DESC`)
+    /// 2. A column of the result table, which includes SELECT aliases
+    /// 3. A SELECT item written the same way, as in `ORDER BY COUNT(*)`
+    /// 4. Any other expression, evaluated for each result row, or for each row of
+    ///    `source` when it refers to columns that were not selected
+    ///
+    /// Keys that are not result columns are computed into hidden columns, which are
+    /// removed again once the rows are sorted.
+    ///
     /// # Arguments
     /// * `table` - The table to sort
     /// * `order_by` - The ORDER BY expressions from the SQL query
+    /// * `items` - The SELECT items that produced the table
+    /// * `source` - The table the result was projected from, row for row, if any
     ///
     /// # Returns
     /// * A new sorted table
+    /// * `Err` if a position is out of range or a key cannot be evaluated
     fn apply_order_by(
         &self,
         table: Table,
         order_by: &[sqlparser::ast::OrderByExpr],
+        items: &[SelectItem],
+        source: Option<&Table>,
     ) -> SqawkResult<Table> {
-        // Convert ORDER BY expressions to column indices and sort directions
-        let mut sort_columns = Vec::new();
+        let column_count = table.column_count();
+        let mut hidden_keys: Vec<Vec<Value>> = Vec::new();
+        let mut sort_keys = Vec::with_capacity(order_by.len());
 
         for order_expr in order_by {
-            // Extract the column index for this ORDER BY expression
-            let col_idx = match &order_expr.expr {
-                Expr::Identifier(ident) => {
-                    // Try to find the column by exact name first
-                    match table.column_index(&ident.value) {
-                        Some(idx) => idx,
-                        None => {
-                            // Try with qualified names (table.column) lookup
-                            let mut found = false;
-                            let mut idx = 0;
-                            for (i, col) in table.columns().iter().enumerate() {
-                                if col.ends_with(&format!(".{}", ident.value)) {
-                                    found = true;
-                                    idx = i;
-                                    break;
-                                }
-                            }
-
-                            if found {
-                                idx
-                            } else {
-                                return Err(SqawkError::ColumnNotFound(ident.value.clone()));
-                            }
-                        }
-                    }
-                }
-                // Handle qualified column reference: table.column
-                Expr::CompoundIdentifier(idents) => {
-                    if idents.len() != 2 {
-                        return Err(SqawkError::UnsupportedSqlFeature(
-                            "Only simple qualified column references (table.column) are supported in ORDER BY".to_string(),
-                        ));
-                    }
-
-                    let table_name = &idents[0].value;
-                    let column_name = &idents[1].value;
-                    let qualified_name = format!("{}.{}", table_name, column_name);
-
-                    match table.column_index(&qualified_name) {
-                        Some(idx) => idx,
-                        None => {
-                            return Err(SqawkError::ColumnNotFound(qualified_name));
-                        }
-                    }
-                }
-                _ => {
-                    return Err(SqawkError::UnsupportedSqlFeature(
-                        "Only simple column references are supported in ORDER BY".to_string(),
-                    ));
+            let column = match self.resolve_order_by_column(&order_expr.expr, &table, items)? {
+                Some(column) => column,
+                None => {
+                    hidden_keys.push(self.evaluate_order_by_key(
+                        &order_expr.expr,
+                        &table,
+                        source,
+                    )?);
+                    column_count + hidden_keys.len() - 1
                 }
             };
 
@@ -679,11 +1117,140 @@ impl<'a> SqlExecutor<'a> {
                 Some(false) => SortDirection::Descending,
             };
 
-            sort_columns.push((col_idx, direction));
+            // NULLS FIRST/LAST overrides the default placement of NULLs
+            let mut sort_key = SortKey::new(column, direction);
+            if let Some(nulls_first) = order_expr.nulls_first {
+                sort_key = sort_key.with_nulls(if nulls_first {
+                    NullOrdering::First
+                } else {
+                    NullOrdering::Last
+                });
+            }
+            sort_keys.push(sort_key);
+        }
+
+        if hidden_keys.is_empty() {
+            return table.sort(sort_keys);
+        }
+
+        // Sort the rows extended with the hidden key columns, then drop those columns
+        let mut columns = table.columns();
+        columns.extend((0..hidden_keys.len()).map(|i| format!("__order_by_{}", i)));
+        let mut extended = Table::new(table.name(), columns, None);
+        for (row_idx, row) in table.rows().iter().enumerate() {
+            let mut extended_row = row.clone();
+            extended_row.extend(hidden_keys.iter().map(|values| values[row_idx].clone()));
+            extended.add_row(extended_row)?;
+        }
+
+        let mut result = Table::new(table.name(), table.columns(), table.file_path().cloned());
+        for row in extended.sort(sort_keys)?.rows() {
+            result.add_row(row[..column_count].to_vec())?;
+        }
+        Ok(result)
+    }
+
+    /// Resolve an ORDER BY expression to a column of the result table
+    ///
+    /// # Arguments
+    /// * `expr` - The ORDER BY expression
+    /// * `table` - The result table
+    /// * `items` - The SELECT items that produced the table
+    ///
+    /// # Returns
+    /// * `Ok(Some(index))` for a position, result column or SELECT item
+    /// * `Ok(None)` for an expression that has to be evaluated
+    /// * `Err` if a position is out of range
+    fn resolve_order_by_column(
+        &self,
+        expr: &Expr,
+        table: &Table,
+        items: &[SelectItem],
+    ) -> SqawkResult<Option<usize>> {
+        match expr {
+            Expr::Value(SqlValue::Number(n, _)) => {
+                let position = n
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|position| (1..=table.column_count()).contains(position))
+                    .ok_or_else(|| {
+                        SqawkError::InvalidSqlQuery(format!(
+                            "ORDER BY position {} is not in the select list",
+                            n
+                        ))
+                    })?;
+                return Ok(Some(position - 1));
+            }
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+                if let Some(idx) = self.column_ref_index(expr, table) {
+                    return Ok(Some(idx));
+                }
+            }
+            _ => {}
+        }
+
+        // SELECT items map to result columns one to one unless a wildcard was expanded
+        let has_wildcard = items.iter().any(|item| {
+            matches!(
+                item,
+                SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..)
+            )
+        });
+        if has_wildcard {
+            return Ok(None);
         }
+        Ok(items
+            .iter()
+            .position(|item| match item {
+                SelectItem::UnnamedExpr(item_expr)
+                | SelectItem::ExprWithAlias {
+                    expr: item_expr, ..
+                } => item_expr == expr,
+                _ => false,
+            })
+            .filter(|&position| position < table.column_count()))
+    }
+
+    /// Evaluate an ORDER BY expression for every row of the result table
+    ///
+    /// The expression is evaluated against the result rows when it only refers to
+    /// result columns, and otherwise against the corresponding rows of `source`.
+    ///
+    /// # Arguments
+    /// * `expr` - The ORDER BY expression
+    /// * `table` - The result table
+    /// * `source` - The table the result was projected from, row for row, if any
+    ///
+    /// # Returns
+    /// * The sort key value of each result row
+    /// * `Err` if the expression refers to an unknown column or fails to evaluate
+    fn evaluate_order_by_key(
+        &self,
+        expr: &Expr,
+        table: &Table,
+        source: Option<&Table>,
+    ) -> SqawkResult<Vec<Value>> {
+        let first_row = match table.rows().first() {
+            Some(row) => row,
+            None => return Ok(Vec::new()),
+        };
 
-        // Sort the table using the calculated columns and directions
-        table.sort(sort_columns)
+        match self.evaluate_expr_with_row(expr, first_row, table) {
+            Ok(_) => table
+                .rows()
+                .iter()
+                .map(|row| self.evaluate_expr_with_row(expr, row, table))
+                .collect(),
+            Err(SqawkError::ColumnNotFound(name)) => match source {
+                Some(source) => source
+                    .rows()
+                    .iter()
+                    .map(|row| self.evaluate_expr_with_row(expr, row, source))
+                    .collect(),
+                None => Err(SqawkError::ColumnNotFound(name)),
+            },
+            Err(err) => Err(err),
+        }
     }
 
     /// Process the FROM clause of a SQL query, including all types of table joins
@@ -713,13 +1280,15 @@ impl<'a> SqlExecutor<'a> {
     fn process_from_clause(&self, from: &[TableWithJoins]) -> SqawkResult<Table> {
         // Start with the first table in the FROM clause
         let first_table_with_joins = &from[0];
-        let first_table_name = self.get_table_name(first_table_with_joins)?;
-        let mut result_table = self.file_handler.get_table(&first_table_name)?.clone();
+        let (first_table, joins) = self.apply_unpivot_clauses(
+            self.resolve_table_factor(&first_table_with_joins.relation)?,
+            &first_table_with_joins.joins,
+        )?;
+        let mut result_table = first_table.into_owned();
 
         // Handle any joins in the first TableWithJoins
-        if !first_table_with_joins.joins.is_empty() {
-            result_table =
-                self.process_table_joins(&result_table, &first_table_with_joins.joins)?;
+        if !joins.is_empty() {
+            result_table = self.process_table_joins(&result_table, joins)?;
         }
 
         // If there are multiple tables in the FROM clause, join them
@@ -729,16 +1298,17 @@ impl<'a> SqlExecutor<'a> {
                 eprintln!("Processing multiple tables in FROM clause as CROSS JOINs");
             }
             for table_with_joins in &from[1..] {
-                let right_table_name = self.get_table_name(table_with_joins)?;
-                let right_table = self.file_handler.get_table(&right_table_name)?;
+                let (right_table, joins) = self.apply_unpivot_clauses(
+                    self.resolve_table_factor(&table_with_joins.relation)?,
+                    &table_with_joins.joins,
+                )?;
 
                 // Cross join with the current result table
-                result_table = result_table.cross_join(right_table)?;
+                result_table = result_table.cross_join(&right_table)?;
 
                 // Process any joins on this table
-                if !table_with_joins.joins.is_empty() {
-                    result_table =
-                        self.process_table_joins(&result_table, &table_with_joins.joins)?;
+                if !joins.is_empty() {
+                    result_table = self.process_table_joins(&result_table, joins)?;
                 }
             }
         }
@@ -746,72 +1316,632 @@ impl<'a> SqlExecutor<'a> {
         Ok(result_table)
     }
 
-    /// Process joins for a table
-    ///
-    /// This function processes a list of explicit JOIN clauses for a table.
-    /// It iterates through each join specification, resolves the right table,
-    /// and applies the appropriate join operation based on the join type.
+    /// Resolve a table factor from a FROM or JOIN clause to a table
     ///
-    /// Supported join types:
-    /// - CROSS JOIN: Returns all combinations of rows from both tables
-    /// - INNER JOIN with ON condition: Returns only rows that match the join condition
+    /// Besides loaded tables, this handles PIVOT, derived tables and table function
+    /// calls such as `generate_series(...)`, which produce new tables.
     ///
     /// # Arguments
-    /// * `left_table` - The left table for the join operations
-    /// * `joins` - Array of SQL join specifications to process
+    /// * `factor` - The table factor to resolve
     ///
     /// # Returns
-    /// * A new table resulting from applying all join operations sequentially
-    ///   Process explicit JOIN clauses in a SQL statement
+    /// * The loaded table, borrowed, or the generated table
+    /// * `Err` if the table doesn't exist or the factor is unsupported
+    fn resolve_table_factor(&self, factor: &TableFactor) -> SqawkResult<Cow<'_, Table>> {
+        let (table, alias) = match factor {
+            TableFactor::Table {
+                name,
+                args: None,
+                alias,
+                ..
+            } => (
+                Cow::Borrowed(self.file_handler.get_table(&object_name_to_string(name))?),
+                alias.as_ref(),
+            ),
+            TableFactor::Table {
+                name,
+                args: Some(args),
+                alias,
+                ..
+            } => (
+                Cow::Owned(self.execute_table_function(name, args)?),
+                alias.as_ref(),
+            ),
+            TableFactor::Pivot {
+                name,
+                aggregate_function,
+                value_column,
+                pivot_values,
+                pivot_alias,
+                ..
+            } => {
+                let source_name = object_name_to_string(name);
+                let source = self.file_handler.get_table(&source_name)?;
+                let result_name = pivot_alias
+                    .as_ref()
+                    .map_or(source_name, |alias| alias.name.value.clone());
+                let table = self.pivot_table(
+                    source,
+                    aggregate_function,
+                    value_column,
+                    pivot_values,
+                    &result_name,
+                )?;
+                (Cow::Owned(table), None)
+            }
+            TableFactor::Derived {
+                lateral: false,
+                subquery,
+                alias,
+            } => (
+                Cow::Owned(self.execute_derived_table(subquery)?),
+                alias.as_ref(),
+            ),
+            _ => {
+                return Err(SqawkError::UnsupportedSqlFeature(
+                    "Only simple table references are supported".to_string(),
+                ))
+            }
+        };
+
+        apply_table_alias(table, alias)
+    }
+
+    /// Apply the UNPIVOT clauses that follow a table factor
     ///
-    /// This function handles the various types of JOIN operations in a SQL statement,
-    /// including INNER JOIN with ON conditions and implicit CROSS JOINs.
+    /// The parser reads `t UNPIVOT (...)` as `t CROSS APPLY UNPIVOT(...)` (see
+    /// [`dialect::parse_sql`]), so each such join reshapes the table before it
+    /// rather than being joined with it.
     ///
     /// # Arguments
-    /// * `left_table` - The left (base) table for the join operations
-    /// * `joins` - Array of JOIN clauses to process sequentially
+    /// * `table` - The resolved table factor
+    /// * `joins` - The joins that follow the factor
     ///
     /// # Returns
-    /// * A new table containing the results of all join operations
-    /// * `Err` if any JOIN syntax is unsupported or tables can't be found
-    ///
-    /// # Implementation Details
-    /// Joins are processed sequentially, with each join building on the result
-    /// of the previous one. The implementation follows these steps:
-    /// 1. Clone the left table as the starting point
-    /// 2. For each join specification:
-    ///    a. Extract the right table name
-    ///    b. Retrieve the right table from the file handler
-    ///    c. Apply the appropriate join algorithm based on the join type
-    fn process_table_joins(&self, left_table: &Table, joins: &[SqlJoin]) -> SqawkResult<Table> {
-        // Start with a clone of the left table as our working result
-        let mut result_table = left_table.clone();
+    /// * The table with the clauses applied, and the joins after them
+    /// * `Err` if a clause names a column that doesn't exist
+    fn apply_unpivot_clauses<'t, 'j>(
+        &self,
+        mut table: Cow<'t, Table>,
+        mut joins: &'j [SqlJoin],
+    ) -> SqawkResult<(Cow<'t, Table>, &'j [SqlJoin])> {
+        while let Some((args, alias)) = joins.first().and_then(dialect::unpivot_clause) {
+            let unpivoted = self.unpivot_table(&table, args)?;
+            table = apply_table_alias(Cow::Owned(unpivoted), alias)?;
+            joins = &joins[1..];
+        }
+        Ok((table, joins))
+    }
 
-        // Process each join clause sequentially
-        for join in joins {
-            // Log join type in verbose mode for debugging
-            if self.config.verbose() {
-                eprintln!("Join type: {:?}", join.join_operator);
-            }
+    /// Execute a subquery in FROM (a derived table)
+    ///
+    /// Columns qualified by the subquery's own tables (e.g. `e.name` from a join) are
+    /// renamed to their unqualified name where that is unambiguous, so the outer
+    /// query can refer to them through the derived table's alias.
+    ///
+    /// # Arguments
+    /// * `subquery` - The query in parentheses
+    ///
+    /// # Returns
+    /// * The subquery result, named `subquery` until an alias is applied
+    /// * `Err` if the subquery fails
+    fn execute_derived_table(&self, subquery: &Query) -> SqawkResult<Table> {
+        let mut table = self.execute_query(subquery.clone())?.ok_or_else(|| {
+            SqawkError::InvalidSqlQuery("Subquery in FROM returned no table".to_string())
+        })?;
+        table.set_name("subquery");
+
+        let columns = table.columns();
+        let unqualified: Vec<&str> = columns
+            .iter()
+            .map(|name| unqualified_column_name(name).unwrap_or(name))
+            .collect();
+        let names = columns
+            .iter()
+            .zip(&unqualified)
+            .map(|(name, short)| {
+                if unqualified.iter().filter(|other| *other == short).count() == 1 {
+                    short.to_string()
+                } else {
+                    name.clone()
+                }
+            })
+            .collect();
+        table.rename_columns(names)?;
+        Ok(table)
+    }
 
-            // Extract the right table name from the join specification
-            // This handles simple table references like "TableName" but not complex expressions
-            let right_table_name = match &join.relation {
-                TableFactor::Table { name, .. } => name
-                    .0
-                    .iter()
-                    .map(|i| i.value.clone())
-                    .collect::<Vec<_>>()
-                    .join("."),
+    /// Execute a table function from the FROM clause
+    ///
+    /// # Arguments
+    /// * `name` - The function name
+    /// * `args` - The function arguments
+    ///
+    /// # Returns
+    /// * The table the function produces
+    /// * `Err` if the function is unknown or its arguments are invalid
+    fn execute_table_function(
+        &self,
+        name: &ObjectName,
+        args: &[sqlparser::ast::FunctionArg],
+    ) -> SqawkResult<Table> {
+        let function_name = object_name_to_string(name);
+        match function_name.to_uppercase().as_str() {
+            "GENERATE_SERIES" => self.generate_series(args),
+            "SPLIT_TO_ROWS" => self.split_to_rows(args),
+            "READ_CSV" => self.read_csv(args),
+            _ => Err(SqawkError::UnsupportedSqlFeature(format!(
+                "Unknown table function: {}",
+                function_name
+            ))),
+        }
+    }
+
+    /// Generate a column of numbers (`generate_series(start, stop[, step])`)
+    ///
+    /// The series runs from `start` to `stop` inclusive. The step defaults to 1 and
+    /// may be negative; the series is empty if it moves away from `stop`. Integer
+    /// arguments produce an INTEGER column, any float argument a REAL column.
+    ///
+    /// # Arguments
+    /// * `args` - The constant start, stop and optional step
+    ///
+    /// # Returns
+    /// * A table with a single `generate_series` column
+    /// * `Err` if the arguments are not numbers or the step is zero
+    fn generate_series(&self, args: &[sqlparser::ast::FunctionArg]) -> SqawkResult<Table> {
+        let (exprs, named) = split_table_function_args("generate_series", args)?;
+        if !named.is_empty() || !(2..=3).contains(&exprs.len()) {
+            return Err(SqawkError::InvalidSqlQuery(
+                "generate_series expects (start, stop[, step])".to_string(),
+            ));
+        }
+        let mut bounds = exprs
+            .iter()
+            .map(|expr| self.evaluate_expr(expr))
+            .collect::<SqawkResult<Vec<_>>>()?;
+        if bounds.len() == 2 {
+            bounds.push(Value::Integer(1));
+        }
+
+        let values: Vec<Value> = match bounds.as_slice() {
+            [Value::Integer(start), Value::Integer(stop), Value::Integer(step)] => {
+                if *step == 0 {
+                    return Err(SqawkError::InvalidSqlQuery(
+                        "generate_series step must not be zero".to_string(),
+                    ));
+                }
+                // Count the values first, in i128 so that no bounds can overflow
+                let span = i128::from(*stop) - i128::from(*start);
+                let count = if span != 0 && (span > 0) != (*step > 0) {
+                    0
+                } else {
+                    span / i128::from(*step) + 1
+                };
+                check_series_length(count as f64)?;
+                (0..count)
+                    .map(|i| Value::Integer((i128::from(*start) + i * i128::from(*step)) as i64))
+                    .collect()
+            }
+            [start, stop, step] => {
+                let as_float = |value: &Value| match value {
+                    Value::Integer(i) => Ok(*i as f64),
+                    Value::Float(f) if f.is_finite() => Ok(*f),
+                    _ => Err(SqawkError::TypeError(format!(
+                        "generate_series expects numeric arguments, got {}",
+                        value
+                    ))),
+                };
+                let (start, stop, step) = (as_float(start)?, as_float(stop)?, as_float(step)?);
+                if step == 0.0 {
+                    return Err(SqawkError::InvalidSqlQuery(
+                        "generate_series step must not be zero".to_string(),
+                    ));
+                }
+                check_series_length(((stop - start) / step).floor() + 1.0)?;
+                // Compute each value from the start to avoid accumulating rounding errors
+                (0u64..)
+                    .map(|i| start + step * i as f64)
+                    .take_while(|value| {
+                        if step > 0.0 {
+                            *value <= stop
+                        } else {
+                            *value >= stop
+                        }
+                    })
+                    .map(Value::Float)
+                    .collect()
+            }
+            _ => unreachable!("generate_series bounds always has three values"),
+        };
+
+        let data_type = DataType::from_values(&values);
+        let schema = vec![ColumnDefinition {
+            name: "generate_series".to_string(),
+            data_type,
+        }];
+        let mut result = Table::new_with_schema("generate_series", schema, None, None);
+        for value in values {
+            result.add_row(vec![value])?;
+        }
+        Ok(result)
+    }
+
+    /// Split a delimited column into rows (`split_to_rows(table, column, delimiter)`)
+    ///
+    /// See [`Table::split_column`].
+    ///
+    /// # Arguments
+    /// * `args` - The source table, the column to split and the constant delimiter
+    ///
+    /// # Returns
+    /// * The table with one row per piece
+    /// * `Err` if the table or column doesn't exist or the delimiter is invalid
+    fn split_to_rows(&self, args: &[sqlparser::ast::FunctionArg]) -> SqawkResult<Table> {
+        let (exprs, named) = split_table_function_args("split_to_rows", args)?;
+        let (table_expr, column_expr, delimiter_expr) = match exprs.as_slice() {
+            [table, column, delimiter] if named.is_empty() => (table, column, delimiter),
+            _ => {
+                return Err(SqawkError::InvalidSqlQuery(
+                    "split_to_rows expects (table, column, delimiter)".to_string(),
+                ))
+            }
+        };
+
+        let table_name = table_function_name_arg("split_to_rows", "table", table_expr)?;
+        let column_expr = table_function_name_arg("split_to_rows", "column", column_expr)?;
+        let source = self.file_handler.get_table(&table_name.to_string())?;
+        let column = self.get_column_ref_index(&column_expr, source)?;
+        let delimiter = match self.evaluate_expr(delimiter_expr)? {
+            Value::String(delimiter) => delimiter,
+            other => {
+                return Err(SqawkError::TypeError(format!(
+                    "split_to_rows delimiter must be a string, got {}",
+                    other
+                )))
+            }
+        };
+
+        source.split_column(column, &delimiter)
+    }
+
+    /// Read a file that was not given on the command line (`read_csv('path')`)
+    ///
+    /// The file is loaded like a command-line file but is not registered as a table,
+    /// so it cannot be modified or written back. A `delim => 'x'` argument reads it
+    /// with that delimiter regardless of its extension.
+    ///
+    /// # Arguments
+    /// * `args` - The constant file path and optional named `delim` argument
+    ///
+    /// # Returns
+    /// * The table loaded from the file, named after it
+    /// * `Err` if the arguments are invalid or the file can't be read
+    fn read_csv(&self, args: &[sqlparser::ast::FunctionArg]) -> SqawkResult<Table> {
+        let (exprs, named) = split_table_function_args("read_csv", args)?;
+        let path = match exprs.as_slice() {
+            [path] => self.evaluate_expr(path)?,
+            _ => {
+                return Err(SqawkError::InvalidSqlQuery(
+                    "read_csv expects a file path and optional delim => 'x'".to_string(),
+                ))
+            }
+        };
+
+        let mut delimiter = None;
+        for (name, expr) in named {
+            match (
+                name.value.to_lowercase().as_str(),
+                self.evaluate_expr(expr)?,
+            ) {
+                ("delim" | "delimiter" | "sep", Value::String(value)) => delimiter = Some(value),
+                (option, value) => {
+                    return Err(SqawkError::InvalidSqlQuery(format!(
+                        "Invalid read_csv option: {} => {}",
+                        option, value
+                    )))
+                }
+            }
+        }
+
+        match path {
+            Value::String(path) => self.file_handler.read_file(&path, delimiter.as_deref()),
+            other => Err(SqawkError::TypeError(format!(
+                "read_csv path must be a string, got {}",
+                other
+            ))),
+        }
+    }
+
+    /// Unpivot a table: turn columns into rows (UNPIVOT)
+    ///
+    /// `wide UNPIVOT (amount FOR month IN (jan, feb))` reaches here with the
+    /// arguments `amount, month, jan, feb`; see [`Table::unpivot`].
+    ///
+    /// # Arguments
+    /// * `source` - The table before the UNPIVOT clause
+    /// * `args` - The value column name, the name column name and the columns to
+    ///   unpivot
+    ///
+    /// # Returns
+    /// * The unpivoted table
+    /// * `Err` if a column doesn't exist
+    fn unpivot_table(
+        &self,
+        source: &Table,
+        args: &[sqlparser::ast::FunctionArg],
+    ) -> SqawkResult<Table> {
+        let mut exprs = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg {
+                sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                    expr @ (Expr::Identifier(_) | Expr::CompoundIdentifier(_)),
+                )) => exprs.push(expr),
                 _ => {
-                    return Err(SqawkError::UnsupportedSqlFeature(
-                        "Only simple table references are supported in joins".to_string(),
-                    ))
+                    return Err(SqawkError::InvalidSqlQuery(format!(
+                        "UNPIVOT expects column names, got {}",
+                        arg
+                    )))
+                }
+            }
+        }
+        if exprs.len() < 3 {
+            return Err(SqawkError::InvalidSqlQuery(
+                "UNPIVOT requires a value column, a name column and at least one column to unpivot"
+                    .to_string(),
+            ));
+        }
+
+        let columns = exprs[2..]
+            .iter()
+            .map(|expr| self.get_column_ref_index(expr, source))
+            .collect::<SqawkResult<Vec<_>>>()?;
+
+        source.unpivot(&exprs[0].to_string(), &exprs[1].to_string(), &columns)
+    }
+
+    /// Pivot a table: turn the values of one column into columns (PIVOT)
+    ///
+    /// `PIVOT (SUM(amount) FOR month IN ('Jan', 'Feb'))` groups the rows by every
+    /// column except `month` and the columns the aggregate reads, and adds one column
+    /// per listed value holding the aggregate over the group's rows with that value.
+    /// Rows with other values still form groups but are not aggregated.
+    ///
+    /// # Arguments
+    /// * `source` - The table to pivot
+    /// * `aggregate_function` - The aggregate call computing each cell
+    /// * `value_column` - The column whose values become columns
+    /// * `pivot_values` - The values that become columns, in order
+    /// * `name` - The name of the resulting table
+    ///
+    /// # Returns
+    /// * The pivoted table, one row per group
+    /// * `Err` if the function is not an aggregate or a column doesn't exist
+    fn pivot_table(
+        &self,
+        source: &Table,
+        aggregate_function: &Expr,
+        value_column: &[sqlparser::ast::Ident],
+        pivot_values: &[SqlValue],
+        name: &str,
+    ) -> SqawkResult<Table> {
+        let func = match aggregate_function {
+            Expr::Function(func) => func,
+            _ => {
+                return Err(SqawkError::InvalidSqlQuery(format!(
+                    "PIVOT requires an aggregate function, got {}",
+                    aggregate_function
+                )))
+            }
+        };
+        let func_name = object_name_to_string(&func.name);
+        let aggregator = self
+            .database
+            .functions()
+            .get_aggregate(&func_name)
+            .ok_or_else(|| {
+                SqawkError::InvalidSqlQuery(format!(
+                    "PIVOT requires an aggregate function, got {}",
+                    func_name
+                ))
+            })?;
+        let call = self.resolve_aggregate_call(aggregator, func, None, source)?;
+
+        let pivot_expr = match value_column {
+            [ident] => Expr::Identifier(ident.clone()),
+            _ => Expr::CompoundIdentifier(value_column.to_vec()),
+        };
+        let pivot_idx = self.get_column_ref_index(&pivot_expr, source)?;
+        let values = pivot_values
+            .iter()
+            .map(|value| self.evaluate_sql_value(value))
+            .collect::<SqawkResult<Vec<_>>>()?;
+
+        // Group by every column the pivot does not consume
+        let mut consumed = vec![pivot_idx];
+        for arg in &func.args {
+            if let sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                expr,
+            )) = arg
+            {
+                self.collect_column_refs(expr, source, &mut consumed);
+            }
+        }
+        for order in &func.order_by {
+            self.collect_column_refs(&order.expr, source, &mut consumed);
+        }
+        let group_indices: Vec<usize> = (0..source.column_count())
+            .filter(|idx| !consumed.contains(idx))
+            .collect();
+
+        // One accumulator per pivot value in each group, in order of first appearance
+        let mut group_positions: std::collections::HashMap<Vec<Value>, usize> =
+            std::collections::HashMap::new();
+        let mut groups: Vec<GroupState> = Vec::new();
+        for row in source.rows() {
+            let group_key: Vec<Value> = group_indices.iter().map(|&i| row[i].clone()).collect();
+            let position = match group_positions.get(&group_key) {
+                Some(&position) => position,
+                None => {
+                    let accumulators = values
+                        .iter()
+                        .map(|_| call.create_accumulator())
+                        .collect::<SqawkResult<Vec<_>>>()?;
+                    groups.push((group_key.clone(), accumulators));
+                    group_positions.insert(group_key, groups.len() - 1);
+                    groups.len() - 1
                 }
             };
 
-            // Fetch the right table from the loaded tables collection
-            let right_table = self.file_handler.get_table(&right_table_name)?;
+            if let Some(value_idx) = values.iter().position(|value| *value == row[pivot_idx]) {
+                self.update_aggregate(&call, &mut groups[position].1[value_idx], row, source)?;
+            }
+        }
+
+        let mut result_rows = Vec::with_capacity(groups.len());
+        for (mut result_row, accumulators) in groups {
+            for accumulator in accumulators {
+                result_row.push(accumulator.finalize(&call)?);
+            }
+            result_rows.push(result_row);
+        }
+
+        // Grouping columns keep their metadata; pivot columns are typed by their values
+        let mut schema: Vec<ColumnDefinition> = group_indices
+            .iter()
+            .map(|&idx| {
+                let column = &source.column_metadata()[idx];
+                ColumnDefinition {
+                    name: column.name.clone(),
+                    data_type: column.data_type,
+                }
+            })
+            .collect();
+        for (i, value) in values.iter().enumerate() {
+            let column_idx = group_indices.len() + i;
+            schema.push(ColumnDefinition {
+                name: value.to_string(),
+                data_type: DataType::from_values(result_rows.iter().map(|row| &row[column_idx])),
+            });
+        }
+
+        let mut result = Table::new_with_schema(name, schema, None, None);
+        for row in result_rows {
+            result.add_row(row)?;
+        }
+        Ok(result)
+    }
+
+    /// Collect the indices of the table columns an expression refers to
+    ///
+    /// # Arguments
+    /// * `expr` - The expression to inspect
+    /// * `table` - The table to resolve column references against
+    /// * `columns` - Receives the column indices not already present
+    fn collect_column_refs(&self, expr: &Expr, table: &Table, columns: &mut Vec<usize>) {
+        match expr {
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+                if let Some(idx) = self.column_ref_index(expr, table) {
+                    if !columns.contains(&idx) {
+                        columns.push(idx);
+                    }
+                }
+            }
+            Expr::BinaryOp { left, right, .. } => {
+                self.collect_column_refs(left, table, columns);
+                self.collect_column_refs(right, table, columns);
+            }
+            Expr::UnaryOp { expr: inner, .. }
+            | Expr::Nested(inner)
+            | Expr::IsNull(inner)
+            | Expr::IsNotNull(inner)
+            | Expr::Cast { expr: inner, .. }
+            | Expr::TryCast { expr: inner, .. }
+            | Expr::SafeCast { expr: inner, .. } => self.collect_column_refs(inner, table, columns),
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                for inner in operand
+                    .iter()
+                    .chain(else_result.iter())
+                    .map(|inner| inner.as_ref())
+                    .chain(conditions)
+                    .chain(results)
+                {
+                    self.collect_column_refs(inner, table, columns);
+                }
+            }
+            Expr::Function(func) => {
+                for arg in &func.args {
+                    if let sqlparser::ast::FunctionArg::Unnamed(
+                        sqlparser::ast::FunctionArgExpr::Expr(inner),
+                    ) = arg
+                    {
+                        self.collect_column_refs(inner, table, columns);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Process joins for a table
+    ///
+    /// This function processes a list of explicit JOIN clauses for a table.
+    /// It iterates through each join specification, resolves the right table,
+    /// and applies the appropriate join operation based on the join type.
+    ///
+    /// Supported join types:
+    /// - CROSS JOIN: Returns all combinations of rows from both tables
+    /// - INNER JOIN with ON condition: Returns only rows that match the join condition
+    ///
+    /// # Arguments
+    /// * `left_table` - The left table for the join operations
+    /// * `joins` - Array of SQL join specifications to process
+    ///
+    /// # Returns
+    /// * A new table resulting from applying all join operations sequentially
+    ///   Process explicit JOIN clauses in a SQL statement
+    ///
+    /// This function handles the various types of JOIN operations in a SQL statement,
+    /// including INNER JOIN with ON conditions and implicit CROSS JOINs.
+    ///
+    /// # Arguments
+    /// * `left_table` - The left (base) table for the join operations
+    /// * `joins` - Array of JOIN clauses to process sequentially
+    ///
+    /// # Returns
+    /// * A new table containing the results of all join operations
+    /// * `Err` if any JOIN syntax is unsupported or tables can't be found
+    ///
+    /// # Implementation Details
+    /// Joins are processed sequentially, with each join building on the result
+    /// of the previous one. The implementation follows these steps:
+    /// 1. Clone the left table as the starting point
+    /// 2. For each join specification:
+    ///    a. Extract the right table name
+    ///    b. Retrieve the right table from the file handler
+    ///    c. Apply the appropriate join algorithm based on the join type
+    fn process_table_joins(&self, left_table: &Table, joins: &[SqlJoin]) -> SqawkResult<Table> {
+        // Start with a clone of the left table as our working result
+        let mut result_table = left_table.clone();
+
+        // Process each join clause sequentially
+        let mut remaining = joins;
+        while let Some((join, rest)) = remaining.split_first() {
+            // Log join type in verbose mode for debugging
+            if self.config.verbose() {
+                eprintln!("Join type: {:?}", join.join_operator);
+            }
+
+            // Resolve the right table: a loaded table or a generated one, with any
+            // UNPIVOT clauses that follow it applied
+            let (right_table, rest) =
+                self.apply_unpivot_clauses(self.resolve_table_factor(&join.relation)?, rest)?;
+            remaining = rest;
 
             // Apply different join algorithms based on join type and constraints
             match &join.join_operator {
@@ -823,7 +1953,7 @@ impl<'a> SqlExecutor<'a> {
                 | JoinOperator::LeftOuter(JoinConstraint::None)
                 | JoinOperator::RightOuter(JoinConstraint::None) => {
                     // Create a Cartesian product of the tables (all possible row combinations)
-                    result_table = result_table.cross_join(right_table)?;
+                    result_table = result_table.cross_join(&right_table)?;
                 }
 
                 // Handle INNER JOIN with ON condition
@@ -834,7 +1964,7 @@ impl<'a> SqlExecutor<'a> {
 
                     // Use inner_join with a closure that evaluates the ON condition
                     // for each potential row combination from the Cartesian product
-                    result_table = result_table.inner_join(right_table, |row, table| {
+                    result_table = result_table.inner_join(&right_table, |row, table| {
                         // This closure evaluates the ON condition for each row in the cross-join result
                         self.evaluate_condition(expr, row, table)
                     })?;
@@ -1011,17 +2141,8 @@ impl<'a> SqlExecutor<'a> {
             if !rows_to_delete.is_empty() {
                 let deleted_count = rows_to_delete.len();
 
-                // Create a new row set excluding the rows to delete
-                let mut new_rows: Vec<Vec<Value>> =
-                    Vec::with_capacity(table.row_count() - deleted_count);
-
-                for (idx, row) in table.rows().iter().enumerate() {
-                    if !rows_to_delete.contains(&idx) {
-                        new_rows.push(row.clone());
-                    }
-                }
-
-                table.replace_rows(new_rows);
+                // Remove the matching rows; the others keep their row IDs
+                table.remove_rows(&rows_to_delete);
 
                 // Mark the table as modified
                 self.modified_tables.insert(table_name);
@@ -1164,25 +2285,92 @@ impl<'a> SqlExecutor<'a> {
         Ok(column_specs)
     }
 
-    /// Get the column index for a simple column name
+    /// Check whether every SELECT item is a wildcard or a plain column reference
     ///
-    /// Helper function that centralizes column index resolution for simple column names
-    fn get_column_index_for_select(&self, column_name: &str, table: &Table) -> SqawkResult<usize> {
-        // First try as an exact column name match
-        if let Some(idx) = table.column_index(column_name) {
-            return Ok(idx);
+    /// Such projections can be resolved to column indices up front and applied
+    /// with `Table::project_with_aliases`. Any other expression (CAST, arithmetic,
+    /// function calls, literals) requires per-row evaluation.
+    ///
+    /// # Arguments
+    /// * `items` - The SELECT items from the query
+    ///
+    /// # Returns
+    /// * `true` if all items are wildcards or (optionally aliased) column references
+    fn select_items_are_column_refs(&self, items: &[SelectItem]) -> bool {
+        items.iter().all(|item| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_))
+            }
+            _ => true,
+        })
+    }
+
+    /// Project a table by evaluating SELECT expressions for every row
+    ///
+    /// This handles projections containing computed expressions such as
+    /// `CAST(age AS REAL)` or `price * quantity`. Wildcards expand to all source
+    /// columns, column references keep their source column name, and other
+    /// expressions are named by their alias or, failing that, their SQL text.
+    ///
+    /// # Arguments
+    /// * `items` - The SELECT items from the query
+    /// * `table` - The (already filtered) source table
+    ///
+    /// # Returns
+    /// * A new table containing the evaluated result columns
+    fn project_select_expressions(
+        &self,
+        items: &[SelectItem],
+        table: &Table,
+    ) -> SqawkResult<Table> {
+        let source_columns = table.columns();
+        let mut columns = Vec::new();
+
+        for item in items {
+            match item {
+                SelectItem::Wildcard(_) => columns.extend(source_columns.iter().cloned()),
+                SelectItem::ExprWithAlias { alias, .. } => columns.push(alias.value.clone()),
+                SelectItem::UnnamedExpr(Expr::Identifier(ident)) => {
+                    let idx = self.get_column_index_for_select(&ident.value, table)?;
+                    columns.push(source_columns[idx].clone());
+                }
+                SelectItem::UnnamedExpr(Expr::CompoundIdentifier(parts)) => {
+                    let idx = self.get_qualified_column_index(parts, table)?;
+                    columns.push(source_columns[idx].clone());
+                }
+                SelectItem::UnnamedExpr(expr) => columns.push(expr.to_string()),
+                _ => {
+                    return Err(SqawkError::UnsupportedSqlFeature(
+                        "Unsupported SELECT item".to_string(),
+                    ));
+                }
+            }
         }
 
-        // Try as qualified name by checking column patterns
-        let suffix = format!(".{}", column_name);
-        for (i, col) in table.columns().iter().enumerate() {
-            if col.ends_with(&suffix) {
-                return Ok(i);
+        let mut result = Table::new(table.name(), columns, None);
+
+        for row in table.rows() {
+            let mut new_row = Vec::with_capacity(result.column_count());
+            for item in items {
+                match item {
+                    SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                        new_row.push(self.evaluate_expr_with_row(expr, row, table)?);
+                    }
+                    _ => new_row.extend(row.iter().cloned()),
+                }
             }
+            result.add_row(new_row)?;
         }
 
-        // If we got here, the column wasn't found
-        Err(SqawkError::ColumnNotFound(column_name.to_string()))
+        Ok(result)
+    }
+
+    /// Get the column index for a simple column name
+    ///
+    /// Helper function that centralizes column index resolution for simple column names
+    fn get_column_index_for_select(&self, column_name: &str, table: &Table) -> SqawkResult<usize> {
+        unqualified_column_index(column_name, table)?
+            .ok_or_else(|| SqawkError::ColumnNotFound(column_name.to_string()))
     }
 
     /// Get the column index for a qualified column reference
@@ -1193,32 +2381,15 @@ impl<'a> SqlExecutor<'a> {
         parts: &[sqlparser::ast::Ident],
         table: &Table,
     ) -> SqawkResult<usize> {
-        // Build the fully qualified column name from parts
-        let qualified_name = parts
-            .iter()
-            .map(|ident| ident.value.clone())
-            .collect::<Vec<_>>()
-            .join(".");
-
-        // Try to find an exact match for the qualified column
-        if let Some(idx) = table.column_index(&qualified_name) {
-            return Ok(idx);
-        }
-
-        // If we didn't find an exact match, try a suffix match
-        // This helps with cases like "users.id" matching "users_orders_cross.users.id"
-        if parts.len() == 2 {
-            let suffix = format!("{}.{}", parts[0].value, parts[1].value);
-
-            for (i, col) in table.columns().iter().enumerate() {
-                if col.ends_with(&suffix) {
-                    return Ok(i);
-                }
-            }
-        }
-
-        // If we got here, the qualified column wasn't found
-        Err(SqawkError::ColumnNotFound(qualified_name))
+        qualified_column_index(parts, table).ok_or_else(|| {
+            SqawkError::ColumnNotFound(
+                parts
+                    .iter()
+                    .map(|ident| ident.value.clone())
+                    .collect::<Vec<_>>()
+                    .join("."),
+            )
+        })
     }
 
     /// Apply a WHERE clause to filter table rows
@@ -1233,21 +2404,31 @@ impl<'a> SqlExecutor<'a> {
     ///
     /// # Returns
     /// * A new table containing only rows that match the condition
+    /// * `Err` if the condition refers to an unknown or ambiguous column
     ///
     /// # Important
     /// This function is called before column projection to ensure all columns
     /// needed for the WHERE condition evaluation are available.
     fn apply_where_clause(&self, table: Table, where_expr: &Expr) -> SqawkResult<Table> {
-        // Create a new table that only includes rows matching the WHERE condition
-        // by calling the table.select method with a closure that evaluates the condition
-        let result = table.select(|row| {
-            // For each row, evaluate the WHERE condition expression
-            // If evaluation fails (returns an error), default to false (exclude the row)
-            self.evaluate_condition(where_expr, row, &table)
-                .unwrap_or(false)
-        });
+        // A row whose values can't be evaluated (e.g. a string compared with a number)
+        // is excluded, but an error in the query itself, such as an ambiguous or unknown
+        // column, is reported
+        let error = std::cell::RefCell::new(None);
+        let result = table.select(
+            |row| match self.evaluate_condition(where_expr, row, &table) {
+                Ok(keep) => keep,
+                Err(err @ (SqawkError::InvalidSqlQuery(_) | SqawkError::ColumnNotFound(_))) => {
+                    error.borrow_mut().get_or_insert(err);
+                    false
+                }
+                Err(_) => false,
+            },
+        );
 
-        Ok(result)
+        match error.into_inner() {
+            Some(err) => Err(err),
+            None => Ok(result),
+        }
     }
 
     /// Apply a HAVING clause to filter grouped results
@@ -1579,34 +2760,51 @@ impl<'a> SqlExecutor<'a> {
     ) -> SqawkResult<bool> {
         let left_val = self.evaluate_expr_with_row(left, row, table)?;
         let right_val = self.evaluate_expr_with_row(right, row, table)?;
+        self.compare_values(&left_val, op, &right_val)
+    }
 
+    /// Compare two evaluated values with a comparison operator
+    ///
+    /// # Arguments
+    /// * `left_val` - The value of the left operand
+    /// * `op` - The comparison operator (=, !=, >, <, >=, <=)
+    /// * `right_val` - The value of the right operand
+    ///
+    /// # Returns
+    /// * `Ok(true)` if the comparison is TRUE
+    /// * `Ok(false)` if the comparison is FALSE or involves NULL
+    /// * `Err` if the operator isn't a comparison or the types can't be compared
+    fn compare_values(
+        &self,
+        left_val: &Value,
+        op: &sqlparser::ast::BinaryOperator,
+        right_val: &Value,
+    ) -> SqawkResult<bool> {
         match op {
             // Equal (=) operator
-            sqlparser::ast::BinaryOperator::Eq => self.evaluate_equality(&left_val, &right_val),
+            sqlparser::ast::BinaryOperator::Eq => self.evaluate_equality(left_val, right_val),
 
             // Not equal (!=) operator
-            sqlparser::ast::BinaryOperator::NotEq => {
-                self.evaluate_inequality(&left_val, &right_val)
-            }
+            sqlparser::ast::BinaryOperator::NotEq => self.evaluate_inequality(left_val, right_val),
 
             // Greater than (>) operator
             sqlparser::ast::BinaryOperator::Gt => {
-                self.compare_values_with_operator(&left_val, &right_val, ">")
+                self.compare_values_with_operator(left_val, right_val, ">")
             }
 
             // Less than (<) operator
             sqlparser::ast::BinaryOperator::Lt => {
-                self.compare_values_with_operator(&left_val, &right_val, "<")
+                self.compare_values_with_operator(left_val, right_val, "<")
             }
 
             // Greater than or equal (>=) operator
             sqlparser::ast::BinaryOperator::GtEq => {
-                self.compare_values_with_operator(&left_val, &right_val, ">=")
+                self.compare_values_with_operator(left_val, right_val, ">=")
             }
 
             // Less than or equal (<=) operator
             sqlparser::ast::BinaryOperator::LtEq => {
-                self.compare_values_with_operator(&left_val, &right_val, "<=")
+                self.compare_values_with_operator(left_val, right_val, "<=")
             }
 
             // Add more operators as needed
@@ -1708,6 +2906,9 @@ impl<'a> SqlExecutor<'a> {
         op_symbol: &str,
     ) -> SqawkResult<bool> {
         match (left_val, right_val) {
+            // A comparison with NULL is unknown, which WHERE, FILTER and CASE treat as false
+            (Value::Null, _) | (_, Value::Null) => Ok(false),
+
             // Integer-Integer comparison
             (Value::Integer(a), Value::Integer(b)) => self.compare_integers(*a, *b, op_symbol),
 
@@ -1885,6 +3086,26 @@ impl<'a> SqlExecutor<'a> {
             Expr::Value(value) => self.evaluate_sql_value(value),
             // Handle unary operations like - (negation)
             Expr::UnaryOp { op, expr } => self.evaluate_unary_operation(op, expr),
+            // Constant arithmetic such as INSERT ... VALUES (2 * 3)
+            Expr::BinaryOp { left, op, right } => {
+                match ArithmeticOperator::from_binary_operator(op) {
+                    Some(arith_op) => {
+                        arith_op.apply(&self.evaluate_expr(left)?, &self.evaluate_expr(right)?)
+                    }
+                    None => self.unsupported_expression_error(expr),
+                }
+            }
+            Expr::Nested(inner) => self.evaluate_expr(inner),
+            // CAST(x AS type) and x::type
+            Expr::Cast { expr, data_type } => {
+                let val = self.evaluate_expr(expr)?;
+                self.apply_cast(val, data_type, false)
+            }
+            // TRY_CAST(x AS type) / SAFE_CAST(x AS type) yield NULL instead of failing
+            Expr::TryCast { expr, data_type } | Expr::SafeCast { expr, data_type } => {
+                let val = self.evaluate_expr(expr)?;
+                self.apply_cast(val, data_type, true)
+            }
             _ => self.unsupported_expression_error(expr),
         }
     }
@@ -1967,14 +3188,7 @@ impl<'a> SqlExecutor<'a> {
     /// * `Ok(Value)` - The negated value
     /// * `Err` - If the value can't be negated
     fn apply_negation(&self, val: &Value) -> SqawkResult<Value> {
-        match val {
-            Value::Integer(i) => Ok(Value::Integer(-i)),
-            Value::Float(f) => Ok(Value::Float(-f)),
-            _ => Err(SqawkError::TypeError(format!(
-                "Cannot apply negation to non-numeric value: {:?}",
-                val
-            ))),
-        }
+        arithmetic::negate(val)
     }
 
     /// Apply boolean NOT to a value
@@ -1995,6 +3209,36 @@ impl<'a> SqlExecutor<'a> {
         }
     }
 
+    /// Convert a value to the type named in a CAST, TRY_CAST or `::` expression
+    ///
+    /// # Arguments
+    /// * `val` - The already-evaluated value to convert
+    /// * `data_type` - The target SQL data type from the expression
+    /// * `try_cast` - If true, a failed conversion yields NULL instead of an error
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The converted value (or NULL for a failed TRY_CAST)
+    /// * `Err(SqawkError::TypeError)` - If a CAST conversion fails
+    /// * `Err(SqawkError::UnsupportedSqlFeature)` - If the target type is not supported
+    fn apply_cast(
+        &self,
+        val: Value,
+        data_type: &SqlDataType,
+        try_cast: bool,
+    ) -> SqawkResult<Value> {
+        let target = DataType::from_sql_name(&data_type.to_string()).ok_or_else(|| {
+            SqawkError::UnsupportedSqlFeature(format!(
+                "Unsupported CAST target type: {}",
+                data_type
+            ))
+        })?;
+
+        match val.cast(target) {
+            Err(SqawkError::TypeError(_)) if try_cast => Ok(Value::Null),
+            result => result,
+        }
+    }
+
     /// Create an error for an unsupported expression
     ///
     /// # Arguments
@@ -2067,7 +3311,7 @@ impl<'a> SqlExecutor<'a> {
                     .unwrap_or_default();
 
                 // First check if this is a supported aggregate function
-                if let Some(_agg_func) = AggregateFunction::from_name(&func_name) {
+                if self.database.functions().is_aggregate(&func_name) {
                     // For aggregate functions in HAVING, look for the result in the current row
 
                     // First try to find a column with the exact function name
@@ -2140,35 +3384,27 @@ impl<'a> SqlExecutor<'a> {
                         }
                     }
                 }
-                // Then check if this is a supported string function
-                else if let Some(string_func) = StringFunction::from_name(&func_name) {
-                    // Evaluate the string function arguments
-                    let mut arg_values = Vec::new();
-                    for arg in &func.args {
-                        match arg {
-                            sqlparser::ast::FunctionArg::Unnamed(expr) => match expr {
-                                sqlparser::ast::FunctionArgExpr::Expr(expr) => {
-                                    let val = self.evaluate_expr_with_row(expr, row, table)?;
-                                    arg_values.push(val);
-                                }
-                                _ => {
-                                    return Err(SqawkError::UnsupportedSqlFeature(format!(
-                                        "Unsupported function argument: {:?}",
-                                        expr
-                                    )));
-                                }
-                            },
-                            _ => {
-                                return Err(SqawkError::UnsupportedSqlFeature(format!(
-                                    "Named arguments are not supported: {:?}",
-                                    arg
-                                )));
-                            }
-                        }
-                    }
-
-                    // Apply the string function with the evaluated arguments
-                    return string_func.apply(&arg_values);
+                // Then check if this is a built-in or user-defined scalar function
+                else if self.database.functions().contains(&func_name) {
+                    // Evaluate the arguments and apply the scalar function
+                    let arg_values = self.evaluate_function_args(&func.args, row, table)?;
+                    let json_args: Vec<bool> = func
+                        .args
+                        .iter()
+                        .map(|arg| {
+                            matches!(
+                                arg,
+                                sqlparser::ast::FunctionArg::Unnamed(
+                                    sqlparser::ast::FunctionArgExpr::Expr(expr)
+                                ) if yields_json(expr)
+                            )
+                        })
+                        .collect();
+                    return self.database.functions().call_with_json_args(
+                        &func_name,
+                        &arg_values,
+                        &json_args,
+                    );
                 }
 
                 // Fall back to standard expression evaluation
@@ -2176,82 +3412,214 @@ impl<'a> SqlExecutor<'a> {
             }
             // Binary operations might need column references from the row
             Expr::BinaryOp { left, op, right } => {
+                use sqlparser::ast::BinaryOperator;
+
+                // AND and OR are conditions, whose value is a boolean
+                if matches!(op, BinaryOperator::And | BinaryOperator::Or) {
+                    return Ok(Value::Boolean(self.evaluate_condition(expr, row, table)?));
+                }
+
                 let left_val = self.evaluate_expr_with_row(left, row, table)?;
                 let right_val = self.evaluate_expr_with_row(right, row, table)?;
 
-                // For basic arithmetic operators, delegate to helpers
+                // Arithmetic operators share their semantics with the VM
+                if let Some(arith_op) = ArithmeticOperator::from_binary_operator(op) {
+                    return arith_op.apply(&left_val, &right_val);
+                }
                 match op {
-                    sqlparser::ast::BinaryOperator::Plus => match (&left_val, &right_val) {
-                        (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a + b)),
-                        (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
-                        (Value::Integer(a), Value::Float(b)) => Ok(Value::Float(*a as f64 + b)),
-                        (Value::Float(a), Value::Integer(b)) => Ok(Value::Float(a + *b as f64)),
-                        _ => Err(SqawkError::TypeError(format!(
-                            "Cannot add {:?} and {:?}",
-                            left_val, right_val
-                        ))),
-                    },
-                    sqlparser::ast::BinaryOperator::Minus => match (&left_val, &right_val) {
-                        (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a - b)),
-                        (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a - b)),
-                        (Value::Integer(a), Value::Float(b)) => Ok(Value::Float(*a as f64 - b)),
-                        (Value::Float(a), Value::Integer(b)) => Ok(Value::Float(a - *b as f64)),
-                        _ => Err(SqawkError::TypeError(format!(
-                            "Cannot subtract {:?} from {:?}",
-                            right_val, left_val
-                        ))),
-                    },
-                    sqlparser::ast::BinaryOperator::Multiply => match (&left_val, &right_val) {
-                        (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a * b)),
-                        (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a * b)),
-                        (Value::Integer(a), Value::Float(b)) => Ok(Value::Float(*a as f64 * b)),
-                        (Value::Float(a), Value::Integer(b)) => Ok(Value::Float(a * *b as f64)),
-                        _ => Err(SqawkError::TypeError(format!(
-                            "Cannot multiply {:?} and {:?}",
-                            left_val, right_val
-                        ))),
-                    },
-                    sqlparser::ast::BinaryOperator::Divide => match (&left_val, &right_val) {
-                        (Value::Integer(a), Value::Integer(b)) => {
-                            if *b == 0 {
-                                return Err(SqawkError::DivideByZero);
-                            }
-                            Ok(Value::Float(*a as f64 / *b as f64))
-                        }
-                        (Value::Float(a), Value::Float(b)) => {
-                            if *b == 0.0 {
-                                return Err(SqawkError::DivideByZero);
-                            }
-                            Ok(Value::Float(a / b))
-                        }
-                        (Value::Integer(a), Value::Float(b)) => {
-                            if *b == 0.0 {
-                                return Err(SqawkError::DivideByZero);
-                            }
-                            Ok(Value::Float(*a as f64 / b))
-                        }
-                        (Value::Float(a), Value::Integer(b)) => {
-                            if *b == 0 {
-                                return Err(SqawkError::DivideByZero);
-                            }
-                            Ok(Value::Float(a / *b as f64))
+                    // A comparison with NULL is unknown, so its value is NULL
+                    BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::Lt
+                    | BinaryOperator::GtEq
+                    | BinaryOperator::LtEq => {
+                        if left_val == Value::Null || right_val == Value::Null {
+                            Ok(Value::Null)
+                        } else {
+                            let result = self.compare_values(&left_val, op, &right_val)?;
+                            Ok(Value::Boolean(result))
                         }
-                        _ => Err(SqawkError::TypeError(format!(
-                            "Cannot divide {:?} by {:?}",
-                            left_val, right_val
-                        ))),
-                    },
+                    }
                     _ => Err(SqawkError::UnsupportedSqlFeature(format!(
                         "Unsupported binary operator in expression: {:?}",
                         op
                     ))),
                 }
             }
+            // CEIL(x) and FLOOR(x) have their own syntax so they can take a date field
+            Expr::Ceil { expr: inner, field } | Expr::Floor { expr: inner, field } => {
+                if *field != sqlparser::ast::DateTimeField::NoDateTime {
+                    return self.unsupported_expression_error(expr);
+                }
+                let func_name = if matches!(expr, Expr::Ceil { .. }) {
+                    "CEIL"
+                } else {
+                    "FLOOR"
+                };
+                let arg_value = self.evaluate_expr_with_row(inner, row, table)?;
+                self.database.functions().call(func_name, &[arg_value])
+            }
+            // TRIM([BOTH | LEADING | TRAILING] [chars FROM] str)
+            Expr::Trim {
+                expr: inner,
+                trim_where,
+                trim_what,
+            } => {
+                let func_name = match trim_where {
+                    Some(sqlparser::ast::TrimWhereField::Leading) => "LTRIM",
+                    Some(sqlparser::ast::TrimWhereField::Trailing) => "RTRIM",
+                    _ => "TRIM",
+                };
+                let mut arg_values = vec![self.evaluate_expr_with_row(inner, row, table)?];
+                if let Some(chars) = trim_what {
+                    arg_values.push(self.evaluate_expr_with_row(chars, row, table)?);
+                }
+                self.database.functions().call(func_name, &arg_values)
+            }
+            // Unary minus/plus may apply to a column, e.g. -price
+            Expr::UnaryOp {
+                op:
+                    op @ (sqlparser::ast::UnaryOperator::Minus | sqlparser::ast::UnaryOperator::Plus),
+                expr: inner,
+            } => {
+                let val = self.evaluate_expr_with_row(inner, row, table)?;
+                match op {
+                    sqlparser::ast::UnaryOperator::Minus => self.apply_negation(&val),
+                    _ => Ok(val),
+                }
+            }
+            Expr::Nested(inner) => self.evaluate_expr_with_row(inner, row, table),
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => self.evaluate_case(
+                operand.as_deref(),
+                conditions,
+                results,
+                else_result.as_deref(),
+                row,
+                table,
+            ),
+            // CAST(x AS type) and x::type
+            Expr::Cast { expr, data_type } => {
+                let val = self.evaluate_expr_with_row(expr, row, table)?;
+                self.apply_cast(val, data_type, false)
+            }
+            // TRY_CAST(x AS type) / SAFE_CAST(x AS type) yield NULL instead of failing
+            Expr::TryCast { expr, data_type } | Expr::SafeCast { expr, data_type } => {
+                let val = self.evaluate_expr_with_row(expr, row, table)?;
+                self.apply_cast(val, data_type, true)
+            }
+            // json -> selector returns JSON text, json ->> selector a SQL value
+            Expr::JsonAccess {
+                left,
+                operator: operator @ (JsonOperator::Arrow | JsonOperator::LongArrow),
+                right,
+            } => {
+                let json = self.evaluate_expr_with_row(left, row, table)?;
+                let selector = self.evaluate_expr_with_row(right, row, table)?;
+                json_access(&json, &selector, *operator == JsonOperator::LongArrow)
+            }
             // Handle other expression types by delegating to the main evaluate_expr function
             _ => self.evaluate_expr(expr),
         }
     }
 
+    /// Evaluate a CASE expression against a row
+    ///
+    /// The searched form (`CASE WHEN cond THEN ...`) takes the first branch whose
+    /// condition is true; the simple form (`CASE x WHEN v THEN ...`) the first branch
+    /// whose value equals the operand. Without a matching branch the result is the
+    /// ELSE value, or NULL if there is none.
+    ///
+    /// # Arguments
+    /// * `operand` - The operand of the simple form, if any
+    /// * `conditions` - The WHEN conditions or values, in order
+    /// * `results` - The THEN result of each branch
+    /// * `else_result` - The ELSE result, if any
+    /// * `row` - The current row values
+    /// * `table` - The table metadata for column resolution
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The result of the selected branch
+    /// * `Err` - If a condition or the selected result fails to evaluate
+    fn evaluate_case(
+        &self,
+        operand: Option<&Expr>,
+        conditions: &[Expr],
+        results: &[Expr],
+        else_result: Option<&Expr>,
+        row: &[Value],
+        table: &Table,
+    ) -> SqawkResult<Value> {
+        for (condition, result) in conditions.iter().zip(results) {
+            let matched = match operand {
+                Some(operand) => self.evaluate_comparison(
+                    operand,
+                    &sqlparser::ast::BinaryOperator::Eq,
+                    condition,
+                    row,
+                    table,
+                )?,
+                None => self.evaluate_condition(condition, row, table)?,
+            };
+            if matched {
+                return self.evaluate_expr_with_row(result, row, table);
+            }
+        }
+
+        match else_result {
+            Some(else_result) => self.evaluate_expr_with_row(else_result, row, table),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Evaluate the arguments of a scalar function call against a row
+    ///
+    /// # Arguments
+    /// * `args` - The function arguments from the parsed call
+    /// * `row` - The current row values
+    /// * `table` - The table metadata for column resolution
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Value>)` - The evaluated argument values, in order
+    /// * `Err` - If an argument is a wildcard or named argument, or fails to evaluate
+    fn evaluate_function_args(
+        &self,
+        args: &[sqlparser::ast::FunctionArg],
+        row: &[Value],
+        table: &Table,
+    ) -> SqawkResult<Vec<Value>> {
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg {
+                sqlparser::ast::FunctionArg::Unnamed(expr) => match expr {
+                    sqlparser::ast::FunctionArgExpr::Expr(expr) => {
+                        let val = self.evaluate_expr_with_row(expr, row, table)?;
+                        arg_values.push(val);
+                    }
+                    _ => {
+                        return Err(SqawkError::UnsupportedSqlFeature(format!(
+                            "Unsupported function argument: {:?}",
+                            expr
+                        )));
+                    }
+                },
+                _ => {
+                    return Err(SqawkError::UnsupportedSqlFeature(format!(
+                        "Named arguments are not supported: {:?}",
+                        arg
+                    )));
+                }
+            }
+        }
+
+        Ok(arg_values)
+    }
+
     /// Check if the SELECT items contain any aggregate functions
     ///
     /// This function analyzes a list of SELECT items and determines if any of them
@@ -2271,10 +3639,15 @@ impl<'a> SqlExecutor<'a> {
         for item in items {
             match item {
                 // Check for aggregate functions in non-aliased expressions
+                SelectItem::UnnamedExpr(Expr::AggregateExpressionWithFilter { .. })
+                | SelectItem::ExprWithAlias {
+                    expr: Expr::AggregateExpressionWithFilter { .. },
+                    ..
+                } => return true,
                 SelectItem::UnnamedExpr(Expr::Function(func)) => {
                     // Check if the function name is one of our supported aggregates
                     let name = func.name.0.first().map(|i| i.value.as_str()).unwrap_or("");
-                    if AggregateFunction::from_name(name).is_some() {
+                    if self.database.functions().is_aggregate(name) {
                         return true;
                     }
                 }
@@ -2286,7 +3659,7 @@ impl<'a> SqlExecutor<'a> {
                 } => {
                     // Check if the function name is one of our supported aggregates
                     let name = func.name.0.first().map(|i| i.value.as_str()).unwrap_or("");
-                    if AggregateFunction::from_name(name).is_some() {
+                    if self.database.functions().is_aggregate(name) {
                         return true;
                     }
                 }
@@ -2324,6 +3697,7 @@ impl<'a> SqlExecutor<'a> {
             match item {
                 SelectItem::UnnamedExpr(expr) => {
                     // Handle function call
+                    let (expr, filter) = split_aggregate_filter(expr);
                     if let Expr::Function(func) = expr {
                         let func_name = func
                             .name
@@ -2333,21 +3707,17 @@ impl<'a> SqlExecutor<'a> {
                             .unwrap_or_default();
 
                         // Check if this is a supported aggregate function
-                        if let Some(agg_func) = AggregateFunction::from_name(&func_name) {
-                            // Process the function arguments
-                            if func.args.len() != 1 {
-                                return Err(SqawkError::InvalidSqlQuery(format!(
-                                    "{} function requires exactly one argument",
-                                    func_name
-                                )));
+                        if let Some(aggregator) =
+                            self.database.functions().get_aggregate(&func_name)
+                        {
+                            // Feed every row of the table to the aggregate
+                            let call =
+                                self.resolve_aggregate_call(aggregator, func, filter, table)?;
+                            let mut accumulator = call.create_accumulator()?;
+                            for row in table.rows() {
+                                self.update_aggregate(&call, &mut accumulator, row, table)?;
                             }
-
-                            // Get the column values for the function argument
-                            let column_values =
-                                self.get_values_for_function_arg(&func.args[0], table)?;
-
-                            // Execute the aggregate function
-                            let result_value = agg_func.execute(&column_values)?;
+                            let result_value = accumulator.finalize(&call)?;
 
                             // Add the result to our output
                             result_columns.push(func_name.clone());
@@ -2367,6 +3737,7 @@ impl<'a> SqlExecutor<'a> {
                 }
                 SelectItem::ExprWithAlias { expr, alias } => {
                     // Handle function call with alias
+                    let (expr, filter) = split_aggregate_filter(expr);
                     match expr {
                         Expr::Function(func) => {
                             let func_name = func
@@ -2377,21 +3748,17 @@ impl<'a> SqlExecutor<'a> {
                                 .unwrap_or_default();
 
                             // Check if this is a supported aggregate function
-                            if let Some(agg_func) = AggregateFunction::from_name(&func_name) {
-                                // Process the function arguments
-                                if func.args.len() != 1 {
-                                    return Err(SqawkError::InvalidSqlQuery(format!(
-                                        "{} function requires exactly one argument",
-                                        func_name
-                                    )));
+                            if let Some(aggregator) =
+                                self.database.functions().get_aggregate(&func_name)
+                            {
+                                // Feed every row of the table to the aggregate
+                                let call =
+                                    self.resolve_aggregate_call(aggregator, func, filter, table)?;
+                                let mut accumulator = call.create_accumulator()?;
+                                for row in table.rows() {
+                                    self.update_aggregate(&call, &mut accumulator, row, table)?;
                                 }
-
-                                // Get the column values for the function argument
-                                let column_values =
-                                    self.get_values_for_function_arg(&func.args[0], table)?;
-
-                                // Execute the aggregate function
-                                let result_value = agg_func.execute(&column_values)?;
+                                let result_value = accumulator.finalize(&call)?;
 
                                 // Add the result to our output with the alias
                                 result_columns.push(alias.value.clone());
@@ -2419,499 +3786,604 @@ impl<'a> SqlExecutor<'a> {
                 }
                 _ => {
                     return Err(SqawkError::UnsupportedSqlFeature(
-                        "Unsupported SELECT item in aggregate query".to_string(),
+                        "Unsupported SELECT item in aggregate query".to_string(),
+                    ));
+                }
+            }
+        }
+
+        // Create a new table with a single row containing the aggregate results
+        let mut result_table = Table::new("aggregate_result", result_columns, None);
+        result_table.add_row(result_values)?;
+        Ok(result_table)
+    }
+
+    /// Apply aggregate functions with GROUP BY clause
+    ///
+    /// This function implements SQL's GROUP BY functionality, which groups rows based on
+    /// grouping keys and applies aggregate functions to each group. This is a key
+    /// component of analytical queries that need to summarize data across groups.
+    ///
+    /// A grouping key can be a column, any expression (e.g. `SUBSTR(date, 1, 7)`), the
+    /// 1-based position of a SELECT item or the alias of one. `ROLLUP`, `CUBE` and
+    /// `GROUPING SETS` group the same rows by several sets of keys in one query; keys
+    /// that are not part of a row's grouping set are NULL in that row, and
+    /// `GROUPING(key, ...)` tells such subtotal rows apart.
+    ///
+    /// # Arguments
+    /// * `items` - The SELECT items from the SQL query (columns and expressions to include)
+    /// * `table` - The source table to apply grouping and aggregation to
+    /// * `group_by` - The GROUP BY expressions defining how to group the rows
+    ///
+    /// # Implementation Details
+    /// The GROUP BY implementation follows these steps:
+    /// 1. Resolve the grouping keys and the grouping sets they form
+    /// 2. Resolve each SELECT item to a grouping key, a GROUPING() call or an aggregate
+    /// 3. Stream the rows, feeding each one to the accumulators of its group in every
+    ///    grouping set (a `HashMap` per set maps key values to their group)
+    /// 4. For each grouping set, and each of its groups in order of first appearance,
+    ///    generate one output row with the SELECT items in order
+    ///
+    /// # Returns
+    /// * A new table containing one row per group
+    /// * `Err` if a grouping key or function argument is invalid or unsupported
+    fn apply_grouped_aggregate_functions(
+        &self,
+        items: &[SelectItem],
+        table: &Table,
+        group_by: &[Expr],
+    ) -> SqawkResult<Table> {
+        // Resolve the grouping keys. Each GROUP BY element contributes one or more
+        // grouping sets, and the query groups by their cross product.
+        let mut keys = Vec::new();
+        let mut grouping_sets: Vec<Vec<usize>> = vec![Vec::new()];
+        for expr in group_by {
+            let element_sets = self.resolve_grouping_element(expr, items, table, &mut keys)?;
+            grouping_sets = grouping_sets
+                .iter()
+                .flat_map(|set| {
+                    element_sets.iter().map(move |element| {
+                        let mut combined = set.clone();
+                        for key in element {
+                            if !combined.contains(key) {
+                                combined.push(*key);
+                            }
+                        }
+                        combined
+                    })
+                })
+                .collect();
+        }
+
+        // Resolve each SELECT item to its output column
+        let mut result_columns = Vec::new();
+        let mut outputs = Vec::new();
+        let mut calls = Vec::new();
+        for item in items {
+            let (expr, alias) = match item {
+                SelectItem::UnnamedExpr(expr) => (expr, None),
+                SelectItem::ExprWithAlias { expr, alias } => (expr, Some(alias.value.clone())),
+                SelectItem::Wildcard(_) => {
+                    return Err(SqawkError::UnsupportedSqlFeature(
+                        "Wildcard (*) is not supported in GROUP BY queries".to_string(),
+                    ));
+                }
+                _ => {
+                    return Err(SqawkError::UnsupportedSqlFeature(
+                        "Unsupported SELECT item in GROUP BY query".to_string(),
+                    ));
+                }
+            };
+
+            let (expr, filter) = split_aggregate_filter(expr);
+            let func_name = match expr {
+                Expr::Function(func) => func.name.0.first().map(|i| i.value.clone()),
+                _ => None,
+            }
+            .unwrap_or_default();
+
+            let (name, output) = match expr {
+                Expr::Function(func) if self.database.functions().is_aggregate(&func_name) => {
+                    let aggregator = self
+                        .database
+                        .functions()
+                        .get_aggregate(&func_name)
+                        .ok_or_else(|| {
+                            SqawkError::UnsupportedSqlFeature(format!(
+                                "Unsupported function: {}",
+                                func_name
+                            ))
+                        })?;
+                    calls.push(self.resolve_aggregate_call(aggregator, func, filter, table)?);
+                    (func_name, GroupedColumn::Aggregate(calls.len() - 1))
+                }
+                _ if filter.is_some() => {
+                    return Err(SqawkError::InvalidSqlQuery(
+                        "FILTER can only be applied to aggregate functions".to_string(),
+                    ));
+                }
+                Expr::Function(func) if func_name.eq_ignore_ascii_case("GROUPING") => {
+                    let mut grouping_keys = Vec::with_capacity(func.args.len());
+                    for arg in &func.args {
+                        let key = match arg {
+                            sqlparser::ast::FunctionArg::Unnamed(
+                                sqlparser::ast::FunctionArgExpr::Expr(arg_expr),
+                            ) => self.find_grouping_key(arg_expr, &keys, table),
+                            _ => None,
+                        };
+                        grouping_keys.push(key.ok_or_else(|| {
+                            SqawkError::InvalidSqlQuery(format!(
+                                "GROUPING argument '{}' is not a GROUP BY expression",
+                                arg
+                            ))
+                        })?);
+                    }
+                    if grouping_keys.is_empty() {
+                        return Err(SqawkError::InvalidFunctionArguments(
+                            "GROUPING requires at least 1 argument, got 0".to_string(),
+                        ));
+                    }
+                    (func_name, GroupedColumn::Grouping(grouping_keys))
+                }
+                _ => {
+                    let key = self.find_grouping_key(expr, &keys, table).ok_or_else(|| {
+                        SqawkError::InvalidSqlQuery(format!(
+                            "Column '{}' must appear in the GROUP BY clause or be used in an aggregate function",
+                            expr
+                        ))
+                    })?;
+                    let name = match self.column_ref_index(expr, table) {
+                        Some(idx) => table.columns()[idx].clone(),
+                        None => expr.to_string(),
+                    };
+                    (name, GroupedColumn::Key(key))
+                }
+            };
+
+            result_columns.push(alias.unwrap_or(name));
+            outputs.push(output);
+        }
+
+        // Group the rows, feeding each row to its group's accumulators in every
+        // grouping set. Groups are kept in order of first appearance; keys that are
+        // not part of a grouping set are NULL in its group keys.
+        let mut group_positions: Vec<std::collections::HashMap<Vec<Value>, usize>> =
+            vec![std::collections::HashMap::new(); grouping_sets.len()];
+        let mut groups: Vec<Vec<GroupState>> = grouping_sets.iter().map(|_| Vec::new()).collect();
+
+        for row in table.rows() {
+            let key_values = keys
+                .iter()
+                .map(|key| self.evaluate_expr_with_row(key, row, table))
+                .collect::<SqawkResult<Vec<_>>>()?;
+
+            for (set_idx, set) in grouping_sets.iter().enumerate() {
+                let group_key: Vec<Value> = key_values
+                    .iter()
+                    .enumerate()
+                    .map(|(key, value)| {
+                        if set.contains(&key) {
+                            value.clone()
+                        } else {
+                            Value::Null
+                        }
+                    })
+                    .collect();
+
+                let position = match group_positions[set_idx].get(&group_key) {
+                    Some(&position) => position,
+                    None => {
+                        let accumulators = calls
+                            .iter()
+                            .map(|call| call.create_accumulator())
+                            .collect::<SqawkResult<Vec<_>>>()?;
+                        groups[set_idx].push((group_key.clone(), accumulators));
+                        group_positions[set_idx].insert(group_key, groups[set_idx].len() - 1);
+                        groups[set_idx].len() - 1
+                    }
+                };
+
+                for (accumulator, call) in groups[set_idx][position].1.iter_mut().zip(&calls) {
+                    self.update_aggregate(call, accumulator, row, table)?;
+                }
+            }
+        }
+
+        // The empty grouping set, as in the grand total of a ROLLUP, forms one group
+        // even without input rows, like an aggregate query without GROUP BY
+        for (set, set_groups) in grouping_sets.iter().zip(groups.iter_mut()) {
+            if set.is_empty() && set_groups.is_empty() {
+                let accumulators = calls
+                    .iter()
+                    .map(|call| call.create_accumulator())
+                    .collect::<SqawkResult<Vec<_>>>()?;
+                set_groups.push((vec![Value::Null; keys.len()], accumulators));
+            }
+        }
+
+        // Create the result table
+        let mut result_table = Table::new("grouped_result", result_columns, None);
+
+        // Generate a row for each group with the SELECT items in order
+        for (set, set_groups) in grouping_sets.iter().zip(groups) {
+            for (group_key, accumulators) in set_groups {
+                let results = accumulators
+                    .into_iter()
+                    .zip(&calls)
+                    .map(|(accumulator, call)| accumulator.finalize(call))
+                    .collect::<SqawkResult<Vec<_>>>()?;
+
+                let result_row = outputs
+                    .iter()
+                    .map(|output| match output {
+                        GroupedColumn::Key(key) => group_key[*key].clone(),
+                        GroupedColumn::Grouping(grouping_keys) => Value::Integer(
+                            grouping_keys
+                                .iter()
+                                .fold(0, |bits, key| (bits << 1) | i64::from(!set.contains(key))),
+                        ),
+                        GroupedColumn::Aggregate(idx) => results[*idx].clone(),
+                    })
+                    .collect();
+
+                // Add this group's result row to the table
+                result_table.add_row(result_row)?;
+            }
+        }
+
+        Ok(result_table)
+    }
+
+    /// Resolve one element of a GROUP BY clause to the grouping sets it contributes
+    ///
+    /// A plain expression contributes a single set holding its key. `ROLLUP(a, b)`
+    /// contributes `(a, b)`, `(a)` and `()`; `CUBE(a, b)` every subset of its keys; and
+    /// `GROUPING SETS (...)` the listed sets. New keys are appended to `keys`.
+    ///
+    /// # Arguments
+    /// * `expr` - The GROUP BY element
+    /// * `items` - The SELECT items, for ordinal and alias references
+    /// * `table` - The source table
+    /// * `keys` - The grouping keys resolved so far
+    ///
+    /// # Returns
+    /// * The grouping sets, each a list of key indices
+    /// * `Err` if a key cannot be resolved
+    fn resolve_grouping_element(
+        &self,
+        expr: &Expr,
+        items: &[SelectItem],
+        table: &Table,
+        keys: &mut Vec<Expr>,
+    ) -> SqawkResult<Vec<Vec<usize>>> {
+        let mut resolve_lists = |lists: &[Vec<Expr>]| -> SqawkResult<Vec<Vec<usize>>> {
+            lists
+                .iter()
+                .map(|list| {
+                    list.iter()
+                        .map(|key| self.add_grouping_key(key, items, table, keys))
+                        .collect()
+                })
+                .collect()
+        };
+
+        match expr {
+            Expr::Rollup(lists) => {
+                let elements = resolve_lists(lists)?;
+                Ok((0..=elements.len())
+                    .rev()
+                    .map(|n| elements[..n].concat())
+                    .collect())
+            }
+            Expr::Cube(lists) => {
+                let elements = resolve_lists(lists)?;
+                if elements.len() > 16 {
+                    return Err(SqawkError::UnsupportedSqlFeature(
+                        "CUBE supports at most 16 elements".to_string(),
                     ));
                 }
+                // Subsets from all elements down to none, earlier elements kept longest
+                let count = elements.len();
+                Ok((0..1usize << count)
+                    .rev()
+                    .map(|mask| {
+                        elements
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| mask & (1 << (count - 1 - i)) != 0)
+                            .flat_map(|(_, element)| element.iter().copied())
+                            .collect()
+                    })
+                    .collect())
             }
+            Expr::GroupingSets(sets) => resolve_lists(sets),
+            _ => Ok(vec![vec![self.add_grouping_key(expr, items, table, keys)?]]),
         }
-
-        // Create a new table with a single row containing the aggregate results
-        let mut result_table = Table::new("aggregate_result", result_columns, None);
-        result_table.add_row(result_values)?;
-        Ok(result_table)
     }
 
-    /// Apply aggregate functions with GROUP BY clause
+    /// Resolve a grouping key and return its index in `keys`, appending it if new
     ///
-    /// This function implements SQL's GROUP BY functionality, which groups rows based on
-    /// specified columns and applies aggregate functions to each group. This is a key
-    /// component of analytical queries that need to summarize data across groups.
+    /// An integer literal refers to a SELECT item by 1-based position, and a name that
+    /// is not a column of the table refers to a SELECT item by alias.
     ///
     /// # Arguments
-    /// * `items` - The SELECT items from the SQL query (columns and expressions to include)
-    /// * `table` - The source table to apply grouping and aggregation to
-    /// * `group_by` - The GROUP BY expressions defining how to group the rows
-    ///
-    /// # Returns
-    ///
-    /// # Implementation Details
-    /// The GROUP BY implementation follows these steps:
-    /// 1. Identify the columns to group by
-    /// 2. Build a `HashMap` that groups row indices by their group key values
-    /// 3. Process each SELECT item to determine which outputs to include
-    /// 4. For each group, generate one output row with:
-    ///    - The GROUP BY column values
-    ///    - The results of aggregate functions applied to that group
+    /// * `expr` - The grouping key as written in the GROUP BY clause
+    /// * `items` - The SELECT items, for ordinal and alias references
+    /// * `table` - The source table
+    /// * `keys` - The grouping keys resolved so far
     ///
     /// # Returns
-    /// * A new table containing the results of all aggregate functions, one row per group
-    /// * `Err` if any function arguments are invalid or unsupported
-    fn apply_grouped_aggregate_functions(
+    /// * The index of the key
+    /// * `Err` if the position, alias or column does not exist or names an aggregate
+    fn add_grouping_key(
+        &self,
+        expr: &Expr,
         items: &[SelectItem],
         table: &Table,
-        group_by: &Vec<sqlparser::ast::Expr>,
-    ) -> SqawkResult<Table> {
-        // Extract GROUP BY columns
-        let mut group_columns = Vec::new();
-        let mut group_column_indices = Vec::new();
-
-        // Process each GROUP BY expression
-        for expr in group_by {
-            match expr {
-                Expr::Identifier(ident) => {
-                    // Simple column reference
-                    let col_name = ident.value.clone();
-                    if let Some(col_idx) = table.column_index(&col_name) {
-                        group_columns.push(col_name);
-                        group_column_indices.push(col_idx);
-                    } else {
-                        // Try suffix match for qualified columns
-                        let suffix = format!(".{}", col_name);
-                        let mut found = false;
-                        for (i, col) in table.columns().iter().enumerate() {
-                            if col.ends_with(&suffix) {
-                                found = true;
-                                group_columns.push(col.clone());
-                                group_column_indices.push(i);
-                                break;
-                            }
-                        }
-
-                        if !found {
-                            return Err(SqawkError::ColumnNotFound(col_name));
-                        }
+        keys: &mut Vec<Expr>,
+    ) -> SqawkResult<usize> {
+        let selected = match expr {
+            Expr::Value(SqlValue::Number(n, _)) => {
+                let item = n
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|position| items.get(position.checked_sub(1)?))
+                    .ok_or_else(|| {
+                        SqawkError::InvalidSqlQuery(format!(
+                            "GROUP BY position {} is not in the select list",
+                            n
+                        ))
+                    })?;
+                match item {
+                    SelectItem::UnnamedExpr(item_expr)
+                    | SelectItem::ExprWithAlias {
+                        expr: item_expr, ..
+                    } => Some(item_expr),
+                    _ => {
+                        return Err(SqawkError::InvalidSqlQuery(format!(
+                            "GROUP BY position {} does not refer to an expression",
+                            n
+                        )))
                     }
                 }
-                Expr::CompoundIdentifier(parts) => {
-                    // Qualified column reference (table.column)
-                    let qualified_name = parts
-                        .iter()
-                        .map(|ident| ident.value.clone())
-                        .collect::<Vec<_>>()
-                        .join(".");
-
-                    if let Some(col_idx) = table.column_index(&qualified_name) {
-                        group_columns.push(qualified_name);
-                        group_column_indices.push(col_idx);
-                    } else {
-                        // Try suffix match
-                        if parts.len() == 2 {
-                            let suffix = format!("{}.{}", parts[0].value, parts[1].value);
-
-                            let mut found = false;
-                            for (i, col) in table.columns().iter().enumerate() {
-                                if col.ends_with(&suffix) {
-                                    found = true;
-                                    group_columns.push(col.clone());
-                                    group_column_indices.push(i);
-                                    break;
-                                }
-                            }
+            }
+            Expr::Identifier(ident) if self.column_ref_index(expr, table).is_none() => {
+                items.iter().find_map(|item| match item {
+                    SelectItem::ExprWithAlias {
+                        expr: item_expr,
+                        alias,
+                    } if alias.value == ident.value => Some(item_expr),
+                    _ => None,
+                })
+            }
+            _ => None,
+        };
 
-                            if !found {
-                                return Err(SqawkError::ColumnNotFound(qualified_name));
-                            }
-                        } else {
-                            return Err(SqawkError::ColumnNotFound(qualified_name));
-                        }
-                    }
-                }
-                _ => {
-                    return Err(SqawkError::UnsupportedSqlFeature(
-                        "Only simple column references are supported in GROUP BY".to_string(),
-                    ));
-                }
+        if let Some(selected) = selected {
+            if self.is_aggregate_call(selected) {
+                return Err(SqawkError::InvalidSqlQuery(format!(
+                    "Aggregate function '{}' cannot be used in GROUP BY",
+                    selected
+                )));
             }
         }
+        let key = selected.unwrap_or(expr);
 
-        // Group the rows based on GROUP BY columns
-        let mut groups: std::collections::HashMap<Vec<Value>, Vec<usize>> =
-            std::collections::HashMap::new();
-
-        for (row_idx, row) in table.rows().iter().enumerate() {
-            // Build the group key from the values of GROUP BY columns
-            let group_key: Vec<Value> = group_column_indices
-                .iter()
-                .map(|&col_idx| row[col_idx].clone())
-                .collect();
-
-            // Add this row's index to the appropriate group
-            groups.entry(group_key).or_default().push(row_idx);
+        if let Some(idx) = self.find_grouping_key(key, keys, table) {
+            return Ok(idx);
         }
+        if matches!(key, Expr::Identifier(_) | Expr::CompoundIdentifier(_)) {
+            self.get_column_ref_index(key, table)?;
+        }
+        keys.push(key.clone());
+        Ok(keys.len() - 1)
+    }
 
-        // Prepare the result table columns (GROUP BY columns + aggregate function results)
-        let mut result_columns = group_columns.clone();
-
-        // Process each SELECT item to identify function columns
-        let mut function_info = Vec::new();
-        for item in items {
-            match item {
-                SelectItem::UnnamedExpr(expr) => {
-                    // Handle simple column references that should match GROUP BY columns
-                    if let Expr::Identifier(ident) = expr {
-                        // Skip if this column is already in result_columns (GROUP BY column)
-                        if table.column_index(&ident.value).is_some()
-                            && !group_columns.contains(&ident.value)
-                        {
-                            // Non-aggregated column not in GROUP BY is not allowed
-                            return Err(SqawkError::InvalidSqlQuery(
-                                format!("Column '{}' must appear in the GROUP BY clause or be used in an aggregate function", ident.value)
-                            ));
-                        }
-                    } else if let Expr::Function(func) = expr {
-                        // Handle aggregate function
-                        let func_name = func
-                            .name
-                            .0
-                            .first()
-                            .map(|i| i.value.clone())
-                            .unwrap_or_default();
-
-                        // Check if this is a supported aggregate function
-                        if let Some(_agg_func) = AggregateFunction::from_name(&func_name) {
-                            // Process the function arguments
-                            if func.args.len() != 1 {
-                                return Err(SqawkError::InvalidSqlQuery(format!(
-                                    "{} function requires exactly one argument",
-                                    func_name
-                                )));
-                            }
-
-                            // Add the function column to results
-                            result_columns.push(func_name.clone());
+    /// Find the grouping key an expression refers to
+    ///
+    /// Expressions match a key when they are identical or are column references
+    /// that resolve to the same column (e.g. `name` and `users.name`).
+    fn find_grouping_key(&self, expr: &Expr, keys: &[Expr], table: &Table) -> Option<usize> {
+        let column = self.column_ref_index(expr, table);
+        keys.iter().position(|key| {
+            key == expr || (column.is_some() && self.column_ref_index(key, table) == column)
+        })
+    }
 
-                            // Store function info for later execution
-                            function_info.push((func_name, func.args[0].clone(), None));
-                        } else {
-                            return Err(SqawkError::UnsupportedSqlFeature(format!(
-                                "Unsupported function: {}",
-                                func_name
-                            )));
-                        }
-                    } else {
-                        return Err(SqawkError::UnsupportedSqlFeature(
-                            "Only column references and aggregate functions are supported in GROUP BY queries".to_string()
-                        ));
-                    }
-                }
-                SelectItem::ExprWithAlias { expr, alias } => {
-                    // Handle expressions with aliases
-                    if let Expr::Function(func) = expr {
-                        let func_name = func
-                            .name
-                            .0
-                            .first()
-                            .map(|i| i.value.clone())
-                            .unwrap_or_default();
+    /// Resolve a column reference expression to its column index
+    ///
+    /// Unqualified names match exactly or as the suffix of a qualified column, and
+    /// qualified names match exactly or as a suffix in join results.
+    ///
+    /// # Returns
+    /// * `Some(index)` if the expression is a column reference to an existing column
+    /// * `None` otherwise, including for an ambiguous reference
+    fn column_ref_index(&self, expr: &Expr, table: &Table) -> Option<usize> {
+        self.get_column_ref_index(expr, table).ok()
+    }
 
-                        // Check if this is a supported aggregate function
-                        if let Some(_agg_func) = AggregateFunction::from_name(&func_name) {
-                            // Process the function arguments
-                            if func.args.len() != 1 {
-                                return Err(SqawkError::InvalidSqlQuery(format!(
-                                    "{} function requires exactly one argument",
-                                    func_name
-                                )));
-                            }
+    /// Resolve a column reference expression to its column index, or explain why not
+    ///
+    /// # Returns
+    /// * The index of the referenced column
+    /// * `Err` if the column doesn't exist or an unqualified name is ambiguous
+    fn get_column_ref_index(&self, expr: &Expr, table: &Table) -> SqawkResult<usize> {
+        let idx = match expr {
+            Expr::Identifier(ident) => unqualified_column_index(&ident.value, table)?,
+            Expr::CompoundIdentifier(parts) => qualified_column_index(parts, table),
+            _ => None,
+        };
+        idx.ok_or_else(|| SqawkError::ColumnNotFound(expr.to_string()))
+    }
 
-                            // Add the aliased column to results
-                            result_columns.push(alias.value.clone());
+    /// Check whether an expression is an aggregate function call, with or without FILTER
+    fn is_aggregate_call(&self, expr: &Expr) -> bool {
+        match split_aggregate_filter(expr) {
+            (Expr::Function(func), _) => self
+                .database
+                .functions()
+                .is_aggregate(func.name.0.first().map(|i| i.value.as_str()).unwrap_or("")),
+            _ => false,
+        }
+    }
 
-                            // Store function info for later execution with alias
-                            function_info.push((
-                                func_name,
-                                func.args[0].clone(),
-                                Some(alias.value.clone()),
-                            ));
-                        } else {
-                            return Err(SqawkError::UnsupportedSqlFeature(format!(
-                                "Unsupported function: {}",
-                                func_name
-                            )));
-                        }
-                    } else {
-                        return Err(SqawkError::UnsupportedSqlFeature(
-                            "Only aggregate functions can have aliases in GROUP BY queries"
-                                .to_string(),
-                        ));
-                    }
-                }
-                SelectItem::Wildcard(_) => {
-                    return Err(SqawkError::UnsupportedSqlFeature(
-                        "Wildcard (*) is not supported in GROUP BY queries".to_string(),
-                    ));
-                }
+    /// Resolve an aggregate function call against the input table
+    ///
+    /// The first argument is the aggregated value; any further arguments are
+    /// constants passed to the aggregate when its accumulators are created, such
+    /// as the separator of GROUP_CONCAT or the fraction of PERCENTILE_CONT.
+    ///
+    /// # Arguments
+    /// * `aggregator` - The aggregate function being called
+    /// * `func` - The parsed function call, including DISTINCT and ORDER BY
+    /// * `filter` - The condition of a FILTER (WHERE ...) clause, if any
+    /// * `table` - The table the aggregate runs over
+    ///
+    /// # Returns
+    /// * The resolved call
+    /// * `Err` if there is no argument or an argument cannot be resolved
+    fn resolve_aggregate_call<'f>(
+        &self,
+        aggregator: &'f dyn Aggregator,
+        func: &sqlparser::ast::Function,
+        filter: Option<&Expr>,
+        table: &Table,
+    ) -> SqawkResult<AggregateCall<'f>> {
+        let (value_arg, param_args) = func.args.split_first().ok_or_else(|| {
+            SqawkError::InvalidSqlQuery(format!(
+                "{} function requires at least one argument",
+                aggregator.name()
+            ))
+        })?;
+
+        let mut params = Vec::with_capacity(param_args.len());
+        for arg in param_args {
+            match arg {
+                sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                    expr,
+                )) => params.push(self.evaluate_expr(expr)?),
                 _ => {
-                    return Err(SqawkError::UnsupportedSqlFeature(
-                        "Unsupported SELECT item in GROUP BY query".to_string(),
-                    ));
+                    return Err(SqawkError::UnsupportedSqlFeature(format!(
+                        "Unsupported argument to {}: {}",
+                        aggregator.name(),
+                        arg
+                    )))
                 }
             }
         }
 
-        // Create the result table
-        let mut result_table = Table::new("grouped_result", result_columns, None);
-
-        // Generate a row for each group
-        for (group_key, row_indices) in groups {
-            let mut result_row = Vec::new();
-
-            // Add the GROUP BY column values
-            result_row.extend(group_key);
-
-            // Apply aggregate functions to each group
-            for (func_name, func_arg, _alias) in &function_info {
-                // Extract values for this function's column in this group
-                let mut group_values = Vec::new();
+        let mut order_by = Vec::with_capacity(func.order_by.len());
+        for order in &func.order_by {
+            let key = sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                order.expr.clone(),
+            ));
+            order_by.push((
+                self.resolve_aggregate_input(&key, table)?,
+                order.asc.unwrap_or(true),
+            ));
+        }
 
-                for &row_idx in &row_indices {
-                    if let sqlparser::ast::FunctionArg::Unnamed(expr) = func_arg {
-                        match expr {
-                            sqlparser::ast::FunctionArgExpr::Wildcard => {
-                                // For COUNT(*), one value per row
-                                group_values.push(Value::Integer(1));
-                            }
-                            sqlparser::ast::FunctionArgExpr::QualifiedWildcard(_) => {
-                                // For COUNT(table.*), one value per row like COUNT(*)
-                                group_values.push(Value::Integer(1));
-                            }
-                            sqlparser::ast::FunctionArgExpr::Expr(expr) => {
-                                match expr {
-                                    Expr::Identifier(ident) => {
-                                        // Get column index
-                                        if let Some(col_idx) = table.column_index(&ident.value) {
-                                            group_values
-                                                .push(table.rows()[row_idx][col_idx].clone());
-                                        } else {
-                                            // Try suffix match for qualified columns
-                                            let suffix = format!(".{}", ident.value);
-                                            let mut found = false;
-                                            let mut value = Value::Null;
-
-                                            for (col_idx, col_name) in
-                                                table.columns().iter().enumerate()
-                                            {
-                                                if col_name.ends_with(&suffix) {
-                                                    found = true;
-                                                    value = table.rows()[row_idx][col_idx].clone();
-                                                    break;
-                                                }
-                                            }
-
-                                            if found {
-                                                group_values.push(value);
-                                            } else {
-                                                return Err(SqawkError::ColumnNotFound(
-                                                    ident.value.clone(),
-                                                ));
-                                            }
-                                        }
-                                    }
-                                    Expr::CompoundIdentifier(parts) => {
-                                        // Handle qualified column references
-                                        let qualified_name = parts
-                                            .iter()
-                                            .map(|ident| ident.value.clone())
-                                            .collect::<Vec<_>>()
-                                            .join(".");
-
-                                        if let Some(col_idx) = table.column_index(&qualified_name) {
-                                            group_values
-                                                .push(table.rows()[row_idx][col_idx].clone());
-                                        } else {
-                                            // Try suffix match
-                                            if parts.len() == 2 {
-                                                let suffix = format!(
-                                                    "{}.{}",
-                                                    parts[0].value, parts[1].value
-                                                );
-
-                                                let mut found = false;
-                                                let mut value = Value::Null;
-
-                                                for (col_idx, col_name) in
-                                                    table.columns().iter().enumerate()
-                                                {
-                                                    if col_name.ends_with(&suffix) {
-                                                        found = true;
-                                                        value =
-                                                            table.rows()[row_idx][col_idx].clone();
-                                                        break;
-                                                    }
-                                                }
-
-                                                if found {
-                                                    group_values.push(value);
-                                                } else {
-                                                    return Err(SqawkError::ColumnNotFound(
-                                                        qualified_name,
-                                                    ));
-                                                }
-                                            } else {
-                                                return Err(SqawkError::ColumnNotFound(
-                                                    qualified_name,
-                                                ));
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(SqawkError::UnsupportedSqlFeature(
-                                            "Only column references are supported in aggregate functions".to_string()
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        return Err(SqawkError::UnsupportedSqlFeature(
-                            "Only unnamed arguments are supported in aggregate functions"
-                                .to_string(),
-                        ));
-                    }
-                }
+        Ok(AggregateCall {
+            aggregator,
+            params,
+            input: self.resolve_aggregate_input(value_arg, table)?,
+            distinct: func.distinct,
+            order_by,
+            filter: filter.cloned(),
+        })
+    }
 
-                // Execute the aggregate function on this group's values
-                if let Some(agg_func) = AggregateFunction::from_name(func_name) {
-                    let result_value = agg_func.execute(&group_values)?;
-                    result_row.push(result_value);
-                } else {
-                    return Err(SqawkError::UnsupportedSqlFeature(format!(
-                        "Unsupported function: {}",
-                        func_name
-                    )));
-                }
+    /// Feed one input row to an aggregate call's accumulator
+    ///
+    /// Rows that do not satisfy the call's FILTER clause are skipped.
+    ///
+    /// # Arguments
+    /// * `call` - The resolved aggregate call
+    /// * `accumulator` - The accumulator of the row's group
+    /// * `row` - The input row
+    /// * `table` - The table metadata for column resolution
+    ///
+    /// # Returns
+    /// * `Ok(())` if the row was aggregated or filtered out
+    /// * `Err` if the filter, the input or a sort key fails to evaluate
+    fn update_aggregate(
+        &self,
+        call: &AggregateCall,
+        accumulator: &mut GroupAccumulator,
+        row: &[Value],
+        table: &Table,
+    ) -> SqawkResult<()> {
+        if let Some(filter) = &call.filter {
+            if !self.evaluate_condition(filter, row, table)? {
+                return Ok(());
             }
-
-            // Add this group's result row to the table
-            result_table.add_row(result_row)?;
         }
 
-        Ok(result_table)
+        let value = self.aggregate_input_value(&call.input, row, table)?;
+        let keys = call
+            .order_by
+            .iter()
+            .map(|(key, _)| self.aggregate_input_value(key, row, table))
+            .collect::<SqawkResult<Vec<_>>>()?;
+        accumulator.update(call, value, keys)
+    }
+
+    /// Get an aggregate's input value for a row
+    fn aggregate_input_value(
+        &self,
+        input: &AggregateInput,
+        row: &[Value],
+        table: &Table,
+    ) -> SqawkResult<Value> {
+        match input {
+            AggregateInput::EveryRow => Ok(Value::Integer(1)),
+            AggregateInput::Column(idx) => Ok(row[*idx].clone()),
+            AggregateInput::Expr(expr) => self.evaluate_expr_with_row(expr, row, table),
+        }
     }
 
-    /// Get values for a function argument
+    /// Resolve the argument of an aggregate function to its per-row input
     ///
-    /// This function extracts all values from a table column specified in an aggregate
-    /// function argument. It handles special cases like:
-    /// - COUNT(*) wildcard (returns placeholder values)
+    /// The argument is resolved once per query so that aggregation can then
+    /// stream over the rows. It handles special cases like:
+    /// - COUNT(*) wildcard (a placeholder value for every row)
     /// - Simple column references (e.g., "age")
     /// - Qualified column references (e.g., "users.age")
     /// - Column name resolution in join results
+    /// - Any other expression, evaluated per row (e.g., "price * quantity")
     ///
     /// # Arguments
-    /// * `arg` - The SQL function argument (column reference or wildcard)
+    /// * `arg` - The SQL function argument (expression or wildcard)
     /// * `table` - The source table containing the column data
     ///
     /// # Returns
-    /// * A vector of all values from the specified column
-    /// * For COUNT(*), a vector of placeholder values (one per row)
+    /// * The input to feed the aggregate for each row
     /// * `Err` if the column doesn't exist or the argument type is unsupported
-    fn get_values_for_function_arg(
+    fn resolve_aggregate_input(
         &self,
         arg: &sqlparser::ast::FunctionArg,
         table: &Table,
-    ) -> SqawkResult<Vec<Value>> {
+    ) -> SqawkResult<AggregateInput> {
         match arg {
             sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Wildcard) => {
-                // For COUNT(*), return a list of non-null placeholders, one for each row
-                Ok(table.rows().iter().map(|_| Value::Integer(1)).collect())
+                // For COUNT(*), a non-null placeholder for each row
+                Ok(AggregateInput::EveryRow)
             }
             sqlparser::ast::FunctionArg::Unnamed(
                 sqlparser::ast::FunctionArgExpr::QualifiedWildcard(_),
             ) => {
-                // For COUNT(table.*), return a list of non-null placeholders, one for each row
-                Ok(table.rows().iter().map(|_| Value::Integer(1)).collect())
+                // For COUNT(table.*), a non-null placeholder for each row
+                Ok(AggregateInput::EveryRow)
             }
             sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(expr)) => {
                 match expr {
-                    Expr::Identifier(ident) => {
-                        // Get column index
-                        let col_idx = match table.column_index(&ident.value) {
-                            Some(idx) => idx,
-                            None => {
-                                // Try suffix match for qualified columns
-                                let mut found = false;
-                                let mut idx = 0;
-                                for (i, col) in table.columns().iter().enumerate() {
-                                    if col.ends_with(&format!(".{}", ident.value)) {
-                                        found = true;
-                                        idx = i;
-                                        break;
-                                    }
-                                }
-
-                                if found {
-                                    idx
-                                } else {
-                                    return Err(SqawkError::ColumnNotFound(ident.value.clone()));
-                                }
-                            }
-                        };
-
-                        // Extract all values for this column
-                        Ok(table
-                            .rows()
-                            .iter()
-                            .map(|row| row[col_idx].clone())
-                            .collect())
-                    }
-                    Expr::CompoundIdentifier(parts) => {
-                        // Handle qualified column references like table.column
-                        let qualified_name = parts
-                            .iter()
-                            .map(|ident| ident.value.clone())
-                            .collect::<Vec<_>>()
-                            .join(".");
-
-                        // Get column index
-                        let col_idx = match table.column_index(&qualified_name) {
-                            Some(idx) => idx,
-                            None => {
-                                // Try suffix match
-                                if parts.len() == 2 {
-                                    let suffix = format!("{}.{}", parts[0].value, parts[1].value);
-
-                                    let mut found = false;
-                                    let mut idx = 0;
-                                    for (i, col) in table.columns().iter().enumerate() {
-                                        if col.ends_with(&suffix) {
-                                            found = true;
-                                            idx = i;
-                                            break;
-                                        }
-                                    }
-
-                                    if found {
-                                        idx
-                                    } else {
-                                        return Err(SqawkError::ColumnNotFound(qualified_name));
-                                    }
-                                } else {
-                                    return Err(SqawkError::ColumnNotFound(qualified_name));
-                                }
-                            }
-                        };
-
-                        // Extract all values for this column
-                        Ok(table
-                            .rows()
-                            .iter()
-                            .map(|row| row[col_idx].clone())
-                            .collect())
-                    }
-                    _ => Err(SqawkError::UnsupportedSqlFeature(
-                        "Only column references are supported in aggregate functions".to_string(),
+                    Expr::Identifier(_) | Expr::CompoundIdentifier(_) => self
+                        .get_column_ref_index(expr, table)
+                        .map(AggregateInput::Column),
+                    _ if self.is_aggregate_call(expr) => Err(SqawkError::InvalidSqlQuery(
+                        "Aggregate function calls cannot be nested".to_string(),
                     )),
+                    _ => Ok(AggregateInput::Expr(expr.clone())),
                 }
             }
             _ => Err(SqawkError::UnsupportedSqlFeature(
@@ -2927,7 +4399,7 @@ impl<'a> SqlExecutor<'a> {
     ///
     /// 1. First attempt: Exact match with a column name in the table
     /// 2. Second attempt: Match as suffix of qualified column names (e.g., 'name' matches 'table1.name')
-    /// 3. If multiple matches found in step 2, use the first match (left-most table in the FROM clause)
+    /// 3. If step 2 matches columns of more than one table, the reference is ambiguous
     ///
     /// The column resolution logic is critical for SQL's natural join behavior
     /// and for handling simple column references in queries involving multiple tables.
@@ -2937,21 +4409,10 @@ impl<'a> SqlExecutor<'a> {
         row: &[Value],
         table: &Table,
     ) -> SqawkResult<Value> {
-        // First try as an exact column name match
-        if let Some(idx) = table.column_index(column_name) {
-            return self.get_row_value_at_index(idx, row);
+        match unqualified_column_index(column_name, table)? {
+            Some(idx) => self.get_row_value_at_index(idx, row),
+            None => Err(SqawkError::ColumnNotFound(column_name.to_string())),
         }
-
-        // Try to find a matching qualified column (e.g., for "name", match "table.name")
-        let suffix = format!(".{}", column_name);
-        for (i, col) in table.columns().iter().enumerate() {
-            if col.ends_with(&suffix) {
-                return self.get_row_value_at_index(i, row);
-            }
-        }
-
-        // If we got here, the column wasn't found
-        Err(SqawkError::ColumnNotFound(column_name.to_string()))
     }
 
     /// Resolves a qualified column reference like 'table.column' or 'schema.table.column'
@@ -2963,7 +4424,9 @@ impl<'a> SqlExecutor<'a> {
     /// 1. Build the fully qualified name from the provided parts
     /// 2. Look for an exact match in the table's column names
     /// 3. If not found, attempt suffix matching for JOIN scenarios
-    ///    (e.g., 'customers.id' might match 'orders_customers.id' in a join)
+    ///    (e.g., 'customers.id' might match 'orders.customers.id' in a join)
+    /// 4. If not found, match 'table.column' against the unqualified columns of
+    ///    a table (or alias) of that name
     ///
     /// This approach properly handles column name disambiguation in queries
     /// involving multiple tables, particularly for JOIN operations where
@@ -2974,54 +4437,8 @@ impl<'a> SqlExecutor<'a> {
         row: &[Value],
         table: &Table,
     ) -> SqawkResult<Value> {
-        // Build the fully qualified column name from parts
-        let qualified_name = parts
-            .iter()
-            .map(|ident| ident.value.clone())
-            .collect::<Vec<_>>()
-            .join(".");
-
-        // Try to find an exact match for the qualified column
-        if let Some(idx) = table.column_index(&qualified_name) {
-            return self.get_row_value_at_index(idx, row);
-        }
-
-        // If we didn't find an exact match, try a suffix match
-        // This helps with cases like "users.id" matching "users_orders_cross.users.id"
-        if parts.len() == 2 {
-            return self.try_suffix_match(parts, row, table);
-        }
-
-        // If we got here, the qualified column wasn't found
-        Err(SqawkError::ColumnNotFound(qualified_name))
-    }
-
-    /// Try to match a column reference as a suffix
-    ///
-    /// This helps with joins where the full reference might be something like
-    /// 'users_orders_cross.users.id' but the user references 'users.id'
-    fn try_suffix_match(
-        &self,
-        parts: &[sqlparser::ast::Ident],
-        row: &[Value],
-        table: &Table,
-    ) -> SqawkResult<Value> {
-        let suffix = format!("{}.{}", parts[0].value, parts[1].value);
-
-        for (i, col) in table.columns().iter().enumerate() {
-            if col.ends_with(&suffix) {
-                return self.get_row_value_at_index(i, row);
-            }
-        }
-
-        // If no suffix match was found, report the column as not found
-        Err(SqawkError::ColumnNotFound(
-            parts
-                .iter()
-                .map(|ident| ident.value.clone())
-                .collect::<Vec<_>>()
-                .join("."),
-        ))
+        let idx = self.get_qualified_column_index(parts, table)?;
+        self.get_row_value_at_index(idx, row)
     }
 
     /// Safely retrieves a value from a row at the specified column index with bounds checking
@@ -3144,20 +4561,15 @@ impl<'a> SqlExecutor<'a> {
                 let name = col.name.value;
 
                 // Convert SQL data type to our internal DataType
-                let data_type = match col.data_type.to_string().to_uppercase().as_str() {
-                    "INTEGER" | "INT" => DataType::Integer,
-                    "REAL" | "FLOAT" | "DOUBLE" => DataType::Float,
-                    "TEXT" | "VARCHAR" | "CHAR" | "STRING" => DataType::Text,
-                    "BOOLEAN" | "BOOL" => DataType::Boolean,
-                    other => {
-                        // Default to TEXT for unsupported types
-                        eprintln!(
-                            "Warning: Unsupported data type '{}', using TEXT instead",
-                            other
-                        );
-                        DataType::Text
-                    }
-                };
+                let type_name = col.data_type.to_string();
+                let data_type = DataType::from_sql_name(&type_name).unwrap_or_else(|| {
+                    // Default to TEXT for unsupported types
+                    eprintln!(
+                        "Warning: Unsupported data type '{}', using TEXT instead",
+                        type_name.to_uppercase()
+                    );
+                    DataType::Text
+                });
 
                 ColumnDefinition { name, data_type }
             })
@@ -3414,7 +4826,8 @@ impl<'a> SqlExecutor<'a> {
     /// This function writes any tables that have been modified during execution
     /// (e.g., through INSERT, UPDATE, or DELETE statements) back to their source files.
     /// Only tables that have been modified will be saved, preserving the original
-    /// files if no changes were made.
+    /// files if no changes were made. Tables read from standard input have no file
+    /// and are skipped with a warning.
     ///
     /// # Returns
     /// * `Ok(())` if all modified tables were saved successfully
@@ -3422,6 +4835,15 @@ impl<'a> SqlExecutor<'a> {
     pub fn save_modified_tables(&self) -> Result<usize> {
         let mut count = 0;
         for table_name in &self.modified_tables {
+            // A table read from standard input has no file to write back to
+            if self.file_handler.is_stdin_table(table_name) {
+                eprintln!(
+                    "Warning: not saving table '{}', which was read from standard input",
+                    table_name
+                );
+                continue;
+            }
+
             // Use the file handler to write the table back to its source file
             self.file_handler.save_table(table_name)?;
             count += 1;
@@ -3508,6 +4930,9 @@ impl<'a> SqlExecutor<'a> {
 
     /// Execute SQL statement and return a ResultSet for REPL mode
     ///
+    /// Values are rendered as text with the configured value format, the same
+    /// way the text output formats display them.
+    ///
     /// # Arguments
     /// * `sql` - SQL statement to execute
     ///
@@ -3518,7 +4943,7 @@ impl<'a> SqlExecutor<'a> {
 
         Ok(result.map(|table| ResultSet {
             columns: table.columns().to_vec(),
-            rows: table.rows_as_strings(),
+            rows: output::rows_as_strings(&table, self.config.value_format()),
         }))
     }
 
@@ -1,13 +1,33 @@
-//! String function implementation module for sqawk
+//! Scalar function implementation module for sqawk
 //!
-//! This module implements standard SQL string functions including:
-//! - UPPER(): Convert string to uppercase
-//! - LOWER(): Convert string to lowercase
-//! - TRIM(): Remove leading and trailing whitespace
+//! This module implements the built-in SQL scalar functions. Despite the module
+//! name it covers more than strings. Each `StringFunction` implements the
+//! `ScalarFunction` trait and is registered with the `FunctionRegistry` under
+//! every name in `StringFunction::NAMES`.
+//!
+//! String functions:
+//! - UPPER(), LOWER(): Convert string case
+//! - TRIM(), LTRIM(), RTRIM(): Remove whitespace or a set of characters
 //! - SUBSTR(): Extract a substring
 //! - REPLACE(): Replace occurrences of a substring
+//! - LENGTH(), INSTR(): Measure and search strings
+//! - LPAD(), RPAD(): Pad strings to a length
+//! - CONCAT(), CONCAT_WS(): Join values into one string
+//! - REVERSE(), INITCAP(), SPLIT_PART(): Reshape strings
+//!
+//! NULL handling functions:
+//! - COALESCE(), IFNULL(), NULLIF()
+//!
+//! Math functions:
+//! - ABS(), ROUND(), FLOOR(), CEIL(), POWER(), SQRT(), LN(), SIGN()
+//!
+//! Unless noted otherwise, a NULL argument produces a NULL result. UPPER, LOWER,
+//! TRIM, LTRIM, RTRIM, SUBSTR and REPLACE require string arguments; the other
+//! string functions convert numbers and booleans to their text form.
 
+use crate::arithmetic;
 use crate::error::{SqawkError, SqawkResult};
+use crate::functions::{function_names, ArgType, ScalarFunction, Signature};
 use crate::table::Value;
 
 /// Enum of supported string functions
@@ -23,21 +43,86 @@ pub enum StringFunction {
     Substr,
     /// Replace occurrences of a substring - REPLACE(str, search, replace)
     Replace,
+    /// Remove leading whitespace or characters - LTRIM(str[, chars])
+    Ltrim,
+    /// Remove trailing whitespace or characters - RTRIM(str[, chars])
+    Rtrim,
+    /// Number of characters in a string - LENGTH(str)
+    Length,
+    /// 1-based position of a substring, or 0 if absent - INSTR(str, search)
+    Instr,
+    /// Left-pad a string to a length - LPAD(str, length[, pad])
+    Lpad,
+    /// Right-pad a string to a length - RPAD(str, length[, pad])
+    Rpad,
+    /// Concatenate values, skipping NULLs - CONCAT(value, ...)
+    Concat,
+    /// Concatenate values with a separator, skipping NULLs - CONCAT_WS(sep, value, ...)
+    ConcatWs,
+    /// Reverse the characters of a string - REVERSE(str)
+    Reverse,
+    /// Capitalize the first letter of each word - INITCAP(str)
+    Initcap,
+    /// Field of a delimited string - SPLIT_PART(str, delimiter, n)
+    SplitPart,
+    /// First non-NULL argument - COALESCE(value, ...)
+    Coalesce,
+    /// Second argument if the first is NULL - IFNULL(value, default)
+    IfNull,
+    /// NULL if both arguments are equal, else the first - NULLIF(a, b)
+    NullIf,
+    /// Absolute value - ABS(x)
+    Abs,
+    /// Round half away from zero - ROUND(x[, digits])
+    Round,
+    /// Largest integral value not greater than x - FLOOR(x)
+    Floor,
+    /// Smallest integral value not less than x - CEIL(x)
+    Ceil,
+    /// Raise to a power - POWER(x, y)
+    Power,
+    /// Square root - SQRT(x)
+    Sqrt,
+    /// Natural logarithm - LN(x)
+    Ln,
+    /// Sign of a number (-1, 0 or 1) - SIGN(x)
+    Sign,
 }
 
-impl StringFunction {
-    /// Create a StringFunction from its name
-    pub fn from_name(name: &str) -> Option<Self> {
-        match name.to_uppercase().as_str() {
-            "UPPER" => Some(StringFunction::Upper),
-            "LOWER" => Some(StringFunction::Lower),
-            "TRIM" => Some(StringFunction::Trim),
-            "SUBSTR" => Some(StringFunction::Substr),
-            "REPLACE" => Some(StringFunction::Replace),
-            _ => None,
-        }
-    }
+/// The longest string, in characters, that LPAD and RPAD will produce
+const MAX_PAD_LENGTH: i64 = 1_000_000;
+
+function_names!(StringFunction {
+    Upper => "UPPER",
+    Lower => "LOWER",
+    Trim => "TRIM",
+    Substr => "SUBSTR",
+    Replace => "REPLACE",
+    Ltrim => "LTRIM",
+    Rtrim => "RTRIM",
+    Length => "LENGTH" | "CHAR_LENGTH",
+    Instr => "INSTR",
+    Lpad => "LPAD",
+    Rpad => "RPAD",
+    Concat => "CONCAT",
+    ConcatWs => "CONCAT_WS",
+    Reverse => "REVERSE",
+    Initcap => "INITCAP",
+    SplitPart => "SPLIT_PART",
+    Coalesce => "COALESCE",
+    IfNull => "IFNULL",
+    NullIf => "NULLIF",
+    Abs => "ABS",
+    Round => "ROUND",
+    Floor => "FLOOR",
+    Ceil => "CEIL" | "CEILING",
+    Power => "POWER" | "POW",
+    Sqrt => "SQRT",
+    Ln => "LN",
+    Sign => "SIGN",
+});
 
+impl StringFunction {
     /// Apply the string function to its arguments
     pub fn apply(&self, args: &[Value]) -> SqawkResult<Value> {
         match self {
@@ -46,6 +131,28 @@ impl StringFunction {
             StringFunction::Trim => self.apply_trim(args),
             StringFunction::Substr => self.apply_substr(args),
             StringFunction::Replace => self.apply_replace(args),
+            StringFunction::Ltrim => self.apply_side_trim(args, "LTRIM", true, false),
+            StringFunction::Rtrim => self.apply_side_trim(args, "RTRIM", false, true),
+            StringFunction::Length => self.apply_length(args),
+            StringFunction::Instr => self.apply_instr(args),
+            StringFunction::Lpad => self.apply_pad(args, "LPAD", true),
+            StringFunction::Rpad => self.apply_pad(args, "RPAD", false),
+            StringFunction::Concat => self.apply_concat(args),
+            StringFunction::ConcatWs => self.apply_concat_ws(args),
+            StringFunction::Reverse => self.apply_reverse(args),
+            StringFunction::Initcap => self.apply_initcap(args),
+            StringFunction::SplitPart => self.apply_split_part(args),
+            StringFunction::Coalesce => self.apply_coalesce(args),
+            StringFunction::IfNull => self.apply_ifnull(args),
+            StringFunction::NullIf => self.apply_nullif(args),
+            StringFunction::Abs => self.apply_abs(args),
+            StringFunction::Round => self.apply_round(args),
+            StringFunction::Floor => self.apply_floor_ceil(args, "FLOOR", f64::floor),
+            StringFunction::Ceil => self.apply_floor_ceil(args, "CEIL", f64::ceil),
+            StringFunction::Power => self.apply_power(args),
+            StringFunction::Sqrt => self.apply_sqrt(args),
+            StringFunction::Ln => self.apply_ln(args),
+            StringFunction::Sign => self.apply_sign(args),
         }
     }
 
@@ -58,20 +165,8 @@ impl StringFunction {
             ));
         }
 
-        // Handle input value
-        match &args[0] {
-            // Pass NULL through
-            Value::Null => Ok(Value::Null),
-
-            // Convert string to lowercase
-            Value::String(s) => Ok(Value::String(s.to_lowercase())),
-
-            // Error for non-string inputs
-            _ => Err(SqawkError::TypeError(format!(
-                "LOWER function requires a string argument, got {:?}",
-                args[0]
-            ))),
-        }
+        // Convert the text to lowercase, passing NULL through
+        Ok(text_arg(args, 0).map_or(Value::Null, |s| Value::String(s.to_lowercase())))
     }
 
     /// Apply UPPER function - convert to uppercase
@@ -83,45 +178,56 @@ impl StringFunction {
             ));
         }
 
-        // Handle input value
-        match &args[0] {
-            // Pass NULL through
-            Value::Null => Ok(Value::Null),
-
-            // Convert string to uppercase
-            Value::String(s) => Ok(Value::String(s.to_uppercase())),
-
-            // Error for non-string inputs
-            _ => Err(SqawkError::TypeError(format!(
-                "UPPER function requires a string argument, got {:?}",
-                args[0]
-            ))),
-        }
+        // Convert the text to uppercase, passing NULL through
+        Ok(text_arg(args, 0).map_or(Value::Null, |s| Value::String(s.to_uppercase())))
     }
 
-    /// Apply TRIM function - remove leading/trailing whitespace
+    /// Apply TRIM function - remove leading/trailing whitespace or characters
     fn apply_trim(&self, args: &[Value]) -> SqawkResult<Value> {
+        self.apply_side_trim(args, "TRIM", true, true)
+    }
+
+    /// Apply TRIM, LTRIM or RTRIM - remove whitespace or a set of characters
+    ///
+    /// With one argument, whitespace is removed. With a second argument, any of the
+    /// characters it contains are removed instead.
+    fn apply_side_trim(
+        &self,
+        args: &[Value],
+        name: &str,
+        leading: bool,
+        trailing: bool,
+    ) -> SqawkResult<Value> {
         // Validate argument count
-        if args.len() != 1 {
-            return Err(SqawkError::InvalidFunctionArguments(
-                "TRIM requires exactly one argument".to_string(),
-            ));
-        }
+        check_arg_count(name, args, 1, 2)?;
 
-        // Handle input value
-        match &args[0] {
-            // Pass NULL through
-            Value::Null => Ok(Value::Null),
+        // Get the text, passing NULL through
+        let Some(string) = text_arg(args, 0) else {
+            return Ok(Value::Null);
+        };
 
-            // Trim string
-            Value::String(s) => Ok(Value::String(s.trim().to_string())),
+        // Characters to strip, or None for whitespace
+        let chars: Option<Vec<char>> = match args.get(1) {
+            None => None,
+            Some(_) => match text_arg(args, 1) {
+                Some(chars) => Some(chars.chars().collect()),
+                None => return Ok(Value::Null),
+            },
+        };
+        let should_strip = |c: char| match &chars {
+            Some(chars) => chars.contains(&c),
+            None => c.is_whitespace(),
+        };
 
-            // Error for non-string inputs
-            _ => Err(SqawkError::TypeError(format!(
-                "TRIM function requires a string argument, got {:?}",
-                args[0]
-            ))),
+        let mut result = string.as_str();
+        if leading {
+            result = result.trim_start_matches(should_strip);
         }
+        if trailing {
+            result = result.trim_end_matches(should_strip);
+        }
+
+        Ok(Value::String(result.to_string()))
     }
 
     /// Apply SUBSTR function - extract substring
@@ -133,20 +239,9 @@ impl StringFunction {
             ));
         }
 
-        // Handle NULL input
-        if let Value::Null = args[0] {
+        // Get the text, passing NULL through
+        let Some(string) = text_arg(args, 0) else {
             return Ok(Value::Null);
-        }
-
-        // Get the string
-        let string = match &args[0] {
-            Value::String(s) => s,
-            _ => {
-                return Err(SqawkError::TypeError(format!(
-                    "First argument to SUBSTR must be a string, got {:?}",
-                    args[0]
-                )))
-            }
         };
 
         // Get the start position (1-indexed in SQL)
@@ -213,47 +308,503 @@ impl StringFunction {
             ));
         }
 
-        // Handle NULL input
-        if let Value::Null = args[0] {
+        // Get the source text, search text and replacement; any NULL gives NULL
+        let (Some(string), Some(pattern), Some(replacement)) =
+            (text_arg(args, 0), text_arg(args, 1), text_arg(args, 2))
+        else {
             return Ok(Value::Null);
+        };
+
+        // Perform the replacement
+        let result = string.replace(&pattern, &replacement);
+        Ok(Value::String(result))
+    }
+
+    /// Apply LENGTH function - number of characters in a string
+    fn apply_length(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("LENGTH", args, 1, 1)?;
+
+        match text_arg(args, 0) {
+            Some(s) => Ok(Value::Integer(s.chars().count() as i64)),
+            None => Ok(Value::Null),
         }
+    }
 
-        // Get the source string
-        let string = match &args[0] {
-            Value::String(s) => s,
-            _ => {
-                return Err(SqawkError::TypeError(format!(
-                    "First argument to REPLACE must be a string, got {:?}",
-                    args[0]
-                )))
+    /// Apply INSTR function - 1-based character position of a substring
+    fn apply_instr(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("INSTR", args, 2, 2)?;
+
+        let (Some(string), Some(search)) = (text_arg(args, 0), text_arg(args, 1)) else {
+            return Ok(Value::Null);
+        };
+
+        // Convert the byte offset into a character position
+        let position = match string.find(&search) {
+            Some(byte_idx) => string[..byte_idx].chars().count() as i64 + 1,
+            None => 0,
+        };
+
+        Ok(Value::Integer(position))
+    }
+
+    /// Apply LPAD or RPAD function - pad a string to a length
+    ///
+    /// The pad string (a single space by default) is repeated as needed. A string
+    /// longer than the target length is truncated to it. The length must be between
+    /// 0 and `MAX_PAD_LENGTH`, so a mistyped length can't exhaust memory.
+    fn apply_pad(&self, args: &[Value], name: &str, left: bool) -> SqawkResult<Value> {
+        check_arg_count(name, args, 2, 3)?;
+
+        let Some(string) = text_arg(args, 0) else {
+            return Ok(Value::Null);
+        };
+        let Some(length) = integer_arg(name, args, 1)? else {
+            return Ok(Value::Null);
+        };
+        let pad = if args.len() == 3 {
+            match text_arg(args, 2) {
+                Some(pad) => pad,
+                None => return Ok(Value::Null),
             }
+        } else {
+            " ".to_string()
         };
 
-        // Get the search pattern
-        let pattern = match &args[1] {
-            Value::String(s) => s,
-            _ => {
-                return Err(SqawkError::TypeError(format!(
-                    "Second argument to REPLACE must be a string, got {:?}",
-                    args[1]
-                )))
+        if !(0..=MAX_PAD_LENGTH).contains(&length) {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "{} length must be between 0 and {}, got {}",
+                name, MAX_PAD_LENGTH, length
+            )));
+        }
+        let length = length as usize;
+        let char_count = string.chars().count();
+
+        // Truncate when the string is already long enough, or when there is nothing to pad with
+        if char_count >= length || pad.is_empty() {
+            return Ok(Value::String(string.chars().take(length).collect()));
+        }
+
+        let padding: String = pad.chars().cycle().take(length - char_count).collect();
+        let result = if left {
+            padding + &string
+        } else {
+            string + &padding
+        };
+
+        Ok(Value::String(result))
+    }
+
+    /// Apply CONCAT function - join values, skipping NULLs
+    fn apply_concat(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("CONCAT", args, 1, usize::MAX)?;
+
+        let result: String = (0..args.len()).filter_map(|i| text_arg(args, i)).collect();
+        Ok(Value::String(result))
+    }
+
+    /// Apply CONCAT_WS function - join values with a separator, skipping NULLs
+    fn apply_concat_ws(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("CONCAT_WS", args, 2, usize::MAX)?;
+
+        let Some(separator) = text_arg(args, 0) else {
+            return Ok(Value::Null);
+        };
+
+        let parts: Vec<String> = (1..args.len()).filter_map(|i| text_arg(args, i)).collect();
+        Ok(Value::String(parts.join(&separator)))
+    }
+
+    /// Apply REVERSE function - reverse the characters of a string
+    fn apply_reverse(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("REVERSE", args, 1, 1)?;
+
+        match text_arg(args, 0) {
+            Some(s) => Ok(Value::String(s.chars().rev().collect())),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Apply INITCAP function - capitalize the first letter of each word
+    ///
+    /// Words are runs of alphanumeric characters; all other letters are lowercased.
+    fn apply_initcap(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("INITCAP", args, 1, 1)?;
+
+        let Some(string) = text_arg(args, 0) else {
+            return Ok(Value::Null);
+        };
+
+        let mut result = String::with_capacity(string.len());
+        let mut at_word_start = true;
+        for c in string.chars() {
+            if at_word_start {
+                result.extend(c.to_uppercase());
+            } else {
+                result.extend(c.to_lowercase());
             }
+            at_word_start = !c.is_alphanumeric();
+        }
+
+        Ok(Value::String(result))
+    }
+
+    /// Apply SPLIT_PART function - return the n-th field of a delimited string
+    ///
+    /// Fields are numbered from 1; a negative n counts from the end. A field
+    /// number past the end yields an empty string.
+    fn apply_split_part(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("SPLIT_PART", args, 3, 3)?;
+
+        let (Some(string), Some(delimiter)) = (text_arg(args, 0), text_arg(args, 1)) else {
+            return Ok(Value::Null);
+        };
+        let Some(n) = integer_arg("SPLIT_PART", args, 2)? else {
+            return Ok(Value::Null);
         };
+        if n == 0 {
+            return Err(SqawkError::InvalidFunctionArguments(
+                "Field position in SPLIT_PART must not be 0".to_string(),
+            ));
+        }
 
-        // Get the replacement string
-        let replacement = match &args[2] {
-            Value::String(s) => s,
-            _ => {
-                return Err(SqawkError::TypeError(format!(
-                    "Third argument to REPLACE must be a string, got {:?}",
-                    args[2]
-                )))
+        let fields: Vec<&str> = if delimiter.is_empty() {
+            vec![string.as_str()]
+        } else {
+            string.split(delimiter.as_str()).collect()
+        };
+
+        let index = if n > 0 {
+            usize::try_from(n - 1).ok()
+        } else {
+            fields.len().checked_sub(n.unsigned_abs() as usize)
+        };
+
+        let field = index.and_then(|i| fields.get(i)).copied().unwrap_or("");
+        Ok(Value::String(field.to_string()))
+    }
+
+    /// Apply COALESCE function - return the first non-NULL argument
+    fn apply_coalesce(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("COALESCE", args, 1, usize::MAX)?;
+
+        Ok(args
+            .iter()
+            .find(|v| !matches!(v, Value::Null))
+            .cloned()
+            .unwrap_or(Value::Null))
+    }
+
+    /// Apply IFNULL function - return the second argument if the first is NULL
+    fn apply_ifnull(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("IFNULL", args, 2, 2)?;
+        self.apply_coalesce(args)
+    }
+
+    /// Apply NULLIF function - return NULL if both arguments are equal
+    fn apply_nullif(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("NULLIF", args, 2, 2)?;
+
+        if args[0] != Value::Null && args[0] == args[1] {
+            Ok(Value::Null)
+        } else {
+            Ok(args[0].clone())
+        }
+    }
+
+    /// Apply ABS function - absolute value
+    fn apply_abs(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("ABS", args, 1, 1)?;
+
+        match number_arg("ABS", args, 0)? {
+            Some(Value::Integer(i)) if i < 0 => arithmetic::negate(&Value::Integer(i)),
+            Some(Value::Float(f)) => Ok(Value::Float(f.abs())),
+            Some(value) => Ok(value),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Apply ROUND function - round half away from zero to a number of decimal places
+    ///
+    /// Integers stay integers; a negative number of digits rounds to tens, hundreds, etc.
+    fn apply_round(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("ROUND", args, 1, 2)?;
+
+        let Some(value) = number_arg("ROUND", args, 0)? else {
+            return Ok(Value::Null);
+        };
+        let digits = if args.len() == 2 {
+            match integer_arg("ROUND", args, 1)? {
+                Some(digits) => digits,
+                None => return Ok(Value::Null),
             }
+        } else {
+            0
         };
 
-        // Perform the replacement
-        let result = string.replace(pattern, replacement);
-        Ok(Value::String(result))
+        match value {
+            Value::Integer(i) if digits >= 0 => Ok(Value::Integer(i)),
+            Value::Integer(i) => {
+                // Round to a power of ten; anything beyond 10^18 rounds to zero
+                let Some(factor) = u32::try_from(-digits)
+                    .ok()
+                    .and_then(|exp| 10i64.checked_pow(exp))
+                else {
+                    return Ok(Value::Integer(0));
+                };
+                // Round half away from zero in integer arithmetic to keep precision
+                let mut quotient = i / factor;
+                let remainder = i % factor;
+                if remainder.unsigned_abs() * 2 >= factor as u64 {
+                    quotient += remainder.signum();
+                }
+                Ok(quotient
+                    .checked_mul(factor)
+                    .map(Value::Integer)
+                    .unwrap_or(Value::Float(quotient as f64 * factor as f64)))
+            }
+            Value::Float(f) => {
+                let factor = 10f64.powi(digits.clamp(-308, 308) as i32);
+                // Adding 0.0 turns a negative zero into zero
+                let rounded = (f * factor).round() / factor + 0.0;
+                // Very large scale factors overflow; the value is already exact then
+                Ok(Value::Float(if rounded.is_finite() { rounded } else { f }))
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Apply FLOOR or CEIL function - round down or up to an integral value
+    ///
+    /// Integers are returned unchanged and floats stay floats.
+    fn apply_floor_ceil(
+        &self,
+        args: &[Value],
+        name: &str,
+        op: fn(f64) -> f64,
+    ) -> SqawkResult<Value> {
+        check_arg_count(name, args, 1, 1)?;
+
+        match number_arg(name, args, 0)? {
+            Some(Value::Float(f)) => Ok(Value::Float(op(f))),
+            Some(value) => Ok(value),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Apply POWER function - raise a number to a power
+    ///
+    /// An integer raised to a non-negative integer power stays an integer unless it
+    /// overflows, in which case the result is a float.
+    fn apply_power(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("POWER", args, 2, 2)?;
+
+        let (Some(base), Some(exponent)) =
+            (number_arg("POWER", args, 0)?, number_arg("POWER", args, 1)?)
+        else {
+            return Ok(Value::Null);
+        };
+
+        if let (Value::Integer(b), Value::Integer(e)) = (&base, &exponent) {
+            if let Some(result) = u32::try_from(*e).ok().and_then(|e| b.checked_pow(e)) {
+                return Ok(Value::Integer(result));
+            }
+        }
+
+        let result = as_f64(&base).powf(as_f64(&exponent));
+        if result.is_nan() {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "POWER({}, {}) is not a real number",
+                base, exponent
+            )));
+        }
+        if result.is_infinite() {
+            return Err(SqawkError::ArithmeticOverflow(format!(
+                "POWER({}, {}) is out of range",
+                base, exponent
+            )));
+        }
+
+        Ok(Value::Float(result))
+    }
+
+    /// Apply SQRT function - square root
+    fn apply_sqrt(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("SQRT", args, 1, 1)?;
+
+        let Some(value) = number_arg("SQRT", args, 0)? else {
+            return Ok(Value::Null);
+        };
+        let x = as_f64(&value);
+        if x < 0.0 {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "Cannot take the square root of negative number {}",
+                value
+            )));
+        }
+
+        Ok(Value::Float(x.sqrt()))
+    }
+
+    /// Apply LN function - natural logarithm
+    fn apply_ln(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("LN", args, 1, 1)?;
+
+        let Some(value) = number_arg("LN", args, 0)? else {
+            return Ok(Value::Null);
+        };
+        let x = as_f64(&value);
+        if x <= 0.0 {
+            return Err(SqawkError::InvalidFunctionArguments(format!(
+                "Cannot take the logarithm of non-positive number {}",
+                value
+            )));
+        }
+
+        Ok(Value::Float(x.ln()))
+    }
+
+    /// Apply SIGN function - -1, 0 or 1 according to the sign of a number
+    fn apply_sign(&self, args: &[Value]) -> SqawkResult<Value> {
+        check_arg_count("SIGN", args, 1, 1)?;
+
+        match number_arg("SIGN", args, 0)? {
+            Some(Value::Integer(i)) => Ok(Value::Integer(i.signum())),
+            Some(Value::Float(f)) if f == 0.0 || f.is_nan() => Ok(Value::Float(f)),
+            Some(Value::Float(f)) => Ok(Value::Float(f.signum())),
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
+impl ScalarFunction for StringFunction {
+    fn name(&self) -> &str {
+        StringFunction::name(self)
+    }
+
+    /// Argument counts and types of the built-ins
+    ///
+    /// Every text argument accepts any value and converts it to text, so
+    /// `UPPER(id)` works on a numeric column as `LENGTH(id)` does; the others
+    /// must be integers or numbers as declared here.
+    fn signature(&self) -> Signature {
+        use ArgType::{Any, Integer, Number};
+        match self {
+            StringFunction::Trim | StringFunction::Ltrim | StringFunction::Rtrim => {
+                Signature::optional(vec![Any], vec![Any])
+            }
+            StringFunction::Substr => Signature::optional(vec![Any, Integer], vec![Integer]),
+            StringFunction::Replace => Signature::exact(vec![Any, Any, Any]),
+            StringFunction::Upper
+            | StringFunction::Lower
+            | StringFunction::Length
+            | StringFunction::Reverse
+            | StringFunction::Initcap => Signature::exact(vec![Any]),
+            StringFunction::Instr | StringFunction::IfNull | StringFunction::NullIf => {
+                Signature::exact(vec![Any, Any])
+            }
+            StringFunction::Lpad | StringFunction::Rpad => {
+                Signature::optional(vec![Any, Integer], vec![Any])
+            }
+            StringFunction::SplitPart => Signature::exact(vec![Any, Any, Integer]),
+            StringFunction::Concat | StringFunction::Coalesce => {
+                Signature::variadic(vec![Any], Any)
+            }
+            StringFunction::ConcatWs => Signature::variadic(vec![Any, Any], Any),
+            StringFunction::Abs
+            | StringFunction::Floor
+            | StringFunction::Ceil
+            | StringFunction::Sqrt
+            | StringFunction::Ln
+            | StringFunction::Sign => Signature::exact(vec![Number]),
+            StringFunction::Round => Signature::optional(vec![Number], vec![Integer]),
+            StringFunction::Power => Signature::exact(vec![Number, Number]),
+        }
+    }
+
+    fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+        self.apply(args)
+    }
+}
+
+/// Check that a function received an acceptable number of arguments
+///
+/// # Arguments
+/// * `name` - The function name, used in the error message
+/// * `args` - The arguments passed to the function
+/// * `min` - The minimum number of arguments
+/// * `max` - The maximum number of arguments (`usize::MAX` for no limit)
+pub(crate) fn check_arg_count(
+    name: &str,
+    args: &[Value],
+    min: usize,
+    max: usize,
+) -> SqawkResult<()> {
+    if args.len() >= min && args.len() <= max {
+        return Ok(());
+    }
+
+    let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+    let expected = if min == max {
+        format!("exactly {} {}", min, plural(min))
+    } else if max == usize::MAX {
+        format!("at least {} {}", min, plural(min))
+    } else {
+        format!("{} to {} arguments", min, max)
+    };
+
+    Err(SqawkError::InvalidFunctionArguments(format!(
+        "{} requires {}, got {}",
+        name,
+        expected,
+        args.len()
+    )))
+}
+
+/// Get an argument as text, converting numbers and booleans to their text form
+///
+/// Returns None for a NULL argument.
+pub(crate) fn text_arg(args: &[Value], idx: usize) -> Option<String> {
+    match &args[idx] {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Get an integer argument, returning None for NULL
+pub(crate) fn integer_arg(name: &str, args: &[Value], idx: usize) -> SqawkResult<Option<i64>> {
+    match &args[idx] {
+        Value::Null => Ok(None),
+        Value::Integer(i) => Ok(Some(*i)),
+        other => Err(SqawkError::TypeError(format!(
+            "Argument {} to {} must be an integer, got {:?}",
+            idx + 1,
+            name,
+            other
+        ))),
+    }
+}
+
+/// Get a numeric (integer or float) argument, returning None for NULL
+fn number_arg(name: &str, args: &[Value], idx: usize) -> SqawkResult<Option<Value>> {
+    match &args[idx] {
+        Value::Null => Ok(None),
+        value @ (Value::Integer(_) | Value::Float(_)) => Ok(Some(value.clone())),
+        other => Err(SqawkError::TypeError(format!(
+            "Argument {} to {} must be a number, got {:?}",
+            idx + 1,
+            name,
+            other
+        ))),
+    }
+}
+
+/// Convert a numeric value to f64 (callers pass only integers and floats)
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Integer(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => f64::NAN,
     }
 }
 
@@ -274,14 +825,11 @@ mod tests {
 
         // Test with NULL
         let result = func.apply(&[Value::Null]);
-        assert!(match &result {
-            Ok(Value::Null) => true,
-            _ => false,
-        });
+        assert!(matches!(&result, Ok(Value::Null)));
 
-        // Test with wrong argument type
+        // Numbers are converted to text
         let result = func.apply(&[Value::Integer(42)]);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap(), Value::String("42".to_string()));
 
         // Test with wrong argument count
         let result = func.apply(&[]);
@@ -313,14 +861,11 @@ mod tests {
 
         // Test with NULL
         let result = func.apply(&[Value::Null]);
-        assert!(match &result {
-            Ok(Value::Null) => true,
-            _ => false,
-        });
+        assert!(matches!(&result, Ok(Value::Null)));
 
-        // Test with wrong argument type
+        // Numbers are converted to text
         let result = func.apply(&[Value::Integer(42)]);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap(), Value::String("42".to_string()));
 
         // Test with wrong argument count
         let result = func.apply(&[]);
@@ -361,14 +906,11 @@ mod tests {
 
         // Test with NULL
         let result = func.apply(&[Value::Null]);
-        assert!(match &result {
-            Ok(Value::Null) => true,
-            _ => false,
-        });
+        assert!(matches!(&result, Ok(Value::Null)));
 
-        // Test with wrong argument type
+        // Numbers are converted to text
         let result = func.apply(&[Value::Integer(42)]);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap(), Value::String("42".to_string()));
 
         // Test with wrong argument count
         let result = func.apply(&[]);
@@ -417,10 +959,7 @@ mod tests {
 
         // Test with NULL
         let result = func.apply(&[Value::Null, Value::Integer(1)]);
-        assert!(match &result {
-            Ok(Value::Null) => true,
-            _ => false,
-        });
+        assert!(matches!(&result, Ok(Value::Null)));
 
         // Test with invalid start position
         let result = func.apply(&[Value::String("hello".to_string()), Value::Integer(0)]);
@@ -496,18 +1035,15 @@ mod tests {
             Value::String("a".to_string()),
             Value::String("b".to_string()),
         ]);
-        assert!(match &result {
-            Ok(Value::Null) => true,
-            _ => false,
-        });
+        assert!(matches!(&result, Ok(Value::Null)));
 
-        // Test with wrong argument types
+        // Numbers are converted to text
         let result = func.apply(&[
-            Value::String("hello".to_string()),
-            Value::Integer(123),
-            Value::String("world".to_string()),
+            Value::Integer(1213),
+            Value::Integer(1),
+            Value::String("x".to_string()),
         ]);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap(), Value::String("x2x3".to_string()));
 
         // Test with wrong argument count
         let result = func.apply(&[
@@ -555,8 +1091,304 @@ mod tests {
             Some(StringFunction::Trim)
         );
 
+        // Test newer functions and aliases
+        assert_eq!(
+            StringFunction::from_name("concat_ws"),
+            Some(StringFunction::ConcatWs)
+        );
+        assert_eq!(
+            StringFunction::from_name("CEILING"),
+            Some(StringFunction::Ceil)
+        );
+        assert_eq!(
+            StringFunction::from_name("pow"),
+            Some(StringFunction::Power)
+        );
+
         // Test non-existent function
         assert_eq!(StringFunction::from_name("UNKNOWN"), None);
         assert_eq!(StringFunction::from_name(""), None);
     }
+
+    /// Helper to build a string value
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_trim_family_with_characters() {
+        assert_eq!(
+            StringFunction::Trim
+                .apply(&[text("xxhixx"), text("x")])
+                .unwrap(),
+            text("hi")
+        );
+        assert_eq!(
+            StringFunction::Ltrim.apply(&[text("  hi  ")]).unwrap(),
+            text("hi  ")
+        );
+        assert_eq!(
+            StringFunction::Rtrim
+                .apply(&[text("hi.,;"), text(";,.")])
+                .unwrap(),
+            text("hi")
+        );
+        assert_eq!(
+            StringFunction::Ltrim
+                .apply(&[Value::Integer(100), Value::Integer(1)])
+                .unwrap(),
+            text("00")
+        );
+    }
+
+    #[test]
+    fn test_length_instr_and_reverse() {
+        assert_eq!(
+            StringFunction::Length.apply(&[text("héllo")]).unwrap(),
+            Value::Integer(5)
+        );
+        assert_eq!(
+            StringFunction::Length
+                .apply(&[Value::Integer(12345)])
+                .unwrap(),
+            Value::Integer(5)
+        );
+        assert_eq!(
+            StringFunction::Length.apply(&[Value::Null]).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            StringFunction::Instr
+                .apply(&[text("héllo"), text("l")])
+                .unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            StringFunction::Instr
+                .apply(&[text("hello"), text("z")])
+                .unwrap(),
+            Value::Integer(0)
+        );
+        assert_eq!(
+            StringFunction::Reverse.apply(&[text("abc")]).unwrap(),
+            text("cba")
+        );
+        assert!(StringFunction::Length.apply(&[]).is_err());
+    }
+
+    #[test]
+    fn test_pad_functions() {
+        assert_eq!(
+            StringFunction::Lpad
+                .apply(&[Value::Integer(7), Value::Integer(3), text("0")])
+                .unwrap(),
+            text("007")
+        );
+        assert_eq!(
+            StringFunction::Rpad
+                .apply(&[text("ab"), Value::Integer(5), text("xy")])
+                .unwrap(),
+            text("abxyx")
+        );
+        assert_eq!(
+            StringFunction::Lpad
+                .apply(&[text("abc"), Value::Integer(4)])
+                .unwrap(),
+            text(" abc")
+        );
+        // Longer strings are truncated to the requested length
+        assert_eq!(
+            StringFunction::Rpad
+                .apply(&[text("abcdef"), Value::Integer(3)])
+                .unwrap(),
+            text("abc")
+        );
+        assert!(StringFunction::Lpad
+            .apply(&[text("abc"), text("4")])
+            .is_err());
+        // Negative and huge lengths are rejected before anything is allocated
+        assert!(StringFunction::Lpad
+            .apply(&[text("abc"), Value::Integer(-1)])
+            .is_err());
+        assert!(StringFunction::Rpad
+            .apply(&[text("abc"), Value::Integer(MAX_PAD_LENGTH + 1)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_concat_functions() {
+        assert_eq!(
+            StringFunction::Concat
+                .apply(&[
+                    text("a"),
+                    Value::Null,
+                    Value::Integer(1),
+                    Value::Boolean(true)
+                ])
+                .unwrap(),
+            text("a1true")
+        );
+        assert_eq!(
+            StringFunction::ConcatWs
+                .apply(&[text(", "), text("a"), Value::Null, text("b")])
+                .unwrap(),
+            text("a, b")
+        );
+        assert_eq!(
+            StringFunction::ConcatWs
+                .apply(&[Value::Null, text("a")])
+                .unwrap(),
+            Value::Null
+        );
+        assert!(StringFunction::Concat.apply(&[]).is_err());
+    }
+
+    #[test]
+    fn test_initcap_and_split_part() {
+        assert_eq!(
+            StringFunction::Initcap
+                .apply(&[text("hELLO wORLD-wide")])
+                .unwrap(),
+            text("Hello World-Wide")
+        );
+        let parts = [text("a,b,c"), text(",")];
+        for (n, expected) in [(1, "a"), (3, "c"), (4, ""), (-1, "c"), (-3, "a"), (-4, "")] {
+            let mut args = parts.to_vec();
+            args.push(Value::Integer(n));
+            assert_eq!(
+                StringFunction::SplitPart.apply(&args).unwrap(),
+                text(expected)
+            );
+        }
+        assert!(StringFunction::SplitPart
+            .apply(&[text("a,b"), text(","), Value::Integer(0)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_null_functions() {
+        assert_eq!(
+            StringFunction::Coalesce
+                .apply(&[Value::Null, Value::Integer(2), Value::Integer(3)])
+                .unwrap(),
+            Value::Integer(2)
+        );
+        assert_eq!(
+            StringFunction::Coalesce.apply(&[Value::Null]).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            StringFunction::IfNull
+                .apply(&[Value::Null, text("n/a")])
+                .unwrap(),
+            text("n/a")
+        );
+        assert!(StringFunction::IfNull.apply(&[Value::Null]).is_err());
+        assert_eq!(
+            StringFunction::NullIf
+                .apply(&[Value::Integer(1), Value::Float(1.0)])
+                .unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            StringFunction::NullIf
+                .apply(&[text("a"), text("b")])
+                .unwrap(),
+            text("a")
+        );
+    }
+
+    #[test]
+    fn test_rounding_functions() {
+        assert_eq!(
+            StringFunction::Abs.apply(&[Value::Integer(-3)]).unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            StringFunction::Abs
+                .apply(&[Value::Integer(i64::MIN)])
+                .unwrap(),
+            Value::Float(-(i64::MIN as f64))
+        );
+        assert_eq!(
+            StringFunction::Round.apply(&[Value::Float(2.5)]).unwrap(),
+            Value::Float(3.0)
+        );
+        assert_eq!(
+            StringFunction::Round
+                .apply(&[Value::Float(-2.345), Value::Integer(2)])
+                .unwrap(),
+            Value::Float(-2.35)
+        );
+        assert_eq!(
+            StringFunction::Round
+                .apply(&[Value::Integer(1250), Value::Integer(-2)])
+                .unwrap(),
+            Value::Integer(1300)
+        );
+        assert_eq!(
+            StringFunction::Round
+                .apply(&[Value::Integer(-1250), Value::Integer(-2)])
+                .unwrap(),
+            Value::Integer(-1300)
+        );
+        assert_eq!(
+            StringFunction::Floor.apply(&[Value::Float(-1.5)]).unwrap(),
+            Value::Float(-2.0)
+        );
+        assert_eq!(
+            StringFunction::Ceil.apply(&[Value::Float(1.2)]).unwrap(),
+            Value::Float(2.0)
+        );
+        assert_eq!(
+            StringFunction::Ceil.apply(&[Value::Integer(4)]).unwrap(),
+            Value::Integer(4)
+        );
+        assert!(StringFunction::Floor.apply(&[text("1.5")]).is_err());
+    }
+
+    #[test]
+    fn test_power_and_logarithm_functions() {
+        assert_eq!(
+            StringFunction::Power
+                .apply(&[Value::Integer(2), Value::Integer(10)])
+                .unwrap(),
+            Value::Integer(1024)
+        );
+        assert_eq!(
+            StringFunction::Power
+                .apply(&[Value::Integer(2), Value::Integer(-1)])
+                .unwrap(),
+            Value::Float(0.5)
+        );
+        assert!(matches!(
+            StringFunction::Power.apply(&[Value::Integer(10), Value::Integer(100)]),
+            Ok(Value::Float(_))
+        ));
+        assert!(StringFunction::Power
+            .apply(&[Value::Integer(-8), Value::Float(0.5)])
+            .is_err());
+        assert_eq!(
+            StringFunction::Sqrt.apply(&[Value::Integer(9)]).unwrap(),
+            Value::Float(3.0)
+        );
+        assert!(StringFunction::Sqrt.apply(&[Value::Integer(-1)]).is_err());
+        assert_eq!(
+            StringFunction::Ln.apply(&[Value::Integer(1)]).unwrap(),
+            Value::Float(0.0)
+        );
+        assert!(StringFunction::Ln.apply(&[Value::Integer(0)]).is_err());
+        assert_eq!(
+            StringFunction::Sign.apply(&[Value::Integer(-5)]).unwrap(),
+            Value::Integer(-1)
+        );
+        assert_eq!(
+            StringFunction::Sign.apply(&[Value::Float(0.25)]).unwrap(),
+            Value::Float(1.0)
+        );
+        assert_eq!(
+            StringFunction::Sign.apply(&[Value::Null]).unwrap(),
+            Value::Null
+        );
+    }
 }
@@ -9,11 +9,9 @@
 //! - Table joins (cross joins and inner joins via WHERE conditions)
 //! - Column resolution with qualified names (table.column)
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::path::PathBuf;
-
-use anyhow::Result;
+use std::path::{Path, PathBuf};
 
 /// Represents a reference to a column, which can be qualified with a table name
 ///
@@ -35,7 +33,12 @@ impl ColumnRef {
     // Removed unused methods
 }
 
+use crate::compression::Compression;
+use crate::csv_dialect::CsvDialect;
+use crate::encoding::FileEncoding;
 use crate::error::{SqawkError, SqawkResult};
+use crate::header_options::HeaderLayout;
+use crate::json_handler::JsonLayout;
 
 /// A unique identifier for a row in a table
 ///
@@ -111,14 +114,14 @@ impl std::hash::Hash for Value {
                 0_i32.hash(state);
             }
             Value::Integer(i) => {
-                // Hash the integer value
+                // Numbers hash as the float that equality compares them by,
+                // so that 1 and 1.0 land in the same bucket
                 1_i32.hash(state);
-                i.hash(state);
+                number_hash_bits(*i as f64).hash(state);
             }
             Value::Float(f) => {
-                // Convert float to bits for hashing to avoid NaN issues
-                2_i32.hash(state);
-                f.to_bits().hash(state);
+                1_i32.hash(state);
+                number_hash_bits(*f).hash(state);
             }
             Value::String(s) => {
                 // Hash the string value
@@ -134,6 +137,15 @@ impl std::hash::Hash for Value {
     }
 }
 
+/// Get the bits a number is hashed by, with -0.0 hashed like 0.0 since they are equal
+fn number_hash_bits(value: f64) -> u64 {
+    if value == 0.0 {
+        0.0_f64.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
 /// Implementation of ordering comparison for Value
 ///
 /// This implementation allows ordering comparison between different types with appropriate
@@ -207,26 +219,297 @@ impl fmt::Display for Value {
 /// without requiring explicit type declarations in the input files.
 impl From<&str> for Value {
     fn from(s: &str) -> Self {
-        // Try to parse as integer first
-        if let Ok(i) = s.parse::<i64>() {
-            return Value::Integer(i);
+        if s.is_empty() {
+            return Value::Null;
         }
+        infer_value(s, true)
+    }
+}
 
-        // Try to parse as float
-        if let Ok(fl) = s.parse::<f64>() {
-            return Value::Float(fl);
-        }
+/// Infer the type of a non-NULL text field
+///
+/// The single set of inference rules shared by `Value::from(&str)` and
+/// `ValueFormat::parse`, so the two can't drift apart.
+///
+/// # Arguments
+/// * `s` - The raw field contents
+/// * `infer_booleans` - Whether to recognize true/false/yes/no as booleans
+///
+/// # Returns
+/// * An integer, float or boolean if the text is one, otherwise a string
+fn infer_value(s: &str, infer_booleans: bool) -> Value {
+    // Try to parse as integer first
+    if let Ok(i) = s.parse::<i64>() {
+        return Value::Integer(i);
+    }
 
-        // Try to parse as boolean
+    // Try to parse as float
+    if let Ok(fl) = s.parse::<f64>() {
+        return Value::Float(fl);
+    }
+
+    // Try to parse as boolean
+    if infer_booleans {
         match s.to_lowercase().as_str() {
-            "true" | "yes" | "1" => return Value::Boolean(true),
-            "false" | "no" | "0" => return Value::Boolean(false),
-            "" => return Value::Null,
+            "true" | "yes" => return Value::Boolean(true),
+            "false" | "no" => return Value::Boolean(false),
             _ => {}
         }
+    }
+
+    // Default to string
+    Value::String(s.to_string())
+}
+
+/// Rules for converting between raw text fields and Values
+///
+/// `Value::from(&str)` encodes the default rules: empty fields are NULL and
+/// common boolean spellings are recognized. A `ValueFormat` makes those rules
+/// configurable so that files using other conventions (e.g. `NA` or `\N` for
+/// missing values) load correctly, and controls how NULL is written out.
+///
+/// NULL is rendered as `NULL` when printing results unless an explicit output
+/// representation is configured. When writing tables back to files the first
+/// input null token is used instead, so that files round-trip unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueFormat {
+    /// Field contents that are loaded as NULL
+    null_values: Vec<String>,
+
+    /// Whether to recognize true/false/yes/no as booleans when loading
+    infer_booleans: bool,
+
+    /// Explicit text to use for NULL on output (results and write-back)
+    null_output: Option<String>,
+}
+
+impl Default for ValueFormat {
+    fn default() -> Self {
+        Self {
+            null_values: vec![String::new()],
+            infer_booleans: true,
+            null_output: None,
+        }
+    }
+}
+
+impl ValueFormat {
+    /// Create a new value format
+    ///
+    /// # Arguments
+    /// * `null_values` - Field contents to load as NULL (e.g. "", "NA", "\N")
+    /// * `infer_booleans` - Whether to recognize boolean spellings when loading
+    /// * `null_output` - Explicit text to write for NULL values, if any
+    pub fn new(
+        null_values: Vec<String>,
+        infer_booleans: bool,
+        null_output: Option<String>,
+    ) -> Self {
+        Self {
+            null_values,
+            infer_booleans,
+            null_output,
+        }
+    }
+
+    /// Convert a raw text field to a Value using these rules
+    ///
+    /// Null tokens are checked first, then the field is inferred as an
+    /// integer, a float, and (if enabled) a boolean, falling back to a string.
+    ///
+    /// # Arguments
+    /// * `s` - The raw field contents
+    ///
+    /// # Returns
+    /// * The inferred Value
+    pub fn parse(&self, s: &str) -> Value {
+        if self.null_values.iter().any(|token| token == s) {
+            return Value::Null;
+        }
+        infer_value(s, self.infer_booleans)
+    }
+
+    /// Render a value for display in query results
+    ///
+    /// # Arguments
+    /// * `value` - The value to render
+    ///
+    /// # Returns
+    /// * The display text, using `NULL` for NULL unless overridden
+    pub fn display(&self, value: &Value) -> String {
+        match value {
+            Value::Null => self
+                .null_output
+                .clone()
+                .unwrap_or_else(|| "NULL".to_string()),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Render a value for writing back to a data file
+    ///
+    /// # Arguments
+    /// * `value` - The value to render
+    ///
+    /// # Returns
+    /// * The field text, using the configured output or first input null token for NULL
+    pub fn write(&self, value: &Value) -> String {
+        match value {
+            Value::Null => self
+                .null_output
+                .clone()
+                .or_else(|| self.null_values.first().cloned())
+                .unwrap_or_default(),
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl Value {
+    /// Convert this value to the given data type (the semantics of SQL `CAST`)
+    ///
+    /// Conversions follow the same coercion rules used for comparisons:
+    /// - NULL casts to NULL for every target type
+    /// - Integers and floats convert freely between each other (floats are
+    ///   truncated toward zero when cast to INTEGER)
+    /// - Booleans become 1/0 as numbers and "true"/"false" as text
+    /// - Strings are parsed (after trimming surrounding whitespace) as numbers,
+    ///   or as the same boolean spellings accepted when loading files
+    /// - Anything can be cast to TEXT using its display representation
+    ///
+    /// # Arguments
+    /// * `target` - The data type to convert to
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The converted value
+    /// * `Err(SqawkError::TypeError)` - If the value cannot be represented in the target type
+    pub fn cast(&self, target: DataType) -> SqawkResult<Value> {
+        let fail = || {
+            SqawkError::TypeError(format!(
+                "Cannot cast {} '{}' to {}",
+                self.type_name(),
+                self,
+                target
+            ))
+        };
+
+        match (self, target) {
+            (Value::Null, _) => Ok(Value::Null),
+
+            (Value::Integer(i), DataType::Integer) => Ok(Value::Integer(*i)),
+            (Value::Float(f), DataType::Integer) => float_to_integer(*f).ok_or_else(fail),
+            (Value::Boolean(b), DataType::Integer) => Ok(Value::Integer(*b as i64)),
+            (Value::String(s), DataType::Integer) => {
+                let s = s.trim();
+                if let Ok(i) = s.parse::<i64>() {
+                    Ok(Value::Integer(i))
+                } else {
+                    s.parse::<f64>()
+                        .ok()
+                        .and_then(float_to_integer)
+                        .ok_or_else(fail)
+                }
+            }
+
+            (Value::Integer(i), DataType::Float) => Ok(Value::Float(*i as f64)),
+            (Value::Float(f), DataType::Float) => Ok(Value::Float(*f)),
+            (Value::Boolean(b), DataType::Float) => Ok(Value::Float(if *b { 1.0 } else { 0.0 })),
+            (Value::String(s), DataType::Float) => s
+                .trim()
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|_| fail()),
+
+            (Value::String(s), DataType::Text) => Ok(Value::String(s.clone())),
+            (_, DataType::Text) => Ok(Value::String(self.to_string())),
+
+            (Value::Boolean(b), DataType::Boolean) => Ok(Value::Boolean(*b)),
+            (Value::Integer(i), DataType::Boolean) => Ok(Value::Boolean(*i != 0)),
+            (Value::Float(f), DataType::Boolean) => Ok(Value::Boolean(*f != 0.0)),
+            (Value::String(s), DataType::Boolean) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(Value::Boolean(true)),
+                "false" | "no" | "0" => Ok(Value::Boolean(false)),
+                _ => Err(fail()),
+            },
+        }
+    }
+
+    /// Get the SQL type name of this value, used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "NULL",
+            Value::Integer(_) => "INTEGER",
+            Value::Float(_) => "REAL",
+            Value::String(_) => "TEXT",
+            Value::Boolean(_) => "BOOLEAN",
+        }
+    }
+
+    /// Compare two values for sorting
+    ///
+    /// Unlike `partial_cmp`, this is a total order over all values, so sorting never
+    /// depends on how a pair of values happens to be visited:
+    /// - NULLs are equal to each other and less than any other value
+    /// - Booleans order false before true
+    /// - Integers and floats compare numerically with each other, exactly even for
+    ///   integers too large for a float; NaN sorts after every other number
+    /// - Strings compare by their UTF-8 bytes
+    /// - Values of different kinds order NULL < Boolean < Number < String
+    ///
+    /// # Arguments
+    /// * `other` - The value to compare with
+    ///
+    /// # Returns
+    /// * The ordering of `self` relative to `other`
+    pub fn sort_cmp(&self, other: &Value) -> std::cmp::Ordering {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Null => 0,
+                Value::Boolean(_) => 1,
+                Value::Integer(_) | Value::Float(_) => 2,
+                Value::String(_) => 3,
+            }
+        }
+
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a
+                .partial_cmp(b)
+                .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan())),
+            (Value::Integer(a), Value::Float(b)) => compare_integer_float(*a, *b),
+            (Value::Float(a), Value::Integer(b)) => compare_integer_float(*b, *a).reverse(),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+/// Compare an integer with a float exactly, with NaN greater than every integer
+fn compare_integer_float(i: i64, f: f64) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    // 2^63, the first float above the i64 range
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    if f.is_nan() || f >= LIMIT {
+        Ordering::Less
+    } else if f < -LIMIT {
+        Ordering::Greater
+    } else {
+        let whole = f.trunc();
+        i.cmp(&(whole as i64))
+            .then_with(|| 0.0.partial_cmp(&(f - whole)).unwrap())
+    }
+}
 
-        // Default to string
-        Value::String(s.to_string())
+/// Truncate a float toward zero, returning None if it does not fit in an i64
+fn float_to_integer(f: f64) -> Option<Value> {
+    let truncated = f.trunc();
+    if truncated.is_finite() && truncated >= i64::MIN as f64 && truncated < i64::MAX as f64 {
+        Some(Value::Integer(truncated as i64))
+    } else {
+        None
     }
 }
 
@@ -262,6 +545,24 @@ pub struct Table {
 
     /// Custom delimiter for this table's file (default is comma)
     delimiter: String,
+
+    /// Header line and surrounding lines of this table's file, for write-back
+    header_layout: HeaderLayout,
+
+    /// Quoting, comments and line terminator of this table's file, for write-back
+    csv_dialect: CsvDialect,
+
+    /// Character encoding and byte order mark of this table's file, for write-back
+    file_encoding: FileEncoding,
+
+    /// Compression codec of this table's file, for write-back
+    compression: Compression,
+
+    /// Key paths, file form and per-row value kinds of a JSON file, for write-back
+    json_layout: Option<JsonLayout>,
+
+    /// Text of each field as loaded, by row ID, so write-back can keep unchanged values as written
+    source_fields: HashMap<RowId, Vec<String>>,
 }
 
 /// Data type for a column in a table schema
@@ -277,6 +578,64 @@ pub enum DataType {
     Boolean,
 }
 
+impl DataType {
+    /// Map a SQL type name (e.g. "INT", "VARCHAR(20)", "DOUBLE") to a DataType
+    ///
+    /// Any parenthesized length or precision suffix is ignored.
+    ///
+    /// # Arguments
+    /// * `name` - The SQL type name, case-insensitive
+    ///
+    /// # Returns
+    /// * `Some(DataType)` for recognized type names
+    /// * `None` if the name does not map to a supported type
+    pub fn from_sql_name(name: &str) -> Option<DataType> {
+        let base = name.split('(').next().unwrap_or(name).trim().to_uppercase();
+        match base.as_str() {
+            "INTEGER" | "INT" | "BIGINT" | "SMALLINT" | "TINYINT" => Some(DataType::Integer),
+            "REAL" | "FLOAT" | "DOUBLE" | "DOUBLE PRECISION" | "NUMERIC" | "DECIMAL" => {
+                Some(DataType::Float)
+            }
+            "TEXT" | "VARCHAR" | "CHAR" | "STRING" => Some(DataType::Text),
+            "BOOLEAN" | "BOOL" => Some(DataType::Boolean),
+            _ => None,
+        }
+    }
+
+    /// Infer the data type of a generated column from its values
+    ///
+    /// NULLs are ignored. Integers stay Integer, a mix of integers and floats is
+    /// Float, booleans are Boolean, and anything else (or no values at all) is Text.
+    ///
+    /// # Arguments
+    /// * `values` - The values of the column
+    ///
+    /// # Returns
+    /// * The narrowest data type that holds every value
+    pub fn from_values<'a>(values: impl IntoIterator<Item = &'a Value>) -> DataType {
+        let mut inferred = None;
+        for value in values {
+            let data_type = match value {
+                Value::Null => continue,
+                Value::Integer(_) => DataType::Integer,
+                Value::Float(_) => DataType::Float,
+                Value::Boolean(_) => DataType::Boolean,
+                Value::String(_) => return DataType::Text,
+            };
+            inferred = match (inferred, data_type) {
+                (None, data_type) => Some(data_type),
+                (Some(a), b) if a == b => Some(a),
+                (
+                    Some(DataType::Integer | DataType::Float),
+                    DataType::Integer | DataType::Float,
+                ) => Some(DataType::Float),
+                _ => return DataType::Text,
+            };
+        }
+        inferred.unwrap_or(DataType::Text)
+    }
+}
+
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -321,6 +680,80 @@ pub enum SortDirection {
     Descending,
 }
 
+/// Placement of NULL values in a sorted column (NULLS FIRST / NULLS LAST)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullOrdering {
+    /// NULL values come before all other values
+    First,
+    /// NULL values come after all other values
+    Last,
+}
+
+/// One key of an ORDER BY: a column, its direction and where its NULLs go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    /// Index of the column to sort by
+    pub column: usize,
+    /// Sort direction
+    pub direction: SortDirection,
+    /// Placement of NULL values
+    pub nulls: NullOrdering,
+}
+
+impl SortKey {
+    /// Create a sort key with the default NULL placement
+    ///
+    /// NULLs sort as the smallest values: first when ascending, last when descending.
+    pub fn new(column: usize, direction: SortDirection) -> Self {
+        let nulls = match direction {
+            SortDirection::Ascending => NullOrdering::First,
+            SortDirection::Descending => NullOrdering::Last,
+        };
+        SortKey {
+            column,
+            direction,
+            nulls,
+        }
+    }
+
+    /// Set where NULL values are placed, regardless of direction
+    pub fn with_nulls(mut self, nulls: NullOrdering) -> Self {
+        self.nulls = nulls;
+        self
+    }
+
+    /// Compare two values of this key's column
+    ///
+    /// # Arguments
+    /// * `a` - The value from the first row
+    /// * `b` - The value from the second row
+    ///
+    /// # Returns
+    /// * The order of the rows according to this key alone
+    pub fn compare(&self, a: &Value, b: &Value) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        match (a, b) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => match self.nulls {
+                NullOrdering::First => Ordering::Less,
+                NullOrdering::Last => Ordering::Greater,
+            },
+            (_, Value::Null) => match self.nulls {
+                NullOrdering::First => Ordering::Greater,
+                NullOrdering::Last => Ordering::Less,
+            },
+            _ => {
+                let ordering = a.sort_cmp(b);
+                match self.direction {
+                    SortDirection::Ascending => ordering,
+                    SortDirection::Descending => ordering.reverse(),
+                }
+            }
+        }
+    }
+}
+
 impl Table {
     /// Create a new table with the given name and column names
     ///
@@ -352,6 +785,12 @@ impl Table {
             file_path,
             modified: false,
             delimiter: ",".to_string(), // Default to comma delimiter
+            header_layout: HeaderLayout::default(),
+            csv_dialect: CsvDialect::default(),
+            file_encoding: FileEncoding::default(),
+            compression: Compression::default(),
+            json_layout: None,
+            source_fields: HashMap::new(),
         }
     }
 
@@ -403,6 +842,12 @@ impl Table {
             file_path,
             modified: true, // Tables created with schema are considered modified
             delimiter: delimiter.unwrap_or_else(|| ",".to_string()),
+            header_layout: HeaderLayout::default(),
+            csv_dialect: CsvDialect::default(),
+            file_encoding: FileEncoding::default(),
+            compression: Compression::default(),
+            json_layout: None,
+            source_fields: HashMap::new(),
         }
     }
 
@@ -451,6 +896,47 @@ impl Table {
         &self.name
     }
 
+    /// Set the name of the table
+    ///
+    /// Used when a table is given an alias in a query, so that its columns are
+    /// qualified with the alias in joins.
+    ///
+    /// # Arguments
+    /// * `name` - The new table name
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Rename all columns of the table, keeping their types and values
+    ///
+    /// # Arguments
+    /// * `names` - The new column names, one per column
+    ///
+    /// # Returns
+    /// * `Ok(())` if the columns were renamed
+    /// * Error if the number of names doesn't match the number of columns
+    pub fn rename_columns(&mut self, names: Vec<String>) -> SqawkResult<()> {
+        if names.len() != self.cols.len() {
+            return Err(SqawkError::InvalidSqlQuery(format!(
+                "Table '{}' has {} columns but {} column names were given",
+                self.name,
+                self.cols.len(),
+                names.len()
+            )));
+        }
+
+        for (col, name) in self.cols.iter_mut().zip(names) {
+            col.name = name;
+        }
+        self.column_map = self
+            .cols
+            .iter()
+            .enumerate()
+            .map(|(i, col)| (col.name.clone(), i))
+            .collect();
+        Ok(())
+    }
+
     /// Get the row count
     ///
     /// Returns the number of rows in the table. This is useful for
@@ -623,6 +1109,124 @@ impl Table {
         &self.delimiter
     }
 
+    /// Get the header layout of this table's file
+    ///
+    /// Describes whether the file has a header line and which skipped lines
+    /// surround it, so that write-back reproduces them.
+    pub fn header_layout(&self) -> &HeaderLayout {
+        &self.header_layout
+    }
+
+    /// Set the header layout of this table's file
+    ///
+    /// # Arguments
+    /// * `layout` - How the header was laid out when the file was loaded
+    pub fn set_header_layout(&mut self, layout: HeaderLayout) {
+        self.header_layout = layout;
+    }
+
+    /// Get the CSV dialect of this table's file
+    ///
+    /// Write-back quotes, escapes and terminates lines the way the file was read.
+    pub fn csv_dialect(&self) -> &CsvDialect {
+        &self.csv_dialect
+    }
+
+    /// Set the CSV dialect of this table's file
+    ///
+    /// # Arguments
+    /// * `dialect` - The dialect the file was loaded with
+    pub fn set_csv_dialect(&mut self, dialect: CsvDialect) {
+        self.csv_dialect = dialect;
+    }
+
+    /// Get the character encoding of this table's file
+    ///
+    /// Write-back encodes the file as it was read, with its byte order mark if it had one.
+    pub fn file_encoding(&self) -> &FileEncoding {
+        &self.file_encoding
+    }
+
+    /// Set the character encoding of this table's file
+    ///
+    /// # Arguments
+    /// * `encoding` - The encoding the file was loaded with
+    pub fn set_file_encoding(&mut self, encoding: FileEncoding) {
+        self.file_encoding = encoding;
+    }
+
+    /// Get the compression codec of this table's file
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Set the compression codec of this table's file
+    ///
+    /// # Arguments
+    /// * `compression` - The codec the file was decompressed with when loaded
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Write this table's formatted text to a file as the table's own file was stored
+    ///
+    /// The text is converted to the encoding it was read with, BOM included, and
+    /// compressed as it was read; a new file is compressed if its extension says so.
+    ///
+    /// # Arguments
+    /// * `file_path` - The file to write
+    /// * `text` - The table formatted as UTF-8 text
+    ///
+    /// # Returns
+    /// * `Ok(())` if the file was written
+    /// * `Err` if the text can't be encoded or the file can't be written
+    pub fn write_file(&self, file_path: &Path, text: Vec<u8>) -> SqawkResult<()> {
+        let bytes = self.file_encoding.encode(text)?;
+        let compression = match self.compression {
+            Compression::None => Compression::from_extension(file_path),
+            compression => compression,
+        };
+        let bytes = compression.compress(bytes)?;
+        std::fs::write(file_path, bytes).map_err(SqawkError::IoError)
+    }
+
+    /// Get the JSON layout of this table's file
+    ///
+    /// Present for tables loaded from JSON, so that write-back rebuilds the
+    /// objects the rows came from.
+    pub fn json_layout(&self) -> Option<&JsonLayout> {
+        self.json_layout.as_ref()
+    }
+
+    /// Set the JSON layout of this table's file
+    ///
+    /// # Arguments
+    /// * `layout` - How the objects were laid out when the file was loaded
+    pub fn set_json_layout(&mut self, layout: JsonLayout) {
+        self.json_layout = Some(layout);
+    }
+
+    /// Get the text of a row's fields as they were loaded
+    ///
+    /// # Arguments
+    /// * `row_id` - The row's unique identifier
+    ///
+    /// # Returns
+    /// * `Some(fields)` if the row was loaded from a file that records its field text
+    /// * `None` for other rows
+    pub fn source_fields(&self, row_id: RowId) -> Option<&[String]> {
+        self.source_fields.get(&row_id).map(Vec::as_slice)
+    }
+
+    /// Record the text of a row's fields as they were loaded
+    ///
+    /// # Arguments
+    /// * `row_id` - The row's unique identifier
+    /// * `fields` - The text of each field, in column order
+    pub fn set_source_fields(&mut self, row_id: RowId, fields: Vec<String>) {
+        self.source_fields.insert(row_id, fields);
+    }
+
     /// Get the index of a column by name
     ///
     /// Looks up a column by name and returns its index in the table.
@@ -639,39 +1243,6 @@ impl Table {
         self.column_map.get(name).copied()
     }
 
-    /// Print the table to stdout
-    ///
-    /// Formats and prints the table contents to standard output in comma-delimited format.
-    /// This is used for displaying query results to the user.
-    ///
-    /// # Returns
-    /// * `Ok(())` if the table was successfully printed
-    /// * `Err` if there was an error writing to stdout
-    pub fn print_to_stdout(&self) -> Result<()> {
-        // Print header
-        let column_names = self.columns();
-        for (i, col) in column_names.iter().enumerate() {
-            if i > 0 {
-                print!(",");
-            }
-            print!("{}", col);
-        }
-        println!();
-
-        // Print rows
-        for row in &self.rows {
-            for (i, val) in row.iter().enumerate() {
-                if i > 0 {
-                    print!(",");
-                }
-                print!("{}", val);
-            }
-            println!();
-        }
-
-        Ok(())
-    }
-
     /// Create a new table with a subset of rows matching a predicate
     ///
     /// Filters the table rows based on a provided predicate function.
@@ -711,10 +1282,34 @@ impl Table {
     /// # Arguments
     /// * `new_rows` - The new set of rows to replace the existing ones
     pub fn replace_rows(&mut self, new_rows: Vec<Row>) {
+        self.row_ids = (0..new_rows.len())
+            .map(|offset| RowId::new(self.next_row_id + offset as u64))
+            .collect();
+        self.next_row_id += new_rows.len() as u64;
         self.rows = new_rows;
         self.modified = true;
     }
 
+    /// Remove the rows at the given indices
+    ///
+    /// The remaining rows keep their row IDs.
+    ///
+    /// # Arguments
+    /// * `indices` - Indices of the rows to remove
+    pub fn remove_rows(&mut self, indices: &[usize]) {
+        let remove: HashSet<usize> = indices.iter().copied().collect();
+        let rows = std::mem::take(&mut self.rows);
+        let row_ids = std::mem::take(&mut self.row_ids);
+        (self.rows, self.row_ids) = rows
+            .into_iter()
+            .zip(row_ids)
+            .enumerate()
+            .filter(|(index, _)| !remove.contains(index))
+            .map(|(_, row)| row)
+            .unzip();
+        self.modified = true;
+    }
+
     /// Add a column to the table with a specified data type
     ///
     /// This method adds a new column to the table with the given name and data type.
@@ -789,20 +1384,6 @@ impl Table {
         Ok(())
     }
 
-    /// Convert all rows to string representation
-    ///
-    /// This method converts all table rows to a vector of string vectors,
-    /// useful for displaying in the REPL interface.
-    ///
-    /// # Returns
-    /// * Vec<Vec<String>> - All rows converted to strings
-    pub fn rows_as_strings(&self) -> Vec<Vec<String>> {
-        self.rows
-            .iter()
-            .map(|row| row.iter().map(|value| value.to_string()).collect())
-            .collect()
-    }
-
     /// Create a new table with only specified columns
     ///
     /// Projects the table to include only the columns specified by their indices.
@@ -989,7 +1570,9 @@ impl Table {
     /// Add columns with table name prefixes to a column list
     ///
     /// This function adds column names to a list, prefixing them with
-    /// the table name if they don't already have a prefix.
+    /// the table name if they don't already have a prefix. Columns of a table
+    /// loaded from a file are always prefixed, since a dot in their name (such
+    /// as a flattened JSON field) is part of the name rather than a qualifier.
     ///
     /// # Arguments
     /// * `columns` - The column list to add to
@@ -997,7 +1580,7 @@ impl Table {
         for col in self.columns() {
             // If the column already has a table prefix, keep it as is
             // Otherwise, add the table name prefix
-            if col.contains('.') {
+            if col.contains('.') && self.file_path.is_none() {
                 columns.push(col.clone());
             } else {
                 columns.push(format!("{}.{}", self.name, col));
@@ -1102,23 +1685,24 @@ impl Table {
     /// Sort the table by one or more columns
     ///
     /// This method implements the ORDER BY functionality for SQL queries.
-    /// It takes a list of column indices and their respective sort directions,
-    /// then sorts the table rows accordingly. Sort direction can be either
-    /// ascending (the default) or descending.
+    /// It takes a list of sort keys, each naming a column, a sort direction and
+    /// the placement of NULLs, then sorts the table rows accordingly. Values are
+    /// compared with [`Value::sort_cmp`]. The sort is stable: rows that compare
+    /// equal on every key keep their original order.
     ///
     /// # Arguments
-    /// * `sort_columns` - A vector of tuples containing (column_index, sort_direction)
+    /// * `sort_keys` - The sort keys, most significant first
     ///
     /// # Returns
     /// * A new sorted table if successful
     /// * Error if any column index is invalid
-    pub fn sort(&self, sort_columns: Vec<(usize, SortDirection)>) -> SqawkResult<Self> {
+    pub fn sort(&self, sort_keys: Vec<SortKey>) -> SqawkResult<Self> {
         // Validate column indices
-        for (col_idx, _) in &sort_columns {
-            if *col_idx >= self.column_count() {
+        for key in &sort_keys {
+            if key.column >= self.column_count() {
                 return Err(SqawkError::ColumnNotFound(format!(
                     "Column index {} out of bounds for ORDER BY (table has {} columns)",
-                    col_idx,
+                    key.column,
                     self.column_count()
                 )));
             }
@@ -1130,24 +1714,13 @@ impl Table {
         // Clone the rows for sorting
         let mut sorted_rows = self.rows.clone();
 
-        // Sort the rows based on the specified columns and directions
+        // Sort the rows based on the specified keys (sort_by is a stable sort)
         sorted_rows.sort_by(|row_a, row_b| {
-            // Compare each sort column in order until a difference is found
-            for &(col_idx, direction) in &sort_columns {
-                // Compare values using our PartialOrd implementation
-                match row_a[col_idx].partial_cmp(&row_b[col_idx]) {
-                    Some(ordering) => {
-                        // If not equal, return the ordering (possibly reversed for DESC)
-                        if ordering != std::cmp::Ordering::Equal {
-                            return match direction {
-                                SortDirection::Ascending => ordering,
-                                SortDirection::Descending => ordering.reverse(),
-                            };
-                        }
-                    }
-                    // If values can't be compared (which shouldn't happen with our implementation),
-                    // continue to the next column
-                    None => continue,
+            // Compare each sort key in order until a difference is found
+            for key in &sort_keys {
+                let ordering = key.compare(&row_a[key.column], &row_b[key.column]);
+                if ordering != std::cmp::Ordering::Equal {
+                    return ordering;
                 }
             }
 
@@ -1163,6 +1736,147 @@ impl Table {
         Ok(result)
     }
 
+    /// Turn columns into rows (UNPIVOT)
+    ///
+    /// Each input row produces one output row for each unpivoted column whose value
+    /// is not NULL. The output has the remaining columns, then `name_column` holding
+    /// the unpivoted column's name and `value_column` holding its value.
+    ///
+    /// # Arguments
+    /// * `value_column` - Name of the output column for the values
+    /// * `name_column` - Name of the output column for the column names
+    /// * `columns` - Indices of the columns to unpivot
+    ///
+    /// # Returns
+    /// * A new table in long format
+    /// * Error if a column index is invalid or an output column name is taken
+    pub fn unpivot(
+        &self,
+        value_column: &str,
+        name_column: &str,
+        columns: &[usize],
+    ) -> SqawkResult<Self> {
+        if let Some(&idx) = columns.iter().find(|&&idx| idx >= self.column_count()) {
+            return Err(SqawkError::ColumnNotFound(format!(
+                "Column index {} out of bounds for UNPIVOT (table has {} columns)",
+                idx,
+                self.column_count()
+            )));
+        }
+
+        let kept: Vec<usize> = (0..self.column_count())
+            .filter(|idx| !columns.contains(idx))
+            .collect();
+        for name in [name_column, value_column] {
+            if kept.iter().any(|&idx| self.cols[idx].name == name) || name_column == value_column {
+                return Err(SqawkError::InvalidSqlQuery(format!(
+                    "UNPIVOT output column '{}' conflicts with another column",
+                    name
+                )));
+            }
+        }
+
+        // The value column keeps the type of the unpivoted columns if they agree
+        let value_type = match columns.first() {
+            Some(&first)
+                if columns
+                    .iter()
+                    .all(|&idx| self.cols[idx].data_type == self.cols[first].data_type) =>
+            {
+                self.cols[first].data_type
+            }
+            _ => DataType::Text,
+        };
+
+        let mut schema: Vec<ColumnDefinition> = kept
+            .iter()
+            .map(|&idx| ColumnDefinition {
+                name: self.cols[idx].name.clone(),
+                data_type: self.cols[idx].data_type,
+            })
+            .collect();
+        schema.push(ColumnDefinition {
+            name: name_column.to_string(),
+            data_type: DataType::Text,
+        });
+        schema.push(ColumnDefinition {
+            name: value_column.to_string(),
+            data_type: value_type,
+        });
+
+        let mut result = Table::new_with_schema(&self.name, schema, None, None);
+        for row in &self.rows {
+            for &idx in columns {
+                if row[idx] == Value::Null {
+                    continue;
+                }
+                let mut new_row: Row = kept.iter().map(|&k| row[k].clone()).collect();
+                new_row.push(Value::String(self.cols[idx].name.clone()));
+                new_row.push(row[idx].clone());
+                result.add_row(new_row)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Split a delimited column into one row per piece
+    ///
+    /// Each row is repeated once for every piece of its value in `column`, with the
+    /// piece (as text) in place of the value. Empty pieces are kept, and a NULL value
+    /// keeps its row unchanged. Non-text values are split on their display form.
+    ///
+    /// # Arguments
+    /// * `column` - Index of the column to split
+    /// * `delimiter` - The separator between pieces
+    ///
+    /// # Returns
+    /// * A new table with the column exploded into rows
+    /// * Error if the column index is invalid or the delimiter is empty
+    pub fn split_column(&self, column: usize, delimiter: &str) -> SqawkResult<Self> {
+        if column >= self.column_count() {
+            return Err(SqawkError::ColumnNotFound(format!(
+                "Column index {} out of bounds (table has {} columns)",
+                column,
+                self.column_count()
+            )));
+        }
+        if delimiter.is_empty() {
+            return Err(SqawkError::InvalidSqlQuery(
+                "Split delimiter must not be empty".to_string(),
+            ));
+        }
+
+        let schema = self
+            .cols
+            .iter()
+            .enumerate()
+            .map(|(idx, col)| ColumnDefinition {
+                name: col.name.clone(),
+                data_type: if idx == column {
+                    DataType::Text
+                } else {
+                    col.data_type
+                },
+            })
+            .collect();
+
+        let mut result = Table::new_with_schema(&self.name, schema, None, None);
+        for row in &self.rows {
+            if row[column] == Value::Null {
+                result.add_row(row.clone())?;
+                continue;
+            }
+            for piece in row[column].to_string().split(delimiter) {
+                let mut new_row = row.clone();
+                new_row[column] = Value::String(piece.to_string());
+                result.add_row(new_row)?;
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Limits the number of rows in the table
     ///
     /// This method applies LIMIT and OFFSET to the table, returning a new table
@@ -1195,3 +1909,277 @@ impl Table {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_numeric_conversions() {
+        assert_eq!(
+            Value::Float(7.9).cast(DataType::Integer).unwrap(),
+            Value::Integer(7)
+        );
+        assert_eq!(
+            Value::Float(-7.9).cast(DataType::Integer).unwrap(),
+            Value::Integer(-7)
+        );
+        assert_eq!(
+            Value::Integer(3).cast(DataType::Float).unwrap(),
+            Value::Float(3.0)
+        );
+        assert_eq!(
+            Value::String(" 42 ".to_string())
+                .cast(DataType::Integer)
+                .unwrap(),
+            Value::Integer(42)
+        );
+        assert_eq!(
+            Value::String("2.5".to_string())
+                .cast(DataType::Integer)
+                .unwrap(),
+            Value::Integer(2)
+        );
+        assert_eq!(
+            Value::Boolean(true).cast(DataType::Integer).unwrap(),
+            Value::Integer(1)
+        );
+    }
+
+    #[test]
+    fn test_cast_text_and_boolean() {
+        assert_eq!(
+            Value::Integer(42).cast(DataType::Text).unwrap(),
+            Value::String("42".to_string())
+        );
+        assert_eq!(
+            Value::String("No".to_string())
+                .cast(DataType::Boolean)
+                .unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            Value::Float(0.5).cast(DataType::Boolean).unwrap(),
+            Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_cast_null_and_failures() {
+        assert_eq!(Value::Null.cast(DataType::Integer).unwrap(), Value::Null);
+        assert!(matches!(
+            Value::String("abc".to_string()).cast(DataType::Float),
+            Err(SqawkError::TypeError(_))
+        ));
+        assert!(matches!(
+            Value::Float(f64::INFINITY).cast(DataType::Integer),
+            Err(SqawkError::TypeError(_))
+        ));
+        assert!(matches!(
+            Value::String("maybe".to_string()).cast(DataType::Boolean),
+            Err(SqawkError::TypeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_value_format_default_matches_value_from() {
+        let format = ValueFormat::default();
+        for field in ["", "42", "4.5", "yes", "False", "hello", "NA"] {
+            assert_eq!(format.parse(field), Value::from(field));
+        }
+        assert_eq!(format.display(&Value::Null), "NULL");
+        assert_eq!(format.write(&Value::Null), "");
+    }
+
+    #[test]
+    fn test_value_format_custom_tokens() {
+        let format = ValueFormat::new(vec!["NA".to_string(), "\\N".to_string()], false, None);
+        assert_eq!(format.parse("NA"), Value::Null);
+        assert_eq!(format.parse("\\N"), Value::Null);
+        assert_eq!(format.parse(""), Value::String(String::new()));
+        assert_eq!(format.parse("yes"), Value::String("yes".to_string()));
+        assert_eq!(format.parse("7"), Value::Integer(7));
+        assert_eq!(format.write(&Value::Null), "NA");
+
+        let format = ValueFormat::new(vec![String::new()], true, Some("-".to_string()));
+        assert_eq!(format.display(&Value::Null), "-");
+        assert_eq!(format.write(&Value::Null), "-");
+        assert_eq!(format.write(&Value::Integer(3)), "3");
+    }
+
+    #[test]
+    fn test_data_type_from_sql_name() {
+        assert_eq!(DataType::from_sql_name("int"), Some(DataType::Integer));
+        assert_eq!(DataType::from_sql_name("VARCHAR(20)"), Some(DataType::Text));
+        assert_eq!(DataType::from_sql_name("DOUBLE"), Some(DataType::Float));
+        assert_eq!(DataType::from_sql_name("DATE"), None);
+    }
+
+    #[test]
+    fn test_sort_cmp_total_order() {
+        use std::cmp::Ordering;
+
+        let mut values = vec![
+            Value::String("b".to_string()),
+            Value::Float(f64::NAN),
+            Value::Integer(2),
+            Value::Null,
+            Value::Float(1.5),
+            Value::Boolean(true),
+            Value::String("a".to_string()),
+            Value::Integer(i64::MAX),
+            Value::Boolean(false),
+        ];
+        values.sort_by(|a, b| a.sort_cmp(b));
+        assert_eq!(
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec![
+                "NULL",
+                "false",
+                "true",
+                "1.5",
+                "2",
+                "9223372036854775807",
+                "NaN",
+                "a",
+                "b"
+            ]
+        );
+
+        // Integers and floats compare exactly
+        assert_eq!(
+            Value::Integer(2).sort_cmp(&Value::Float(2.0)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            Value::Integer(i64::MAX).sort_cmp(&Value::Float(9.223372036854776e18)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Value::Float(-0.5).sort_cmp(&Value::Integer(0)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort_is_stable_with_null_placement() {
+        let mut table = Table::new("t", vec!["k".to_string(), "n".to_string()], None);
+        for (k, n) in [
+            (Value::Integer(1), 1),
+            (Value::Null, 2),
+            (Value::Integer(1), 3),
+            (Value::Integer(0), 4),
+        ] {
+            table.add_row(vec![k, Value::Integer(n)]).unwrap();
+        }
+        let order = |keys: Vec<SortKey>| -> Vec<String> {
+            let sorted = table.sort(keys).unwrap();
+            sorted.rows().iter().map(|row| row[1].to_string()).collect()
+        };
+
+        assert_eq!(
+            order(vec![SortKey::new(0, SortDirection::Ascending)]),
+            ["2", "4", "1", "3"]
+        );
+        assert_eq!(
+            order(vec![SortKey::new(0, SortDirection::Descending)]),
+            ["1", "3", "4", "2"]
+        );
+        assert_eq!(
+            order(vec![
+                SortKey::new(0, SortDirection::Ascending).with_nulls(NullOrdering::Last)
+            ]),
+            ["4", "1", "3", "2"]
+        );
+        assert_eq!(
+            order(vec![
+                SortKey::new(0, SortDirection::Descending).with_nulls(NullOrdering::First)
+            ]),
+            ["2", "1", "3", "4"]
+        );
+    }
+
+    #[test]
+    fn test_unpivot_skips_nulls_and_types_values() {
+        let schema = [
+            ("region", DataType::Text),
+            ("jan", DataType::Integer),
+            ("feb", DataType::Integer),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnDefinition {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect();
+        let mut table = Table::new_with_schema("wide", schema, None, None);
+        table
+            .add_row(vec![
+                Value::String("north".to_string()),
+                Value::Integer(10),
+                Value::Null,
+            ])
+            .unwrap();
+
+        let long = table.unpivot("amount", "month", &[1, 2]).unwrap();
+        assert_eq!(long.columns(), ["region", "month", "amount"]);
+        assert_eq!(long.column_metadata()[2].data_type, DataType::Integer);
+        assert_eq!(
+            long.rows(),
+            [vec![
+                Value::String("north".to_string()),
+                Value::String("jan".to_string()),
+                Value::Integer(10),
+            ]]
+        );
+
+        assert!(table.unpivot("region", "month", &[1, 2]).is_err());
+        assert!(table.unpivot("amount", "month", &[3]).is_err());
+    }
+
+    #[test]
+    fn test_data_type_from_values() {
+        let ints = [Value::Integer(1), Value::Null, Value::Integer(2)];
+        assert_eq!(DataType::from_values(&ints), DataType::Integer);
+        let mixed = [Value::Integer(1), Value::String("a".to_string())];
+        assert_eq!(DataType::from_values(&mixed), DataType::Text);
+    }
+
+    #[test]
+    fn test_split_column_explodes_pieces() {
+        let mut table = Table::new("t", vec!["id".to_string(), "tags".to_string()], None);
+        for (id, tags) in [
+            (1, Value::String("a;b".to_string())),
+            (2, Value::Null),
+            (3, Value::String("c;".to_string())),
+        ] {
+            table.add_row(vec![Value::Integer(id), tags]).unwrap();
+        }
+
+        let split = table.split_column(1, ";").unwrap();
+        let rows: Vec<String> = split
+            .rows()
+            .iter()
+            .map(|row| format!("{}:{}", row[0], row[1]))
+            .collect();
+        assert_eq!(rows, ["1:a", "1:b", "2:NULL", "3:c", "3:"]);
+        assert_eq!(split.column_metadata()[1].data_type, DataType::Text);
+
+        assert!(table.split_column(2, ";").is_err());
+        assert!(table.split_column(1, "").is_err());
+    }
+
+    #[test]
+    fn test_rename_columns_updates_lookup() {
+        let mut table = Table::new("t", vec!["a".to_string(), "b".to_string()], None);
+        table.set_name("alias");
+        table
+            .rename_columns(vec!["x".to_string(), "y".to_string()])
+            .unwrap();
+
+        assert_eq!(table.name(), "alias");
+        assert_eq!(table.column_index("y"), Some(1));
+        assert_eq!(table.column_index("a"), None);
+        assert!(table.rename_columns(vec!["z".to_string()]).is_err());
+    }
+}
@@ -7,6 +7,7 @@
 //! Each instruction has a specific semantics that controls how data is loaded,
 //! manipulated, and stored during SQL query execution.
 
+use crate::arithmetic::ArithmeticOperator;
 use crate::table::Value;
 use std::fmt;
 
@@ -32,6 +33,7 @@ pub enum OpCode {
     Integer,   // Load integer constant
     String,    // Load string constant
     Null,      // Load NULL value
+    Copy,      // Copy register P1 into register P2
     ResultRow, // Return result row to client
 
     // Transaction operations
@@ -48,6 +50,15 @@ pub enum OpCode {
     Ne,      // Not equal comparison (P1 != P2, result in P3)
     Gt,      // Greater than comparison (P1 > P2, result in P3)
     Ge,      // Greater than or equal comparison (P1 >= P2, result in P3)
+
+    // Arithmetic operations (semantics shared with the direct executor, see crate::arithmetic)
+    Add,       // Addition (P1 + P2, result in P3)
+    Subtract,  // Subtraction (P1 - P2, result in P3)
+    Multiply,  // Multiplication (P1 * P2, result in P3)
+    Divide,    // Division, always floating point (P1 / P2, result in P3)
+    IntDivide, // Integer division truncating toward zero (P1 DIV P2, result in P3)
+    Remainder, // Remainder with the sign of the dividend (P1 % P2, result in P3)
+    Negate,    // Arithmetic negation (-P1, result in P2)
     
     // Conditional jumps
     IfZ,    // Jump to P2 if register P1 contains 0
@@ -58,6 +69,33 @@ pub enum OpCode {
     Noop, // No operation
 }
 
+impl OpCode {
+    /// Get the opcode that implements an arithmetic operator
+    pub fn for_arithmetic(op: ArithmeticOperator) -> Self {
+        match op {
+            ArithmeticOperator::Add => OpCode::Add,
+            ArithmeticOperator::Subtract => OpCode::Subtract,
+            ArithmeticOperator::Multiply => OpCode::Multiply,
+            ArithmeticOperator::Divide => OpCode::Divide,
+            ArithmeticOperator::IntegerDivide => OpCode::IntDivide,
+            ArithmeticOperator::Modulo => OpCode::Remainder,
+        }
+    }
+
+    /// Get the arithmetic operator implemented by this opcode, if any
+    pub fn arithmetic_operator(&self) -> Option<ArithmeticOperator> {
+        match self {
+            OpCode::Add => Some(ArithmeticOperator::Add),
+            OpCode::Subtract => Some(ArithmeticOperator::Subtract),
+            OpCode::Multiply => Some(ArithmeticOperator::Multiply),
+            OpCode::Divide => Some(ArithmeticOperator::Divide),
+            OpCode::IntDivide => Some(ArithmeticOperator::IntegerDivide),
+            OpCode::Remainder => Some(ArithmeticOperator::Modulo),
+            _ => None,
+        }
+    }
+}
+
 /// A SQL VM instruction with opcode and parameters
 #[derive(Debug, Clone)]
 pub struct Instruction {
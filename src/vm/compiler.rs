@@ -5,12 +5,12 @@
 //! It implements a visitor pattern to walk the AST generated by sqlparser.
 
 use sqlparser::ast::{ObjectName, Query, Select, SelectItem, SetExpr, Statement, TableWithJoins};
-use sqlparser::dialect::HiveDialect;
-use sqlparser::parser::Parser;
 use std::collections::HashMap;
 
 use super::bytecode::{Instruction, OpCode, Program};
+use crate::arithmetic::ArithmeticOperator;
 use crate::database::Database;
+use crate::dialect;
 use crate::error::{SqawkError, SqawkResult};
 use crate::table::Table;
 
@@ -76,9 +76,8 @@ impl<'a> SqlCompiler<'a> {
         self.reset_registers();
         self.table_map.clear();
 
-        // Parse the SQL statement
-        let dialect = HiveDialect {};
-        let statements = Parser::parse_sql(&dialect, sql).map_err(SqawkError::SqlParseError)?;
+        // Parse the SQL statement with the same dialect and rewrites as the executor
+        let statements = dialect::parse_sql(sql).map_err(SqawkError::SqlParseError)?;
 
         if statements.is_empty() {
             return Err(SqawkError::InvalidSqlQuery(
@@ -196,6 +195,27 @@ impl<'a> SqlCompiler<'a> {
             }
         }
 
+        // ResultRow reads consecutive registers, but expressions such as arithmetic
+        // use intermediate registers, so gather the results into a contiguous block
+        let is_contiguous = result_regs.windows(2).all(|pair| pair[1] == pair[0] + 1);
+        if !is_contiguous {
+            let mut output_regs = Vec::with_capacity(result_regs.len());
+            for reg in result_regs {
+                let output_reg = self.allocate_register();
+                self.program.add_instruction(Instruction::new(
+                    OpCode::Copy,
+                    reg,
+                    output_reg,
+                    0,
+                    None,
+                    0,
+                    Some(format!("r[{}] = r[{}]", output_reg, reg)),
+                ));
+                output_regs.push(output_reg);
+            }
+            result_regs = output_regs;
+        }
+
         // Generate the result row
         if !result_regs.is_empty() {
             // Output the result row
@@ -271,6 +291,59 @@ impl<'a> SqlCompiler<'a> {
                     }
                 }
             }
+            sqlparser::ast::Expr::Nested(inner) => {
+                // Parentheses only affect parsing, so compile the inner expression
+                return self.compile_expr(inner);
+            }
+            sqlparser::ast::Expr::BinaryOp { left, op, right } => {
+                let arith_op = ArithmeticOperator::from_binary_operator(op).ok_or_else(|| {
+                    SqawkError::UnsupportedSqlFeature(format!(
+                        "Unsupported binary operator: {:?}",
+                        op
+                    ))
+                })?;
+
+                // Evaluate both operands, then combine them into the result register
+                let left_reg = self.compile_expr(left)?;
+                let right_reg = self.compile_expr(right)?;
+                self.program.add_instruction(Instruction::new(
+                    OpCode::for_arithmetic(arith_op),
+                    left_reg,
+                    right_reg,
+                    result_reg,
+                    None,
+                    0,
+                    Some(format!(
+                        "r[{}] = r[{}] {} r[{}]",
+                        result_reg,
+                        left_reg,
+                        arith_op.symbol(),
+                        right_reg
+                    )),
+                ));
+            }
+            sqlparser::ast::Expr::UnaryOp {
+                op: sqlparser::ast::UnaryOperator::Plus,
+                expr: inner,
+            } => {
+                // Unary plus leaves its operand unchanged
+                return self.compile_expr(inner);
+            }
+            sqlparser::ast::Expr::UnaryOp {
+                op: sqlparser::ast::UnaryOperator::Minus,
+                expr: inner,
+            } => {
+                let operand_reg = self.compile_expr(inner)?;
+                self.program.add_instruction(Instruction::new(
+                    OpCode::Negate,
+                    operand_reg,
+                    result_reg,
+                    0,
+                    None,
+                    0,
+                    Some(format!("r[{}] = -r[{}]", result_reg, operand_reg)),
+                ));
+            }
             // Could add support for other expression types here
             _ => {
                 return Err(SqawkError::UnsupportedSqlFeature(format!(
@@ -81,6 +81,9 @@ enum TransactionState {
     RolledBack,
 }
 
+/// A single transaction log entry: (cursor ID, original table, column changes)
+type TransactionLogEntry = (usize, Table, Vec<(usize, Value)>);
+
 /// SQL VM engine that executes bytecode
 pub struct VmEngine<'a> {
     /// The database containing tables
@@ -111,7 +114,7 @@ pub struct VmEngine<'a> {
     /// - First element (usize): Cursor ID that made the modification
     /// - Second element (Table): Original state of the table before modification
     /// - Third element (Vec<(usize, Value)>): List of (column_index, new_value) pairs representing changes
-    transaction_log: Vec<(usize, Table, Vec<(usize, Value)>)>,
+    transaction_log: Vec<TransactionLogEntry>,
 
     // Removed unused fields for column_names, modified_tables, and affected_rows
     /// Whether the engine is in verbose mode
@@ -147,7 +150,11 @@ impl<'a> VmEngine<'a> {
             .program
             .instructions
             .iter()
-            .map(|i| i.p1.max(i.p2).max(i.p3))
+            .map(|i| match i.opcode {
+                // Integer holds a constant in P1, not a register index
+                OpCode::Integer => i.p2.max(i.p3),
+                _ => i.p1.max(i.p2).max(i.p3),
+            })
             .max()
             .unwrap_or(10);
 
@@ -382,6 +389,14 @@ impl<'a> VmEngine<'a> {
                 }
             }
 
+            OpCode::Copy => {
+                // Copy the value in register P1 into register P2
+                let value = self.get_register(inst.p1 as usize)?;
+                self.set_register(inst.p2 as usize, value)?;
+
+                Ok(ExecuteResult::Continue)
+            }
+
             OpCode::ResultRow => {
                 // Return a result row from registers
                 let start_reg = inst.p1 as usize;
@@ -676,6 +691,41 @@ impl<'a> VmEngine<'a> {
                 Ok(ExecuteResult::Continue)
             }
             
+            OpCode::Add
+            | OpCode::Subtract
+            | OpCode::Multiply
+            | OpCode::Divide
+            | OpCode::IntDivide
+            | OpCode::Remainder => {
+                // Arithmetic operation (P1 op P2, result in P3)
+                // P1 and P2 are register indices of the operands
+                // P3 is the destination register for the result
+
+                let op = inst.opcode.arithmetic_operator().ok_or_else(|| {
+                    SqawkError::VmError(format!("Not an arithmetic opcode: {:?}", inst.opcode))
+                })?;
+
+                // Use the same arithmetic rules as the direct executor
+                let left: Value = self.get_register(inst.p1 as usize)?.into();
+                let right: Value = self.get_register(inst.p2 as usize)?.into();
+                let result = op.apply(&left, &right)?;
+
+                self.set_register(inst.p3 as usize, Register::from(result))?;
+
+                Ok(ExecuteResult::Continue)
+            }
+
+            OpCode::Negate => {
+                // Arithmetic negation (-P1, result in P2), following the same
+                // rules as the direct executor's unary minus
+                let operand: Value = self.get_register(inst.p1 as usize)?.into();
+                let result = crate::arithmetic::negate(&operand)?;
+
+                self.set_register(inst.p2 as usize, Register::from(result))?;
+
+                Ok(ExecuteResult::Continue)
+            }
+
             // JumpIfTrue and JumpIfFalse have been replaced by SQLite-style opcodes:
             // - IfZ (jump if zero/false)
             // - IfPos (jump if positive)
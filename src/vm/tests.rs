@@ -1190,4 +1190,73 @@ mod comparison_tests {
             }
         }
     }
+
+    /// Test the arithmetic opcodes, including overflow promotion
+    #[test]
+    fn test_arithmetic_opcodes() {
+        let database = Database::new();
+
+        // Test cases: [(opcode, first_value, second_value, expected_result)]
+        let test_cases = vec![
+            (OpCode::Add, 7, 2, Value::Integer(9)),
+            (OpCode::Subtract, 7, 2, Value::Integer(5)),
+            (OpCode::Multiply, 7, 2, Value::Integer(14)),
+            (OpCode::Divide, 7, 2, Value::Float(3.5)),
+            (OpCode::IntDivide, -7, 2, Value::Integer(-3)),
+            (OpCode::Remainder, -7, 2, Value::Integer(-1)),
+            (OpCode::Add, i64::MAX, 1, Value::Float(i64::MAX as f64 + 1.0)),
+        ];
+
+        for (opcode, val1, val2, expected) in test_cases {
+            let instructions = vec![
+                create_instruction(OpCode::Init, 0, 1, 0, None, None),
+                create_instruction(OpCode::Integer, val1, 1, 0, None, None),
+                create_instruction(OpCode::Integer, val2, 2, 0, None, None),
+                // r3 = r1 op r2
+                create_instruction(opcode, 1, 2, 3, None, None),
+                create_instruction(OpCode::ResultRow, 3, 1, 0, None, None),
+                create_instruction(OpCode::Halt, 0, 0, 0, None, None),
+            ];
+
+            let table = execute_bytecode_program(instructions, &database)
+                .expect("Failed to execute program with arithmetic opcode")
+                .expect("Expected a result table");
+
+            assert_eq!(
+                table.rows()[0][0], expected,
+                "Unexpected result for {:?} {} {}",
+                opcode, val1, val2
+            );
+        }
+
+        // Negation follows the same rules, promoting the smallest integer to float
+        for (value, expected) in [
+            (7, Value::Integer(-7)),
+            (i64::MIN, Value::Float(-(i64::MIN as f64))),
+        ] {
+            let instructions = vec![
+                create_instruction(OpCode::Init, 0, 1, 0, None, None),
+                create_instruction(OpCode::Integer, value, 1, 0, None, None),
+                // r2 = -r1
+                create_instruction(OpCode::Negate, 1, 2, 0, None, None),
+                create_instruction(OpCode::ResultRow, 2, 1, 0, None, None),
+                create_instruction(OpCode::Halt, 0, 0, 0, None, None),
+            ];
+
+            let table = execute_bytecode_program(instructions, &database)
+                .expect("Failed to execute program with negation")
+                .expect("Expected a result table");
+            assert_eq!(table.rows()[0][0], expected);
+        }
+
+        // Division by zero is reported as an error
+        let instructions = vec![
+            create_instruction(OpCode::Init, 0, 1, 0, None, None),
+            create_instruction(OpCode::Integer, 1, 1, 0, None, None),
+            create_instruction(OpCode::Integer, 0, 2, 0, None, None),
+            create_instruction(OpCode::Remainder, 1, 2, 3, None, None),
+            create_instruction(OpCode::Halt, 0, 0, 0, None, None),
+        ];
+        assert!(execute_bytecode_program(instructions, &database).is_err());
+    }
 }
@@ -1,6 +1,8 @@
 //! Tests for SQL aggregate functions in sqawk
 //!
-//! Tests for COUNT, SUM, AVG, MIN, MAX functions and their combinations.
+//! Tests for COUNT, SUM, AVG, MIN, MAX functions and their combinations, DISTINCT
+//! aggregates, STRING_AGG, the statistical aggregates, FILTER clauses and aggregates
+//! over expressions.
 
 use crate::helpers::create_temp_dir;
 use assert_cmd::Command;
@@ -107,3 +109,209 @@ fn test_aggregate_on_basic_table() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_distinct_aggregates() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT COUNT(DISTINCT department) AS depts, COUNT(department) AS rows_with_dept FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("depts,rows_with_dept"))
+        .stdout(predicate::str::contains("3,5"));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_agg_with_separator_and_order() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT department, STRING_AGG(name, '|' ORDER BY age DESC) AS names FROM aggregates GROUP BY department ORDER BY department")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Engineering,Charlie|Alice"))
+        .stdout(predicate::str::contains("Marketing,Eve|Bob"))
+        .stdout(predicate::str::contains("Sales,David"));
+
+    Ok(())
+}
+
+#[test]
+fn test_statistical_aggregates() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    // Ages are 30, 25, 35, 40 and 28; salaries are 70000, 55000, 65000, 80000 and 60000
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT VAR_POP(salary) AS vp, VARIANCE(salary) AS vs, MEDIAN(age) AS med, PERCENTILE_CONT(age, 0.25) AS p25, PERCENTILE_DISC(0.9) WITHIN GROUP (ORDER BY salary) AS p90, MODE(department) AS mode FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("vp,vs,med,p25,p90,mode"))
+        .stdout(predicate::str::contains(
+            "74000000,92500000,30,28,80000,Engineering",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_percentile_fraction_out_of_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT PERCENTILE_CONT(age, 1.5) FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("fraction must be between 0 and 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_aggregate_filter_clause() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    // Several conditional counts next to the total, in one scan
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT COUNT(*) AS total, COUNT(*) FILTER (WHERE department = 'Engineering') AS eng, SUM(salary) FILTER (WHERE age >= 30 AND age < 40) AS thirties FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("total,eng,thirties"))
+        .stdout(predicate::str::contains("5,2,135000"));
+
+    Ok(())
+}
+
+#[test]
+fn test_grouped_aggregate_filter_clause() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    // A group whose rows are all filtered out still appears in the result
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT department, COUNT(*) AS n, COUNT(*) FILTER (WHERE salary > 60000) AS high, MAX(name) FILTER (WHERE age < 30) AS young FROM aggregates GROUP BY department")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Engineering,2,2,"))
+        .stdout(predicate::str::contains("Marketing,2,0,Eve"))
+        .stdout(predicate::str::contains("Sales,1,1,"));
+
+    Ok(())
+}
+
+#[test]
+fn test_aggregates_over_expressions() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT SUM(CASE WHEN age >= 30 THEN 1 ELSE 0 END) AS over_30, SUM(salary / 1000) AS k, MAX(CASE department WHEN 'Sales' THEN salary END) AS sales_max FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("over_30,k,sales_max"))
+        .stdout(predicate::str::contains("3,330,80000"));
+
+    Ok(())
+}
+
+#[test]
+fn test_filter_and_case_with_nulls() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("staff.csv");
+    fs::write(
+        &file_path,
+        "name,age,salary\nAlice,35,70000\nBob,,55000\nCarol,28,60000\nDan,41,\n",
+    )?;
+
+    // A NULL age is neither over nor under 30: FILTER skips the row and CASE
+    // falls through to ELSE
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT SUM(salary) FILTER (WHERE age > 30) AS senior, COUNT(*) FILTER (WHERE age <= 30) AS junior, SUM(CASE WHEN age > 30 THEN 1 WHEN age <= 30 THEN 0 ELSE 100 END) AS flags FROM staff")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout("senior,junior,flags\n70000,1,102\n");
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT name, CASE WHEN age > 30 THEN 'senior' WHEN age <= 30 THEN 'junior' END AS band FROM staff")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout("name,band\nAlice,senior\nBob,NULL\nCarol,junior\nDan,senior\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_bool_aggregates_over_predicates() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT department, BOOL_AND(age > 26) AS all_over_26, BOOL_OR(name = 'Eve') AS has_eve, BOOL_AND(salary >= 60000 AND age < 40) AS senior FROM aggregates GROUP BY department ORDER BY department")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success().stdout(
+        "department,all_over_26,has_eve,senior\nEngineering,true,false,true\nMarketing,false,true,false\nSales,true,false,false\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_count_distinct_mixed_numbers() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("keys.csv");
+    fs::write(&file_path, "k,age\n1,30\n1.0,\n2,25\n")?;
+
+    // 1 and 1.0 are equal, so they count once; a NULL predicate is ignored
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT COUNT(DISTINCT k) AS keys, BOOL_AND(age > 20) AS adults FROM keys")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success().stdout("keys,adults\n2,true\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_nested_aggregate_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_aggregates_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT SUM(COUNT(*)) FROM aggregates")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be nested"));
+
+    Ok(())
+}
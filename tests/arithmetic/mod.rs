@@ -0,0 +1,169 @@
+//! Tests for arithmetic operators in sqawk
+//!
+//! Tests for +, -, *, /, DIV and %, including overflow promotion, NULL
+//! propagation and agreement between the direct executor and the VM.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Helper function to create a test file with values near the 64-bit integer limits
+fn create_numbers_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("numbers.csv");
+
+    let content = "id,a,b\n1,7,2\n2,-7,2\n3,9223372036854775807,1\n4,10,\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    // Return both the TempDir (to keep it alive) and the file path
+    Ok((temp_dir, file_path))
+}
+
+#[test]
+fn test_integer_division_and_modulo() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, a / b AS quotient, a DIV b AS whole, a % b AS rest FROM numbers WHERE id < 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,quotient,whole,rest"))
+        .stdout(predicate::str::contains("1,3.5,3,1"))
+        .stdout(predicate::str::contains("2,-3.5,-3,-1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_modulo_in_where() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id FROM numbers WHERE id % 2 = 0")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2\n4"))
+        .stdout(predicate::str::contains("1\n").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_overflow_promotes_to_float() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT a + b AS total, a * 2 AS doubled FROM numbers WHERE id = 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "9223372036854776000,18446744073709552000",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_sum_overflow_promotes_to_float() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT SUM(a) FROM numbers")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("9223372036854776000"));
+
+    Ok(())
+}
+
+#[test]
+fn test_float_overflow_is_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT a * 1e308 FROM numbers WHERE id = 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Numeric overflow"));
+
+    Ok(())
+}
+
+#[test]
+fn test_null_operand_and_division_by_zero() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, a % b AS rest FROM numbers WHERE id = 4")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("4,NULL"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT a DIV 0 FROM numbers")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Division by zero"));
+
+    Ok(())
+}
+
+#[test]
+fn test_vm_arithmetic_matches_executor() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("--vm")
+        .arg("-s")
+        .arg("SELECT 7 / 2, 7 DIV 2, 7 % 2, (9223372036854775807 + 1), -7 DIV 2, -(-7 % 2)")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("3.5,3,1,9223372036854776000,-3,1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_integer_division_overflow_is_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_numbers_file()?;
+
+    for engine_args in [&[][..], &["--vm"][..]] {
+        let mut cmd = Command::cargo_bin("sqawk")?;
+        cmd.args(engine_args)
+            .arg("-s")
+            .arg("SELECT (-9223372036854775807 - 1) DIV -1")
+            .arg(file_path.to_str().unwrap());
+
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("Numeric overflow"));
+    }
+
+    Ok(())
+}
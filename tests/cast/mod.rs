@@ -0,0 +1,150 @@
+//! Tests for type conversion in sqawk
+//!
+//! Tests for CAST, TRY_CAST and the PostgreSQL-style `::` conversion operator.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Helper function to create a test file with values the type inference gets wrong
+fn create_cast_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("readings.csv");
+
+    // "code" looks numeric but is really text, "score" mixes numbers and junk
+    let content = "id,code,score,active\n1,007,7.9,yes\n2,042,3,no\n3,100,n/a,1\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    // Return both the TempDir (to keep it alive) and the file path
+    Ok((temp_dir, file_path))
+}
+
+#[test]
+fn test_cast_in_select() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, CAST(id AS REAL) AS real_id, CAST(active AS INTEGER) AS flag FROM readings WHERE id < 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,real_id,flag"))
+        .stdout(predicate::str::contains("1,1,1"))
+        .stdout(predicate::str::contains("2,2,0"));
+
+    Ok(())
+}
+
+#[test]
+fn test_cast_float_to_integer_truncates() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, CAST(score AS INTEGER) AS whole FROM readings WHERE id = 1")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,whole"))
+        .stdout(predicate::str::contains("1,7"));
+
+    Ok(())
+}
+
+#[test]
+fn test_cast_failure_is_type_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT CAST(score AS INTEGER) FROM readings")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Type error"))
+        .stderr(predicate::str::contains("n/a"));
+
+    Ok(())
+}
+
+#[test]
+fn test_try_cast_returns_null() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, TRY_CAST(score AS REAL) AS score FROM readings")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,score"))
+        .stdout(predicate::str::contains("1,7.9"))
+        .stdout(predicate::str::contains("2,3"))
+        .stdout(predicate::str::contains("3,NULL"));
+
+    Ok(())
+}
+
+#[test]
+fn test_double_colon_cast_operator() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    // The inference loaded "042" as the integer 42; casting back to TEXT
+    // lets it be compared as a string
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, code::TEXT AS code FROM readings WHERE code::TEXT = '42'")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,code"))
+        .stdout(predicate::str::contains("2,42"))
+        .stdout(predicate::str::contains("1,7").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_cast_in_where_clause() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id FROM readings WHERE TRY_CAST(score AS REAL) > 5")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1"))
+        .stdout(predicate::str::contains("2").not())
+        .stdout(predicate::str::contains("3").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_cast_to_unsupported_type() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_cast_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT TRY_CAST(id AS DATE) FROM readings")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported CAST target type"));
+
+    Ok(())
+}
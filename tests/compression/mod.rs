@@ -0,0 +1,166 @@
+//! Tests for compressed files
+//!
+//! Tests for reading gzip, zstd and bzip2 compressed files and stdin, naming
+//! tables past the compression extension, and recompressing on write-back.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use sqawk::compression::Compression;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const SALES: &str = "id,region,amount\n1,north,10\n2,south,20\n3,north,30\n";
+
+// Helper function to write a compressed file
+fn write_compressed(
+    path: &Path,
+    compression: Compression,
+    text: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(path, compression.compress(text.as_bytes().to_vec())?)?;
+    Ok(())
+}
+
+// Helper function to read a compressed file back as text
+fn read_compressed(path: &Path) -> Result<(String, Compression), Box<dyn std::error::Error>> {
+    let (mut reader, compression) = Compression::decompress(fs::File::open(path)?, None)?;
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok((text, compression))
+}
+
+/// Test every codec is read transparently, with the table named past both extensions
+#[test]
+fn test_read_compressed() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    for (compression, extension) in [
+        (Compression::Gzip, "gz"),
+        (Compression::Zstd, "zst"),
+        (Compression::Bzip2, "bz2"),
+    ]
+    .into_iter()
+    .filter(|(compression, _)| compression.is_available())
+    {
+        let file_path = temp_dir.path().join(format!("sales.csv.{}", extension));
+        write_compressed(&file_path, compression, SALES)?;
+
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg("SELECT region, SUM(amount) AS total FROM sales GROUP BY region ORDER BY region")
+            .arg(file_path.to_str().unwrap())
+            .assert()
+            .success()
+            .stdout("region,total\nnorth,40\nsouth,20\n");
+    }
+    Ok(())
+}
+
+/// Test write-back recompresses with the codec the file was read with
+#[test]
+fn test_compressed_write_back() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    for (compression, extension) in [
+        (Compression::Gzip, "gz"),
+        (Compression::Zstd, "zst"),
+        (Compression::Bzip2, "bz2"),
+    ]
+    .into_iter()
+    .filter(|(compression, _)| compression.is_available())
+    {
+        let file_path = temp_dir.path().join(format!("sales.csv.{}", extension));
+        write_compressed(&file_path, compression, SALES)?;
+
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg("DELETE FROM sales WHERE region = 'north'")
+            .arg(file_path.to_str().unwrap())
+            .arg("--write")
+            .assert()
+            .success();
+
+        assert_eq!(
+            read_compressed(&file_path)?,
+            ("id,region,amount\n2,south,20\n".to_string(), compression)
+        );
+    }
+    Ok(())
+}
+
+/// Test the format behind the compression extension is detected
+#[test]
+fn test_compressed_ndjson() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("events.ndjson.gz");
+    write_compressed(
+        &file_path,
+        Compression::Gzip,
+        "{\"id\":1,\"kind\":\"click\"}\n{\"id\":2,\"kind\":\"view\"}\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT id FROM events WHERE kind = 'view'")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("id\n2\n");
+    Ok(())
+}
+
+/// Test compressed stdin is recognized by its magic bytes
+#[test]
+fn test_compressed_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    for compression in [Compression::Gzip, Compression::Zstd, Compression::Bzip2]
+        .into_iter()
+        .filter(Compression::is_available)
+    {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg("SELECT COUNT(*) AS n FROM stdin")
+            .arg("-")
+            .write_stdin(compression.compress(SALES.as_bytes().to_vec())?)
+            .assert()
+            .success()
+            .stdout("n\n3\n");
+    }
+    Ok(())
+}
+
+/// Test a codec left out of the build is reported rather than read as text
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_unavailable_codec() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("sales.csv.zst");
+    fs::write(&file_path, b"\x28\xB5\x2F\xFD")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM sales")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "rebuild it with the 'zstd' feature",
+        ));
+    Ok(())
+}
+
+/// Test a corrupt compressed file reports the codec
+#[test]
+fn test_corrupt_compressed_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("broken.csv.gz");
+    fs::write(&file_path, "id,name\n1,Alice\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM broken")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't decompress gzip data"));
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Tests for CSV dialect options
+//!
+//! Tests for the --quote, --escape, --double-quote, --comment, --trim and
+//! --line-terminator options, and for writing files back in their own dialect.
+
+use crate::helpers::{create_custom_csv, create_temp_dir};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+/// Test rows starting with # are comments by default and data with --comment none
+#[test]
+fn test_comment_none() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "tags.csv", "tag,n\n#rust,1\n#sql,2\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT COUNT(*) AS n FROM tags")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("n\n0\n");
+
+    Command::cargo_bin("sqawk")?
+        .arg("--comment")
+        .arg("none")
+        .arg("-s")
+        .arg("SELECT tag FROM tags WHERE n = 2")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("tag\n#sql\n");
+    Ok(())
+}
+
+/// Test a custom quote and escape character are read and written back
+#[test]
+fn test_quote_and_escape() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "people.csv",
+        "id,name\n1,'O\\'Brien, Pat'\n2,Lee\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--quote")
+        .arg("'")
+        .arg("--escape")
+        .arg("\\")
+        .arg("-s")
+        .arg("UPDATE people SET id = 3 WHERE name = 'Lee'")
+        .arg("-s")
+        .arg("SELECT name FROM people WHERE id = 1")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("O'Brien, Pat"));
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "id,name\n1,'O\\'Brien, Pat'\n3,Lee\n"
+    );
+    Ok(())
+}
+
+/// Test quotes are ordinary text with --quote none
+#[test]
+fn test_quote_none() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "sizes.csv", "size,n\n12\",1\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--quote")
+        .arg("none")
+        .arg("-s")
+        .arg("UPDATE sizes SET n = 2")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&file_path)?, "size,n\n12\",2\n");
+    Ok(())
+}
+
+/// Test --trim removes spaces around fields and column names
+#[test]
+fn test_trim() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "padded.csv", " id , name \n 1 , Alice \n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--trim")
+        .arg("true")
+        .arg("-s")
+        .arg("SELECT id + 1 AS next, name FROM padded")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("next,name\n2,Alice\n");
+    Ok(())
+}
+
+/// Test CRLF files are written back with CRLF, and --line-terminator changes it
+#[test]
+fn test_line_terminators() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "crlf.csv", "id,name\r\n1,Alice\r\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("INSERT INTO crlf VALUES (2, 'Bob')")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "id,name\r\n1,Alice\r\n2,Bob\r\n"
+    );
+
+    Command::cargo_bin("sqawk")?
+        .arg("--line-terminator")
+        .arg("crlf=lf")
+        .arg("-s")
+        .arg("DELETE FROM crlf WHERE id = 1")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&file_path)?, "id,name\n2,Bob\n");
+    Ok(())
+}
+
+/// Test invalid dialect options are rejected
+#[test]
+fn test_invalid_dialect_options() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "data.csv", "id\n1\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--quote")
+        .arg("''")
+        .arg("-s")
+        .arg("SELECT * FROM data")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid dialect character"));
+
+    Command::cargo_bin("sqawk")?
+        .arg("--line-terminator")
+        .arg("cr")
+        .arg("-s")
+        .arg("SELECT * FROM data")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown line terminator 'cr'"));
+    Ok(())
+}
@@ -1,8 +1,9 @@
 //! Tests for delimiter options in sqawk
 //!
-//! Tests for different file delimiter options (-F flag) with tab and colon separators.
+//! Tests for different file delimiter options (-F flag) with tab and colon separators,
+//! awk-style whitespace splitting, and multi-character and regex separators.
 
-use crate::helpers::create_temp_dir;
+use crate::helpers::{create_custom_csv, create_temp_dir};
 use assert_cmd::Command;
 use std::fs;
 use std::io::Write;
@@ -138,3 +139,146 @@ fn test_colon_delimiter() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_whitespace_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+    // Like awk's default FS: runs of blanks separate fields, and the header fixes the
+    // column count so the last column keeps the rest of each line
+    let output = "USER       PID %CPU COMMAND\nroot         1  0.0 /sbin/init splash\n  alice   4242 12.5   vim  notes.txt\n";
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg(" ")
+        .arg("-s")
+        .arg("SELECT USER, COMMAND FROM ps WHERE PID > 1")
+        .arg("ps=-")
+        .write_stdin(output)
+        .assert()
+        .success()
+        .stdout("USER,COMMAND\nalice,vim  notes.txt\n");
+
+    // Without a header, columns are generated for the longest line and short lines padded
+    Command::cargo_bin("sqawk")?
+        .arg("--no-header")
+        .arg("-F")
+        .arg(" ")
+        .arg("-s")
+        .arg("SELECT a, c FROM ls")
+        .arg("ls=-")
+        .write_stdin("total 8\n-rw-r--r-- 1 notes.txt\n")
+        .assert()
+        .success()
+        .stdout("a,c\ntotal,NULL\n-rw-r--r--,notes.txt\n");
+
+    // `-F whitespace` is the same separator; --skip-lines drops the "total" line
+    Command::cargo_bin("sqawk")?
+        .arg("--no-header")
+        .arg("--skip-lines")
+        .arg("1")
+        .arg("-F")
+        .arg("whitespace")
+        .arg("-s")
+        .arg("SELECT a, c FROM ls")
+        .arg("ls=-")
+        .write_stdin("total 8\n-rw-r--r-- 1 notes.txt\n")
+        .assert()
+        .success()
+        .stdout("a,c\n-rw-r--r--,notes.txt\n");
+    Ok(())
+}
+
+#[test]
+fn test_multi_character_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "pairs.txt",
+        "key::value\nhost::db.local\nport::5432\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg("::")
+        .arg("-s")
+        .arg("UPDATE pairs SET value = 5433 WHERE key = 'port'")
+        .arg("-s")
+        .arg("SELECT value FROM pairs")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success()
+        .stdout("value\ndb.local\n5433\n");
+
+    // The table is written back with the same separator
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "key::value\nhost::db.local\nport::5433\n"
+    );
+
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg(" | ")
+        .arg("-s")
+        .arg("SELECT name FROM t WHERE id = 2")
+        .arg("t=-")
+        .write_stdin("id | name\n1 | Alice\n2 | Bob\n")
+        .assert()
+        .success()
+        .stdout("name\nBob\n");
+    Ok(())
+}
+
+#[test]
+fn test_delimiter_with_json_input() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let pipes = create_custom_csv(temp_dir.path(), "p.txt", "id|kind\n1|click\n2|view\n")?;
+    let events = create_custom_csv(
+        temp_dir.path(),
+        "ev.ndjson",
+        "{\"id\":1,\"user\":\"ann\"}\n{\"id\":2,\"user\":\"bo\"}\n",
+    )?;
+    let users = create_custom_csv(
+        temp_dir.path(),
+        "u.json",
+        "[{\"user\":\"bo\",\"city\":\"Oslo\"}]",
+    )?;
+
+    // -F only changes the separator of the text file; JSON is still read as JSON
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg("|")
+        .arg("-s")
+        .arg("SELECT p.kind, ev.user, u.city FROM p INNER JOIN ev ON p.id = ev.id INNER JOIN u ON ev.user = u.user")
+        .arg(pipes.to_str().unwrap())
+        .arg(events.to_str().unwrap())
+        .arg(users.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("p.kind,ev.user,u.city\nview,bo,Oslo\n");
+    Ok(())
+}
+
+#[test]
+fn test_regex_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg("/\\s*[,;]\\s*/")
+        .arg("-s")
+        .arg("SELECT name, age FROM t")
+        .arg("t=-")
+        .write_stdin("id, name;age\n1 ;Alice,  32\n")
+        .assert()
+        .success()
+        .stdout("name,age\nAlice,32\n");
+
+    Command::cargo_bin("sqawk")?
+        .arg("-F")
+        .arg("/[/")
+        .arg("-s")
+        .arg("SELECT * FROM t")
+        .arg("t=-")
+        .write_stdin("a\n")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid delimiter regex /[/"));
+    Ok(())
+}
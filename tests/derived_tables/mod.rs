@@ -0,0 +1,133 @@
+//! Tests for table aliases and derived tables in FROM
+//!
+//! Tests for `FROM table alias`, self-joins under two aliases and subqueries in FROM.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const EMPLOYEES: &str = "id,name,manager_id\n1,Ann,\n2,Bob,1\n3,Cid,1\n4,Dee,2\n";
+
+/// Test columns can be qualified by the table name or its alias
+#[test]
+fn test_table_alias_qualifies_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT e.name FROM employees e WHERE e.id > 2 ORDER BY e.id DESC",
+    )?;
+    assert_eq!(lines, vec!["name", "Dee", "Cid"]);
+
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT employees.name FROM employees WHERE employees.id = 1",
+    )?;
+    assert_eq!(lines, vec!["name", "Ann"]);
+    Ok(())
+}
+
+/// Test joining a table to itself under two aliases
+#[test]
+fn test_self_join() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT e.name, m.name AS manager FROM employees e \
+         INNER JOIN employees m ON e.manager_id = m.id ORDER BY e.id",
+    )?;
+    assert_eq!(
+        lines,
+        vec!["e.name,manager", "Bob,Ann", "Cid,Ann", "Dee,Bob"]
+    );
+
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT e.name FROM employees AS e, employees AS m \
+         WHERE e.manager_id = m.id AND m.name = 'Bob'",
+    )?;
+    assert_eq!(lines, vec!["e.name", "Dee"]);
+    Ok(())
+}
+
+/// Test an unqualified column present on both sides of a self-join is rejected
+#[test]
+fn test_self_join_ambiguous_column() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "employees.csv", EMPLOYEES)?;
+
+    for sql in [
+        "SELECT name FROM employees a JOIN employees b ON a.id = b.id",
+        "SELECT a.name FROM employees a JOIN employees b ON a.id = b.id WHERE name = 'Ann'",
+        "SELECT name, COUNT(*) FROM employees a JOIN employees b ON a.id = b.id GROUP BY name",
+    ] {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg(sql)
+            .arg(file_path.to_str().unwrap())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Ambiguous column reference 'name' could be any of a.name, b.name",
+            ));
+    }
+
+    // A column that only one side has needs no qualifier
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT a.name FROM employees a JOIN (SELECT id AS boss FROM employees) b \
+         ON a.manager_id = b.boss WHERE boss = 2",
+    )?;
+    assert_eq!(lines, vec!["a.name", "Dee"]);
+    Ok(())
+}
+
+/// Test subqueries in FROM, including aggregating over a derived join
+#[test]
+fn test_derived_tables() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT t.name, t.n FROM (SELECT name, id * 10 AS n FROM employees WHERE id > 1) AS t \
+         WHERE t.n < 40",
+    )?;
+    assert_eq!(lines, vec!["name,n", "Bob,20", "Cid,30"]);
+
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT manager, COUNT(*) AS reports FROM \
+         (SELECT e.name, m.name AS manager FROM employees e \
+          INNER JOIN employees m ON e.manager_id = m.id) AS chain \
+         GROUP BY manager ORDER BY reports DESC",
+    )?;
+    assert_eq!(lines, vec!["manager,reports", "Ann,2", "Bob,1"]);
+    Ok(())
+}
+
+/// Test column aliases on derived tables and table functions
+#[test]
+fn test_column_aliases() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "employees.csv",
+        EMPLOYEES,
+        "SELECT g.n, employees.name FROM generate_series(1, 2) AS g(n) \
+         INNER JOIN employees ON employees.id = g.n",
+    )?;
+    assert_eq!(lines, vec!["g.n,employees.name", "1,Ann", "2,Bob"]);
+
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "employees.csv", EMPLOYEES)?;
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM (SELECT id FROM employees) AS t(a, b)")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "has 1 columns but 2 column names were given",
+        ));
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Tests for character encodings
+//!
+//! Tests for byte order mark removal and preservation, the --encoding and --bom
+//! options, and writing files back in the encoding they were read with.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+/// Test a UTF-8 BOM is kept out of the first column name and written back
+#[test]
+fn test_utf8_bom() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("people.csv");
+    fs::write(&file_path, b"\xEF\xBB\xBFid,name\n1,Alice\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE people SET name = 'Bob' WHERE id = 1")
+        .arg("-s")
+        .arg("SELECT id FROM people")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success()
+        .stdout("id\n1\n");
+
+    assert_eq!(fs::read(&file_path)?, b"\xEF\xBB\xBFid,name\n1,Bob\n");
+
+    // --bom false removes it on write-back
+    Command::cargo_bin("sqawk")?
+        .arg("--bom")
+        .arg("false")
+        .arg("-s")
+        .arg("UPDATE people SET name = 'Cy'")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read(&file_path)?, b"id,name\n1,Cy\n");
+    Ok(())
+}
+
+/// Test a Windows-1252 file is transcoded on load and back on write
+#[test]
+fn test_windows_1252() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("names.csv");
+    fs::write(&file_path, b"id,name\r\n1,Ren\xE9\r\n")?;
+
+    // Without --encoding the file is rejected rather than misread
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM names")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not valid UTF-8"));
+
+    Command::cargo_bin("sqawk")?
+        .arg("--encoding")
+        .arg("windows-1252")
+        .arg("-s")
+        .arg("INSERT INTO names VALUES (2, 'Zoë €')")
+        .arg("-s")
+        .arg("SELECT name FROM names WHERE id = 1")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success()
+        .stdout("name\nRené\n");
+
+    assert_eq!(
+        fs::read(&file_path)?,
+        b"id,name\r\n1,Ren\xE9\r\n2,Zo\xEB \x80\r\n"
+    );
+    Ok(())
+}
+
+/// Test characters the encoding can't represent fail without touching the file
+#[test]
+fn test_unmappable_character() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("names.csv");
+    fs::write(&file_path, b"id,name\n1,Ren\xE9\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--encoding")
+        .arg("names=latin1")
+        .arg("-s")
+        .arg("UPDATE names SET name = '中文'")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't be written in windows-1252"));
+
+    assert_eq!(fs::read(&file_path)?, b"id,name\n1,Ren\xE9\n");
+    Ok(())
+}
+
+/// Test a UTF-16 file is recognized by its BOM and written back as UTF-16
+#[test]
+fn test_utf16_bom() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("counts.csv");
+    fs::write(&file_path, b"\xFF\xFEn\0\n\x001\0\n\0")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE counts SET n = 2")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read(&file_path)?, b"\xFF\xFEn\0\n\x002\0\n\0");
+    Ok(())
+}
+
+/// Test an unknown encoding name is rejected
+#[test]
+fn test_unknown_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("--encoding")
+        .arg("ebcdic-42")
+        .arg("-s")
+        .arg("SELECT 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown encoding 'ebcdic-42'"));
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Tests for fixed-width files
+//!
+//! Tests for loading fixed-width files through a --tabledef column layout,
+//! layout sidecar files, and keeping or re-padding values on write-back.
+
+use crate::helpers::{create_custom_csv, create_temp_dir};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+const ACCOUNTS: &str = "A00001John Smith                1234.50\nA00002Jane Doe                    99.00\n\nA00003Ann Lee                          7\n";
+const LAYOUT: &str = "accounts:id@1-6,name@7-30,amount@31-40";
+
+/// Test fields are cut at the layout's positions and trimmed
+#[test]
+fn test_select_fixed_width() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "accounts.dat", ACCOUNTS)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg(LAYOUT)
+        .arg("-s")
+        .arg("SELECT id, name, amount FROM accounts WHERE amount > 50 ORDER BY amount")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("id,name,amount\nA00002,Jane Doe,99\nA00001,John Smith,1234.5\n");
+    Ok(())
+}
+
+/// Test a layout read from a sidecar file, with comments and one column per line
+#[test]
+fn test_layout_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "accounts.dat", ACCOUNTS)?;
+    let layout_path = create_custom_csv(
+        temp_dir.path(),
+        "accounts.layout",
+        "# Account export, record length 40\nid@1-6\nname@7-30  # padded with spaces\namount@31-40\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg(format!("accounts:@{}", layout_path.to_str().unwrap()))
+        .arg("-s")
+        .arg("SELECT name FROM accounts WHERE id = 'A00003'")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("name\nAnn Lee\n");
+    Ok(())
+}
+
+/// Test write-back keeps unchanged values as written and re-pads changed ones
+#[test]
+fn test_fixed_width_write_back() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "accounts.dat", ACCOUNTS)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg(LAYOUT)
+        .arg("-s")
+        .arg("UPDATE accounts SET name = 'Ann Lee-Smith', amount = 8.25 WHERE id = 'A00003'")
+        .arg("-s")
+        .arg("DELETE FROM accounts WHERE id = 'A00002'")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    // The untouched row keeps "1234.50"; changed text is left-aligned and
+    // changed numbers right-aligned
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "A00001John Smith                1234.50 \nA00003Ann Lee-Smith                 8.25\n"
+    );
+    Ok(())
+}
+
+/// Test zero-padded numbers survive write-back when their row changes elsewhere
+#[test]
+fn test_fixed_width_keeps_unchanged_text() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "codes.dat", "0001Alice\n0002Bob  \n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg("codes:code@1-4,name@5-9")
+        .arg("-s")
+        .arg("UPDATE codes SET name = 'Carol' WHERE code = 2")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&file_path)?, "0001Alice\n0002Carol\n");
+    Ok(())
+}
+
+/// Test a relative layout file is read once, so changing directory in the REPL doesn't break saving
+#[test]
+fn test_layout_file_after_cd() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "accounts.dat", ACCOUNTS)?;
+    create_custom_csv(
+        temp_dir.path(),
+        "accounts.layout",
+        "id@1-6,name@7-30,amount@31-40\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .current_dir(temp_dir.path())
+        .arg("--tabledef")
+        .arg("accounts:@accounts.layout")
+        .arg("--interactive")
+        .arg(file_path.to_str().unwrap())
+        .write_stdin(format!(
+            ".cd {}\nDELETE FROM accounts WHERE id = 'A00002';\n.save\n.exit\n",
+            std::env::temp_dir().to_str().unwrap()
+        ))
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "A00001John Smith                1234.50 \nA00003Ann Lee                          7\n"
+    );
+    Ok(())
+}
+
+/// Test invalid layouts and values too wide for their columns
+#[test]
+fn test_fixed_width_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "accounts.dat", ACCOUNTS)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg("accounts:id@1-6,name@6-30")
+        .arg("-s")
+        .arg("SELECT * FROM accounts")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Fixed-width columns 'id' and 'name' overlap",
+        ));
+
+    Command::cargo_bin("sqawk")?
+        .arg("--tabledef")
+        .arg(LAYOUT)
+        .arg("-s")
+        .arg("UPDATE accounts SET id = 'A0000001'")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Value 'A0000001' doesn't fit in the 6 characters of column 'id'",
+        ));
+
+    // The file is left untouched when a value doesn't fit
+    assert_eq!(fs::read_to_string(&file_path)?, ACCOUNTS);
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Tests for user-defined scalar and aggregate functions registered through the library API
+
+use sqawk::aggregate::{Accumulator, Aggregator};
+use sqawk::config::AppConfig;
+use sqawk::database::Database;
+use sqawk::error::{SqawkError, SqawkResult};
+use sqawk::file_handler::FileHandler;
+use sqawk::functions::{ArgType, ScalarFunction, Signature};
+use sqawk::sql_executor::SqlExecutor;
+use sqawk::table::{Table, Value};
+
+/// Example domain helper: product code derived from category and id
+struct ProductSku;
+
+impl ScalarFunction for ProductSku {
+    fn name(&self) -> &str {
+        "product_sku"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::exact(vec![ArgType::String, ArgType::Integer])
+    }
+
+    fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+        match (&args[0], &args[1]) {
+            (Value::String(category), Value::Integer(id)) => Ok(Value::String(format!(
+                "{}-{:05}",
+                category[..3].to_uppercase(),
+                id
+            ))),
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
+/// Replacement for the built-in UPPER that marks its output
+struct LoudUpper;
+
+impl ScalarFunction for LoudUpper {
+    fn name(&self) -> &str {
+        "UPPER"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::exact(vec![ArgType::String])
+    }
+
+    fn call(&self, args: &[Value]) -> SqawkResult<Value> {
+        Ok(match &args[0] {
+            Value::String(s) => Value::String(format!("{}!", s.to_uppercase())),
+            other => other.clone(),
+        })
+    }
+}
+
+/// Example aggregate: difference between the largest and smallest number
+struct Spread;
+
+impl Aggregator for Spread {
+    fn name(&self) -> &str {
+        "spread"
+    }
+
+    fn create_accumulator(&self, params: &[Value]) -> SqawkResult<Box<dyn Accumulator>> {
+        if !params.is_empty() {
+            return Err(SqawkError::InvalidFunctionArguments(
+                "SPREAD requires exactly one argument".to_string(),
+            ));
+        }
+        Ok(Box::new(SpreadAccumulator { range: None }))
+    }
+}
+
+struct SpreadAccumulator {
+    range: Option<(i64, i64)>,
+}
+
+impl Accumulator for SpreadAccumulator {
+    fn update(&mut self, value: &Value) -> SqawkResult<()> {
+        if let Value::Integer(n) = value {
+            self.range = Some(match self.range {
+                Some((lo, hi)) => (lo.min(*n), hi.max(*n)),
+                None => (*n, *n),
+            });
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<Value> {
+        match self.range {
+            Some((lo, hi)) => vec![Value::Integer(lo), Value::Integer(hi)],
+            None => Vec::new(),
+        }
+    }
+
+    fn merge(&mut self, state: &[Value]) -> SqawkResult<()> {
+        state.iter().try_for_each(|value| self.update(value))
+    }
+
+    fn finalize(&self) -> SqawkResult<Value> {
+        Ok(match self.range {
+            Some((lo, hi)) => Value::Integer(hi - lo),
+            None => Value::Null,
+        })
+    }
+}
+
+// Helper function to run one query against tests/data/products.csv with extra functions
+fn run_query(sql: &str, register: impl FnOnce(&mut Database)) -> SqawkResult<Option<Table>> {
+    let config = AppConfig::new(false, None, Vec::new(), false, false);
+    let mut database = Database::new();
+    register(&mut database);
+
+    let mut file_handler = FileHandler::new(&config, &mut database);
+    file_handler.load_file("tests/data/products.csv")?;
+
+    let mut executor = SqlExecutor::new(&mut database, &mut file_handler, &config);
+    executor.execute(sql)
+}
+
+#[test]
+fn test_user_defined_function() -> SqawkResult<()> {
+    let table = run_query(
+        "SELECT product_sku(category, product_id) AS sku FROM products WHERE product_sku(category, product_id) = 'ELE-00101'",
+        |db| db.register_function(ProductSku),
+    )?
+    .expect("SELECT returns a table");
+
+    assert_eq!(table.columns(), vec!["sku".to_string()]);
+    assert_eq!(table.rows().len(), 1);
+    assert_eq!(table.rows()[0][0], Value::String("ELE-00101".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_user_defined_function_argument_checks() {
+    let result = run_query("SELECT product_sku(name) FROM products", |db| {
+        db.register_function(ProductSku)
+    });
+    assert!(matches!(
+        result,
+        Err(SqawkError::InvalidFunctionArguments(ref msg)) if msg.contains("exactly 2 arguments")
+    ));
+
+    let result = run_query("SELECT product_sku(name, name) FROM products", |db| {
+        db.register_function(ProductSku)
+    });
+    assert!(matches!(
+        result,
+        Err(SqawkError::InvalidFunctionArguments(ref msg)) if msg.contains("argument 2 must be an integer")
+    ));
+}
+
+#[test]
+fn test_override_builtin_function() -> SqawkResult<()> {
+    let table = run_query("SELECT upper(name) FROM products LIMIT 1", |db| {
+        db.register_function(LoudUpper)
+    })?
+    .expect("SELECT returns a table");
+
+    assert_eq!(table.rows()[0][0], Value::String("LAPTOP!".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_user_defined_aggregate() -> SqawkResult<()> {
+    let table = run_query("SELECT SPREAD(price) AS spread FROM products", |db| {
+        db.register_aggregate(Spread)
+    })?
+    .expect("SELECT returns a table");
+    assert_eq!(table.rows()[0][0], Value::Integer(1120));
+
+    let table = run_query(
+        "SELECT category, spread(price) AS spread FROM products GROUP BY category ORDER BY category",
+        |db| db.register_aggregate(Spread),
+    )?
+    .expect("SELECT returns a table");
+    assert_eq!(
+        table.columns(),
+        vec!["category".to_string(), "spread".to_string()]
+    );
+    assert_eq!(
+        table.rows(),
+        &[
+            vec![Value::String("Accessories".to_string()), Value::Integer(70)],
+            vec![
+                Value::String("Electronics".to_string()),
+                Value::Integer(850)
+            ],
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_execute_sql_result_set() -> Result<(), Box<dyn std::error::Error>> {
+    let config = AppConfig::new(false, None, Vec::new(), false, false);
+    let mut database = Database::new();
+    let mut file_handler = FileHandler::new(&config, &mut database);
+    file_handler.load_file("tests/data/products.csv")?;
+
+    let mut executor = SqlExecutor::new(&mut database, &mut file_handler, &config);
+    let result = executor
+        .execute_sql("SELECT name, NULL AS note FROM products LIMIT 1")?
+        .expect("SELECT returns a result set");
+
+    assert_eq!(result.columns, vec!["name".to_string(), "note".to_string()]);
+    assert_eq!(
+        result.rows,
+        vec![vec!["Laptop".to_string(), "NULL".to_string()]]
+    );
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 //! Tests for GROUP BY functionality in sqawk
 //!
-//! Tests for SQL GROUP BY clause with various aggregate functions.
+//! Tests for SQL GROUP BY clause with various aggregate functions, grouping by
+//! expressions, ordinals and aliases, and ROLLUP, CUBE and GROUPING SETS.
 
 use crate::helpers::create_temp_dir;
 use assert_cmd::Command;
@@ -24,6 +25,19 @@ fn create_departments_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std
     Ok((temp_dir, file_path))
 }
 
+// Helper function to create a sales test file
+fn create_sales_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("sales.csv");
+
+    let content = "date,region,product,amount\n2024-01-03,East,Widget,100\n2024-01-15,West,Widget,200\n2024-02-02,East,Gadget,50\n2024-02-20,East,Widget,25\n2024-03-01,West,Gadget,75\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    Ok((temp_dir, file_path))
+}
+
 #[test]
 fn test_basic_group_by() -> Result<(), Box<dyn std::error::Error>> {
     // Create test data file - keep temp_dir alive for the test duration
@@ -274,3 +288,116 @@ fn test_group_by_with_having_avg() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_group_by_expression_alias_and_ordinal() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_sales_file()?;
+
+    // The same monthly totals, grouping by the expression, its alias and its position
+    for group_by in ["SUBSTR(date, 1, 7)", "month", "1"] {
+        let mut cmd = Command::cargo_bin("sqawk")?;
+        cmd.arg("-s")
+            .arg(format!(
+                "SELECT SUBSTR(date, 1, 7) AS month, SUM(amount) AS total FROM sales GROUP BY {}",
+                group_by
+            ))
+            .arg(file_path.to_str().unwrap());
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("month,total"))
+            .stdout(predicate::str::contains("2024-01,300"))
+            .stdout(predicate::str::contains("2024-02,75"))
+            .stdout(predicate::str::contains("2024-03,75"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_rollup_with_grouping() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_sales_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT region, product, SUM(amount) AS total, GROUPING(region, product) AS level FROM sales GROUP BY ROLLUP(region, product)")
+        .arg(file_path.to_str().unwrap());
+
+    // Detail rows, then per-region subtotals, then the grand total
+    cmd.assert().success().stdout(
+        "region,product,total,level\n\
+         East,Widget,125,0\n\
+         West,Widget,200,0\n\
+         East,Gadget,50,0\n\
+         West,Gadget,75,0\n\
+         East,NULL,175,1\n\
+         West,NULL,275,1\n\
+         NULL,NULL,450,3\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_cube_and_grouping_sets() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_sales_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT region, product, SUM(amount) AS total FROM sales GROUP BY CUBE(region, product)")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("East,Widget,125"))
+        .stdout(predicate::str::contains("West,NULL,275"))
+        .stdout(predicate::str::contains("NULL,Gadget,125"))
+        .stdout(predicate::str::contains("NULL,NULL,450"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT region, product, COUNT(*) AS n FROM sales GROUP BY GROUPING SETS ((region), (product), ())")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success().stdout(
+        "region,product,n\n\
+         East,NULL,3\n\
+         West,NULL,2\n\
+         NULL,Widget,3\n\
+         NULL,Gadget,2\n\
+         NULL,NULL,5\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_invalid_references() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_sales_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT region, SUM(amount) FROM sales GROUP BY 3")
+        .arg(file_path.to_str().unwrap());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("GROUP BY position 3"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT region, SUM(amount) FROM sales GROUP BY 2")
+        .arg(file_path.to_str().unwrap());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used in GROUP BY"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT product, SUM(amount) FROM sales GROUP BY region")
+        .arg(file_path.to_str().unwrap());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Column 'product' must appear in the GROUP BY clause",
+    ));
+
+    Ok(())
+}
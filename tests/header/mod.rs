@@ -0,0 +1,158 @@
+//! Tests for header options
+//!
+//! Tests for the --header, --no-header, --header-row and --skip-lines options,
+//! per-table overrides, and how each header mode is preserved on write-back.
+
+use crate::helpers::{create_custom_csv, create_temp_dir};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+/// Test a headerless file gets generated column names, or names from --tabledef
+#[test]
+fn test_no_header() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "people.csv", "1,Alice\n2,Bob\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--no-header")
+        .arg("-s")
+        .arg("SELECT b FROM people WHERE a = 2")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("b\nBob\n");
+
+    Command::cargo_bin("sqawk")?
+        .arg("--header")
+        .arg("absent")
+        .arg("--tabledef")
+        .arg("people:id,name")
+        .arg("-s")
+        .arg("SELECT name FROM people ORDER BY id")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("name\nAlice\nBob\n");
+    Ok(())
+}
+
+/// Test auto mode treats the first line as a header only when it looks like one
+#[test]
+fn test_auto_header() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let with_header = create_custom_csv(temp_dir.path(), "named.csv", "id,name\n1,Alice\n2,Bob\n")?;
+    let without_header = create_custom_csv(temp_dir.path(), "plain.csv", "1,Alice\n2,Bob\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--header")
+        .arg("auto")
+        .arg("-s")
+        .arg("SELECT COUNT(*) AS n FROM named")
+        .arg("-s")
+        .arg("SELECT COUNT(*) AS n FROM plain")
+        .arg(with_header.to_str().unwrap())
+        .arg(without_header.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("n\n2\nn\n2\n");
+    Ok(())
+}
+
+/// Test --header-row and --skip-lines, and that the skipped lines survive write-back
+#[test]
+fn test_header_row_and_skip_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let content =
+        "Sales report\nGenerated 2024-01-01\nregion,amount\nunits,usd\nnorth,10\nsouth,20\n";
+    let file_path = create_custom_csv(temp_dir.path(), "sales.csv", content)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--header-row")
+        .arg("3")
+        .arg("--skip-lines")
+        .arg("1")
+        .arg("-s")
+        .arg("UPDATE sales SET amount = 15 WHERE region = 'north'")
+        .arg("-s")
+        .arg("SELECT region, amount FROM sales")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success()
+        .stdout("region,amount\nnorth,15\nsouth,20\n");
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "Sales report\nGenerated 2024-01-01\nregion,amount\nunits,usd\nnorth,15\nsouth,20\n"
+    );
+    Ok(())
+}
+
+/// Test a headerless file is written back without a generated header
+#[test]
+fn test_no_header_write_back() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "people.csv", "1,Alice\n2,Bob\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--no-header")
+        .arg("-s")
+        .arg("DELETE FROM people WHERE a = 1")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&file_path)?, "2,Bob\n");
+    Ok(())
+}
+
+/// Test a table-specific mode overrides the global one
+#[test]
+fn test_per_table_header_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let people = create_custom_csv(temp_dir.path(), "people.csv", "id,name\n1,Alice\n")?;
+    let scores = create_custom_csv(temp_dir.path(), "scores.csv", "1,90\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--no-header")
+        .arg("--header")
+        .arg("people=present")
+        .arg("-s")
+        .arg("SELECT people.name, scores.b FROM people, scores WHERE people.id = scores.a")
+        .arg(people.to_str().unwrap())
+        .arg(scores.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alice,90"));
+    Ok(())
+}
+
+/// Test invalid header options are rejected
+#[test]
+fn test_invalid_header_options() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "people.csv", "id,name\n1,Alice\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("--header")
+        .arg("maybe")
+        .arg("-s")
+        .arg("SELECT * FROM people")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown header mode 'maybe'"));
+
+    Command::cargo_bin("sqawk")?
+        .arg("--header-row")
+        .arg("0")
+        .arg("-s")
+        .arg("SELECT * FROM people")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--header-row counts lines from 1"));
+    Ok(())
+}
@@ -186,3 +186,28 @@ pub fn create_custom_csv(
     fs::write(&file_path, content)?;
     Ok(file_path)
 }
+
+/// Run a query with one custom file loaded and return its output lines
+///
+/// The file is created in a temporary directory, so its name determines both
+/// the table name and the detected format. The query must succeed.
+pub fn run_query(
+    file_name: &str,
+    content: &str,
+    sql: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), file_name, content)?;
+
+    let output = Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg(sql)
+        .arg(file_path.to_str().unwrap())
+        .output()?;
+    assert!(output.status.success());
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
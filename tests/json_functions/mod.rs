@@ -0,0 +1,135 @@
+//! Tests for JSON functions and the -> and ->> operators
+//!
+//! Tests for JSON_EXTRACT, JSON_TYPE, JSON_ARRAY_LENGTH, JSON_OBJECT, JSON_ARRAY and JSON
+//! applied to a JSON payload embedded in a CSV column.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const EVENTS: &str = "id,payload\n\
+                      1,\"{\"\"status\"\":\"\"ok\"\",\"\"user\"\":{\"\"name\"\":\"\"ann\"\",\"\"age\"\":30},\"\"items\"\":[5,6,7]}\"\n\
+                      2,\"{\"\"status\"\":\"\"fail\"\",\"\"user\"\":{\"\"name\"\":\"\"bob\"\",\"\"age\"\":19},\"\"items\"\":[]}\"\n\
+                      3,\n";
+
+/// Test JSON_EXTRACT returns SQL values for scalars and JSON text otherwise
+#[test]
+fn test_json_extract() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT id, json_extract(payload, '$.user.name') AS name, \
+         json_extract(payload, '$.items[#-1]') + 1 AS last, \
+         json_extract(payload, '$.user') AS user FROM events",
+    )?;
+    assert_eq!(
+        lines,
+        vec![
+            "id,name,last,user",
+            "1,ann,8,\"{\"\"name\"\":\"\"ann\"\",\"\"age\"\":30}\"",
+            "2,bob,NULL,\"{\"\"name\"\":\"\"bob\"\",\"\"age\"\":19}\"",
+            "3,NULL,NULL,NULL",
+        ]
+    );
+    Ok(())
+}
+
+/// Test the -> and ->> operators in SELECT, WHERE and ORDER BY
+#[test]
+fn test_arrow_operators() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT id, payload -> 'status' AS raw, payload ->> 'status' AS status FROM events \
+         WHERE payload ->> '$.user.age' >= 18 ORDER BY payload ->> '$.user.name' DESC",
+    )?;
+    assert_eq!(
+        lines,
+        vec![
+            "id,raw,status",
+            "2,\"\"\"fail\"\"\",fail",
+            "1,\"\"\"ok\"\"\",ok",
+        ]
+    );
+
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT id FROM events WHERE payload ->> 'status' = 'ok'",
+    )?;
+    assert_eq!(lines, vec!["id", "1"]);
+
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT payload -> 'items' ->> 0 AS first FROM events WHERE id = 1",
+    )?;
+    assert_eq!(lines, vec!["first", "5"]);
+    Ok(())
+}
+
+/// Test JSON_TYPE and JSON_ARRAY_LENGTH
+#[test]
+fn test_json_type_and_array_length() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT json_type(payload) AS t, json_type(payload, '$.user.age') AS age_type, \
+         json_array_length(payload, '$.items') AS n FROM events WHERE id < 3",
+    )?;
+    assert_eq!(
+        lines,
+        vec!["t,age_type,n", "object,integer,3", "object,integer,0"]
+    );
+    Ok(())
+}
+
+/// Test JSON_OBJECT and JSON_ARRAY build JSON text, with nesting
+#[test]
+fn test_json_constructors() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT json_object('id', id, 'tags', json_array('a', id * 2, NULL)) AS doc \
+         FROM events WHERE id = 1",
+    )?;
+    assert_eq!(
+        lines,
+        vec!["doc", "\"{\"\"id\"\":1,\"\"tags\"\":[\"\"a\"\",2,null]}\""]
+    );
+    Ok(())
+}
+
+/// Test only JSON results are embedded as JSON; text that looks like JSON stays a string
+#[test]
+fn test_json_constructors_embed_only_json() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.csv",
+        EVENTS,
+        "SELECT json_array('[1]', json('[1]'), payload -> 'items', (json_array(2))) AS doc \
+         FROM events WHERE id = 1",
+    )?;
+    assert_eq!(lines, vec!["doc", "\"[\"\"[1]\"\",[1],[5,6,7],[2]]\""]);
+    Ok(())
+}
+
+/// Test malformed JSON and invalid paths are reported as errors
+#[test]
+fn test_json_errors() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT json_extract(name, '$') FROM sample")
+        .arg(get_static_sample_file())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("malformed JSON"));
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT json_extract('{}', 'a.b') FROM sample")
+        .arg(get_static_sample_file())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid JSON path: a.b"));
+    Ok(())
+}
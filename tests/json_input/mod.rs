@@ -0,0 +1,216 @@
+//! Tests for JSON and NDJSON input files
+//!
+//! Tests for loading, flattening and writing back JSON arrays and newline-delimited JSON.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+const EVENTS: &str =
+    "{\"id\":1,\"user\":{\"name\":\"ann\",\"age\":30},\"tags\":[\"a\",\"b\"],\"ok\":true}\n\
+                      \n\
+                      {\"id\":2,\"user\":{\"name\":\"bob\"},\"score\":1.5,\"ok\":null}\n";
+
+/// Test NDJSON keys become columns, with nested objects flattened
+#[test]
+fn test_ndjson_columns_and_flattening() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query("events.ndjson", EVENTS, "SELECT * FROM events")?;
+    assert_eq!(
+        lines,
+        vec![
+            "id,user.name,user.age,tags,ok,score",
+            "1,ann,30,\"[\"\"a\"\",\"\"b\"\"]\",true,NULL",
+            "2,bob,NULL,NULL,NULL,1.5",
+        ]
+    );
+    Ok(())
+}
+
+/// Test JSON values keep their types in expressions
+#[test]
+fn test_json_value_types() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "events.jsonl",
+        EVENTS,
+        "SELECT id, user.name FROM events WHERE user.age > 21 AND ok = true",
+    )?;
+    assert_eq!(lines, vec!["id,user.name", "1,ann"]);
+
+    let lines = run_query(
+        "events.jsonl",
+        EVENTS,
+        "SELECT SUM(score), COUNT(user.age) FROM events",
+    )?;
+    assert_eq!(lines, vec!["SUM,COUNT", "1.5,1"]);
+    Ok(())
+}
+
+/// Test a .json file holding an array of objects, and qualified dotted columns
+#[test]
+fn test_json_array_and_qualified_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "people.json",
+        "[\n  {\"id\": 1, \"name\": {\"first\": \"Ann\"}},\n  {\"id\": 2, \"name\": {\"first\": \"Bo\"}}\n]\n",
+        "SELECT p.name.first FROM people p ORDER BY p.id DESC",
+    )?;
+    assert_eq!(lines, vec!["name.first", "Bo", "Ann"]);
+    Ok(())
+}
+
+/// Test joining a JSON table keeps its dotted columns qualified
+#[test]
+fn test_join_json_with_csv() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let events = create_custom_csv(temp_dir.path(), "events.ndjson", EVENTS)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT s.name, e.user.name FROM sample s JOIN events e ON s.id = e.id")
+        .arg(get_static_sample_file())
+        .arg(events.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("s.name,e.user.name\nAlice,ann\nBob,bob\n");
+    Ok(())
+}
+
+/// Test write-back keeps NDJSON as one object per line and re-nests objects
+#[test]
+fn test_ndjson_write_back() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "events.ndjson", EVENTS)?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE events SET score = 2 WHERE id = 1")
+        .arg("-s")
+        .arg("DELETE FROM events WHERE id = 2")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "{\"id\":1,\"user\":{\"name\":\"ann\",\"age\":30},\"tags\":[\"a\",\"b\"],\"ok\":true,\"score\":2}\n"
+    );
+    Ok(())
+}
+
+/// Test write-back keeps a .json file as an array of objects
+#[test]
+fn test_json_array_write_back() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "items.json",
+        "[{\"id\": 1, \"qty\": 5}, {\"id\": 2, \"qty\": 7}]",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("INSERT INTO items VALUES (3, 9)")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "[\n  {\"id\":1,\"qty\":5},\n  {\"id\":2,\"qty\":7},\n  {\"id\":3,\"qty\":9}\n]\n"
+    );
+    Ok(())
+}
+
+/// Test write-back rebuilds the loaded objects rather than guessing from names and text
+#[test]
+fn test_json_write_back_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "data.json",
+        "{\"id\":1,\"s\":\"[1]\",\"a.b\":2,\"x\":{\"y\":3},\"t\":[1]}\n{\"id\":2}\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE data SET id = 11 WHERE id = 1")
+        .arg("-s")
+        .arg("UPDATE data SET id = 12 WHERE id = 2")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    // The string "[1]" stays a string, the flat key "a.b" stays flat, the
+    // second row gains no keys, and the NDJSON .json file stays NDJSON
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "{\"id\":11,\"s\":\"[1]\",\"a.b\":2,\"x\":{\"y\":3},\"t\":[1]}\n{\"id\":12}\n"
+    );
+    Ok(())
+}
+
+/// Test values that weren't changed are written back with their exact text
+#[test]
+fn test_json_write_back_keeps_unchanged_values() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "ids.ndjson",
+        "{\"id\":12345678901234567890,\"price\":1.50}\n{\"id\":2,\"price\":2.50}\n{\"id\":3,\"price\":1e2}\n",
+    )?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE ids SET price = 9.5 WHERE id = 2")
+        .arg("-s")
+        .arg("DELETE FROM ids WHERE id = 3")
+        .arg(file_path.to_str().unwrap())
+        .arg("--write")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&file_path)?,
+        "{\"id\":12345678901234567890,\"price\":1.50}\n{\"id\":2,\"price\":9.5}\n"
+    );
+    Ok(())
+}
+
+/// Test keys that flatten to the same column name are rejected
+#[test]
+fn test_json_column_name_collision() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path =
+        create_custom_csv(temp_dir.path(), "dup.json", "[{\"a\":{\"b\":1},\"a.b\":2}]")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM dup")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "both flatten to the column name 'a.b'",
+        ));
+    Ok(())
+}
+
+/// Test malformed lines report the file and line number
+#[test]
+fn test_invalid_ndjson_line() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "bad.ndjson", "{\"a\":1}\n{\"a\":\n")?;
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM bad")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("JSON parse error"))
+        .stderr(predicate::str::contains("at line 2"));
+    Ok(())
+}
@@ -14,13 +14,32 @@ mod update; // UPDATE statement tests
 mod advanced; // Tests for advanced SQL features and edge cases
 mod aggregate; // Tests for aggregate functions (COUNT, SUM, AVG, MIN, MAX)
 mod alias; // Tests for column aliases (AS keyword)
+mod arithmetic; // Tests for arithmetic operators (+, -, *, /, DIV, %)
+mod cast; // Tests for CAST, TRY_CAST and :: type conversion
+mod compression; // Tests for gzip, zstd and bzip2 compressed files
+mod csv_dialect; // Tests for --quote, --escape, --comment, --trim and --line-terminator
 mod csv_handler; // Tests for CSV handler features (comments, error recovery)
 mod delimiter; // Tests for delimiter options (-F flag)
+mod derived_tables; // Tests for table aliases, self-joins and subqueries in FROM
+mod encoding; // Tests for BOM handling, --encoding and --bom
+mod fixed_width; // Tests for fixed-width files laid out with --tabledef column positions
+mod function_registry; // Tests for user-defined scalar functions via the library API
 mod group_by; // Tests for GROUP BY functionality
+mod header; // Tests for --header, --no-header, --header-row and --skip-lines
 mod join_on; // Tests for JOIN ON syntax (as opposed to WHERE for joins)
+mod json_functions; // Tests for JSON_EXTRACT and related functions and the -> and ->> operators
+mod json_input; // Tests for JSON and NDJSON input files and write-back
 mod limit_offset; // Tests for LIMIT and OFFSET clauses
+mod null_handling; // Tests for NULL/boolean recognition and NULL output options
+mod output_format; // Tests for --output-format and the REPL .mode command
+mod pivot; // Tests for PIVOT and UNPIVOT table factors
+mod regex_functions; // Tests for REGEXP_EXTRACT, REGEXP_REPLACE, REGEXP_COUNT, REGEXP_SPLIT_PART
 mod repl; // Tests for interactive REPL functionality with pre-generated input
+mod scalar_functions; // Tests for NULL handling, string and math scalar functions
+mod stdin; // Tests for reading tables from stdin and --input-format
 mod string_functions; // Tests for string functions (UPPER, LOWER, TRIM, SUBSTR, REPLACE)
+mod table_functions; // Tests for table-valued functions (generate_series, split_to_rows, read_csv)
+mod values; // Tests for VALUES lists and SELECT without FROM
 
 // Support modules
 mod helpers; // Test helpers and utilities
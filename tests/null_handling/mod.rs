@@ -0,0 +1,161 @@
+//! Tests for NULL and boolean recognition options in sqawk
+//!
+//! Tests for the --null-value, --no-bool-inference and --null-output options,
+//! covering loading, result output and write-back.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Helper function to create a file that uses several missing-value conventions
+fn create_nulls_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("survey.csv");
+
+    let content = "id,name,answer,score\n1,Alice,yes,NA\n2,,no,3\n3,Carol,maybe,\\N\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    // Return both the TempDir (to keep it alive) and the file path
+    Ok((temp_dir, file_path))
+}
+
+#[test]
+fn test_default_null_tokens() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    // Only the empty name is NULL by default; NA and \N are plain text
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id FROM survey WHERE name IS NULL OR score IS NULL")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2"))
+        .stdout(predicate::str::contains("1").not())
+        .stdout(predicate::str::contains("3").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_null_tokens() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, score FROM survey WHERE score IS NULL")
+        .arg("--null-value")
+        .arg("NA")
+        .arg("--null-value")
+        .arg("\\N")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1,NULL"))
+        .stdout(predicate::str::contains("3,NULL"))
+        .stdout(predicate::str::contains("2,").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_null_tokens_replace_empty() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    // With only NA configured, the empty name is an empty string rather than NULL
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id FROM survey WHERE name = ''")
+        .arg("--null-value")
+        .arg("NA")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success().stdout(predicate::str::contains("2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_disable_bool_inference() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, answer FROM survey WHERE answer = 'yes'")
+        .arg("--no-bool-inference")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1,yes"));
+
+    Ok(())
+}
+
+#[test]
+fn test_null_output_in_results() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, name FROM survey WHERE id = 2")
+        .arg("--null-output")
+        .arg("<missing>")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2,<missing>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_write_back_preserves_null_token() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("UPDATE survey SET score = 5 WHERE id = 2")
+        .arg("--null-value")
+        .arg("NA")
+        .arg("--write")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success();
+
+    // NULLs are written back using the first input token, never as "NULL"
+    let content = fs::read_to_string(&file_path)?;
+    assert!(content.contains("1,Alice,true,NA"));
+    assert!(content.contains("2,,false,5"));
+    assert!(!content.contains("NULL"));
+
+    Ok(())
+}
+
+#[test]
+fn test_write_back_with_null_output() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_nulls_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("DELETE FROM survey WHERE id = 3")
+        .arg("--null-output")
+        .arg("-")
+        .arg("--write")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().success();
+
+    let content = fs::read_to_string(&file_path)?;
+    assert!(content.contains("2,-,false,3"));
+
+    Ok(())
+}
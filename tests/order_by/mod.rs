@@ -1,6 +1,7 @@
 //! Tests for ORDER BY functionality in sqawk
 //!
-//! Tests for sorting functionality with different column combinations and directions.
+//! Tests for sorting functionality with different column combinations and directions,
+//! positions, aliases, expressions and NULLS FIRST/LAST.
 
 use crate::helpers::*;
 use assert_cmd::Command;
@@ -148,3 +149,78 @@ fn test_order_by_with_projection() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// Helper function to run a query over a small table with NULL ages and check the exact output
+fn assert_ordered_output(sql: &str, expected: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let content = "id,name,age\n1,Alice,32\n2,Bob,\n3,Charlie,35\n4,Dan,25\n5,Eve,\n";
+    let file_path = create_custom_csv(temp_dir.path(), "people.csv", content)?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s").arg(sql).arg(file_path.to_str().unwrap());
+    cmd.assert().success().stdout(expected.to_string());
+
+    Ok(())
+}
+
+/// Test ordering by select-list position and alias
+#[test]
+fn test_order_by_position_and_alias() -> Result<(), Box<dyn std::error::Error>> {
+    assert_ordered_output(
+        "SELECT name, age FROM people WHERE age > 0 ORDER BY 2 DESC",
+        "name,age\nCharlie,35\nAlice,32\nDan,25\n",
+    )?;
+    assert_ordered_output(
+        "SELECT name, age * 2 AS doubled FROM people WHERE age > 0 ORDER BY doubled",
+        "name,doubled\nDan,50\nAlice,64\nCharlie,70\n",
+    )
+}
+
+/// Test ordering by expressions, including columns that are not selected
+#[test]
+fn test_order_by_expression() -> Result<(), Box<dyn std::error::Error>> {
+    assert_ordered_output(
+        "SELECT name FROM people ORDER BY LENGTH(name) DESC, name",
+        "name\nCharlie\nAlice\nBob\nDan\nEve\n",
+    )?;
+    assert_ordered_output(
+        "SELECT name FROM people WHERE age > 0 ORDER BY age",
+        "name\nDan\nAlice\nCharlie\n",
+    )
+}
+
+/// Test the default placement of NULLs and NULLS FIRST/LAST
+#[test]
+fn test_order_by_nulls_first_last() -> Result<(), Box<dyn std::error::Error>> {
+    // NULLs sort as the smallest values by default, and ties keep their input order
+    assert_ordered_output(
+        "SELECT name, age FROM people ORDER BY age",
+        "name,age\nBob,NULL\nEve,NULL\nDan,25\nAlice,32\nCharlie,35\n",
+    )?;
+    assert_ordered_output(
+        "SELECT name, age FROM people ORDER BY age NULLS LAST",
+        "name,age\nDan,25\nAlice,32\nCharlie,35\nBob,NULL\nEve,NULL\n",
+    )?;
+    assert_ordered_output(
+        "SELECT name, age FROM people ORDER BY age DESC NULLS FIRST, name DESC",
+        "name,age\nEve,NULL\nBob,NULL\nCharlie,35\nAlice,32\nDan,25\n",
+    )
+}
+
+/// Test that an ORDER BY position outside the select list is rejected
+#[test]
+fn test_order_by_invalid_position() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let content = "id,name\n1,Alice\n";
+    let file_path = create_custom_csv(temp_dir.path(), "people.csv", content)?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT name FROM people ORDER BY 2")
+        .arg(file_path.to_str().unwrap());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "ORDER BY position 2 is not in the select list",
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! Tests for output formats (--output-format and the REPL .mode command)
+//!
+//! Tests for CSV quoting and the TSV, table, markdown, JSON, NDJSON and HTML writers.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Run a query against a file with awkward values in the given output format
+fn run_with_format(format: &str, sql: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "notes.csv",
+        "id,note,score\n1,\"a, b\",1.5\n2,\"say \"\"hi\"\"\",\n",
+    )?;
+
+    let output = Command::cargo_bin("sqawk")?
+        .arg("--output-format")
+        .arg(format)
+        .arg("-s")
+        .arg(sql)
+        .arg(file_path.to_str().unwrap())
+        .output()?;
+    assert!(output.status.success());
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Test the default CSV output quotes commas and quotes as RFC 4180 requires
+#[test]
+fn test_csv_output_quoting() -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = run_with_format("csv", "SELECT * FROM notes")?;
+    assert_eq!(
+        stdout,
+        "id,note,score\n1,\"a, b\",1.5\n2,\"say \"\"hi\"\"\",NULL\n"
+    );
+    Ok(())
+}
+
+/// Test TSV and markdown output
+#[test]
+fn test_tsv_and_markdown_output() -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = run_with_format("tsv", "SELECT id, note FROM notes")?;
+    assert_eq!(stdout, "id\tnote\n1\ta, b\n2\tsay \"hi\"\n");
+
+    let stdout = run_with_format("markdown", "SELECT id, note FROM notes WHERE id = 1")?;
+    assert_eq!(stdout, "| id | note |\n| --- | --- |\n| 1 | a, b |\n");
+    Ok(())
+}
+
+/// Test aligned table output
+#[test]
+fn test_table_output() -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = run_with_format("table", "SELECT id, note FROM notes")?;
+    assert_eq!(
+        stdout,
+        "+----+----------+\n\
+         | id | note     |\n\
+         +----+----------+\n\
+         |  1 | a, b     |\n\
+         |  2 | say \"hi\" |\n\
+         +----+----------+\n"
+    );
+    Ok(())
+}
+
+/// Test JSON and NDJSON output keep numbers and NULL as JSON types
+#[test]
+fn test_json_output() -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = run_with_format("json", "SELECT * FROM notes")?;
+    assert_eq!(
+        stdout,
+        "[\n  {\"id\":1,\"note\":\"a, b\",\"score\":1.5},\n  \
+         {\"id\":2,\"note\":\"say \\\"hi\\\"\",\"score\":null}\n]\n"
+    );
+
+    let stdout = run_with_format("ndjson", "SELECT id FROM notes")?;
+    assert_eq!(stdout, "{\"id\":1}\n{\"id\":2}\n");
+
+    let stdout = run_with_format("json", "SELECT id FROM notes WHERE id > 5")?;
+    assert_eq!(stdout, "[]\n");
+    Ok(())
+}
+
+/// Test HTML output escapes markup
+#[test]
+fn test_html_output() -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = run_with_format("html", "SELECT note FROM notes WHERE id = 2")?;
+    assert_eq!(
+        stdout,
+        "<table>\n<tr><th>note</th></tr>\n<tr><td>say &quot;hi&quot;</td></tr>\n</table>\n"
+    );
+    Ok(())
+}
+
+/// Test unknown formats are rejected
+#[test]
+fn test_unknown_output_format() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("--output-format")
+        .arg("yaml")
+        .arg("-s")
+        .arg("SELECT * FROM sample")
+        .arg(get_static_sample_file())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown output format 'yaml'"));
+    Ok(())
+}
+
+/// Test the REPL .mode command switches the output format
+#[test]
+fn test_repl_mode_command() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("--interactive")
+        .arg(get_static_sample_file())
+        .write_stdin(".mode ndjson\nSELECT name FROM sample WHERE id = 1;\n.mode\n.exit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Output mode set to ndjson"))
+        .stdout(predicate::str::contains("{\"name\":\"Alice\"}\n"))
+        .stdout(predicate::str::contains("Output mode: ndjson"));
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Tests for PIVOT and UNPIVOT table factors
+//!
+//! Tests for turning column values into columns and columns into rows.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test UNPIVOT turns columns into rows and skips NULL values
+#[test]
+fn test_unpivot_wide_to_long() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "wide.csv",
+        "region,jan,feb\nnorth,10,20\nsouth,5,\n",
+        "SELECT * FROM wide UNPIVOT (amount FOR month IN (jan, feb)) ORDER BY amount",
+    )?;
+
+    assert_eq!(
+        lines,
+        vec![
+            "region,month,amount",
+            "south,jan,5",
+            "north,jan,10",
+            "north,feb,20",
+        ]
+    );
+    Ok(())
+}
+
+/// Test UNPIVOT results can be filtered and aggregated like any table
+#[test]
+fn test_unpivot_with_aggregate() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "wide.csv",
+        "region,jan,feb\nnorth,10,20\nsouth,5,\n",
+        "SELECT month, SUM(amount) FROM wide UNPIVOT (amount FOR month IN (jan, feb)) \
+         WHERE region = 'north' OR month = 'jan' GROUP BY month",
+    )?;
+
+    assert_eq!(lines, vec!["month,SUM", "jan,15", "feb,20"]);
+    Ok(())
+}
+
+/// Test UNPIVOT applies to an aliased table or a subquery, with an alias of its own
+#[test]
+fn test_unpivot_after_alias_and_subquery() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "wide.csv",
+        "region,jan,feb\nnorth,10,20\nsouth,5,\n",
+        "SELECT w.region, amount FROM wide w UNPIVOT (amount FOR month IN (jan, feb)) \
+         WHERE amount > 5 ORDER BY amount",
+    )?;
+    assert_eq!(lines, vec!["region,amount", "north,10", "north,20"]);
+
+    let lines = run_query(
+        "wide.csv",
+        "region,jan,feb\nnorth,10,20\nsouth,5,\n",
+        "SELECT u.region, u.amount FROM (SELECT region, feb FROM wide) s \
+         UNPIVOT (amount FOR month IN (feb)) AS u",
+    )?;
+    assert_eq!(lines, vec!["region,amount", "north,20"]);
+    Ok(())
+}
+
+/// Test PIVOT aggregates per listed value, grouping by the remaining columns
+#[test]
+fn test_pivot_sum_with_implicit_grouping() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sales.csv",
+        "region,month,amount\nnorth,Jan,10\nnorth,Feb,20\nsouth,Jan,5\nnorth,Jan,1\nwest,Mar,3\n",
+        "SELECT * FROM sales PIVOT (SUM(amount) FOR month IN ('Jan', 'Feb')) AS p",
+    )?;
+
+    assert_eq!(
+        lines,
+        vec![
+            "region,Jan,Feb",
+            "north,11,20",
+            "south,5,NULL",
+            "west,NULL,NULL"
+        ]
+    );
+    Ok(())
+}
+
+/// Test PIVOT columns can be referenced by the values that created them
+#[test]
+fn test_pivot_columns_in_select() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sales.csv",
+        "region,month\nnorth,Jan\nnorth,Feb\nsouth,Jan\n",
+        "SELECT region, \"Feb\" FROM sales PIVOT (COUNT(*) FOR month IN ('Jan', 'Feb')) \
+         WHERE \"Jan\" > 0 ORDER BY region DESC",
+    )?;
+
+    assert_eq!(lines, vec!["region,Feb", "south,0", "north,1"]);
+    Ok(())
+}
+
+/// Test PIVOT and UNPIVOT report unknown columns and non-aggregate functions
+#[test]
+fn test_pivot_unpivot_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(
+        temp_dir.path(),
+        "sales.csv",
+        "region,month,amount\nnorth,Jan,10\n",
+    )?;
+
+    for (sql, message) in [
+        (
+            "SELECT * FROM sales PIVOT (SUM(amount) FOR quarter IN ('Q1'))",
+            "Column 'quarter' not found",
+        ),
+        (
+            "SELECT * FROM sales PIVOT (UPPER(amount) FOR month IN ('Jan'))",
+            "PIVOT requires an aggregate function",
+        ),
+        (
+            "SELECT * FROM sales UNPIVOT (value FOR name IN (amount, nope))",
+            "Column 'nope' not found",
+        ),
+        (
+            "SELECT * FROM sales s UNPIVOT (value IN (amount))",
+            "Expected UNPIVOT (value FOR name IN (column, ...))",
+        ),
+    ] {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg(sql)
+            .arg(file_path.to_str().unwrap())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Tests for regular expression functions in sqawk
+//!
+//! Tests for REGEXP_EXTRACT, REGEXP_REPLACE, REGEXP_COUNT and REGEXP_SPLIT_PART
+//! applied to free-form log lines, in both SELECT lists and WHERE clauses.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Helper function to create a file of web server log lines
+fn create_log_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("access.txt");
+
+    // One unquoted field per line, loaded with a tab separator
+    let content = "line\n\
+                   127.0.0.1 GET /index.html 200 2326\n\
+                   10.0.0.5 POST /api/login 401 12\n\
+                   10.0.0.9 GET /img/logo.png 200 5120\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    // Return both the TempDir (to keep it alive) and the file path
+    Ok((temp_dir, file_path))
+}
+
+#[test]
+fn test_regexp_extract_groups() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_log_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-F")
+        .arg("\\t")
+        .arg("-s")
+        .arg("SELECT regexp_extract(line, '^\\S+') AS ip, regexp_extract(line, '(GET|POST) (\\S+)', 2) AS path, regexp_extract(line, '(?P<status>\\d{3}) \\d+$', 'status') AS status FROM access")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("ip,path,status"))
+        .stdout(predicate::str::contains("127.0.0.1,/index.html,200"))
+        .stdout(predicate::str::contains("10.0.0.5,/api/login,401"));
+
+    Ok(())
+}
+
+#[test]
+fn test_regexp_in_where() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_log_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-F")
+        .arg("\\t")
+        .arg("-s")
+        .arg("SELECT line FROM access WHERE regexp_extract(line, ' (\\d{3}) ', 1) = '200' AND regexp_count(line, '/') > 1")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("/img/logo.png"))
+        .stdout(predicate::str::contains("/index.html").not())
+        .stdout(predicate::str::contains("/api/login").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_regexp_replace_and_split_part() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_log_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-F")
+        .arg("\\t")
+        .arg("-s")
+        .arg("SELECT regexp_replace(line, '^(\\d+)\\.(\\d+)\\.\\d+\\.\\d+', '\\1.\\2.x.x') AS masked, regexp_split_part(line, '\\s+', -1) AS bytes FROM access")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "127.0.x.x GET /index.html 200 2326,2326",
+        ))
+        .stdout(predicate::str::contains(
+            "10.0.x.x POST /api/login 401 12,12",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_pattern() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_log_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-F")
+        .arg("\\t")
+        .arg("-s")
+        .arg("SELECT regexp_count(line, '(') FROM access")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid regular expression"));
+
+    Ok(())
+}
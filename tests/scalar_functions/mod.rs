@@ -0,0 +1,138 @@
+//! Tests for scalar functions in sqawk
+//!
+//! Tests for the NULL handling (COALESCE, IFNULL, NULLIF), string (LENGTH, INSTR,
+//! LPAD, CONCAT, SPLIT_PART, ...) and math (ABS, ROUND, FLOOR, CEIL, ...) functions
+//! in SELECT lists and WHERE clauses. Edge cases are covered by the unit tests in
+//! src/string_functions.rs.
+
+use crate::helpers::create_temp_dir;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Helper function to create a contacts file with missing values and numbers
+fn create_contacts_file() -> Result<(tempfile::TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = temp_dir.path().join("contacts.csv");
+
+    let content = "id,name,phone,mobile,email,balance\n\
+                   1,ada lovelace,555-0100,,ada@example.com,-12.345\n\
+                   2,alan turing,,555-0199,alan@test.org,7\n\
+                   3,grace hopper,,,grace@navy.mil,1250\n";
+
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(content.as_bytes())?;
+
+    // Return both the TempDir (to keep it alive) and the file path
+    Ok((temp_dir, file_path))
+}
+
+#[test]
+fn test_null_handling_functions() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_contacts_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, COALESCE(phone, mobile, 'none') AS contact, IFNULL(mobile, '-') AS m, NULLIF(balance, 7) AS b FROM contacts")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,contact,m,b"))
+        .stdout(predicate::str::contains("1,555-0100,-,-12.345"))
+        .stdout(predicate::str::contains("2,555-0199,555-0199,NULL"))
+        .stdout(predicate::str::contains("3,none,-,1250"));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_functions_in_select() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_contacts_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT LPAD(id, 3, '0') AS code, INITCAP(name) AS full_name, SPLIT_PART(email, '@', 2) AS domain, CONCAT_WS('/', phone, mobile) AS phones FROM contacts WHERE id < 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("code,full_name,domain,phones"))
+        .stdout(predicate::str::contains(
+            "001,Ada Lovelace,example.com,555-0100",
+        ))
+        .stdout(predicate::str::contains(
+            "002,Alan Turing,test.org,555-0199",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_functions_in_where() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_contacts_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id FROM contacts WHERE LENGTH(name) > 11 AND INSTR(email, '.org') = 0")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1\n3"))
+        .stdout(predicate::str::contains("2").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_math_functions() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_contacts_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT id, ABS(balance) AS a, ROUND(balance, 1) AS r, FLOOR(balance) AS f, CEIL(balance) AS c, SIGN(balance) AS s FROM contacts WHERE id = 1")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1,12.345,-12.3,-13,-12,-1"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT ROUND(balance, -2) AS hundreds, POWER(balance, 2) AS squared, SQRT(balance) AS root FROM contacts WHERE id = 3")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1300,1562500,35.35533905932738"));
+
+    Ok(())
+}
+
+#[test]
+fn test_function_argument_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp_dir, file_path) = create_contacts_file()?;
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT LN(balance) FROM contacts")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("non-positive"));
+
+    let mut cmd = Command::cargo_bin("sqawk")?;
+    cmd.arg("-s")
+        .arg("SELECT IFNULL(phone) FROM contacts")
+        .arg(file_path.to_str().unwrap());
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "IFNULL requires exactly 2 arguments",
+    ));
+
+    Ok(())
+}
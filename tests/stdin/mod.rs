@@ -0,0 +1,118 @@
+//! Tests for reading tables from standard input
+//!
+//! Tests for the `-` file path, naming stdin tables, and --input-format.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test `-` reads CSV from stdin as a table named "stdin"
+#[test]
+fn test_stdin_as_default_table() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT name FROM stdin WHERE age > 30 ORDER BY name")
+        .arg("-")
+        .write_stdin("id,name,age\n1,Alice,32\n2,Bob,25\n3,Charlie,35\n")
+        .assert()
+        .success()
+        .stdout("name\nAlice\nCharlie\n");
+    Ok(())
+}
+
+/// Test `table=-` names the stdin table and can be joined with files
+#[test]
+fn test_named_stdin_table_join() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT s.name, o.total FROM orders o JOIN sample s ON o.id = s.id")
+        .arg("orders=-")
+        .arg(get_static_sample_file())
+        .write_stdin("id,total\n2,9.5\n3,12\n")
+        .assert()
+        .success()
+        .stdout("s.name,o.total\nBob,9.5\nCharlie,12\n");
+    Ok(())
+}
+
+/// Test --input-format selects the format of stdin and extensionless files
+#[test]
+fn test_input_format_override() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("--input-format")
+        .arg("ndjson")
+        .arg("-s")
+        .arg("SELECT user.name FROM events WHERE id > 1")
+        .arg("events=-")
+        .write_stdin(
+            "{\"id\":1,\"user\":{\"name\":\"ann\"}}\n{\"id\":2,\"user\":{\"name\":\"bob\"}}\n",
+        )
+        .assert()
+        .success()
+        .stdout("user.name\nbob\n");
+
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "data.txt", "id:name\n1:Alice\n")?;
+    Command::cargo_bin("sqawk")?
+        .arg("--input-format")
+        .arg("tsv")
+        .arg("-F")
+        .arg(":")
+        .arg("-s")
+        .arg("SELECT name FROM data")
+        .arg(file_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("name\nAlice\n");
+
+    Command::cargo_bin("sqawk")?
+        .arg("--input-format")
+        .arg("xml")
+        .arg("-s")
+        .arg("SELECT 1")
+        .arg("-")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown input format 'xml'"));
+    Ok(())
+}
+
+/// Test stdin can only be used once and is skipped by --write
+#[test]
+fn test_stdin_restrictions() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM a")
+        .arg("a=-")
+        .arg("b=-")
+        .write_stdin("x\n1\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Standard input can only be read once",
+        ));
+
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("UPDATE stdin SET x = 2")
+        .arg("-s")
+        .arg("SELECT * FROM stdin")
+        .arg("-")
+        .arg("--write")
+        .write_stdin("x\n1\n")
+        .assert()
+        .success()
+        .stdout("x\n2\n")
+        .stderr(predicate::str::contains(
+            "not saving table 'stdin', which was read from standard input",
+        ));
+
+    Command::cargo_bin("sqawk")?
+        .arg("--interactive")
+        .arg("-")
+        .write_stdin(".exit\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("interactive mode"));
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Tests for table-valued functions in FROM
+//!
+//! Tests for generate_series, split_to_rows and read_csv.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test generate_series with default, positive and negative steps
+#[test]
+fn test_generate_series() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query("empty.csv", "id\n", "SELECT * FROM generate_series(1, 3)")?;
+    assert_eq!(lines, vec!["generate_series", "1", "2", "3"]);
+
+    let lines = run_query(
+        "empty.csv",
+        "id\n",
+        "SELECT generate_series * 10 AS n FROM generate_series(9, 1, -4)",
+    )?;
+    assert_eq!(lines, vec!["n", "90", "50", "10"]);
+    Ok(())
+}
+
+/// Test oversized series are rejected instead of exhausting memory
+#[test]
+fn test_generate_series_limit() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "empty.csv", "id\n")?;
+
+    for sql in [
+        "SELECT COUNT(*) FROM generate_series(1, 1000000000000)",
+        "SELECT COUNT(*) FROM generate_series(-9223372036854775807, 9223372036854775807)",
+        "SELECT COUNT(*) FROM generate_series(0.0, 1e12)",
+    ] {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg(sql)
+            .arg(file_path.to_str().unwrap())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "would produce more than 10000000 rows",
+            ));
+    }
+
+    // The last value of a series ending at the integer limit doesn't overflow
+    let lines = run_query(
+        "empty.csv",
+        "id\n",
+        "SELECT * FROM generate_series(9223372036854775806, 9223372036854775807, 5)",
+    )?;
+    assert_eq!(lines, vec!["generate_series", "9223372036854775806"]);
+    Ok(())
+}
+
+/// Test generate_series can be joined with loaded tables
+#[test]
+fn test_generate_series_join() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "days.csv",
+        "day,amount\n1,10\n3,30\n3,5\n",
+        "SELECT generate_series, COUNT(day) FROM generate_series(1, 4), days \
+         WHERE day = generate_series GROUP BY generate_series",
+    )?;
+    assert_eq!(
+        lines,
+        vec!["generate_series.generate_series,COUNT", "1,1", "3,2"]
+    );
+    Ok(())
+}
+
+/// Test split_to_rows explodes a delimited column into one row per piece
+#[test]
+fn test_split_to_rows() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "items.csv",
+        "id,tags\n1,red;blue\n2,\n3,red\n",
+        "SELECT tags, COUNT(*) AS n FROM split_to_rows(items, tags, ';') \
+         WHERE tags IS NOT NULL GROUP BY tags ORDER BY n DESC, tags",
+    )?;
+    assert_eq!(lines, vec!["tags,n", "red,2", "blue,1"]);
+    Ok(())
+}
+
+/// Test split_to_rows accepts the table and column names as string literals
+#[test]
+fn test_split_to_rows_quoted_names() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "tg.csv",
+        "id,tags\n1,a;b\n2,c\n",
+        "SELECT id, tags FROM split_to_rows('tg', 'tags', ';') ORDER BY tags",
+    )?;
+    assert_eq!(lines, vec!["id,tags", "1,a", "1,b", "2,c"]);
+    Ok(())
+}
+
+/// Test read_csv loads a file that was not given on the command line
+#[test]
+fn test_read_csv_with_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let users = create_custom_csv(temp_dir.path(), "users.csv", "id,name\n1,Alice\n2,Bob\n")?;
+    let scores = create_custom_csv(temp_dir.path(), "scores.txt", "id|score\n1|90\n2|75\n")?;
+
+    let sql = format!(
+        "SELECT users.name, scores.score FROM users INNER JOIN read_csv('{}', delim => '|') \
+         ON users.id = scores.id WHERE scores.score > 80",
+        scores.to_str().unwrap()
+    );
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg(sql)
+        .arg(users.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout("users.name,scores.score\nAlice,90\n");
+    Ok(())
+}
+
+/// Test table functions report invalid arguments and unknown names
+#[test]
+fn test_table_function_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = create_temp_dir()?;
+    let file_path = create_custom_csv(temp_dir.path(), "items.csv", "id,tags\n1,a\n")?;
+
+    for (sql, message) in [
+        (
+            "SELECT * FROM generate_series(1, 5, 0)",
+            "step must not be zero",
+        ),
+        (
+            "SELECT * FROM split_to_rows(items, nope, ';')",
+            "Column 'nope' not found",
+        ),
+        (
+            "SELECT * FROM split_to_rows(items, 1, ';')",
+            "expects an identifier or string literal for the column name",
+        ),
+        (
+            "SELECT * FROM read_csv('missing.csv', header => 'yes')",
+            "Invalid read_csv option",
+        ),
+        ("SELECT * FROM explode(items)", "Unknown table function"),
+    ] {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg(sql)
+            .arg(file_path.to_str().unwrap())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+    Ok(())
+}
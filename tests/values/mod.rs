@@ -0,0 +1,108 @@
+//! Tests for VALUES lists and SELECT without FROM
+//!
+//! Tests for inline VALUES tables, as queries and as derived tables, and for
+//! evaluating expressions without a source table.
+
+use crate::helpers::*;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const SAMPLE: &str = "id,name,age\n1,Alice,32\n2,Bob,25\n3,Charlie,35\n";
+
+/// Test SELECT without FROM evaluates expressions and functions once
+#[test]
+fn test_select_without_from() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sample.csv",
+        SAMPLE,
+        "SELECT 1 + 1 AS two, UPPER('x'), 7 / 2 AS half",
+    )?;
+    assert_eq!(lines, vec!["two,UPPER('x'),half", "2,X,3.5"]);
+    Ok(())
+}
+
+/// Test table-free statements run without any input file
+#[test]
+fn test_no_input_files() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT 1+1")
+        .arg("-s")
+        .arg("VALUES (1, 'a')")
+        .assert()
+        .success()
+        .stdout("1 + 1\n2\ncolumn1,column2\n1,a\n");
+
+    // A statement that reads a table still needs its file
+    Command::cargo_bin("sqawk")?
+        .arg("-s")
+        .arg("SELECT * FROM sample")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Table 'sample' not found"));
+    Ok(())
+}
+
+/// Test a bare VALUES list with generated column names, ORDER BY and LIMIT
+#[test]
+fn test_bare_values() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sample.csv",
+        SAMPLE,
+        "VALUES (3, 'c'), (1, 'a'), (2, NULL) ORDER BY column1 LIMIT 2",
+    )?;
+    assert_eq!(lines, vec!["column1,column2", "1,a", "2,NULL"]);
+    Ok(())
+}
+
+/// Test a VALUES derived table with column aliases can be filtered
+#[test]
+fn test_values_derived_table() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sample.csv",
+        SAMPLE,
+        "SELECT label FROM (VALUES (1, 'one'), (2, 'two'), (3, 'three')) AS t(code, label) \
+         WHERE code >= 2",
+    )?;
+    assert_eq!(lines, vec!["label", "two", "three"]);
+    Ok(())
+}
+
+/// Test a VALUES list maps codes to labels in a join
+#[test]
+fn test_values_join_lookup() -> Result<(), Box<dyn std::error::Error>> {
+    let lines = run_query(
+        "sample.csv",
+        SAMPLE,
+        "SELECT sample.name, codes.label FROM sample \
+         INNER JOIN (VALUES (1, 'gold'), (3, 'silver')) AS codes(id, label) \
+         ON sample.id = codes.id",
+    )?;
+    assert_eq!(
+        lines,
+        vec!["sample.name,codes.label", "Alice,gold", "Charlie,silver"]
+    );
+    Ok(())
+}
+
+/// Test malformed VALUES lists and SELECT * without FROM are rejected
+#[test]
+fn test_values_errors() -> Result<(), Box<dyn std::error::Error>> {
+    for (sql, message) in [
+        ("VALUES (1), (2, 3)", "same number of values"),
+        (
+            "SELECT * FROM (VALUES (1, 2)) AS t(a)",
+            "has 2 columns but 1 column names",
+        ),
+        ("SELECT *", "SELECT * requires a FROM clause"),
+    ] {
+        Command::cargo_bin("sqawk")?
+            .arg("-s")
+            .arg(sql)
+            .arg(get_static_sample_file())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+    Ok(())
+}